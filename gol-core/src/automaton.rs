@@ -0,0 +1,64 @@
+use crate::grid::CellState;
+use crate::grid::Grid;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A color, as RGBA components between 0 and 1
+pub type Color = [f32; 4];
+
+/// Changes made to a grid by a step
+#[derive(Clone, Copy, Default, Debug)]
+pub struct UpdateStats {
+    /// Number of cells which were born
+    pub births: usize,
+    /// Number of cells which died
+    pub deaths: usize,
+    /// Number of alive cells after the step, if the automaton counted them
+    /// while updating the grid
+    pub population: Option<usize>,
+}
+
+/// A cellular automaton, computing the next generation of a grid
+///
+/// The main loop, the renders and the exporters only know about this trait,
+/// so new rule engines can be added without touching them.
+pub trait Automaton: Send {
+    /// Get a short description of the automaton, such as its rule
+    fn name(&self) -> String;
+
+    /// Update the grid to its next generation
+    ///
+    /// @param grid The grid to update
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats;
+
+    /// Get the number of states a cell can be in
+    fn states(&self) -> usize;
+
+    /// Get the color of each state, indexed by state
+    fn palette(&self) -> Vec<Color>;
+
+    /// Get the name of each state, indexed by state
+    fn state_names(&self) -> Vec<String> {
+        (0..self.states())
+            .map(|state| match state {
+                0 => "dead".to_string(),
+                1 if self.states() == 2 => "alive".to_string(),
+                _ => format!("state {}", state),
+            })
+            .collect()
+    }
+
+    /// Get the states of the alive cells of the random soups, picked evenly,
+    /// the first one being the state of the alive cells of the patterns
+    fn seed_states(&self) -> Vec<CellState> {
+        vec![CellState::ALIVE]
+    }
+
+    /// Get the number of bytes kept by the automaton between the steps, such
+    /// as its caches
+    fn get_memory_usage(&self) -> usize {
+        0
+    }
+}
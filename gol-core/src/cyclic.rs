@@ -0,0 +1,111 @@
+//! The cyclic cellular automaton
+//!
+//! The states follow each other in a cycle. A cell advances to the next
+//! state when one of its neighbors is already in it, so the random soups
+//! organize themselves into waves and spirals.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::CellState;
+use crate::grid::Grid;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Default number of states
+pub const DEFAULT_STATES: usize = 14;
+
+/// The cyclic cellular automaton
+pub struct Cyclic {
+    /// Number of states
+    states: usize,
+}
+
+impl Cyclic {
+    /// Create the automaton
+    ///
+    /// @param states Number of states, between 2 and 256
+    pub fn new(states: usize) -> Self {
+        Self {
+            states: states.clamp(2, u8::MAX as usize + 1),
+        }
+    }
+}
+
+impl Default for Cyclic {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATES)
+    }
+}
+
+impl Automaton for Cyclic {
+    fn name(&self) -> String {
+        format!("cyclic {} states", self.states)
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = grid.get_tiles().to_vec();
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                let cell_state = grid.get_tile(x, y);
+                let next_state = CellState(((cell_state.0 as usize + 1) % self.states) as u8);
+                let advances = [
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (-1, 0),
+                    (1, 0),
+                    (-1, 1),
+                    (0, 1),
+                    (1, 1),
+                ]
+                .iter()
+                // The neighbors beyond the edges which do not wrap are ignored
+                .filter_map(|(dx, dy)| grid.get_neighbor(x, y, *dx, *dy))
+                .any(|(x, y)| grid.get_tile(x, y) == next_state);
+
+                if advances {
+                    // Leaving or entering the state 0, counted as dead
+                    if next_state == CellState::DEAD {
+                        stats.deaths += 1;
+                    } else if cell_state == CellState::DEAD {
+                        stats.births += 1;
+                    }
+                    *new_cell = next_state;
+                }
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        self.states
+    }
+
+    /// A color wheel, going once around the hues
+    fn palette(&self) -> Vec<Color> {
+        (0..self.states)
+            .map(|state| {
+                let hue = state as f32 / self.states as f32 * 6.0;
+                let component = |offset: f32| {
+                    let distance = ((hue + offset) % 6.0 - 3.0).abs();
+                    (distance - 1.0).clamp(0.0, 1.0)
+                };
+
+                [component(0.0), component(4.0), component(2.0), 1.0]
+            })
+            .collect()
+    }
+
+    /// The soups are made of all the states
+    fn seed_states(&self) -> Vec<CellState> {
+        (0..self.states)
+            .map(|state| CellState(state as u8))
+            .collect()
+    }
+}
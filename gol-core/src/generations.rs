@@ -0,0 +1,122 @@
+//! The Generations automata
+//!
+//! A Life-like rule with dying states: an alive cell which does not survive
+//! does not die at once but goes through the dying states, one per
+//! generation, before it is dead. The dying cells are neither alive
+//! neighbors nor can they be born again. The rule is written as the
+//! Life-like one followed by the number of states, `B2/S/C3` being Brian's
+//! Brain.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::CellState;
+use crate::grid::Grid;
+use crate::rule::Rule;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// A Life-like rule with dying states
+pub struct Generations {
+    /// When the cells are born and survive
+    rule: Rule,
+    /// Number of states, dead and alive included
+    states: usize,
+}
+
+impl Generations {
+    /// Create the automaton
+    ///
+    /// @param rule When the cells are born and survive
+    /// @param states Number of states, dead and alive included, between 2
+    /// and 256
+    pub fn new(rule: Rule, states: usize) -> Self {
+        Self {
+            rule,
+            states: states.clamp(2, u8::MAX as usize + 1),
+        }
+    }
+}
+
+impl Automaton for Generations {
+    fn name(&self) -> String {
+        format!("generations {}/C{}", self.rule, self.states)
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = grid.get_tiles().to_vec();
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                let alive_neighbors = [
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (-1, 0),
+                    (1, 0),
+                    (-1, 1),
+                    (0, 1),
+                    (1, 1),
+                ]
+                .iter()
+                // The neighbors beyond the edges which do not wrap are dead
+                .filter_map(|(dx, dy)| grid.get_neighbor(x, y, *dx, *dy))
+                .filter(|(x, y)| grid.get_tile(*x, *y) == CellState::ALIVE)
+                .count();
+
+                let cell_state = grid.get_tile(x, y);
+                *new_cell = match cell_state {
+                    CellState::DEAD if self.rule.is_birth(alive_neighbors) => {
+                        stats.births += 1;
+                        CellState::ALIVE
+                    }
+                    CellState::DEAD => CellState::DEAD,
+                    CellState::ALIVE if self.rule.is_survival(alive_neighbors) => CellState::ALIVE,
+                    // Dying, dead after the last state
+                    _ => {
+                        let next_state = (cell_state.0 as usize + 1) % self.states;
+                        if next_state == 0 {
+                            stats.deaths += 1;
+                        }
+                        CellState(next_state as u8)
+                    }
+                };
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        self.states
+    }
+
+    /// Black when dead, white when alive, then fading from yellow to red
+    /// while dying
+    fn palette(&self) -> Vec<Color> {
+        let dying_states = self.states.saturating_sub(2).max(1) as f32;
+        (0..self.states)
+            .map(|state| match state {
+                0 => [0.0, 0.0, 0.0, 1.0],
+                1 => [1.0, 1.0, 1.0, 1.0],
+                _ => {
+                    let age = (state - 2) as f32 / dying_states;
+                    [1.0 - 0.5 * age, 0.9 * (1.0 - age), 0.0, 1.0]
+                }
+            })
+            .collect()
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        (0..self.states)
+            .map(|state| match state {
+                0 => "dead".to_string(),
+                1 => "alive".to_string(),
+                _ => format!("dying {}", state - 1),
+            })
+            .collect()
+    }
+}
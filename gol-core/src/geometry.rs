@@ -0,0 +1,158 @@
+//! Positions and areas of the cells
+//!
+//! The points and the rectangles know nothing about the edges of a grid.
+//! Moving a point across them goes through `Grid::translate`, which wraps
+//! around the edges which do and stops at the others.
+
+use core::fmt;
+
+/// The position of a cell
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Point {
+    /// Column of the cell
+    pub x: usize,
+    /// Row of the cell
+    pub y: usize,
+}
+
+impl Point {
+    /// Create a point
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// Get the index of the cell in row major tiles
+    ///
+    /// @param width Width of the grid
+    pub fn get_index(&self, width: usize) -> usize {
+        self.y * width + self.x
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Point> for (usize, usize) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// A rectangle of cells
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Rect {
+    /// Column of the top left corner
+    pub x: usize,
+    /// Row of the top left corner
+    pub y: usize,
+    /// Number of columns
+    pub width: usize,
+    /// Number of rows
+    pub height: usize,
+}
+
+impl Rect {
+    /// Create a rectangle
+    ///
+    /// @param x Column of the top left corner
+    /// @param y Row of the top left corner
+    /// @param width Number of columns
+    /// @param height Number of rows
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Create a rectangle from its top left corner
+    ///
+    /// @param origin The top left corner
+    /// @param width Number of columns
+    /// @param height Number of rows
+    pub fn at(origin: Point, width: usize, height: usize) -> Self {
+        Self::new(origin.x, origin.y, width, height)
+    }
+
+    /// Get the top left corner
+    pub fn get_origin(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    /// Get the column after the last one
+    pub fn get_right(&self) -> usize {
+        self.x + self.width
+    }
+
+    /// Get the row after the last one
+    pub fn get_bottom(&self) -> usize {
+        self.y + self.height
+    }
+
+    /// Get the number of cells
+    pub fn get_area(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Whether a cell is in the rectangle
+    ///
+    /// @param point The position of the cell
+    pub fn contains(&self, point: Point) -> bool {
+        (self.x..self.get_right()).contains(&point.x)
+            && (self.y..self.get_bottom()).contains(&point.y)
+    }
+
+    /// Get the cells shared with another rectangle
+    ///
+    /// Returns None if they share none.
+    ///
+    /// @param other The other rectangle
+    pub fn get_intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.get_right().min(other.get_right());
+        let bottom = self.get_bottom().min(other.get_bottom());
+        if x >= right || y >= bottom {
+            return None;
+        }
+
+        Some(Self::new(x, y, right - x, bottom - y))
+    }
+
+    /// Get the positions of the cells, row major
+    pub fn get_points(&self) -> impl Iterator<Item = Point> {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = *self;
+
+        (y..y + height).flat_map(move |y| (x..x + width).map(move |x| Point::new(x, y)))
+    }
+}
+
+impl fmt::Display for Rect {
+    /// Write the rectangle as `WxH@X,Y`, as the soups are given
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}@{},{}", self.width, self.height, self.x, self.y)
+    }
+}
@@ -0,0 +1,403 @@
+use crate::automaton::UpdateStats;
+use crate::geometry::{Point, Rect};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// An error occurring when a cell is set out of a grid
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OutOfBoundsError {
+    /// Column of the cell
+    pub x: usize,
+    /// Row of the cell
+    pub y: usize,
+}
+
+impl fmt::Display for OutOfBoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cell ({}, {}) out of the grid", self.x, self.y)
+    }
+}
+
+impl core::error::Error for OutOfBoundsError {}
+
+/// The state of a cell, an index in the palette of the automaton
+///
+/// The state 0 is always dead. The Life-like automatons only use the dead
+/// and alive states, the others may use more.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CellState(pub u8);
+
+impl CellState {
+    /// A dead cell
+    pub const DEAD: CellState = CellState(0);
+    /// An alive cell
+    pub const ALIVE: CellState = CellState(1);
+
+    /// Whether the cell is not dead
+    pub fn is_alive(&self) -> bool {
+        *self != CellState::DEAD
+    }
+}
+
+/// Offsets of the eight neighbors of a cell, row major from the top left
+pub const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// The statistics of a region of a grid
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct RegionStats {
+    /// The region, clipped to the grid
+    pub area: Rect,
+    /// Number of alive cells
+    pub population: usize,
+    /// Number of cells born since the previous generation
+    pub births: usize,
+    /// Number of cells dead since the previous generation
+    pub deaths: usize,
+    /// Fraction of the cells whose state changed since the previous
+    /// generation, between 0 and 1
+    pub activity: f32,
+}
+
+impl fmt::Display for RegionStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} population {} births {} deaths {} activity {:.1}%",
+            self.area,
+            self.population,
+            self.births,
+            self.deaths,
+            self.activity * 100.0
+        )
+    }
+}
+
+/// A grid of cells
+#[derive(Clone)]
+pub struct Grid {
+    /// Width of the grid
+    width: usize,
+    /// Height of the grid
+    height: usize,
+    /// Tiles of the grid, row major
+    tiles: Vec<CellState>,
+    /// Number of alive cells, None once the tiles were lent mutably, until
+    /// they are replaced
+    population: Option<usize>,
+    /// Whether the left and right edges are connected
+    wrap_x: bool,
+    /// Whether the top and bottom edges are connected
+    wrap_y: bool,
+}
+
+impl Grid {
+    /// Create a grid of dead cells, wrapping around its edges
+    ///
+    /// @param width Width of the grid
+    /// @param height Height of the grid
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![CellState::DEAD; width * height],
+            population: Some(0),
+            wrap_x: true,
+            wrap_y: true,
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_tile(&self, x: usize, y: usize) -> CellState {
+        self.tiles[y * self.width + x]
+    }
+
+    pub fn set_tile(&mut self, x: usize, y: usize, cell_state: CellState) {
+        let tile = &mut self.tiles[y * self.width + x];
+        let mut stats = UpdateStats::default();
+        count_change(&mut stats, *tile, cell_state);
+        *tile = cell_state;
+        self.update_population(&stats);
+    }
+
+    /// Set many cells at once
+    ///
+    /// Each position is checked against the size of the grid once, then the
+    /// cell is written directly. The returned stats count the cells which
+    /// were born or died, so a population known before the edit can be kept
+    /// up to date without counting the grid again.
+    ///
+    /// Stops at the first cell out of the grid, the previous ones being set.
+    ///
+    /// @param cells The column, the row and the new state of each cell
+    pub fn set_cells<I>(&mut self, cells: I) -> Result<UpdateStats, OutOfBoundsError>
+    where
+        I: IntoIterator<Item = (usize, usize, CellState)>,
+    {
+        let (width, height) = (self.width, self.height);
+        let mut stats = UpdateStats::default();
+        for (x, y, cell_state) in cells {
+            if x >= width || y >= height {
+                return Err(OutOfBoundsError { x, y });
+            }
+            let tile = &mut self.tiles[y * width + x];
+            let mut change = UpdateStats::default();
+            count_change(&mut change, *tile, cell_state);
+            *tile = cell_state;
+            // Keep the population right for the cells set before an error
+            self.update_population(&change);
+            stats.births += change.births;
+            stats.deaths += change.deaths;
+        }
+
+        Ok(stats)
+    }
+
+    /// Copy rows of cells to the top left corner of the grid
+    ///
+    /// The rows may be shorter than the grid and fewer than its rows, the
+    /// cells they do not cover keep their state. They are all checked before
+    /// any cell is set, so nothing changes if one does not fit. The returned
+    /// stats count the cells which were born or died, as `set_cells`.
+    ///
+    /// @param rows The states of the cells, the top row first
+    pub fn load_rows(&mut self, rows: &[&[CellState]]) -> Result<UpdateStats, OutOfBoundsError> {
+        if rows.len() > self.height {
+            return Err(OutOfBoundsError {
+                x: 0,
+                y: self.height,
+            });
+        }
+        // The first cell beyond the right edge is the one out of the grid
+        if let Some(y) = rows.iter().position(|row| row.len() > self.width) {
+            return Err(OutOfBoundsError { x: self.width, y });
+        }
+
+        let mut stats = UpdateStats::default();
+        for (row, tiles) in rows.iter().zip(self.tiles.chunks_mut(self.width.max(1))) {
+            for (cell_state, tile) in row.iter().zip(tiles.iter_mut()) {
+                count_change(&mut stats, *tile, *cell_state);
+                *tile = *cell_state;
+            }
+        }
+        self.update_population(&stats);
+
+        Ok(stats)
+    }
+
+    pub fn is_wrapping_x(&self) -> bool {
+        self.wrap_x
+    }
+
+    pub fn is_wrapping_y(&self) -> bool {
+        self.wrap_y
+    }
+
+    /// Connect or disconnect the opposite edges of the grid
+    ///
+    /// Both axes wrap around by default, making a torus. Wrapping around a
+    /// single axis makes a cylinder. The cells beyond the disconnected edges
+    /// are considered dead.
+    ///
+    /// @param wrap_x Whether the left and right edges are connected
+    /// @param wrap_y Whether the top and bottom edges are connected
+    pub fn set_wrapping(&mut self, wrap_x: bool, wrap_y: bool) {
+        self.wrap_x = wrap_x;
+        self.wrap_y = wrap_y;
+    }
+
+    /// Get the position of a neighbor of a cell
+    ///
+    /// Returns None if the neighbor is beyond an edge which does not wrap.
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    /// @param dx Horizontal offset of the neighbor
+    /// @param dy Vertical offset of the neighbor
+    pub fn get_neighbor(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<(usize, usize)> {
+        let offset = |position: usize, delta: isize, size: usize, wrap: bool| {
+            let position = position as isize + delta;
+            if (0..size as isize).contains(&position) {
+                Some(position as usize)
+            } else if wrap {
+                Some(position.rem_euclid(size as isize) as usize)
+            } else {
+                None
+            }
+        };
+
+        Some((
+            offset(x, dx, self.width, self.wrap_x)?,
+            offset(y, dy, self.height, self.wrap_y)?,
+        ))
+    }
+
+    /// Get the rectangle covering the whole grid
+    pub fn get_bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    /// Move a point, wrapping around the edges which wrap
+    ///
+    /// Returns None if the point moves beyond an edge which does not wrap.
+    ///
+    /// @param point The point to move
+    /// @param dx Number of columns to move by
+    /// @param dy Number of rows to move by
+    pub fn translate(&self, point: Point, dx: isize, dy: isize) -> Option<Point> {
+        self.get_neighbor(point.x, point.y, dx, dy).map(Point::from)
+    }
+
+    /// Get the states of the eight neighbors of a cell, in the order of
+    /// `NEIGHBOR_OFFSETS`
+    ///
+    /// The neighbors beyond the edges which do not wrap are dead.
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    pub fn get_neighbors(&self, x: usize, y: usize) -> [CellState; 8] {
+        let mut neighbors = [CellState::DEAD; 8];
+        for (neighbor, (dx, dy)) in neighbors.iter_mut().zip(NEIGHBOR_OFFSETS.iter()) {
+            if let Some((x, y)) = self.get_neighbor(x, y, *dx, *dy) {
+                *neighbor = self.get_tile(x, y);
+            }
+        }
+
+        neighbors
+    }
+
+    /// Count the neighbors of a cell which are not dead
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    pub fn get_live_neighbor_count(&self, x: usize, y: usize) -> usize {
+        self.get_neighbors(x, y)
+            .iter()
+            .filter(|cell_state| cell_state.is_alive())
+            .count()
+    }
+
+    /// Get all the tiles, row major
+    pub fn get_tiles(&self) -> &[CellState] {
+        &self.tiles
+    }
+
+    /// Get all the tiles mutably, row major
+    ///
+    /// The population is counted again on demand afterwards.
+    pub fn get_tiles_mut(&mut self) -> &mut [CellState] {
+        self.population = None;
+        &mut self.tiles
+    }
+
+    /// Replace all the tiles
+    ///
+    /// @param tiles The new tiles, row major, as many as the current ones
+    pub fn set_tiles(&mut self, tiles: Vec<CellState>) {
+        assert_eq!(tiles.len(), self.tiles.len());
+        self.population = Some(count_population(&tiles));
+        self.tiles = tiles;
+    }
+
+    /// Get the number of bytes taken by the grid
+    pub fn get_memory_usage(&self) -> usize {
+        core::mem::size_of::<Self>() + self.tiles.capacity() * core::mem::size_of::<CellState>()
+    }
+
+    /// Get the number of alive cells, only counting them if the tiles were
+    /// lent mutably since they were last replaced
+    pub fn get_population(&self) -> usize {
+        self.population
+            .unwrap_or_else(|| count_population(&self.tiles))
+    }
+
+    /// Keep the population up to date after some cells changed
+    ///
+    /// @param stats The cells which were born or died
+    fn update_population(&mut self, stats: &UpdateStats) {
+        if let Some(population) = self.population.as_mut() {
+            *population = *population + stats.births - stats.deaths;
+        }
+    }
+
+    /// Get the statistics of a region of the grid
+    ///
+    /// The births, the deaths and the activity compare the region to the
+    /// previous generation, they are 0 without one or if its size differs.
+    ///
+    /// @param area The region, clipped to the grid
+    /// @param previous The tiles of the previous generation, row major
+    pub fn get_region_stats(&self, area: Rect, previous: Option<&[CellState]>) -> RegionStats {
+        let area = area
+            .get_intersection(&self.get_bounds())
+            .unwrap_or_default();
+        let previous = previous.filter(|previous| previous.len() == self.tiles.len());
+
+        let mut stats = RegionStats {
+            area,
+            ..RegionStats::default()
+        };
+        let mut changes = UpdateStats::default();
+        let mut changed_count = 0;
+        for point in area.get_points() {
+            let index = point.get_index(self.width);
+            let cell_state = self.tiles[index];
+            if cell_state.is_alive() {
+                stats.population += 1;
+            }
+            if let Some(previous) = previous {
+                let previous_state = previous[index];
+                if previous_state != cell_state {
+                    count_change(&mut changes, previous_state, cell_state);
+                    changed_count += 1;
+                }
+            }
+        }
+        stats.births = changes.births;
+        stats.deaths = changes.deaths;
+        if !area.is_empty() {
+            stats.activity = changed_count as f32 / area.get_area() as f32;
+        }
+
+        stats
+    }
+}
+
+/// Count a cell which is born or dies in the stats of an edit
+///
+/// @param stats Where the change is counted
+/// @param old The state of the cell before the edit
+/// @param new The state of the cell after the edit
+fn count_change(stats: &mut UpdateStats, old: CellState, new: CellState) {
+    match (old.is_alive(), new.is_alive()) {
+        (false, true) => stats.births += 1,
+        (true, false) => stats.deaths += 1,
+        _ => {}
+    }
+}
+
+/// Count the alive cells of some tiles
+///
+/// @param tiles The tiles
+fn count_population(tiles: &[CellState]) -> usize {
+    tiles
+        .iter()
+        .filter(|cell_state| cell_state.is_alive())
+        .count()
+}
@@ -0,0 +1,22 @@
+//! The grid, the rules and the automata of gol
+//!
+//! Without the `std` feature, the crate only needs `core` and `alloc`, so the
+//! automata can step the worlds of microcontrollers driving small displays.
+//! The random soups, the timings and the renders are left to the `gol`
+//! crate, which re-exports these modules.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod automaton;
+pub mod cyclic;
+pub mod generations;
+pub mod geometry;
+pub mod grid;
+pub mod life;
+pub mod rule;
+pub mod species;
+#[cfg(feature = "std")]
+pub mod threads;
+pub mod wireworld;
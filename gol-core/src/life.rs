@@ -0,0 +1,868 @@
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::CellState;
+use crate::grid::Grid;
+use crate::rule::Rule;
+#[cfg(feature = "std")]
+use crate::threads::{pin_current_thread, ThreadOptions};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Side of the square tiles processed by `TiledLifeLike` and
+/// `ParallelLifeLike`
+pub const TILE_SIZE: usize = 64;
+
+/// A Life-like automaton on a dense grid wrapping around its edges
+pub struct LifeLike {
+    /// The rule of the automaton
+    rule: Rule,
+}
+
+impl LifeLike {
+    /// Create a Life-like automaton
+    ///
+    /// @param rule The rule of the automaton
+    pub fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+}
+
+impl Default for LifeLike {
+    fn default() -> Self {
+        Self::new(Rule::default())
+    }
+}
+
+impl Automaton for LifeLike {
+    fn name(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let height = grid.get_height();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = vec![CellState::DEAD; width * height];
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                let cell_state = grid.get_tile(x, y);
+
+                let neighbors_count = [
+                    // Top left
+                    (-1, -1),
+                    // Top
+                    (0, -1),
+                    // Top right
+                    (1, -1),
+                    // Left
+                    (-1, 0),
+                    // Right
+                    (1, 0),
+                    // Bottom left
+                    (-1, 1),
+                    // Bottom
+                    (0, 1),
+                    // Bottom right
+                    (1, 1),
+                ]
+                .iter()
+                // The neighbors beyond the edges which do not wrap are dead
+                .filter_map(|(dx, dy)| grid.get_neighbor(x, y, *dx, *dy))
+                .map(|(x, y)| grid.get_tile(x, y))
+                .filter(|cell_state| *cell_state == CellState::ALIVE)
+                .count();
+
+                *new_cell = next_state(&self.rule, cell_state, neighbors_count, &mut stats);
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        2
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]]
+    }
+}
+
+/// A Life-like automaton looking the neighbors up by index
+///
+/// Computes the same generations as `LifeLike`, but resolves the edges once
+/// per row and column rather than once per neighbor.
+pub struct FlatLifeLike {
+    /// The rule of the automaton
+    rule: Rule,
+}
+
+impl FlatLifeLike {
+    /// Create a Life-like automaton
+    ///
+    /// @param rule The rule of the automaton
+    pub fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+}
+
+impl Automaton for FlatLifeLike {
+    fn name(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let neighborhood = Neighborhood::new(grid);
+        let tiles = grid.get_tiles();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = vec![CellState::DEAD; tiles.len()];
+        for y in 0..grid.get_height() {
+            for x in 0..grid.get_width() {
+                let index = y * grid.get_width() + x;
+                new_tiles[index] = next_state(
+                    &self.rule,
+                    tiles[index],
+                    neighborhood.count(tiles, x, y),
+                    &mut stats,
+                );
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        2
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]]
+    }
+}
+
+/// A Life-like automaton processing the grid by square tiles
+///
+/// Computes the same generations as `FlatLifeLike`, but the rows of a tile
+/// and their neighbors stay in the cache while the tile is processed, which
+/// matters for the worlds wider than a few thousand cells.
+pub struct TiledLifeLike {
+    /// The rule of the automaton
+    rule: Rule,
+}
+
+impl TiledLifeLike {
+    /// Create a Life-like automaton
+    ///
+    /// @param rule The rule of the automaton
+    pub fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+}
+
+impl Automaton for TiledLifeLike {
+    fn name(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let height = grid.get_height();
+        let neighborhood = Neighborhood::new(grid);
+        let tiles = grid.get_tiles();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = vec![CellState::DEAD; tiles.len()];
+        for tile_y in (0..height).step_by(TILE_SIZE) {
+            for tile_x in (0..width).step_by(TILE_SIZE) {
+                for y in tile_y..(tile_y + TILE_SIZE).min(height) {
+                    for x in tile_x..(tile_x + TILE_SIZE).min(width) {
+                        let index = y * width + x;
+                        new_tiles[index] = next_state(
+                            &self.rule,
+                            tiles[index],
+                            neighborhood.count(tiles, x, y),
+                            &mut stats,
+                        );
+                    }
+                }
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        2
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]]
+    }
+}
+
+#[cfg(feature = "std")]
+/// A Life-like automaton processing the tiles on several threads
+///
+/// The grid is cut into fixed square tiles. Each thread copies the tiles it
+/// takes along with their halo, the ring of cells bordering them, so it
+/// computes them without touching the grid again. The threads take the next
+/// tile left until there are none, so the busy regions are shared between
+/// the threads whatever their size and place, and the tiles which are dead
+/// with their halo are skipped.
+pub struct ParallelLifeLike {
+    /// The rule of the automaton
+    rule: Rule,
+    /// Number of threads computing the tiles
+    threads: usize,
+    /// Cores to pin the threads to, in order, none to leave them free
+    cores: Vec<usize>,
+}
+
+#[cfg(feature = "std")]
+impl ParallelLifeLike {
+    /// Create a Life-like automaton using all the cores
+    ///
+    /// @param rule The rule of the automaton
+    pub fn new(rule: Rule) -> Self {
+        Self::with_threads(
+            rule,
+            std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        )
+    }
+
+    /// Create a Life-like automaton
+    ///
+    /// @param rule The rule of the automaton
+    /// @param threads Number of threads computing the tiles
+    pub fn with_threads(rule: Rule, threads: usize) -> Self {
+        Self {
+            rule,
+            threads: threads.max(1),
+            cores: Vec::new(),
+        }
+    }
+
+    /// Create a Life-like automaton running and pinning threads as asked
+    ///
+    /// @param rule The rule of the automaton
+    /// @param options The number of threads and whether to pin them
+    pub fn with_options(rule: Rule, options: ThreadOptions) -> Self {
+        Self {
+            rule,
+            threads: options.get_count().max(1),
+            cores: options.get_cores(),
+        }
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+}
+
+#[cfg(feature = "std")]
+impl Automaton for ParallelLifeLike {
+    fn name(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let height = grid.get_height();
+        let neighborhood = Neighborhood::new(grid);
+        let tiles = grid.get_tiles();
+        let tile_positions: Vec<(usize, usize)> = (0..height)
+            .step_by(TILE_SIZE)
+            .flat_map(|tile_y| {
+                (0..width)
+                    .step_by(TILE_SIZE)
+                    .map(move |tile_x| (tile_x, tile_y))
+            })
+            .collect();
+        let next_tile = AtomicUsize::new(0);
+        let rule = &self.rule;
+
+        // Each thread returns the tiles it computed, the dead ones omitted
+        let results: Vec<(Vec<TileUpdate>, UpdateStats)> = std::thread::scope(|scope| {
+            let (cores, next_tile, neighborhood, tile_positions) =
+                (&self.cores, &next_tile, &neighborhood, &tile_positions);
+            let workers: Vec<_> = (0..self.threads.min(tile_positions.len()))
+                .map(|index| {
+                    scope.spawn(move || {
+                        pin_current_thread(cores, index);
+                        let mut updates = Vec::new();
+                        let mut stats = UpdateStats::default();
+                        loop {
+                            let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                            let Some(&(tile_x, tile_y)) = tile_positions.get(index) else {
+                                break;
+                            };
+                            let update = step_tile(
+                                rule,
+                                neighborhood,
+                                tiles,
+                                (tile_x, tile_y),
+                                (
+                                    (tile_x + TILE_SIZE).min(width) - tile_x,
+                                    (tile_y + TILE_SIZE).min(height) - tile_y,
+                                ),
+                                &mut stats,
+                            );
+                            updates.extend(update);
+                        }
+
+                        (updates, stats)
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = vec![CellState::DEAD; tiles.len()];
+        for (updates, thread_stats) in results {
+            stats.births += thread_stats.births;
+            stats.deaths += thread_stats.deaths;
+            for update in updates {
+                for (row, new_row) in update.cells.chunks(update.width).enumerate() {
+                    let start = (update.y + row) * width + update.x;
+                    new_tiles[start..start + update.width].copy_from_slice(new_row);
+                }
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        2
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]]
+    }
+}
+
+#[cfg(feature = "std")]
+/// The next states of a tile computed by a thread of `ParallelLifeLike`
+struct TileUpdate {
+    /// Column of the top left cell of the tile
+    x: usize,
+    /// Row of the top left cell of the tile
+    y: usize,
+    /// Width of the tile
+    width: usize,
+    /// The next states of the cells of the tile, row major
+    cells: Vec<CellState>,
+}
+
+#[cfg(feature = "std")]
+/// Compute the next states of a tile from a copy of it and its halo
+///
+/// Returns None if the tile stays dead.
+///
+/// @param rule The rule
+/// @param neighborhood The neighbors of the cells of the grid
+/// @param tiles The cells of the grid
+/// @param position The column and the row of the top left cell of the tile
+/// @param size The width and the height of the tile
+/// @param stats Where the changes are counted
+fn step_tile(
+    rule: &Rule,
+    neighborhood: &Neighborhood,
+    tiles: &[CellState],
+    (tile_x, tile_y): (usize, usize),
+    (tile_width, tile_height): (usize, usize),
+    stats: &mut UpdateStats,
+) -> Option<TileUpdate> {
+    // The rows and the columns of the halo are those of the neighbors of
+    // the cells on the edges of the tile, None beyond the edges which do not
+    // wrap
+    let halo_rows: Vec<Option<usize>> = core::iter::once(neighborhood.rows[tile_y][0])
+        .chain((tile_y..tile_y + tile_height).map(|y| neighborhood.rows[y][1]))
+        .chain(core::iter::once(
+            neighborhood.rows[tile_y + tile_height - 1][2],
+        ))
+        .collect();
+    let halo_columns: Vec<Option<usize>> = core::iter::once(neighborhood.columns[tile_x][0])
+        .chain((tile_x..tile_x + tile_width).map(|x| neighborhood.columns[x][1]))
+        .chain(core::iter::once(
+            neighborhood.columns[tile_x + tile_width - 1][2],
+        ))
+        .collect();
+
+    let halo_width = tile_width + 2;
+    let mut halo = vec![0u8; halo_width * (tile_height + 2)];
+    for (halo_row, row) in halo.chunks_mut(halo_width).zip(halo_rows.iter()) {
+        for (cell, column) in halo_row.iter_mut().zip(halo_columns.iter()) {
+            if let (Some(row), Some(column)) = (row, column) {
+                *cell = tiles[row + column].is_alive() as u8;
+            }
+        }
+    }
+    if !rule.is_birth(0) && halo.iter().all(|cell| *cell == 0) {
+        return None;
+    }
+
+    let mut cells = Vec::with_capacity(tile_width * tile_height);
+    for y in 1..=tile_height {
+        for x in 1..=tile_width {
+            let index = y * halo_width + x;
+            let neighbors_count = [
+                index - halo_width - 1,
+                index - halo_width,
+                index - halo_width + 1,
+                index - 1,
+                index + 1,
+                index + halo_width - 1,
+                index + halo_width,
+                index + halo_width + 1,
+            ]
+            .iter()
+            .map(|neighbor| halo[*neighbor] as usize)
+            .sum();
+            let cell_state = if halo[index] == 1 {
+                CellState::ALIVE
+            } else {
+                CellState::DEAD
+            };
+            cells.push(next_state(rule, cell_state, neighbors_count, stats));
+        }
+    }
+
+    Some(TileUpdate {
+        x: tile_x,
+        y: tile_y,
+        width: tile_width,
+        cells,
+    })
+}
+
+/// A Life-like automaton keeping the neighbor counts of the cells between
+/// the steps
+///
+/// Only the cells which changed during the previous step and their neighbors
+/// can change, so only they are updated, and the counts of their neighbors
+/// are adjusted. Static regions cost nothing. The changes made to the grid
+/// between the steps are detected, making the counts be computed again.
+pub struct CountingLifeLike {
+    /// The rule of the automaton
+    rule: Rule,
+    /// Width, height and wrapping of the grid the counts were computed for
+    shape: (usize, usize, bool, bool),
+    /// The tiles as left by the last step
+    tiles: Vec<CellState>,
+    /// The neighbors of the cells
+    neighborhood: Neighborhood,
+    /// Number of alive neighbors of each cell
+    counts: Vec<u8>,
+    /// Indexes of the cells which may change during the next step
+    active: Vec<usize>,
+    /// Whether each cell is in the active cells
+    is_active: Vec<bool>,
+}
+
+impl CountingLifeLike {
+    /// Create a Life-like automaton
+    ///
+    /// @param rule The rule of the automaton
+    pub fn new(rule: Rule) -> Self {
+        Self {
+            rule,
+            shape: (0, 0, false, false),
+            tiles: Vec::new(),
+            neighborhood: Neighborhood::new(&Grid::new(0, 0)),
+            counts: Vec::new(),
+            active: Vec::new(),
+            is_active: Vec::new(),
+        }
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Count the neighbors of all the cells of a grid, every cell being
+    /// active
+    ///
+    /// @param grid The grid
+    fn count_all(&mut self, grid: &Grid) {
+        let width = grid.get_width();
+        self.shape = (
+            width,
+            grid.get_height(),
+            grid.is_wrapping_x(),
+            grid.is_wrapping_y(),
+        );
+        self.tiles = grid.get_tiles().to_vec();
+        self.neighborhood = Neighborhood::new(grid);
+        self.counts = (0..self.tiles.len())
+            .map(|index| {
+                self.neighborhood
+                    .count(&self.tiles, index % width, index / width) as u8
+            })
+            .collect();
+        self.active = (0..self.tiles.len()).collect();
+        self.is_active = vec![true; self.tiles.len()];
+    }
+}
+
+impl Automaton for CountingLifeLike {
+    fn name(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let shape = (
+            grid.get_width(),
+            grid.get_height(),
+            grid.is_wrapping_x(),
+            grid.is_wrapping_y(),
+        );
+        if shape != self.shape || self.tiles != grid.get_tiles() {
+            self.count_all(grid);
+        }
+
+        let mut stats = UpdateStats::default();
+        let mut changes = Vec::new();
+        for index in core::mem::take(&mut self.active) {
+            self.is_active[index] = false;
+            let cell_state = self.tiles[index];
+            let new_state = next_state(
+                &self.rule,
+                cell_state,
+                self.counts[index] as usize,
+                &mut stats,
+            );
+            if new_state != cell_state {
+                changes.push((index, new_state));
+            }
+        }
+
+        let width = grid.get_width();
+        for (index, new_state) in changes {
+            self.tiles[index] = new_state;
+            grid.get_tiles_mut()[index] = new_state;
+            activate(&mut self.active, &mut self.is_active, index);
+            for neighbor in self.neighborhood.neighbors(index % width, index / width) {
+                if new_state == CellState::ALIVE {
+                    self.counts[neighbor] += 1;
+                } else {
+                    self.counts[neighbor] -= 1;
+                }
+                activate(&mut self.active, &mut self.is_active, neighbor);
+            }
+        }
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        2
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]]
+    }
+
+    fn get_memory_usage(&self) -> usize {
+        self.tiles.capacity() * core::mem::size_of::<CellState>()
+            + self.counts.capacity()
+            + self.active.capacity() * core::mem::size_of::<usize>()
+            + self.is_active.capacity()
+            + self.neighborhood.get_memory_usage()
+    }
+}
+
+/// A Life-like automaton packing the rows as bits and looking the next
+/// states up in a table
+///
+/// The next state of a cell only depends on the 3x3 window of cells around
+/// it, so the next states of the 512 possible windows are computed once. The
+/// window slides along the packed rows with shifts, making the update a
+/// table lookup per cell.
+pub struct BitPackedLifeLike {
+    /// The rule of the automaton
+    rule: Rule,
+    /// The next state of the center cell of each window, indexed by the
+    /// window whose bit `3 * row + column` is the cell at this position
+    table: [CellState; 512],
+}
+
+impl BitPackedLifeLike {
+    /// Create a Life-like automaton
+    ///
+    /// @param rule The rule of the automaton
+    pub fn new(rule: Rule) -> Self {
+        let mut table = [CellState::DEAD; 512];
+        for (window, next_state) in table.iter_mut().enumerate() {
+            let center = (window >> 4) & 1 == 1;
+            let neighbors_count = (window & !(1 << 4)).count_ones() as usize;
+            if (center && rule.is_survival(neighbors_count))
+                || (!center && rule.is_birth(neighbors_count))
+            {
+                *next_state = CellState::ALIVE;
+            }
+        }
+
+        Self { rule, table }
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+}
+
+impl Automaton for BitPackedLifeLike {
+    fn name(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let height = grid.get_height();
+        let mut stats = UpdateStats::default();
+        if width == 0 || height == 0 {
+            return stats;
+        }
+
+        // Pack each row with the cells beyond its edges, bit x + 1 being the
+        // cell of column x
+        let words = (width + 2).div_ceil(64);
+        let tiles = grid.get_tiles();
+        let mut rows = vec![0u64; words * height];
+        for (row, packed_row) in tiles.chunks(width).zip(rows.chunks_mut(words)) {
+            let mut set = |bit: usize, cell_state: CellState| {
+                packed_row[bit / 64] |= (cell_state.0 as u64) << (bit % 64)
+            };
+            for (x, cell_state) in row.iter().enumerate() {
+                set(x + 1, *cell_state);
+            }
+            if grid.is_wrapping_x() {
+                set(0, row[width - 1]);
+                set(width + 1, row[0]);
+            }
+        }
+
+        let dead_row = vec![0u64; words];
+        let packed_row = |y: Option<(usize, usize)>| match y {
+            Some((_, y)) => &rows[y * words..(y + 1) * words],
+            None => &dead_row[..],
+        };
+        let bit =
+            |packed_row: &[u64], bit: usize| ((packed_row[bit / 64] >> (bit % 64)) & 1) as usize;
+
+        // The bits of the cells, without those beyond the edges
+        let mut inner = vec![0u64; words];
+        for x in 0..width {
+            inner[(x + 1) / 64] |= 1 << ((x + 1) % 64);
+        }
+
+        let mut population = 0;
+        let mut new_tiles = vec![CellState::DEAD; width * height];
+        let mut new_packed_row = vec![0u64; words];
+        for (y, new_row) in new_tiles.chunks_mut(width).enumerate() {
+            let above = packed_row(grid.get_neighbor(0, y, 0, -1));
+            let current = packed_row(Some((0, y)));
+            let below = packed_row(grid.get_neighbor(0, y, 0, 1));
+            let column = |position: usize| {
+                bit(above, position) | bit(current, position) << 3 | bit(below, position) << 6
+            };
+
+            // Slide the window one column to the right at a time
+            new_packed_row.iter_mut().for_each(|word| *word = 0);
+            let mut window = column(0) << 1 | column(1) << 2;
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                window = (window >> 1) & 0b011_011_011 | column(x + 2) << 2;
+                *new_cell = self.table[window];
+                new_packed_row[(x + 1) / 64] |= (new_cell.0 as u64) << ((x + 1) % 64);
+            }
+
+            // Count the changes and the population a word at a time, the
+            // loops compile to vectorized popcounts
+            for ((old, new), inner) in current.iter().zip(new_packed_row.iter()).zip(inner.iter()) {
+                let old = old & inner;
+                stats.births += (new & !old).count_ones() as usize;
+                stats.deaths += (old & !new).count_ones() as usize;
+                population += new.count_ones() as usize;
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+        stats.population = Some(population);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        2
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]]
+    }
+
+    fn get_memory_usage(&self) -> usize {
+        core::mem::size_of_val(&self.table)
+    }
+}
+
+/// Make a cell active for the next step of a `CountingLifeLike`
+///
+/// @param active The active cells
+/// @param is_active Whether each cell is in the active cells
+/// @param index Index of the cell
+fn activate(active: &mut Vec<usize>, is_active: &mut [bool], index: usize) {
+    if !is_active[index] {
+        is_active[index] = true;
+        active.push(index);
+    }
+}
+
+/// The indexes of the neighbors of the cells of a grid, resolved once per row
+/// and column
+struct Neighborhood {
+    /// Index of the first cell of the previous, current and next rows of each
+    /// row, None beyond the edges which do not wrap
+    rows: Vec<[Option<usize>; 3]>,
+    /// The previous, current and next columns of each column, None beyond the
+    /// edges which do not wrap
+    columns: Vec<[Option<usize>; 3]>,
+}
+
+impl Neighborhood {
+    /// Resolve the neighbors of the cells of a grid
+    ///
+    /// @param grid The grid
+    fn new(grid: &Grid) -> Self {
+        let width = grid.get_width();
+        if width == 0 || grid.get_height() == 0 {
+            return Self {
+                rows: Vec::new(),
+                columns: Vec::new(),
+            };
+        }
+
+        Self {
+            rows: (0..grid.get_height())
+                .map(|y| {
+                    [
+                        grid.get_neighbor(0, y, 0, -1).map(|(_, y)| y * width),
+                        Some(y * width),
+                        grid.get_neighbor(0, y, 0, 1).map(|(_, y)| y * width),
+                    ]
+                })
+                .collect(),
+            columns: (0..width)
+                .map(|x| {
+                    [
+                        grid.get_neighbor(x, 0, -1, 0).map(|(x, _)| x),
+                        Some(x),
+                        grid.get_neighbor(x, 0, 1, 0).map(|(x, _)| x),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the indexes of the neighbors of a cell
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = usize> + '_ {
+        let rows = &self.rows[y];
+        let columns = &self.columns[x];
+        (0..3)
+            .flat_map(|row_index| (0..3).map(move |column_index| (row_index, column_index)))
+            .filter(|position| *position != (1, 1))
+            .filter_map(move |(row_index, column_index)| {
+                Some(rows[row_index]? + columns[column_index]?)
+            })
+    }
+
+    /// Count the alive neighbors of a cell
+    ///
+    /// @param tiles The tiles of the grid
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    fn count(&self, tiles: &[CellState], x: usize, y: usize) -> usize {
+        let mut neighbors_count = 0;
+        for (row_index, row) in self.rows[y].iter().enumerate() {
+            for (column_index, column) in self.columns[x].iter().enumerate() {
+                if let (Some(row), Some(column)) = (row, column) {
+                    if (row_index, column_index) != (1, 1) {
+                        neighbors_count += tiles[row + column].0 as usize;
+                    }
+                }
+            }
+        }
+
+        neighbors_count
+    }
+
+    /// Get the number of bytes taken by the neighbors
+    fn get_memory_usage(&self) -> usize {
+        (self.rows.capacity() + self.columns.capacity())
+            * core::mem::size_of::<[Option<usize>; 3]>()
+    }
+}
+
+/// Compute the next state of a cell, counting the change
+///
+/// @param rule The rule
+/// @param cell_state The current state of the cell
+/// @param neighbors_count The number of alive neighbors of the cell
+/// @param stats The changes made by the step so far
+fn next_state(
+    rule: &Rule,
+    cell_state: CellState,
+    neighbors_count: usize,
+    stats: &mut UpdateStats,
+) -> CellState {
+    let new_state = if (cell_state == CellState::ALIVE && rule.is_survival(neighbors_count))
+        || (cell_state == CellState::DEAD && rule.is_birth(neighbors_count))
+    {
+        CellState::ALIVE
+    } else {
+        CellState::DEAD
+    };
+
+    if new_state != cell_state {
+        if new_state == CellState::ALIVE {
+            stats.births += 1;
+        } else {
+            stats.deaths += 1;
+        }
+    }
+
+    new_state
+}
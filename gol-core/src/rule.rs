@@ -0,0 +1,240 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use rand::Rng;
+
+/// An error occurring while parsing a rule
+#[derive(Debug)]
+pub struct ParseRuleError(String);
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid rule {}", self.0)
+    }
+}
+
+impl core::error::Error for ParseRuleError {}
+
+/// A Life-like rule, telling how many alive neighbors make a cell be born or
+/// survive
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rule {
+    /// Whether a dead cell with n alive neighbors is born
+    birth: [bool; 9],
+    /// Whether an alive cell with n alive neighbors survives
+    survival: [bool; 9],
+}
+
+impl Rule {
+    /// Create a rule from the neighbor counts
+    ///
+    /// @param birth Neighbor counts making a dead cell be born
+    /// @param survival Neighbor counts making an alive cell survive
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        let mut rule = Self {
+            birth: [false; 9],
+            survival: [false; 9],
+        };
+        for count in birth.iter().filter(|count| **count <= 8) {
+            rule.birth[*count] = true;
+        }
+        for count in survival.iter().filter(|count| **count <= 8) {
+            rule.survival[*count] = true;
+        }
+
+        rule
+    }
+
+    /// Draw a random rule
+    ///
+    /// Every neighbor count makes a cell be born or survive with an even
+    /// chance, except births without alive neighbor which would fill the
+    /// world with alive cells.
+    ///
+    /// @param rng The random number generator
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        let mut rule = Self::new(&[], &[]);
+        for count in 1..9 {
+            rule.birth[count] = rng.gen();
+        }
+        for count in 0..9 {
+            rule.survival[count] = rng.gen();
+        }
+
+        rule
+    }
+
+    /// Get the common name of the rule, if it is one of the curated rules
+    pub fn get_name(&self) -> Option<&'static str> {
+        CURATED_RULES
+            .iter()
+            .find(|(_, rule)| rule.parse::<Rule>().ok().as_ref() == Some(self))
+            .map(|(name, _)| *name)
+    }
+
+    /// Make a dead cell with this many alive neighbors be born or not
+    ///
+    /// @param neighbors_count The number of alive neighbors
+    /// @param birth Whether the cell is born
+    pub fn set_birth(&mut self, neighbors_count: usize, birth: bool) {
+        self.birth[neighbors_count] = birth;
+    }
+
+    /// Make an alive cell with this many alive neighbors survive or not
+    ///
+    /// @param neighbors_count The number of alive neighbors
+    /// @param survival Whether the cell survives
+    pub fn set_survival(&mut self, neighbors_count: usize, survival: bool) {
+        self.survival[neighbors_count] = survival;
+    }
+
+    /// Whether a dead cell with this many alive neighbors is born
+    pub fn is_birth(&self, neighbors_count: usize) -> bool {
+        self.birth[neighbors_count]
+    }
+
+    /// Whether an alive cell with this many alive neighbors survives
+    pub fn is_survival(&self, neighbors_count: usize) -> bool {
+        self.survival[neighbors_count]
+    }
+}
+
+/// Famous Life-like rules with interesting behaviors, as name and rule
+pub const CURATED_RULES: &[(&str, &str)] = &[
+    ("Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day & Night", "B3678/S34678"),
+    ("Seeds", "B2/S"),
+    ("Life without Death", "B3/S012345678"),
+    ("Maze", "B3/S12345"),
+    ("Mazectric", "B3/S1234"),
+    ("Coral", "B3/S45678"),
+    ("34 Life", "B34/S34"),
+    ("2x2", "B36/S125"),
+    ("Move", "B368/S245"),
+    ("Replicator", "B1357/S1357"),
+    ("Diamoeba", "B35678/S5678"),
+    ("Anneal", "B4678/S35678"),
+    ("DryLife", "B37/S23"),
+    ("Pedestrian Life", "B38/S23"),
+    ("Long Life", "B345/S5"),
+    ("Amoeba", "B357/S1358"),
+];
+
+/// An error occurring while parsing a pool of rules
+#[derive(Debug)]
+pub struct ParseRulePoolError(String);
+
+impl fmt::Display for ParseRulePoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid rule pool {}", self.0)
+    }
+}
+
+impl core::error::Error for ParseRulePoolError {}
+
+/// Where random rules are picked from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RulePool {
+    /// The curated famous rules
+    Curated,
+    /// The whole rule space, see `Rule::random`
+    Uniform,
+}
+
+impl RulePool {
+    /// Pick a random rule from the pool
+    ///
+    /// @param rng The random number generator
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> Rule {
+        match self {
+            RulePool::Curated => CURATED_RULES[rng.gen_range(0, CURATED_RULES.len())]
+                .1
+                .parse()
+                .unwrap(),
+            RulePool::Uniform => Rule::random(rng),
+        }
+    }
+}
+
+impl FromStr for RulePool {
+    type Err = ParseRulePoolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "curated" => Ok(RulePool::Curated),
+            "uniform" => Ok(RulePool::Uniform),
+            _ => Err(ParseRulePoolError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for RulePool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulePool::Curated => write!(f, "curated"),
+            RulePool::Uniform => write!(f, "uniform"),
+        }
+    }
+}
+
+impl Default for Rule {
+    /// Conway's Game of Life
+    fn default() -> Self {
+        Self::new(&[3], &[2, 3])
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parse a rule in the B/S notation (e.g. `B3/S23`) or in the S/B
+    /// notation (e.g. `23/3`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseRuleError(s.to_string());
+        let parse_counts = |counts: &str| {
+            counts
+                .chars()
+                .map(|c| c.to_digit(9).map(|count| count as usize))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(invalid)
+        };
+
+        let (first, second) = s.split_once('/').ok_or_else(invalid)?;
+        let first_upper = first.to_ascii_uppercase();
+        let second_upper = second.to_ascii_uppercase();
+
+        let (birth, survival) = if let (Some(birth), Some(survival)) = (
+            first_upper.strip_prefix('B'),
+            second_upper.strip_prefix('S'),
+        ) {
+            (birth.to_string(), survival.to_string())
+        } else if let (Some(survival), Some(birth)) = (
+            first_upper.strip_prefix('S'),
+            second_upper.strip_prefix('B'),
+        ) {
+            (birth.to_string(), survival.to_string())
+        } else {
+            // S/B notation
+            (second.to_string(), first.to_string())
+        };
+
+        Ok(Self::new(&parse_counts(&birth)?, &parse_counts(&survival)?))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B")?;
+        for count in (0..9).filter(|count| self.birth[*count]) {
+            write!(f, "{}", count)?;
+        }
+        write!(f, "/S")?;
+        for count in (0..9).filter(|count| self.survival[*count]) {
+            write!(f, "{}", count)?;
+        }
+
+        Ok(())
+    }
+}
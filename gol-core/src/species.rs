@@ -0,0 +1,218 @@
+//! Two species competing for the space
+//!
+//! The cells are dead, red or blue. The Life-like rule applies to the alive
+//! neighbors whatever their species, with two twists: a cell is born of the
+//! species of the majority of its alive neighbors, and never when they are
+//! as many of each, and an alive cell surrounded by more enemies than allies
+//! dies, so the mixed crowded regions die out.
+//!
+//! Immigration is the gentler variant: the cells are born the same way but
+//! survive whatever the species of their neighbors, so the species only
+//! spread by births.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::CellState;
+use crate::grid::Grid;
+use crate::rule::Rule;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A cell of the red species
+pub const RED: CellState = CellState(1);
+
+/// A cell of the blue species
+pub const BLUE: CellState = CellState(2);
+
+/// Two species following a Life-like rule and competing for the space
+pub struct TwoSpecies {
+    /// The rule of both species
+    rule: Rule,
+}
+
+impl TwoSpecies {
+    /// Create the automaton
+    ///
+    /// @param rule The rule of both species
+    pub fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+}
+
+impl Default for TwoSpecies {
+    fn default() -> Self {
+        Self::new(Rule::default())
+    }
+}
+
+impl Automaton for TwoSpecies {
+    fn name(&self) -> String {
+        format!("species {}", self.rule)
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = vec![CellState::DEAD; grid.get_tiles().len()];
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                let (reds, blues) = count_species(grid, x, y);
+
+                let cell_state = grid.get_tile(x, y);
+                *new_cell = match cell_state {
+                    RED if self.rule.is_survival(reds + blues) && reds >= blues => RED,
+                    BLUE if self.rule.is_survival(reds + blues) && blues >= reds => BLUE,
+                    RED | BLUE => {
+                        stats.deaths += 1;
+                        CellState::DEAD
+                    }
+                    _ if self.rule.is_birth(reds + blues) && reds != blues => {
+                        stats.births += 1;
+                        if reds > blues {
+                            RED
+                        } else {
+                            BLUE
+                        }
+                    }
+                    _ => CellState::DEAD,
+                };
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        3
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![
+            [1.0, 1.0, 1.0, 1.0],
+            [0.85, 0.1, 0.1, 1.0],
+            [0.1, 0.25, 0.85, 1.0],
+        ]
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        vec!["dead".to_string(), "red".to_string(), "blue".to_string()]
+    }
+
+    /// The soups are made of both species
+    fn seed_states(&self) -> Vec<CellState> {
+        vec![RED, BLUE]
+    }
+}
+
+/// Two species following a Life-like rule, the alive cells keeping their
+/// species whatever their neighbors
+pub struct Immigration {
+    /// The rule of both species
+    rule: Rule,
+}
+
+impl Immigration {
+    /// Create the automaton
+    ///
+    /// @param rule The rule of both species
+    pub fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+}
+
+impl Default for Immigration {
+    fn default() -> Self {
+        Self::new(Rule::default())
+    }
+}
+
+impl Automaton for Immigration {
+    fn name(&self) -> String {
+        format!("immigration {}", self.rule)
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = vec![CellState::DEAD; grid.get_tiles().len()];
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                let (reds, blues) = count_species(grid, x, y);
+
+                let cell_state = grid.get_tile(x, y);
+                *new_cell = match cell_state {
+                    RED | BLUE if self.rule.is_survival(reds + blues) => cell_state,
+                    RED | BLUE => {
+                        stats.deaths += 1;
+                        CellState::DEAD
+                    }
+                    _ if self.rule.is_birth(reds + blues) && reds != blues => {
+                        stats.births += 1;
+                        if reds > blues {
+                            RED
+                        } else {
+                            BLUE
+                        }
+                    }
+                    _ => CellState::DEAD,
+                };
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        3
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        TwoSpecies::default().palette()
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        TwoSpecies::default().state_names()
+    }
+
+    /// The soups are made of both species
+    fn seed_states(&self) -> Vec<CellState> {
+        vec![RED, BLUE]
+    }
+}
+
+/// Count the red and the blue neighbors of a cell
+fn count_species(grid: &Grid, x: usize, y: usize) -> (usize, usize) {
+    let (mut reds, mut blues) = (0, 0);
+    for (dx, dy) in [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+    ] {
+        // The neighbors beyond the edges which do not wrap are dead
+        if let Some((x, y)) = grid.get_neighbor(x, y, dx, dy) {
+            match grid.get_tile(x, y) {
+                RED => reds += 1,
+                BLUE => blues += 1,
+                _ => {}
+            }
+        }
+    }
+
+    (reds, blues)
+}
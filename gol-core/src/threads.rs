@@ -0,0 +1,84 @@
+//! Number of threads and their cores
+//!
+//! The parallel engine and the batches run on every core by default. Their
+//! number of threads can be set, and each thread pinned to one of the cores
+//! the process may run on, so a benchmark does not depend on how the system
+//! moves the threads around, and a run started with `taskset -c 0-3` stays
+//! on these four cores.
+//!
+//! Pinning is only supported on Linux, the threads are left free elsewhere.
+
+/// How many threads to run and whether to pin them
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct ThreadOptions {
+    /// Number of threads, None for one per core
+    pub count: Option<usize>,
+    /// Whether to pin each thread to a core
+    pub pin: bool,
+}
+
+impl ThreadOptions {
+    /// Get the number of threads, one per core if not given
+    pub fn get_count(&self) -> usize {
+        self.count.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, |threads| threads.get())
+        })
+    }
+
+    /// Get the cores to pin the threads to, in order, none if not pinning
+    pub fn get_cores(&self) -> Vec<usize> {
+        if self.pin {
+            get_allowed_cores()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Get the cores the process may run on
+#[cfg(target_os = "linux")]
+pub fn get_allowed_cores() -> Vec<usize> {
+    // The set is plain data, filled by the call
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let result =
+        unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) };
+    if result != 0 {
+        return Vec::new();
+    }
+
+    (0..libc::CPU_SETSIZE as usize)
+        .filter(|core| unsafe { libc::CPU_ISSET(*core, &set) })
+        .collect()
+}
+
+/// Get the cores the process may run on
+#[cfg(not(target_os = "linux"))]
+pub fn get_allowed_cores() -> Vec<usize> {
+    Vec::new()
+}
+
+/// Pin the current thread to a core
+///
+/// The threads beyond the number of cores share them in turn. Does nothing
+/// without cores.
+///
+/// @param cores The cores, as given by `ThreadOptions::get_cores`
+/// @param index Index of the thread
+pub fn pin_current_thread(cores: &[usize], index: usize) {
+    if let Some(core) = cores.get(index % cores.len().max(1)) {
+        pin_to_core(*core);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn pin_to_core(core: usize) {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    unsafe {
+        libc::CPU_SET(core, &mut set);
+        // A thread which cannot be pinned simply runs anywhere
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_to_core(_core: usize) {}
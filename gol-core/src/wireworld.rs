@@ -0,0 +1,103 @@
+//! Wireworld, electrons running along wires
+//!
+//! The cells are empty, wires, electron heads or electron tails. A head
+//! becomes a tail, a tail becomes a wire again, and a wire becomes a head
+//! when one or two of its neighbors are heads, so the electrons move along
+//! the wires, and circuits of wires compute as logic gates do. Nothing is
+//! ever born on the empty cells.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::CellState;
+use crate::grid::Grid;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A cell of wire
+pub const WIRE: CellState = CellState(1);
+
+/// The head of an electron
+pub const HEAD: CellState = CellState(2);
+
+/// The tail of an electron
+pub const TAIL: CellState = CellState(3);
+
+/// The Wireworld automaton
+#[derive(Default)]
+pub struct Wireworld;
+
+impl Automaton for Wireworld {
+    fn name(&self) -> String {
+        "wireworld".to_string()
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut new_tiles = grid.get_tiles().to_vec();
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                *new_cell = match grid.get_tile(x, y) {
+                    HEAD => TAIL,
+                    TAIL => WIRE,
+                    WIRE => {
+                        let heads = [
+                            (-1, -1),
+                            (0, -1),
+                            (1, -1),
+                            (-1, 0),
+                            (1, 0),
+                            (-1, 1),
+                            (0, 1),
+                            (1, 1),
+                        ]
+                        .iter()
+                        // The neighbors beyond the edges which do not wrap
+                        // are empty
+                        .filter_map(|(dx, dy)| grid.get_neighbor(x, y, *dx, *dy))
+                        .filter(|(x, y)| grid.get_tile(*x, *y) == HEAD)
+                        .count();
+                        if heads == 1 || heads == 2 {
+                            HEAD
+                        } else {
+                            WIRE
+                        }
+                    }
+                    cell_state => cell_state,
+                };
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        // The cells never change between empty and not
+        UpdateStats::default()
+    }
+
+    fn states(&self) -> usize {
+        4
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![
+            [0.0, 0.0, 0.0, 1.0],
+            [0.9, 0.6, 0.1, 1.0],
+            [0.2, 0.5, 1.0, 1.0],
+            [1.0, 1.0, 1.0, 1.0],
+        ]
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        vec![
+            "empty".to_string(),
+            "wire".to_string(),
+            "head".to_string(),
+            "tail".to_string(),
+        ]
+    }
+
+    /// The soups are wires with a few electrons, the patterns are wires
+    fn seed_states(&self) -> Vec<CellState> {
+        vec![WIRE, WIRE, WIRE, WIRE, WIRE, WIRE, WIRE, HEAD]
+    }
+}
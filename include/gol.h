@@ -0,0 +1,129 @@
+#ifndef GOL_H
+#define GOL_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A world
+ */
+typedef struct GolWorld GolWorld;
+
+/**
+ * Create a world of dead cells
+ *
+ * Returns null if the world cannot be allocated. The world must be released
+ * with `gol_world_free`.
+ */
+struct GolWorld *gol_world_new(size_t width, size_t height);
+
+/**
+ * Release a world
+ *
+ * # Safety
+ *
+ * `world` must be null or a pointer returned by `gol_world_new` which has
+ * not been released yet.
+ */
+void gol_world_free(struct GolWorld *world);
+
+/**
+ * Get the width of a world
+ *
+ * # Safety
+ *
+ * `world` must be a valid world.
+ */
+size_t gol_world_width(const struct GolWorld *world);
+
+/**
+ * Get the height of a world
+ *
+ * # Safety
+ *
+ * `world` must be a valid world.
+ */
+size_t gol_world_height(const struct GolWorld *world);
+
+/**
+ * Populate a world randomly
+ *
+ * Returns 0 on success, -1 if the density is not within 0 and 1.
+ *
+ * # Safety
+ *
+ * `world` must be a valid world.
+ */
+int gol_world_populate(struct GolWorld *world, float density);
+
+/**
+ * Update a world to its next generation
+ *
+ * Returns 0 on success, -1 if the engine failed, leaving the cells of the
+ * world unspecified.
+ *
+ * # Safety
+ *
+ * `world` must be a valid world.
+ */
+int gol_world_step(struct GolWorld *world);
+
+/**
+ * Count the alive cells of a world
+ *
+ * # Safety
+ *
+ * `world` must be a valid world.
+ */
+size_t gol_world_population(const struct GolWorld *world);
+
+/**
+ * Get the state of a cell, 0 for the cells out of the world
+ *
+ * # Safety
+ *
+ * `world` must be a valid world.
+ */
+uint8_t gol_world_get_cell(const struct GolWorld *world, size_t x, size_t y);
+
+/**
+ * Set the state of a cell
+ *
+ * Returns 0 on success, -1 if the cell is out of the world.
+ *
+ * # Safety
+ *
+ * `world` must be a valid world.
+ */
+int gol_world_set_cell(struct GolWorld *world, size_t x, size_t y, uint8_t alive);
+
+/**
+ * Copy the cells of a world, row major, one byte per cell
+ *
+ * Returns the number of cells copied, or 0 if the buffer cannot hold
+ * width * height cells.
+ *
+ * # Safety
+ *
+ * `world` must be a valid world and `buffer` must be valid for writes of
+ * `length` bytes.
+ */
+size_t gol_world_get_cells(const struct GolWorld *world, uint8_t *buffer, size_t length);
+
+/**
+ * Copy a pattern in the RLE format into a world, wrapping around the edges
+ *
+ * Returns 0 on success, -1 if the pattern is invalid or cannot be stamped.
+ *
+ * # Safety
+ *
+ * `world` must be a valid world and `rle` a valid null terminated string.
+ */
+int gol_world_stamp_rle(struct GolWorld *world, const char *rle, size_t x, size_t y);
+
+#endif  /* GOL_H */
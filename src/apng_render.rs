@@ -0,0 +1,100 @@
+use crate::raster::Rasterizer;
+use crate::render::{ColorOptions, Render, RenderError, RenderOptions};
+use crate::world::World;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// How long each generation is shown in the animation, in milliseconds
+const FRAME_DELAY_MS: u16 = 100;
+
+/// A render writing the generations to an animated PNG file
+///
+/// Each cell is a pixel colored after the palette of the automaton, in
+/// 24-bit colors. The number of frames must be known before writing them, so
+/// the frames are kept in memory and the file is written when the render is
+/// dropped, at the end of the run. The repeated frames, such as the ones
+/// rendered while paused, are only kept once.
+pub struct ApngRenderType {
+    /// Path of the file
+    path: PathBuf,
+    /// The RGB pixels of the frames rendered so far
+    frames: Vec<Vec<u8>>,
+    /// Width of the frames
+    width: usize,
+    /// Height of the frames
+    height: usize,
+    /// Colors of the cells
+    rasterizer: Rasterizer,
+}
+
+impl ApngRenderType {
+    /// Create a render writing to a file
+    ///
+    /// @param path Path of the file
+    /// @param width Width of the world
+    /// @param height Height of the world
+    /// @param options Options of the render, for the colors of the cells
+    pub fn new(path: &Path, width: usize, height: usize, options: &RenderOptions) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            frames: Vec::new(),
+            width,
+            height,
+            rasterizer: Rasterizer::new(options),
+        }
+    }
+
+    /// Write the frames to the file
+    fn write(&self) -> Result<(), png::EncodingError> {
+        let file = BufWriter::new(File::create(&self.path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as u32, 0)?;
+        encoder.set_frame_delay(FRAME_DELAY_MS, 1000)?;
+
+        let mut writer = encoder.write_header()?;
+        for pixels in self.frames.iter() {
+            writer.write_image_data(pixels)?;
+        }
+
+        writer.finish()
+    }
+}
+
+impl Render for ApngRenderType {
+    fn render(&mut self, world: &World) -> Result<(), RenderError> {
+        // The world may only change in its states, not in its size
+        if world.get_width() != self.width || world.get_height() != self.height {
+            return Ok(());
+        }
+
+        self.rasterizer.update(world);
+        let pixels = self
+            .rasterizer
+            .rasterize(world, world.get_bounds(), 1, 1.0)
+            .to_rgb();
+        if self.frames.last() != Some(&pixels) {
+            self.frames.push(pixels);
+        }
+
+        Ok(())
+    }
+
+    fn set_colors(&mut self, colors: ColorOptions) {
+        self.rasterizer.set_colors(colors);
+    }
+}
+
+impl Drop for ApngRenderType {
+    fn drop(&mut self) {
+        if self.frames.is_empty() {
+            return;
+        }
+
+        if let Err(err) = self.write() {
+            eprintln!("error: cannot write {}: {}", self.path.display(), err);
+        }
+    }
+}
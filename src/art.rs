@@ -0,0 +1,218 @@
+//! Colors cycling with the age of the cells and the generations
+//!
+//! In art mode, the alive cells are not drawn in the color of their state
+//! but in a color of a gradient, picked after how long the cell has been
+//! alive and shifted at each generation, so the whole world cycles through
+//! the palette while the still lifes and the fresh growth stand out from
+//! each other.
+
+use crate::automaton::Color;
+use crate::world::{CellState, World};
+use std::fmt;
+use std::str::FromStr;
+
+/// How far along the gradient the colors move at each generation
+const GENERATION_SHIFT: f32 = 0.01;
+
+/// How far along the gradient the colors of two cells one generation apart
+/// in age are
+const AGE_SHIFT: f32 = 0.05;
+
+/// An error occurring while parsing the name of an art palette
+#[derive(Debug)]
+pub struct ParseArtPaletteError(String);
+
+impl fmt::Display for ParseArtPaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown palette {} (available {})",
+            self.0,
+            ArtPalette::all()
+                .iter()
+                .map(|palette| palette.get_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseArtPaletteError {}
+
+/// A gradient the colors of the cells cycle through
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ArtPalette {
+    /// The hues of the color wheel
+    #[default]
+    Rainbow,
+    /// From dark red to yellow and white
+    Fire,
+    /// From deep blue to cyan and foam
+    Ocean,
+    /// Saturated pink, purple and green
+    Neon,
+    /// Soft tints
+    Pastel,
+}
+
+impl ArtPalette {
+    /// Get all the available palettes
+    pub fn all() -> Vec<ArtPalette> {
+        vec![
+            ArtPalette::Rainbow,
+            ArtPalette::Fire,
+            ArtPalette::Ocean,
+            ArtPalette::Neon,
+            ArtPalette::Pastel,
+        ]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ArtPalette::Rainbow => "rainbow",
+            ArtPalette::Fire => "fire",
+            ArtPalette::Ocean => "ocean",
+            ArtPalette::Neon => "neon",
+            ArtPalette::Pastel => "pastel",
+        }
+    }
+
+    /// Get the colors of the gradient, evenly spaced, the last one blending
+    /// back into the first one
+    pub fn get_stops(&self) -> &'static [Color] {
+        match self {
+            ArtPalette::Rainbow => &[
+                [1.0, 0.0, 0.0, 1.0],
+                [1.0, 1.0, 0.0, 1.0],
+                [0.0, 1.0, 0.0, 1.0],
+                [0.0, 1.0, 1.0, 1.0],
+                [0.0, 0.0, 1.0, 1.0],
+                [1.0, 0.0, 1.0, 1.0],
+            ],
+            ArtPalette::Fire => &[
+                [0.5, 0.0, 0.0, 1.0],
+                [1.0, 0.3, 0.0, 1.0],
+                [1.0, 0.8, 0.0, 1.0],
+                [1.0, 1.0, 0.8, 1.0],
+            ],
+            ArtPalette::Ocean => &[
+                [0.0, 0.1, 0.4, 1.0],
+                [0.0, 0.4, 0.8, 1.0],
+                [0.0, 0.8, 0.9, 1.0],
+                [0.8, 1.0, 1.0, 1.0],
+            ],
+            ArtPalette::Neon => &[
+                [1.0, 0.1, 0.6, 1.0],
+                [0.6, 0.1, 1.0, 1.0],
+                [0.1, 1.0, 0.6, 1.0],
+            ],
+            ArtPalette::Pastel => &[
+                [1.0, 0.7, 0.7, 1.0],
+                [1.0, 0.9, 0.7, 1.0],
+                [0.7, 1.0, 0.8, 1.0],
+                [0.7, 0.8, 1.0, 1.0],
+                [0.9, 0.7, 1.0, 1.0],
+            ],
+        }
+    }
+
+    /// Get the color at a position of the gradient
+    ///
+    /// @param position The position, wrapping around every 1
+    pub fn get_color(&self, position: f32) -> Color {
+        let stops = self.get_stops();
+        let position = position.rem_euclid(1.0) * stops.len() as f32;
+        let index = (position as usize).min(stops.len() - 1);
+        let (from, to) = (stops[index], stops[(index + 1) % stops.len()]);
+        let ratio = position - index as f32;
+
+        let mut color = from;
+        for (component, to) in color.iter_mut().zip(to.iter()) {
+            *component += (to - *component) * ratio;
+        }
+
+        color
+    }
+}
+
+impl FromStr for ArtPalette {
+    type Err = ParseArtPaletteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ArtPalette::all()
+            .into_iter()
+            .find(|palette| palette.get_name() == s)
+            .ok_or_else(|| ParseArtPaletteError(s.to_string()))
+    }
+}
+
+impl fmt::Display for ArtPalette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+/// The colors of the cells of a world in art mode
+///
+/// The renders do not know the generations, so the colors are shifted at
+/// each frame whose cells changed.
+pub struct ArtColors {
+    /// The gradient
+    palette: ArtPalette,
+    /// The cells of the last frame
+    tiles: Vec<CellState>,
+    /// Generations since each cell is alive
+    ages: Vec<usize>,
+    /// Number of frames whose cells changed
+    generation: usize,
+}
+
+impl ArtColors {
+    /// Create the colors of a palette
+    ///
+    /// @param palette The gradient
+    pub fn new(palette: ArtPalette) -> Self {
+        Self {
+            palette,
+            tiles: Vec::new(),
+            ages: Vec::new(),
+            generation: 0,
+        }
+    }
+
+    /// Age the cells if the world changed since the last frame
+    ///
+    /// @param world The world about to be rendered
+    pub fn update(&mut self, world: &World) {
+        let tiles = world.get_tiles();
+        if self.tiles == tiles {
+            return;
+        }
+
+        // A world changing size starts over
+        if self.ages.len() != tiles.len() {
+            self.ages = vec![0; tiles.len()];
+        }
+        for (age, cell_state) in self.ages.iter_mut().zip(tiles.iter()) {
+            *age = if *cell_state == CellState::DEAD {
+                0
+            } else {
+                *age + 1
+            };
+        }
+        self.tiles = tiles.to_vec();
+        self.generation += 1;
+    }
+
+    /// Get the color of an alive cell, None for the dead cells, drawn in the
+    /// color of their state
+    ///
+    /// @param index Index of the cell, row major
+    pub fn get_color(&self, index: usize) -> Option<Color> {
+        let age = self.ages.get(index).copied().unwrap_or(0);
+        (age > 0).then(|| {
+            self.palette
+                .get_color(self.generation as f32 * GENERATION_SHIFT + age as f32 * AGE_SHIFT)
+        })
+    }
+}
@@ -0,0 +1,205 @@
+//! Triggers pausing the simulation on the events waited for
+//!
+//! A trigger fires when its event starts: when the population drops below
+//! a threshold, when the world settles into a cycle, when an object appears
+//! or when a generation is reached. Resumed, the simulation does not pause
+//! again for the same event, only once it ended and started again, e.g. when
+//! the population went back above the threshold and dropped again.
+//!
+//! The triggers are given as `population<N`, `period`, `pattern=PATTERN`,
+//! with a builtin pattern or a pattern file matched whatever its
+//! orientation, and `generation=N`.
+
+use crate::census::{census, get_object_name};
+use crate::pattern::{PatternError, PatternSource};
+use crate::stability::StabilityDetector;
+use crate::world::World;
+use std::fmt;
+use std::str::FromStr;
+
+/// An error occurring while parsing a trigger
+#[derive(Debug)]
+pub enum ParseTriggerError {
+    /// The trigger is not one of the known ones
+    Invalid(String),
+    /// The pattern to match cannot be loaded
+    Pattern(PatternError),
+}
+
+impl fmt::Display for ParseTriggerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseTriggerError::Invalid(trigger) => write!(
+                f,
+                "invalid trigger {} (available population<N, period, pattern=PATTERN, generation=N)",
+                trigger
+            ),
+            ParseTriggerError::Pattern(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseTriggerError {}
+
+impl From<PatternError> for ParseTriggerError {
+    fn from(err: PatternError) -> Self {
+        ParseTriggerError::Pattern(err)
+    }
+}
+
+/// An event to pause the simulation on
+#[derive(Clone, Debug, PartialEq)]
+pub enum PauseTrigger {
+    /// The population drops below the given number of cells
+    PopulationBelow(usize),
+    /// The world settles into a cycle
+    Period,
+    /// An object appears, named as by the census
+    Pattern(String),
+    /// The given generation is reached
+    Generation(usize),
+}
+
+impl FromStr for PauseTrigger {
+    type Err = ParseTriggerError;
+
+    /// Parse `population<N`, `period`, `pattern=PATTERN` or `generation=N`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseTriggerError::Invalid(s.to_string());
+
+        if s == "period" {
+            Ok(PauseTrigger::Period)
+        } else if let Some(population) = s.strip_prefix("population<") {
+            Ok(PauseTrigger::PopulationBelow(
+                population.parse().map_err(|_| invalid())?,
+            ))
+        } else if let Some(generation) = s.strip_prefix("generation=") {
+            Ok(PauseTrigger::Generation(
+                generation.parse().map_err(|_| invalid())?,
+            ))
+        } else if let Some(pattern) = s.strip_prefix("pattern=") {
+            let pattern = pattern.parse::<PatternSource>()?.load()?;
+
+            Ok(PauseTrigger::Pattern(get_object_name(&pattern)))
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// Why the simulation paused
+#[derive(Clone, Debug, PartialEq)]
+pub enum PauseEvent {
+    /// The population dropped below the threshold
+    PopulationBelow(usize, usize),
+    /// The world settled into a cycle of the given period
+    Period(usize),
+    /// The object appeared
+    Pattern(String),
+    /// The generation was reached
+    Generation(usize),
+}
+
+impl fmt::Display for PauseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PauseEvent::PopulationBelow(population, threshold) => {
+                write!(f, "population {} below {}", population, threshold)
+            }
+            PauseEvent::Period(1) => write!(f, "stabilized"),
+            PauseEvent::Period(period) => write!(f, "period {} detected", period),
+            PauseEvent::Pattern(name) => write!(f, "{} found", name),
+            PauseEvent::Generation(generation) => write!(f, "generation {} reached", generation),
+        }
+    }
+}
+
+/// Watches the generations for the events of the triggers
+pub struct AutoPause {
+    /// The triggers, with whether their event is going on
+    triggers: Vec<(PauseTrigger, bool)>,
+    /// Detects the cycles, if a trigger waits for them
+    stability: Option<StabilityDetector>,
+}
+
+impl AutoPause {
+    /// Watch for the events of triggers
+    ///
+    /// @param triggers The triggers
+    pub fn new(triggers: Vec<PauseTrigger>) -> Self {
+        let stability = triggers
+            .contains(&PauseTrigger::Period)
+            .then(StabilityDetector::default);
+
+        Self {
+            triggers: triggers
+                .into_iter()
+                .map(|trigger| (trigger, false))
+                .collect(),
+            stability,
+        }
+    }
+
+    /// Look at a generation of the world
+    ///
+    /// Returns the events which started with this generation, the simulation
+    /// should pause if there are any.
+    ///
+    /// @param world The world
+    /// @param generation Its generation
+    pub fn check(&mut self, world: &World, generation: usize) -> Vec<PauseEvent> {
+        if self.triggers.is_empty() {
+            return Vec::new();
+        }
+
+        let population = world.get_population();
+        let period = self
+            .stability
+            .as_mut()
+            .and_then(|stability| stability.update(world));
+        // The census is long on large worlds, only take it when needed
+        let objects = self
+            .triggers
+            .iter()
+            .any(|(trigger, _)| matches!(trigger, PauseTrigger::Pattern(_)))
+            .then(|| census(world));
+
+        let mut events = Vec::new();
+        for (trigger, active) in self.triggers.iter_mut() {
+            let event = match trigger {
+                PauseTrigger::PopulationBelow(threshold) => (population < *threshold)
+                    .then_some(PauseEvent::PopulationBelow(population, *threshold)),
+                PauseTrigger::Period => period.map(PauseEvent::Period),
+                PauseTrigger::Pattern(name) => objects
+                    .as_ref()
+                    .filter(|objects| objects.contains_key(name.as_str()))
+                    .map(|_| PauseEvent::Pattern(name.clone())),
+                PauseTrigger::Generation(target) => {
+                    (generation >= *target).then_some(PauseEvent::Generation(*target))
+                }
+            };
+
+            // Only the events which just started pause the simulation
+            if let Some(event) = event {
+                if !*active {
+                    events.push(event);
+                }
+                *active = true;
+            } else {
+                *active = false;
+            }
+        }
+
+        events
+    }
+
+    /// Forget the events going on, as when the world starts over
+    pub fn reset(&mut self) {
+        for (_, active) in self.triggers.iter_mut() {
+            *active = false;
+        }
+        if let Some(stability) = self.stability.as_mut() {
+            stability.reset();
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! The cellular automata selectable from the command line
+
+use crate::automaton::Automaton;
+use crate::cyclic::{Cyclic, DEFAULT_STATES};
+use crate::forest_fire::{ForestFire, DEFAULT_GROWTH, DEFAULT_LIGHTNING};
+use crate::generations::Generations;
+use crate::life::LifeLike;
+use crate::rule::Rule;
+use crate::sir::{Sir, DEFAULT_INFECTION, DEFAULT_RECOVERY};
+use crate::species::{Immigration, TwoSpecies};
+use crate::wireworld::Wireworld;
+use std::fmt;
+use std::str::FromStr;
+
+/// An error occurring while parsing the name of an automaton
+#[derive(Debug)]
+pub struct ParseAutomatonTypeError(String);
+
+impl fmt::Display for ParseAutomatonTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown automaton {} (available {})",
+            self.0,
+            AutomatonType::all()
+                .iter()
+                .map(|automaton_type| automaton_type.get_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseAutomatonTypeError {}
+
+/// A kind of cellular automaton
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum AutomatonType {
+    /// A Life-like rule
+    #[default]
+    Life,
+    /// Two species competing under a Life-like rule
+    TwoSpecies,
+    /// An epidemic with susceptible, infected and recovered cells
+    Sir,
+    /// Trees growing and burning
+    ForestFire,
+    /// States following each other in a cycle
+    Cyclic,
+    /// A Life-like rule with dying states
+    Generations,
+    /// Electrons running along wires
+    Wireworld,
+    /// Two species under a Life-like rule, keeping their species
+    Immigration,
+}
+
+/// Parameters of the automata, ignored by the automata not using them
+#[derive(Clone, Copy, Debug)]
+pub struct AutomatonOptions {
+    /// The Life-like rule
+    pub rule: Rule,
+    /// Probability of catching the disease from an infected neighbor (SIR)
+    pub infection: f32,
+    /// Probability of recovering at each step (SIR)
+    pub recovery: f32,
+    /// Probability of a tree growing on an empty cell at each step (forest
+    /// fire)
+    pub growth: f32,
+    /// Probability of a tree being struck by lightning at each step (forest
+    /// fire)
+    pub lightning: f32,
+    /// Number of states (cyclic, generations)
+    pub states: usize,
+    /// Seed of the random number generator of the stochastic automata
+    pub seed: u64,
+}
+
+impl Default for AutomatonOptions {
+    fn default() -> Self {
+        Self {
+            rule: Rule::default(),
+            infection: DEFAULT_INFECTION,
+            recovery: DEFAULT_RECOVERY,
+            growth: DEFAULT_GROWTH,
+            lightning: DEFAULT_LIGHTNING,
+            states: DEFAULT_STATES,
+            seed: 0,
+        }
+    }
+}
+
+impl AutomatonType {
+    /// Get all the available automata
+    pub fn all() -> Vec<AutomatonType> {
+        vec![
+            AutomatonType::Life,
+            AutomatonType::TwoSpecies,
+            AutomatonType::Sir,
+            AutomatonType::ForestFire,
+            AutomatonType::Cyclic,
+            AutomatonType::Generations,
+            AutomatonType::Wireworld,
+            AutomatonType::Immigration,
+        ]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            AutomatonType::Life => "life",
+            AutomatonType::TwoSpecies => "species",
+            AutomatonType::Sir => "sir",
+            AutomatonType::ForestFire => "fire",
+            AutomatonType::Cyclic => "cyclic",
+            AutomatonType::Generations => "generations",
+            AutomatonType::Wireworld => "wireworld",
+            AutomatonType::Immigration => "immigration",
+        }
+    }
+
+    /// Create an automaton of this kind
+    ///
+    /// @param options Parameters of the automaton
+    pub fn create(&self, options: &AutomatonOptions) -> Box<dyn Automaton> {
+        match self {
+            AutomatonType::Life => Box::new(LifeLike::new(options.rule)),
+            AutomatonType::TwoSpecies => Box::new(TwoSpecies::new(options.rule)),
+            AutomatonType::Sir => {
+                Box::new(Sir::new(options.infection, options.recovery, options.seed))
+            }
+            AutomatonType::ForestFire => Box::new(ForestFire::new(
+                options.growth,
+                options.lightning,
+                options.seed,
+            )),
+            AutomatonType::Cyclic => Box::new(Cyclic::new(options.states)),
+            AutomatonType::Generations => Box::new(Generations::new(options.rule, options.states)),
+            AutomatonType::Wireworld => Box::new(Wireworld),
+            AutomatonType::Immigration => Box::new(Immigration::new(options.rule)),
+        }
+    }
+}
+
+impl FromStr for AutomatonType {
+    type Err = ParseAutomatonTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AutomatonType::all()
+            .into_iter()
+            .find(|automaton_type| automaton_type.get_name() == s)
+            .ok_or_else(|| ParseAutomatonTypeError(s.to_string()))
+    }
+}
+
+impl fmt::Display for AutomatonType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
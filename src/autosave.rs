@@ -0,0 +1,121 @@
+//! Periodic snapshots of long runs
+//!
+//! Every interval of wall clock time or of generations, the session is saved
+//! to a new snapshot named after its generation, as `autosave-1200.gols` for
+//! the path `autosave.gols`, and the oldest ones beyond the number to keep
+//! are removed. A snapshot is written to a temporary file first and renamed
+//! once complete, so a crash while saving never leaves a truncated one, and a
+//! crash or a power loss loses at most one interval.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Number of snapshots kept when not given
+pub const DEFAULT_KEEP: usize = 3;
+
+/// Decides when to save the snapshots and rotates them
+pub struct Autosave {
+    /// Path the names of the snapshots derive from
+    path: PathBuf,
+    /// Number of snapshots to keep
+    keep: usize,
+    /// Wall clock time between two snapshots
+    interval: Option<Duration>,
+    /// Generations between two snapshots
+    generations: Option<usize>,
+    /// When the last snapshot was saved, or the autosave created
+    last_save: Instant,
+    /// Generation of the last snapshot
+    last_generation: Option<usize>,
+    /// The snapshots saved, oldest first
+    saved: VecDeque<PathBuf>,
+}
+
+impl Autosave {
+    /// Create an autosave
+    ///
+    /// @param path Path the names of the snapshots derive from
+    /// @param keep Number of snapshots to keep, at least 1
+    /// @param interval Wall clock time between two snapshots, if any
+    /// @param generations Generations between two snapshots, if any
+    pub fn new(
+        path: &Path,
+        keep: usize,
+        interval: Option<Duration>,
+        generations: Option<usize>,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            keep: keep.max(1),
+            interval,
+            generations,
+            last_save: Instant::now(),
+            last_generation: None,
+            saved: VecDeque::new(),
+        }
+    }
+
+    /// Whether a snapshot is due, once per generation at most
+    ///
+    /// @param generation The current generation
+    pub fn is_due(&self, generation: usize) -> bool {
+        if self.last_generation == Some(generation) {
+            return false;
+        }
+
+        self.interval
+            .is_some_and(|interval| self.last_save.elapsed() >= interval)
+            || self
+                .generations
+                .is_some_and(|generations| generation.is_multiple_of(generations))
+    }
+
+    /// Get the path of the snapshot of a generation
+    ///
+    /// @param generation The generation
+    pub fn get_path(&self, generation: usize) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map_or_else(|| "autosave".into(), |stem| stem.to_string_lossy());
+        let name = match self.path.extension() {
+            Some(extension) => format!("{}-{}.{}", stem, generation, extension.to_string_lossy()),
+            None => format!("{}-{}", stem, generation),
+        };
+
+        self.path.with_file_name(name)
+    }
+
+    /// Save a snapshot and remove the oldest ones beyond the number to keep
+    ///
+    /// @param generation The current generation
+    /// @param write Write the snapshot to the given path
+    pub fn save<E, F>(&mut self, generation: usize, write: F) -> Result<PathBuf, E>
+    where
+        E: From<std::io::Error>,
+        F: FnOnce(&Path) -> Result<(), E>,
+    {
+        // Try again at the next interval if it fails
+        self.last_save = Instant::now();
+        self.last_generation = Some(generation);
+
+        let path = self.get_path(generation);
+        let mut temporary_path = path.clone().into_os_string();
+        temporary_path.push(".tmp");
+        let temporary_path = PathBuf::from(temporary_path);
+        write(&temporary_path)?;
+        std::fs::rename(&temporary_path, &path)?;
+
+        self.saved.retain(|saved| *saved != path);
+        self.saved.push_back(path.clone());
+        while self.saved.len() > self.keep {
+            if let Some(oldest) = self.saved.pop_front() {
+                // Already removed by hand is fine
+                let _ = std::fs::remove_file(oldest);
+            }
+        }
+
+        Ok(path)
+    }
+}
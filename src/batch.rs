@@ -0,0 +1,244 @@
+//! Batch runs of many independent random worlds
+//!
+//! Every run populates a world from its own seed and updates it until it
+//! settles into a cycle or reaches the maximum number of steps. The outcomes
+//! of the runs are aggregated in a report.
+
+use crate::census::census;
+use crate::rule::Rule;
+use crate::threads::{pin_current_thread, ThreadOptions};
+use crate::world::World;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of objects listed in the census of a report
+const REPORT_CENSUS_SIZE: usize = 20;
+
+/// The settings of a batch
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    /// Width of the worlds
+    pub width: usize,
+    /// Height of the worlds
+    pub height: usize,
+    /// Initial population density of the worlds
+    pub density: f32,
+    /// Rule of the worlds
+    pub rule: Rule,
+    /// Number of runs
+    pub runs: usize,
+    /// Maximum number of steps of a run
+    pub max_steps: usize,
+    /// Seed of the first run, the next runs use the following seeds
+    pub seed: u64,
+    /// Threads running the worlds
+    pub threads: ThreadOptions,
+}
+
+/// The outcome of a run
+#[derive(Clone, Debug)]
+pub struct RunOutcome {
+    /// Seed of the run
+    pub seed: u64,
+    /// Number of steps done
+    pub steps: usize,
+    /// Population of the world at the end of the run
+    pub final_population: usize,
+    /// Generation from which the world repeats itself, None if it was still
+    /// evolving at the end of the run
+    pub lifespan: Option<usize>,
+    /// Period of the cycle the world settled into
+    pub period: Option<usize>,
+    /// Objects of the world at the end of the run, by name
+    pub census: BTreeMap<String, usize>,
+}
+
+/// Run a batch of worlds, in parallel
+///
+/// Returns the outcomes in the order of the runs.
+///
+/// @param config The settings of the batch
+pub fn run_batch(config: &BatchConfig) -> Vec<RunOutcome> {
+    let next_run = AtomicUsize::new(0);
+    let cores = config.threads.get_cores();
+    let mut outcomes: Vec<(usize, RunOutcome)> = std::thread::scope(|scope| {
+        let (next_run, cores) = (&next_run, &cores);
+        let workers: Vec<_> = (0..config.threads.get_count().max(1))
+            .map(|index| {
+                scope.spawn(move || {
+                    pin_current_thread(cores, index);
+                    let mut outcomes = Vec::new();
+                    loop {
+                        let run = next_run.fetch_add(1, Ordering::Relaxed);
+                        if run >= config.runs {
+                            break outcomes;
+                        }
+                        outcomes.push((run, run_one(config, config.seed.wrapping_add(run as u64))));
+                    }
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    outcomes.sort_by_key(|(run, _)| *run);
+
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+/// Run a single world
+///
+/// @param config The settings of the batch
+/// @param seed The seed of the world
+pub fn run_one(config: &BatchConfig, seed: u64) -> RunOutcome {
+    let mut world = World::new(config.width, config.height);
+    world.set_rule(config.rule);
+    world.populate(&mut StdRng::seed_from_u64(seed), config.density);
+
+    // Remember every state to detect when the world repeats itself
+    let mut seen = HashMap::new();
+    seen.insert(hash_world(&world), 0);
+    let mut steps = 0;
+    let mut cycle = None;
+    while steps < config.max_steps {
+        world.update();
+        steps += 1;
+
+        if let Some(first_generation) = seen.insert(hash_world(&world), steps) {
+            cycle = Some((first_generation, steps - first_generation));
+            break;
+        }
+    }
+
+    RunOutcome {
+        seed,
+        steps,
+        final_population: world.get_population(),
+        lifespan: cycle.map(|(lifespan, _)| lifespan),
+        period: cycle.map(|(_, period)| period),
+        census: census(&world),
+    }
+}
+
+/// Hash the cells of a world
+fn hash_world(world: &World) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    world.to_bitmap().hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// Statistics aggregated over the runs of a batch
+pub struct BatchReport {
+    /// Number of runs
+    pub runs: usize,
+    /// Number of runs which ended without any alive cell
+    pub extinct: usize,
+    /// Number of runs which settled into a cycle, extinct ones included
+    pub settled: usize,
+    /// Mean of the final populations
+    pub mean_population: f64,
+    /// Median of the final populations
+    pub median_population: usize,
+    /// Smallest final population
+    pub min_population: usize,
+    /// Largest final population
+    pub max_population: usize,
+    /// Mean lifespan of the runs which settled
+    pub mean_lifespan: Option<f64>,
+    /// Longest lifespan of the runs which settled
+    pub max_lifespan: Option<usize>,
+    /// Objects of all the runs, by name
+    pub census: BTreeMap<String, usize>,
+}
+
+impl BatchReport {
+    /// Aggregate the outcomes of runs
+    ///
+    /// @param outcomes The outcomes of the runs
+    pub fn new(outcomes: &[RunOutcome]) -> Self {
+        let mut populations: Vec<usize> = outcomes
+            .iter()
+            .map(|outcome| outcome.final_population)
+            .collect();
+        populations.sort_unstable();
+        let lifespans: Vec<usize> = outcomes
+            .iter()
+            .filter_map(|outcome| outcome.lifespan)
+            .collect();
+
+        let mut census = BTreeMap::new();
+        for outcome in outcomes {
+            for (name, count) in outcome.census.iter() {
+                *census.entry(name.clone()).or_insert(0) += count;
+            }
+        }
+
+        Self {
+            runs: outcomes.len(),
+            extinct: populations
+                .iter()
+                .filter(|population| **population == 0)
+                .count(),
+            settled: lifespans.len(),
+            mean_population: mean(&populations).unwrap_or(0.0),
+            median_population: populations.get(populations.len() / 2).copied().unwrap_or(0),
+            min_population: populations.first().copied().unwrap_or(0),
+            max_population: populations.last().copied().unwrap_or(0),
+            mean_lifespan: mean(&lifespans),
+            max_lifespan: lifespans.iter().max().copied(),
+            census,
+        }
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "runs: {}", self.runs)?;
+        writeln!(f, "extinct: {}", self.extinct)?;
+        writeln!(f, "settled: {}", self.settled)?;
+        writeln!(f, "still evolving: {}", self.runs - self.settled)?;
+        writeln!(
+            f,
+            "final population: mean {:.1}, median {}, min {}, max {}",
+            self.mean_population, self.median_population, self.min_population, self.max_population
+        )?;
+        match (self.mean_lifespan, self.max_lifespan) {
+            (Some(mean_lifespan), Some(max_lifespan)) => writeln!(
+                f,
+                "lifespan: mean {:.1}, max {}",
+                mean_lifespan, max_lifespan
+            )?,
+            _ => writeln!(f, "lifespan: none settled")?,
+        }
+
+        // Most common objects first
+        let mut census: Vec<(&String, &usize)> = self.census.iter().collect();
+        census.sort_by(|(name_a, count_a), (name_b, count_b)| {
+            count_b.cmp(count_a).then(name_a.cmp(name_b))
+        });
+        writeln!(f, "census:")?;
+        for (name, count) in census.into_iter().take(REPORT_CENSUS_SIZE) {
+            writeln!(f, "    {:>8} {}", count, name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the mean of values, None if there is no value
+fn mean(values: &[usize]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<usize>() as f64 / values.len() as f64)
+    }
+}
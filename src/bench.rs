@@ -0,0 +1,170 @@
+//! Comparison of the speed of the engines
+//!
+//! Every engine runs the same seeded world for the same number of steps. The
+//! final worlds are compared to check the engines agree with each other.
+
+use crate::engine::Engine;
+use crate::rule::Rule;
+use crate::threads::ThreadOptions;
+use crate::world::World;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The settings of a benchmark
+#[derive(Clone, Debug)]
+pub struct BenchConfig {
+    /// Width of the world
+    pub width: usize,
+    /// Height of the world
+    pub height: usize,
+    /// Initial population density of the world
+    pub density: f32,
+    /// Rule of the world
+    pub rule: Rule,
+    /// Number of steps run by each engine
+    pub steps: usize,
+    /// Seed of the world
+    pub seed: u64,
+    /// Threads of the parallel engine
+    pub threads: ThreadOptions,
+}
+
+/// The outcome of the run of an engine
+pub struct BenchResult {
+    /// The engine
+    pub engine: Engine,
+    /// Time spent updating the world
+    pub duration: Duration,
+    /// Population of the world at the end of the run
+    pub final_population: usize,
+    /// Cells of the world at the end of the run, as a bitmap
+    pub final_cells: Vec<u8>,
+    /// Bytes taken by the world at the end of the run
+    pub memory_usage: usize,
+}
+
+/// Run the same world with several engines
+///
+/// @param config The settings of the benchmark
+/// @param engines The engines to compare
+pub fn bench(config: &BenchConfig, engines: &[Engine]) -> BenchReport {
+    let results = engines
+        .iter()
+        .map(|engine| {
+            let mut world = World::new(config.width, config.height);
+            world.set_automaton(engine.create_with_threads(config.rule, config.threads));
+            world.populate(&mut StdRng::seed_from_u64(config.seed), config.density);
+
+            let start = Instant::now();
+            for _ in 0..config.steps {
+                world.update();
+            }
+            let duration = start.elapsed();
+
+            BenchResult {
+                engine: *engine,
+                duration,
+                final_population: world.get_population(),
+                final_cells: world.to_bitmap(),
+                memory_usage: world.get_memory_usage(),
+            }
+        })
+        .collect();
+
+    BenchReport {
+        cells: config.width * config.height,
+        steps: config.steps,
+        results,
+    }
+}
+
+/// The outcomes of the runs of the engines
+pub struct BenchReport {
+    /// Number of cells of the world
+    pub cells: usize,
+    /// Number of steps run by each engine
+    pub steps: usize,
+    /// The outcome of each engine, in the order they ran
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchReport {
+    /// Get the engines whose final world differs from the first engine's
+    pub fn get_mismatches(&self) -> Vec<Engine> {
+        match self.results.split_first() {
+            Some((reference, others)) => others
+                .iter()
+                .filter(|result| result.final_cells != reference.final_cells)
+                .map(|result| result.engine)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<10} {:>12} {:>12} {:>14} {:>8} {:>10} {:>10}",
+            "engine", "total", "per step", "cells/s", "speedup", "memory", "population"
+        )?;
+
+        // Speedups are relative to the first engine
+        let reference = self
+            .results
+            .first()
+            .map(|result| result.duration.as_secs_f64());
+        for result in self.results.iter() {
+            let seconds = result.duration.as_secs_f64();
+            writeln!(
+                f,
+                "{:<10} {:>12} {:>12} {:>14.0} {:>7.2}x {:>10} {:>10}",
+                result.engine.get_name(),
+                format!("{:.2?}", result.duration),
+                format!("{:.2?}", result.duration / self.steps.max(1) as u32),
+                (self.cells * self.steps) as f64 / seconds,
+                reference.map_or(1.0, |reference| reference / seconds),
+                format_bytes(result.memory_usage),
+                result.final_population
+            )?;
+        }
+
+        let mismatches = self.get_mismatches();
+        if mismatches.is_empty() {
+            writeln!(f, "all the engines computed the same world")?;
+        } else {
+            for engine in mismatches {
+                writeln!(
+                    f,
+                    "MISMATCH: {} computed a different world than {}",
+                    engine, self.results[0].engine
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Format a number of bytes with a binary unit, such as `1.5 MiB`
+///
+/// @param bytes The number of bytes
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,134 @@
+//! Census of the objects of a world
+//!
+//! Objects are groups of alive cells touching each other, including
+//! diagonally. Objects are identified whatever their orientation, and named
+//! after the builtin pattern they match, if any. Other objects are named
+//! after their cells in the plaintext format, rows separated by `/`.
+
+use crate::pattern::{Pattern, PatternMetadata, Transform};
+use crate::world::{CellState, World};
+use std::collections::BTreeMap;
+
+/// Count the objects of a world, by name
+///
+/// @param world The world
+pub fn census(world: &World) -> BTreeMap<String, usize> {
+    let builtins = get_builtin_forms();
+
+    let mut census = BTreeMap::new();
+    for object in find_objects(world) {
+        *census.entry(name_object(&object, &builtins)).or_insert(0) += 1;
+    }
+
+    census
+}
+
+/// Get the name a census gives to an object, whatever its orientation
+///
+/// @param object The cells of the object
+pub fn get_object_name(object: &Pattern) -> String {
+    let object = object.clone().with_metadata(PatternMetadata::default());
+
+    name_object(&object, &get_builtin_forms())
+}
+
+/// Get the canonical forms of the builtin patterns, with their names
+fn get_builtin_forms() -> Vec<(String, &'static str)> {
+    Pattern::builtin_names()
+        .into_iter()
+        .filter_map(|name| {
+            Pattern::builtin(name)
+                .ok()
+                .map(|pattern| (canonical_form(&pattern), name))
+        })
+        .collect()
+}
+
+/// Name an object after the builtin pattern it matches, or after its cells
+///
+/// @param object The cells of the object
+/// @param builtins The canonical forms of the builtin patterns
+fn name_object(object: &Pattern, builtins: &[(String, &str)]) -> String {
+    let form = canonical_form(object);
+    builtins
+        .iter()
+        .find(|(builtin_form, _)| *builtin_form == form)
+        .map_or_else(|| form.replace('\n', "/"), |(_, name)| name.to_string())
+}
+
+/// Split the alive cells of a world into objects, following the wrapping
+/// edges
+fn find_objects(world: &World) -> Vec<Pattern> {
+    let grid = world.get_grid();
+    let width = world.get_width();
+    let height = world.get_height();
+    let mut visited = vec![false; width * height];
+    let mut objects = Vec::new();
+
+    for (index, cell_state) in world.get_tiles().iter().enumerate() {
+        if *cell_state != CellState::ALIVE || visited[index] {
+            continue;
+        }
+
+        // Flood fill, keeping the positions relative to the first cell so
+        // the objects crossing the edges stay in one piece
+        let mut cells = Vec::new();
+        let mut pending = vec![((index % width, index / width), (0_isize, 0_isize))];
+        visited[index] = true;
+        while let Some(((cell_x, cell_y), (dx, dy))) = pending.pop() {
+            cells.push((dx, dy));
+            for (ndx, ndy) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                let (x, y) = match grid.get_neighbor(cell_x, cell_y, ndx, ndy) {
+                    Some(position) => position,
+                    None => continue,
+                };
+                let neighbor_index = y * width + x;
+                if world.get_tile(x, y) == CellState::ALIVE && !visited[neighbor_index] {
+                    visited[neighbor_index] = true;
+                    pending.push(((x, y), (dx + ndx, dy + ndy)));
+                }
+            }
+        }
+
+        let min_x = cells.iter().map(|(x, _)| *x).min().unwrap();
+        let min_y = cells.iter().map(|(_, y)| *y).min().unwrap();
+        let max_x = cells.iter().map(|(x, _)| *x).max().unwrap();
+        let max_y = cells.iter().map(|(_, y)| *y).max().unwrap();
+        let mut tiles =
+            vec![vec![CellState::DEAD; (max_x - min_x + 1) as usize]; (max_y - min_y + 1) as usize];
+        for (x, y) in cells {
+            tiles[(y - min_y) as usize][(x - min_x) as usize] = CellState::ALIVE;
+        }
+        objects.push(Pattern::from_rows(tiles));
+    }
+
+    objects
+}
+
+/// Get the representation of a pattern shared by all its orientations
+fn canonical_form(pattern: &Pattern) -> String {
+    let flipped = pattern.transform(Transform::FlipX);
+    [pattern, &flipped]
+        .iter()
+        .flat_map(|pattern| {
+            [
+                pattern.to_plaintext(),
+                pattern.transform(Transform::Rot90).to_plaintext(),
+                pattern.transform(Transform::Rot180).to_plaintext(),
+                pattern.transform(Transform::Rot270).to_plaintext(),
+            ]
+        })
+        .min()
+        .unwrap()
+        .trim_end()
+        .to_string()
+}
@@ -0,0 +1,141 @@
+//! Settings read from a TOML file
+//!
+//! The keys of the file are the long options without their dashes, their
+//! values the values of the options:
+//!
+//! ```toml
+//! rule = "B36/S23"
+//! fg = "#ffcc00"
+//! target-fps = 30
+//! no-wrap-x = true
+//! place = ["glider@10,10", "gun.rle@0,0"]
+//! ```
+//!
+//! A flag is given when its value is true and left out when false, an array
+//! gives the option once per item. The options of the command line come
+//! after the ones of the file, so they override them.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use toml::{Table, Value};
+
+/// An error occurring while loading a config file
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file cannot be read
+    Io(std::io::Error),
+    /// The file is not valid TOML
+    Parse(toml::de::Error),
+    /// The value of a key is a table or a date, which no option takes
+    InvalidValue(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{}", err),
+            ConfigError::Parse(err) => write!(f, "{}", err.message()),
+            ConfigError::InvalidValue(key) => write!(f, "invalid value for {}", key),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(err: toml::de::Error) -> Self {
+        ConfigError::Parse(err)
+    }
+}
+
+/// A config file, as loaded
+pub struct Config {
+    /// Path of the file
+    path: PathBuf,
+    /// When the file was last modified, if the platform knows
+    modified: Option<SystemTime>,
+    /// The keys of the file
+    table: Table,
+}
+
+impl Config {
+    /// Load a config file
+    ///
+    /// @param path Path of the file
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let modified = std::fs::metadata(path)?.modified().ok();
+        let table = std::fs::read_to_string(path)?.parse::<Table>()?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            modified,
+            table,
+        })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file was modified since it was loaded or last checked
+    pub fn check_modified(&mut self) -> bool {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+
+        true
+    }
+
+    /// Get the keys whose values differ between two versions of the file,
+    /// added and removed ones included, sorted
+    ///
+    /// @param other The other version
+    pub fn get_changed_keys(&self, other: &Config) -> Vec<String> {
+        let mut keys = self
+            .table
+            .keys()
+            .chain(other.table.keys())
+            .filter(|key| self.table.get(*key) != other.table.get(*key))
+            .cloned()
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        keys
+    }
+
+    /// Convert the keys into the options of the command line
+    pub fn to_args(&self) -> Result<Vec<String>, ConfigError> {
+        let mut args = Vec::new();
+        for (key, value) in self.table.iter() {
+            let values = match value {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            for value in values {
+                let option = format!("--{}", key);
+                match value {
+                    Value::Boolean(true) => args.push(option),
+                    Value::Boolean(false) => {}
+                    Value::String(value) => args.extend([option, value.clone()]),
+                    Value::Integer(value) => args.extend([option, value.to_string()]),
+                    Value::Float(value) => args.extend([option, value.to_string()]),
+                    _ => return Err(ConfigError::InvalidValue(key.clone())),
+                }
+            }
+        }
+
+        Ok(args)
+    }
+}
@@ -0,0 +1,210 @@
+//! Checking an engine against a reference engine while the simulation runs
+//!
+//! Before each step, the cells of the world are copied into a reference
+//! world computed by another engine, usually the naive one. Both worlds are
+//! then stepped and their fingerprints compared, so the first generation an
+//! optimized engine gets wrong is caught with the cells it got wrong, even
+//! after edits, reseeds or rule changes.
+
+use crate::engine::Engine;
+use crate::rule::Rule;
+use crate::world::{CellState, World};
+use std::fmt;
+
+/// Initial value of the FNV-1a hash
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Multiplier of the FNV-1a hash
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Number of differing cells listed in the reports
+const MAX_REPORTED_CELLS: usize = 10;
+
+/// A cell computed differently by the two engines
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CellDifference {
+    pub x: usize,
+    pub y: usize,
+    /// State computed by the reference engine
+    pub expected: CellState,
+    /// State computed by the checked engine
+    pub actual: CellState,
+}
+
+/// The first generation the two engines disagree on
+#[derive(Debug)]
+pub struct Divergence {
+    /// The generation reached by the step
+    pub generation: usize,
+    /// Name of the checked automaton
+    pub automaton: String,
+    /// The reference engine
+    pub reference: Engine,
+    /// Fingerprint of the cells computed by the reference engine
+    pub expected_fingerprint: u64,
+    /// Fingerprint of the cells computed by the checked engine
+    pub actual_fingerprint: u64,
+    /// Number of differing cells
+    pub count: usize,
+    /// The first differing cells, row major
+    pub cells: Vec<CellDifference>,
+    /// Bounding box of the differing cells, as x, y, width and height
+    pub bounds: (usize, usize, usize, usize),
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y, width, height) = self.bounds;
+        writeln!(
+            f,
+            "{} diverges from the {} engine at generation {}",
+            self.automaton, self.reference, self.generation
+        )?;
+        writeln!(
+            f,
+            "  fingerprint {:016x}, expected {:016x}",
+            self.actual_fingerprint, self.expected_fingerprint
+        )?;
+        write!(
+            f,
+            "  {} cells differ, within {}x{} at ({}, {})",
+            self.count, width, height, x, y
+        )?;
+        for cell in self.cells.iter() {
+            write!(
+                f,
+                "\n  ({}, {}) state {}, expected {}",
+                cell.x, cell.y, cell.actual.0, cell.expected.0
+            )?;
+        }
+        if self.count > self.cells.len() {
+            write!(f, "\n  ...")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Compares the steps of a world with the ones of a reference engine
+pub struct EngineCheck {
+    /// The reference engine
+    engine: Engine,
+    /// The world stepped by the reference engine, None when the step about
+    /// to run is not checked
+    reference: Option<World>,
+}
+
+impl EngineCheck {
+    /// Create a check against a reference engine
+    ///
+    /// @param engine The reference engine
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            reference: None,
+        }
+    }
+
+    /// Copy the world about to be stepped into the reference world
+    ///
+    /// The steps of the automatons which are not Life-like rules are not
+    /// checked.
+    ///
+    /// @param world The world before the step
+    pub fn prepare(&mut self, world: &World) {
+        self.reference = world
+            .get_automaton()
+            .name()
+            .parse::<Rule>()
+            .ok()
+            .map(|rule| {
+                let mut reference = World::new(world.get_width(), world.get_height());
+                reference.set_grid(world.get_grid().clone());
+                reference.set_automaton(self.engine.create(rule));
+                reference
+            });
+    }
+
+    /// Step the reference world and compare it with the stepped world
+    ///
+    /// @param world The world after the step
+    /// @param generation The generation reached by the step
+    pub fn check(&mut self, world: &World, generation: usize) -> Result<(), Divergence> {
+        let mut reference = match self.reference.take() {
+            Some(reference) => reference,
+            None => return Ok(()),
+        };
+        reference.update();
+
+        let expected_fingerprint = fingerprint(&reference);
+        let actual_fingerprint = fingerprint(world);
+        if expected_fingerprint == actual_fingerprint {
+            return Ok(());
+        }
+
+        let width = world.get_width();
+        let mut count = 0;
+        let mut cells = Vec::new();
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+        for (index, (expected, actual)) in reference
+            .get_tiles()
+            .iter()
+            .zip(world.get_tiles().iter())
+            .enumerate()
+        {
+            if expected == actual {
+                continue;
+            }
+
+            let (x, y) = (index % width, index / width);
+            count += 1;
+            if cells.len() < MAX_REPORTED_CELLS {
+                cells.push(CellDifference {
+                    x,
+                    y,
+                    expected: *expected,
+                    actual: *actual,
+                });
+            }
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        let bounds = if count > 0 {
+            (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
+        } else {
+            (0, 0, 0, 0)
+        };
+
+        Err(Divergence {
+            generation,
+            automaton: world.get_automaton().name(),
+            reference: self.engine,
+            expected_fingerprint,
+            actual_fingerprint,
+            count,
+            cells,
+            bounds,
+        })
+    }
+}
+
+/// Hash the size and the cells of a world
+///
+/// The hash is FNV-1a, which does not depend on the platform nor on the
+/// version of Rust, so the fingerprints of runs on different machines can be
+/// compared.
+///
+/// @param world The world
+pub fn fingerprint(world: &World) -> u64 {
+    let size = [world.get_width() as u64, world.get_height() as u64];
+    size.iter()
+        .flat_map(|value| value.to_le_bytes())
+        .chain(world.get_tiles().iter().map(|cell_state| cell_state.0))
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
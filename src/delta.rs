@@ -0,0 +1,123 @@
+//! Differences between two generations of a world
+//!
+//! A delta lists the cells toggled since the previous generation by the
+//! distances between their indices (row major, the first one from index 0),
+//! each as a LEB128 varint. Worlds mostly change in a few places, so deltas
+//! are much smaller than the whole world.
+
+use crate::world::{CellState, World};
+
+/// The cells toggled between two generations
+pub struct Delta {
+    /// The toggled cells, encoded
+    pub toggled: Vec<u8>,
+    /// Number of cells which were born
+    pub births: usize,
+    /// Number of cells which died
+    pub deaths: usize,
+    /// Number of alive cells
+    pub population: usize,
+}
+
+/// Compute the deltas between the successive generations of a world
+#[derive(Default)]
+pub struct DeltaEncoder {
+    /// The world as it was at the previous generation
+    previous_tiles: Vec<CellState>,
+}
+
+impl DeltaEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compute the delta with the previous generation
+    ///
+    /// The first generation, or the first one after the world was resized,
+    /// is compared to a world of dead cells.
+    ///
+    /// @param world The world at the current generation
+    pub fn encode(&mut self, world: &World) -> Delta {
+        let tiles = world.get_tiles();
+        if tiles.len() != self.previous_tiles.len() {
+            self.previous_tiles = vec![CellState::DEAD; tiles.len()];
+        }
+
+        let mut delta = Delta {
+            toggled: Vec::new(),
+            births: 0,
+            deaths: 0,
+            population: 0,
+        };
+        let mut previous_index = 0;
+        for (index, (tile, previous_tile)) in tiles.iter().zip(&self.previous_tiles).enumerate() {
+            if *tile == CellState::ALIVE {
+                delta.population += 1;
+            }
+            if tile != previous_tile {
+                if *tile == CellState::ALIVE {
+                    delta.births += 1;
+                } else {
+                    delta.deaths += 1;
+                }
+                write_varint(&mut delta.toggled, index - previous_index);
+                previous_index = index;
+            }
+        }
+        self.previous_tiles.copy_from_slice(tiles);
+
+        delta
+    }
+}
+
+/// Toggle the cells listed by a delta
+///
+/// Returns None if the delta is truncated or lists cells beyond the world.
+///
+/// @param world The world at the previous generation
+/// @param toggled The toggled cells, encoded
+pub fn apply(world: &mut World, toggled: &[u8]) -> Option<()> {
+    let width = world.get_width();
+    let mut toggled = toggled;
+    let mut index = 0;
+    while !toggled.is_empty() {
+        index += read_varint(&mut toggled)?;
+        if index >= world.get_tiles().len() {
+            return None;
+        }
+
+        let (x, y) = (index % width, index / width);
+        let cell_state = if world.get_tile(x, y) == CellState::ALIVE {
+            CellState::DEAD
+        } else {
+            CellState::ALIVE
+        };
+        world.set_tile(x, y, cell_state);
+    }
+
+    Some(())
+}
+
+/// Write an unsigned integer as a LEB128 varint
+fn write_varint(buffer: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Read a LEB128 varint, advancing the buffer past it
+fn read_varint(buffer: &mut &[u8]) -> Option<usize> {
+    let mut value = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (byte, rest) = buffer.split_first()?;
+        *buffer = rest;
+        value |= ((byte & 0x7f) as usize).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
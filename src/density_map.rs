@@ -0,0 +1,164 @@
+//! Density of the population over the world, written every few generations
+//!
+//! The world is divided into a grid of NxN blocks, each block holding the
+//! fraction of its cells which are alive, so the large simulations can be
+//! studied coarse-grained without saving their whole generations. When the
+//! world is not a multiple of the grid, the blocks differ by a cell, and
+//! when it is smaller than the grid, the blocks without any cell are 0.
+//!
+//! The maps are written as CSV, a line per row of a map holding the
+//! generation, the row and the densities of its blocks, or, if the path ends
+//! with `.npy`, as a NumPy array of 32-bit floats shaped (maps, N, N).
+
+use crate::world::World;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Number of blocks along each axis when not given
+pub const DEFAULT_SIZE: usize = 32;
+
+/// Length of the header of the NumPy arrays, the prefix included, room
+/// enough for the largest shapes so it is rewritten in place at the end
+const NPY_HEADER_LENGTH: usize = 192;
+
+/// Get the density of the population over the blocks of a world
+///
+/// Returns the densities of the NxN blocks, between 0 and 1, row major.
+///
+/// @param world The world
+/// @param size Number of blocks along each axis
+pub fn get_density_map(world: &World, size: usize) -> Vec<f32> {
+    if size == 0 {
+        return Vec::new();
+    }
+    let (width, height) = (world.get_width(), world.get_height());
+    let mut populations = vec![0usize; size * size];
+    let mut areas = vec![0usize; size * size];
+
+    for (y, row) in world.get_tiles().chunks(width.max(1)).enumerate() {
+        let block_y = y * size / height;
+        for (x, cell_state) in row.iter().enumerate() {
+            let index = block_y * size + x * size / width;
+            areas[index] += 1;
+            if cell_state.is_alive() {
+                populations[index] += 1;
+            }
+        }
+    }
+
+    populations
+        .into_iter()
+        .zip(areas)
+        .map(|(population, area)| {
+            if area == 0 {
+                0.0
+            } else {
+                population as f32 / area as f32
+            }
+        })
+        .collect()
+}
+
+/// Writes the density maps of the generations
+pub struct DensityMapWriter<W: Write + Seek> {
+    /// Where to write the maps
+    writer: W,
+    /// Number of blocks along each axis
+    size: usize,
+    /// Whether to write a NumPy array rather than CSV
+    npy: bool,
+    /// Number of maps written so far
+    count: usize,
+}
+
+impl DensityMapWriter<BufWriter<File>> {
+    /// Create a file to write the maps to, as a NumPy array if its path ends
+    /// with `.npy`, as CSV otherwise
+    ///
+    /// @param path Path of the file
+    /// @param size Number of blocks along each axis
+    pub fn create(path: &Path, size: usize) -> std::io::Result<Self> {
+        let npy = path.extension().is_some_and(|extension| extension == "npy");
+
+        Self::new(BufWriter::new(File::create(path)?), size, npy)
+    }
+}
+
+impl<W: Write + Seek> DensityMapWriter<W> {
+    /// Start writing the maps, with the header of the CSV or of the array
+    ///
+    /// @param writer Where to write the maps
+    /// @param size Number of blocks along each axis
+    /// @param npy Whether to write a NumPy array rather than CSV
+    pub fn new(writer: W, size: usize, npy: bool) -> std::io::Result<Self> {
+        let mut density_map_writer = Self {
+            writer,
+            size,
+            npy,
+            count: 0,
+        };
+        if npy {
+            density_map_writer.write_npy_header()?;
+        } else {
+            let columns = (0..size)
+                .map(|column| format!(",{}", column))
+                .collect::<String>();
+            writeln!(density_map_writer.writer, "generation,row{}", columns)?;
+        }
+
+        Ok(density_map_writer)
+    }
+
+    /// Write the density map of a generation
+    ///
+    /// @param world The world
+    /// @param generation Its generation
+    pub fn write(&mut self, world: &World, generation: usize) -> std::io::Result<()> {
+        let densities = get_density_map(world, self.size);
+        if self.npy {
+            for density in densities {
+                self.writer.write_all(&density.to_le_bytes())?;
+            }
+        } else {
+            for (row, densities) in densities.chunks(self.size.max(1)).enumerate() {
+                write!(self.writer, "{},{}", generation, row)?;
+                for density in densities {
+                    write!(self.writer, ",{}", density)?;
+                }
+                writeln!(self.writer)?;
+            }
+        }
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Write the maps still buffered, and the number of maps in the header
+    /// of the array
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        if self.npy {
+            self.writer.seek(SeekFrom::Start(0))?;
+            self.write_npy_header()?;
+            self.writer.seek(SeekFrom::End(0))?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Write the header of the array, shaped after the maps written so far
+    fn write_npy_header(&mut self) -> std::io::Result<()> {
+        let dictionary = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}, {}), }}",
+            self.count, self.size, self.size
+        );
+        // The magic string, the version and the length of the dictionary
+        // come first, the dictionary is padded with spaces and a new line
+        let padding = NPY_HEADER_LENGTH - 10 - dictionary.len() - 1;
+
+        self.writer.write_all(b"\x93NUMPY\x01\x00")?;
+        self.writer
+            .write_all(&((NPY_HEADER_LENGTH - 10) as u16).to_le_bytes())?;
+        writeln!(self.writer, "{}{}", dictionary, " ".repeat(padding))
+    }
+}
@@ -0,0 +1,388 @@
+//! Simulation of a world split across processes or machines
+//!
+//! The world is cut into horizontal bands, each one simulated by a worker.
+//! At each generation, a master sends every worker the rows bordering its
+//! band, the halos, which are the edge rows its neighbors computed at the
+//! previous generation. The master seeds the workers and gathers the bands
+//! when the whole world is needed, to render, export or checkpoint it.
+//!
+//! Every message is prefixed by its length as a little endian u32, then
+//! starts with its kind as u8. All the integers are little endian and the
+//! cells are packed as bitmaps, row major, least significant bit first.
+//!
+//! - assign (kind 0), master to worker: the width and the height of the
+//!   band as u32, whether the band wraps horizontally as u8, the length of
+//!   the rule as u8, the rule as B3/S23 and the cells of the band
+//! - step (kind 1), master to worker: the row above the band then the row
+//!   below it
+//! - stepped (kind 2), worker to master: the births and the deaths as u64,
+//!   then the first and the last rows of the band
+//! - fetch (kind 3), master to worker: empty
+//! - band (kind 4), worker to master: the cells of the band
+
+use crate::automaton::UpdateStats;
+use crate::rule::Rule;
+use crate::world::{CellState, World};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+/// Maximum accepted size of a message
+const MAX_MESSAGE_SIZE: usize = 1 << 30;
+
+/// Kind of a message giving a worker its band
+const MESSAGE_ASSIGN: u8 = 0;
+/// Kind of a message asking for the next generation of a band
+const MESSAGE_STEP: u8 = 1;
+/// Kind of a message answering a step
+const MESSAGE_STEPPED: u8 = 2;
+/// Kind of a message asking for the cells of a band
+const MESSAGE_FETCH: u8 = 3;
+/// Kind of a message answering a fetch
+const MESSAGE_BAND: u8 = 4;
+
+/// A connection to a worker, simulating a band of the world
+struct WorkerConnection {
+    /// Where the answers of the worker are read
+    reader: BufReader<TcpStream>,
+    /// Where the requests to the worker are written
+    writer: BufWriter<TcpStream>,
+    /// Row of the world where the band starts
+    first_y: usize,
+    /// Number of rows of the band
+    height: usize,
+    /// First row of the band, packed
+    first_row: Vec<u8>,
+    /// Last row of the band, packed
+    last_row: Vec<u8>,
+}
+
+/// The coordinator of workers simulating a world together
+pub struct Master {
+    /// The workers, from the top band to the bottom one
+    workers: Vec<WorkerConnection>,
+    /// Width of the world
+    width: usize,
+    /// Height of the world
+    height: usize,
+    /// Whether the left and right edges of the world are connected
+    wrap_x: bool,
+    /// Whether the top and bottom edges of the world are connected
+    wrap_y: bool,
+    /// The rule of the world
+    rule: Rule,
+}
+
+impl Master {
+    /// Connect to the workers and give each one a band of the world
+    ///
+    /// The bands are as tall as possible, the top ones taking the
+    /// remaining rows.
+    ///
+    /// @param addresses The addresses of the workers, as host:port
+    /// @param world The world to split, whose wrapping is kept
+    /// @param rule The rule of the world
+    pub fn connect(addresses: &[String], world: &World, rule: Rule) -> std::io::Result<Self> {
+        let width = world.get_width();
+        let height = world.get_height();
+        if addresses.is_empty() || addresses.len() > height {
+            return Err(invalid_input("between 1 worker and a worker per row"));
+        }
+
+        let grid = world.get_grid();
+        let mut workers = Vec::new();
+        let mut first_y = 0;
+        for (index, address) in addresses.iter().enumerate() {
+            let band_height = height / addresses.len()
+                + if index < height % addresses.len() {
+                    1
+                } else {
+                    0
+                };
+            let band = &world.get_tiles()[first_y * width..(first_y + band_height) * width];
+
+            let stream = TcpStream::connect(address.strip_prefix("tcp://").unwrap_or(address))?;
+            stream.set_nodelay(true)?;
+            let mut writer = BufWriter::new(stream.try_clone()?);
+            let mut message = vec![MESSAGE_ASSIGN];
+            message.extend_from_slice(&(width as u32).to_le_bytes());
+            message.extend_from_slice(&(band_height as u32).to_le_bytes());
+            message.push(grid.is_wrapping_x() as u8);
+            let rule_name = rule.to_string();
+            message.push(rule_name.len() as u8);
+            message.extend_from_slice(rule_name.as_bytes());
+            message.extend(pack(band));
+            write_message(&mut writer, &message)?;
+            writer.flush()?;
+
+            workers.push(WorkerConnection {
+                reader: BufReader::new(stream),
+                writer,
+                first_y,
+                height: band_height,
+                first_row: pack(&band[..width]),
+                last_row: pack(&band[band.len() - width..]),
+            });
+            first_y += band_height;
+        }
+
+        Ok(Self {
+            workers,
+            width,
+            height,
+            wrap_x: grid.is_wrapping_x(),
+            wrap_y: grid.is_wrapping_y(),
+            rule,
+        })
+    }
+
+    pub fn get_worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Compute the next generation of the world
+    ///
+    /// All the workers are sent their halos before any answer is read, so
+    /// they compute their bands at the same time.
+    pub fn step(&mut self) -> std::io::Result<UpdateStats> {
+        let dead_row = pack(&vec![CellState::DEAD; self.width]);
+        let count = self.workers.len();
+        let mut requests = Vec::with_capacity(count);
+        for index in 0..count {
+            let above = if index > 0 {
+                &self.workers[index - 1].last_row
+            } else if self.wrap_y {
+                &self.workers[count - 1].last_row
+            } else {
+                &dead_row
+            };
+            let below = if index + 1 < count {
+                &self.workers[index + 1].first_row
+            } else if self.wrap_y {
+                &self.workers[0].first_row
+            } else {
+                &dead_row
+            };
+
+            let mut message = vec![MESSAGE_STEP];
+            message.extend_from_slice(above);
+            message.extend_from_slice(below);
+            requests.push(message);
+        }
+        for (worker, message) in self.workers.iter_mut().zip(requests.iter()) {
+            write_message(&mut worker.writer, message)?;
+            worker.writer.flush()?;
+        }
+
+        let row_size = dead_row.len();
+        let mut stats = UpdateStats::default();
+        for worker in self.workers.iter_mut() {
+            let message = read_message(&mut worker.reader)?;
+            if message.first() != Some(&MESSAGE_STEPPED) || message.len() != 17 + 2 * row_size {
+                return Err(invalid_data("expected a stepped message"));
+            }
+
+            stats.births += u64::from_le_bytes(read_array(&message[1..9])) as usize;
+            stats.deaths += u64::from_le_bytes(read_array(&message[9..17])) as usize;
+            worker.first_row = message[17..17 + row_size].to_vec();
+            worker.last_row = message[17 + row_size..].to_vec();
+        }
+
+        Ok(stats)
+    }
+
+    /// Gather the bands of the workers into a world
+    pub fn fetch(&mut self) -> std::io::Result<World> {
+        for worker in self.workers.iter_mut() {
+            write_message(&mut worker.writer, &[MESSAGE_FETCH])?;
+            worker.writer.flush()?;
+        }
+
+        let mut world = World::new(self.width, self.height);
+        world.set_rule(self.rule);
+        for worker in self.workers.iter_mut() {
+            let message = read_message(&mut worker.reader)?;
+            if message.first() != Some(&MESSAGE_BAND) {
+                return Err(invalid_data("expected a band message"));
+            }
+
+            let start = worker.first_y * self.width;
+            let end = start + worker.height * self.width;
+            unpack(&message[1..], &mut world.get_tiles_mut()[start..end])
+                .ok_or_else(|| invalid_data("truncated band"))?;
+        }
+        world.set_wrapping(self.wrap_x, self.wrap_y);
+
+        Ok(world)
+    }
+}
+
+/// Simulate bands of worlds for the masters connecting, one at a time
+///
+/// @param address The address to listen on
+pub fn run_worker<A: ToSocketAddrs>(address: A) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let peer = stream.peer_addr().ok();
+        eprintln!("master {:?} connected", peer);
+        match serve_master(stream) {
+            Ok(()) => eprintln!("master {:?} disconnected", peer),
+            Err(err) => eprintln!("master {:?} disconnected: {}", peer, err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Answer the requests of a master until it disconnects
+fn serve_master(stream: TcpStream) -> std::io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+
+    // The band is surrounded by the halos, its first and last rows
+    let mut band: Option<World> = None;
+    loop {
+        let message = match read_message(&mut reader) {
+            Ok(message) => message,
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        match message.first() {
+            Some(&MESSAGE_ASSIGN) if message.len() >= 11 => {
+                let width = u32::from_le_bytes(read_array(&message[1..5])) as usize;
+                let height = u32::from_le_bytes(read_array(&message[5..9])) as usize;
+                let wrap_x = message[9] == 1;
+                let rule_end = 11 + message[10] as usize;
+                let rule = message
+                    .get(11..rule_end)
+                    .and_then(|rule| std::str::from_utf8(rule).ok())
+                    .and_then(|rule| rule.parse::<Rule>().ok())
+                    .ok_or_else(|| invalid_data("invalid rule"))?;
+
+                let mut world = World::new(width, height + 2);
+                world.set_rule(rule);
+                world.set_wrapping(wrap_x, false);
+                unpack(
+                    &message[rule_end..],
+                    &mut world.get_tiles_mut()[width..(height + 1) * width],
+                )
+                .ok_or_else(|| invalid_data("truncated band"))?;
+                eprintln!("simulating a {}x{} band with {}", width, height, rule);
+                band = Some(world);
+            }
+            Some(&MESSAGE_STEP) => {
+                let world = band.as_mut().ok_or_else(|| invalid_data("no band"))?;
+                let width = world.get_width();
+                let height = world.get_height();
+                let row_size = width.div_ceil(8);
+                if message.len() != 1 + 2 * row_size {
+                    return Err(invalid_data("invalid halos"));
+                }
+                let tiles = world.get_tiles_mut();
+                unpack(&message[1..1 + row_size], &mut tiles[..width]);
+                unpack(&message[1 + row_size..], &mut tiles[(height - 1) * width..]);
+
+                // The halos change too, only the band is counted
+                let before = world.get_tiles()[width..(height - 1) * width].to_vec();
+                world.update();
+                let after = &world.get_tiles()[width..(height - 1) * width];
+                let mut stats = UpdateStats::default();
+                for (old, new) in before.iter().zip(after.iter()) {
+                    if !old.is_alive() && new.is_alive() {
+                        stats.births += 1;
+                    } else if old.is_alive() && !new.is_alive() {
+                        stats.deaths += 1;
+                    }
+                }
+
+                let mut answer = vec![MESSAGE_STEPPED];
+                answer.extend_from_slice(&(stats.births as u64).to_le_bytes());
+                answer.extend_from_slice(&(stats.deaths as u64).to_le_bytes());
+                answer.extend(pack(&after[..width]));
+                answer.extend(pack(&after[after.len() - width..]));
+                write_message(&mut writer, &answer)?;
+                writer.flush()?;
+            }
+            Some(&MESSAGE_FETCH) => {
+                let world = band.as_ref().ok_or_else(|| invalid_data("no band"))?;
+                let width = world.get_width();
+                let height = world.get_height();
+                let mut answer = vec![MESSAGE_BAND];
+                answer.extend(pack(&world.get_tiles()[width..(height - 1) * width]));
+                write_message(&mut writer, &answer)?;
+                writer.flush()?;
+            }
+            _ => return Err(invalid_data("unexpected message")),
+        }
+    }
+}
+
+/// Pack cells as a bitmap, least significant bit first
+fn pack(tiles: &[CellState]) -> Vec<u8> {
+    let mut bitmap = vec![0; tiles.len().div_ceil(8)];
+    for (index, cell_state) in tiles.iter().enumerate() {
+        if cell_state.is_alive() {
+            bitmap[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    bitmap
+}
+
+/// Unpack cells packed by `pack`
+///
+/// Returns None if the bitmap is too short for the cells.
+fn unpack(bitmap: &[u8], tiles: &mut [CellState]) -> Option<()> {
+    if bitmap.len() < tiles.len().div_ceil(8) {
+        return None;
+    }
+
+    for (index, cell_state) in tiles.iter_mut().enumerate() {
+        *cell_state = if bitmap[index / 8] & (1 << (index % 8)) != 0 {
+            CellState::ALIVE
+        } else {
+            CellState::DEAD
+        };
+    }
+
+    Some(())
+}
+
+/// Write a length prefixed message
+fn write_message<W: Write>(writer: &mut W, message: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(message.len() as u32).to_le_bytes())?;
+    writer.write_all(message)
+}
+
+/// Read a length prefixed message
+fn read_message<R: Read>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_le_bytes(length) as usize;
+    if length > MAX_MESSAGE_SIZE {
+        return Err(invalid_data("message too large"));
+    }
+
+    let mut message = vec![0; length];
+    reader.read_exact(&mut message)?;
+
+    Ok(message)
+}
+
+fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+fn invalid_input(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
+}
+
+/// Copy a slice into an array of the same size
+fn read_array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut array = [0; N];
+    array.copy_from_slice(bytes);
+
+    array
+}
@@ -0,0 +1,23 @@
+//! Durations written on the command line
+
+use std::time::Duration;
+
+/// Parse a duration made of a number and a unit among ms, s, m and h, as
+/// 90s or 1.5h, the number alone being seconds
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let (number, unit_seconds) = if let Some(number) = s.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = s.strip_suffix('s') {
+        (number, 1.0)
+    } else if let Some(number) = s.strip_suffix('m') {
+        (number, 60.0)
+    } else if let Some(number) = s.strip_suffix('h') {
+        (number, 3600.0)
+    } else {
+        (s, 1.0)
+    };
+    let seconds = number.parse::<f64>().ok()? * unit_seconds;
+
+    // Also rejects the negative, infinite or too long durations
+    Duration::try_from_secs_f64(seconds).ok()
+}
@@ -0,0 +1,119 @@
+//! Engines computing the generations of Life-like rules
+//!
+//! All the engines compute the same generations, they only differ by their
+//! speed and their memory footprint.
+
+use crate::automaton::Automaton;
+use crate::life::{
+    BitPackedLifeLike, CountingLifeLike, FlatLifeLike, LifeLike, ParallelLifeLike, TiledLifeLike,
+};
+use crate::rule::Rule;
+use crate::threads::ThreadOptions;
+use std::fmt;
+use std::str::FromStr;
+
+/// An error occurring while parsing the name of an engine
+#[derive(Debug)]
+pub struct ParseEngineError(String);
+
+impl fmt::Display for ParseEngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown engine {} (available {})",
+            self.0,
+            Engine::all()
+                .iter()
+                .map(|engine| engine.get_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseEngineError {}
+
+/// An engine computing the generations of Life-like rules
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Engine {
+    /// Look every neighbor up by its position
+    Naive,
+    /// Look the neighbors up by their index in the tiles
+    Flat,
+    /// Look the neighbors up by their index, tile by tile
+    Tiled,
+    /// Keep the neighbor counts, only updating the cells around the changes
+    Counting,
+    /// Pack the rows as bits and look the next states up in a table
+    BitPacked,
+    /// Share the tiles and their halos between threads
+    Parallel,
+}
+
+impl Engine {
+    /// Get all the available engines
+    pub fn all() -> Vec<Engine> {
+        vec![
+            Engine::Naive,
+            Engine::Flat,
+            Engine::Tiled,
+            Engine::Counting,
+            Engine::BitPacked,
+            Engine::Parallel,
+        ]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Engine::Naive => "naive",
+            Engine::Flat => "flat",
+            Engine::Tiled => "tiled",
+            Engine::Counting => "counting",
+            Engine::BitPacked => "bitpacked",
+            Engine::Parallel => "parallel",
+        }
+    }
+
+    /// Create an automaton following a rule with this engine
+    ///
+    /// @param rule The rule
+    pub fn create(&self, rule: Rule) -> Box<dyn Automaton> {
+        match self {
+            Engine::Naive => Box::new(LifeLike::new(rule)),
+            Engine::Flat => Box::new(FlatLifeLike::new(rule)),
+            Engine::Tiled => Box::new(TiledLifeLike::new(rule)),
+            Engine::Counting => Box::new(CountingLifeLike::new(rule)),
+            Engine::BitPacked => Box::new(BitPackedLifeLike::new(rule)),
+            Engine::Parallel => Box::new(ParallelLifeLike::new(rule)),
+        }
+    }
+
+    /// Create an automaton following a rule with this engine, running the
+    /// threads of the parallel engine as asked
+    ///
+    /// @param rule The rule
+    /// @param threads The number of threads and whether to pin them
+    pub fn create_with_threads(&self, rule: Rule, threads: ThreadOptions) -> Box<dyn Automaton> {
+        match self {
+            Engine::Parallel => Box::new(ParallelLifeLike::with_options(rule, threads)),
+            _ => self.create(rule),
+        }
+    }
+}
+
+impl FromStr for Engine {
+    type Err = ParseEngineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Engine::all()
+            .into_iter()
+            .find(|engine| engine.get_name() == s)
+            .ok_or_else(|| ParseEngineError(s.to_string()))
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
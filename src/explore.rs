@@ -0,0 +1,128 @@
+//! Exploration of the space of Life-like rules
+//!
+//! Random rules are scored by running random soups under them. Rules whose
+//! soups die out quickly or boil over into chaos score low, rules whose
+//! soups stay active for long and leave a variety of objects score high.
+
+use crate::batch::{run_batch, BatchConfig, BatchReport};
+use crate::rule::Rule;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+/// Number of distinct recurring objects making the structure score perfect
+const DIVERSITY_TARGET: usize = 20;
+
+/// Final density above which soups are considered chaotic
+const CHAOS_DENSITY: f64 = 0.25;
+
+/// The score of a rule
+#[derive(Clone, Debug)]
+pub struct RuleScore {
+    /// The rule
+    pub rule: Rule,
+    /// Overall score, between 0 and 1
+    pub score: f64,
+    /// Mean number of steps before the soups settle, relative to the
+    /// maximum number of steps
+    pub activity: f64,
+    /// Fraction of the soups which died out
+    pub extinction: f64,
+    /// Mean final density of the soups
+    pub density: f64,
+    /// Number of distinct objects left by several soups
+    pub objects: usize,
+}
+
+/// Score a rule by running a batch of soups under it
+///
+/// @param config The settings of the batch, including the rule
+pub fn score_rule(config: &BatchConfig) -> RuleScore {
+    let outcomes = run_batch(config);
+    let report = BatchReport::new(&outcomes);
+
+    // Soups still evolving at the end count as active for all the steps
+    let activity = outcomes
+        .iter()
+        .map(|outcome| outcome.lifespan.unwrap_or(outcome.steps) as f64)
+        .sum::<f64>()
+        / (outcomes.len().max(1) * config.max_steps.max(1)) as f64;
+    let extinction = report.extinct as f64 / report.runs.max(1) as f64;
+    let density = report.mean_population / (config.width * config.height).max(1) as f64;
+
+    // Objects found in a single soup are mostly chaotic debris, not structures
+    let mut soups_by_object = HashMap::new();
+    for name in outcomes.iter().flat_map(|outcome| outcome.census.keys()) {
+        *soups_by_object.entry(name).or_insert(0) += 1;
+    }
+    let objects = soups_by_object
+        .values()
+        .filter(|soups| **soups >= 2)
+        .count();
+
+    let structure = objects.min(DIVERSITY_TARGET) as f64 / DIVERSITY_TARGET as f64;
+    let order = if density > CHAOS_DENSITY {
+        (CHAOS_DENSITY / density).powi(4)
+    } else {
+        1.0
+    };
+
+    RuleScore {
+        rule: config.rule,
+        score: activity * structure * order * (1.0 - extinction),
+        activity,
+        extinction,
+        density,
+        objects,
+    }
+}
+
+/// Score random rules, best first
+///
+/// @param config The settings of the batches, the rule being ignored
+/// @param samples The number of rules to score
+/// @param rng The random number generator drawing the rules
+pub fn explore<R: Rng>(config: &BatchConfig, samples: usize, rng: &mut R) -> Vec<RuleScore> {
+    let mut rules = HashSet::new();
+    let mut scores = Vec::new();
+    while scores.len() < samples {
+        let rule = Rule::random(rng);
+        if !rules.insert(rule.to_string()) {
+            continue;
+        }
+
+        let mut config = config.clone();
+        config.rule = rule;
+        scores.push(score_rule(&config));
+    }
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    scores
+}
+
+/// Write scores as CSV, in the given order
+///
+/// @param writer Where to write the CSV
+/// @param scores The scores
+pub fn write_scores<W: Write>(writer: W, scores: &[RuleScore]) -> std::io::Result<()> {
+    let mut writer = writer;
+    writeln!(
+        writer,
+        "rank,rule,score,activity,extinction,density,objects"
+    )?;
+    for (rank, score) in scores.iter().enumerate() {
+        writeln!(
+            writer,
+            "{},{},{:.4},{:.4},{:.4},{:.4},{}",
+            rank + 1,
+            score.rule,
+            score.score,
+            score.activity,
+            score.extinction,
+            score.density,
+            score.objects
+        )?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,131 @@
+//! Patterns downloaded from the LifeWiki
+//!
+//! `gol fetch name` downloads the RLE of a pattern of the LifeWiki
+//! collection, as `https://conwaylife.com/patterns/gosperglidergun.rle`, into
+//! a cache directory, where `cached:name` finds it later without the network.
+//! The cache is `$XDG_CACHE_HOME/gol/patterns`, or `~/.cache/gol/patterns`.
+//!
+//! The collection is only served over HTTPS and the crate has no TLS of its
+//! own, so the download goes through `curl`.
+
+use crate::pattern::{Pattern, PatternError};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where the patterns are downloaded from when not given
+pub const DEFAULT_URL: &str = "https://conwaylife.com/patterns";
+
+/// An error occurring while fetching a pattern
+#[derive(Debug)]
+pub enum FetchError {
+    /// The name is not the one of a pattern file
+    InvalidName(String),
+    /// The cache cannot be written
+    Io(std::io::Error),
+    /// The download failed, with the reason given by curl
+    Download(String),
+    /// The downloaded file is not a pattern
+    Pattern(PatternError),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::InvalidName(name) => write!(f, "invalid pattern name {}", name),
+            FetchError::Io(err) => write!(f, "{}", err),
+            FetchError::Download(reason) => write!(f, "cannot download: {}", reason),
+            FetchError::Pattern(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<std::io::Error> for FetchError {
+    fn from(err: std::io::Error) -> Self {
+        FetchError::Io(err)
+    }
+}
+
+impl From<PatternError> for FetchError {
+    fn from(err: PatternError) -> Self {
+        FetchError::Pattern(err)
+    }
+}
+
+/// Get the directory of the downloaded patterns
+pub fn get_cache_dir() -> PathBuf {
+    let cache = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir);
+
+    cache.join("gol").join("patterns")
+}
+
+/// Get the path of a pattern in the cache, downloaded or not
+///
+/// The names are the ones of the LifeWiki files, lowercase, and cannot
+/// leave the cache.
+///
+/// @param name Name of the pattern, as `gosperglidergun`
+pub fn get_cached_path(name: &str) -> Result<PathBuf, FetchError> {
+    let is_valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+    if !is_valid {
+        return Err(FetchError::InvalidName(name.to_string()));
+    }
+
+    Ok(get_cache_dir().join(format!("{}.rle", name.to_lowercase())))
+}
+
+/// Download a pattern into the cache, replacing the previous download
+///
+/// The pattern is checked before it replaces the cached one, so a failed
+/// download or an error page never ends in the cache.
+///
+/// @param name Name of the pattern, as `gosperglidergun`
+/// @param url Where the patterns are downloaded from, as `DEFAULT_URL`
+pub fn fetch(name: &str, url: &str) -> Result<PathBuf, FetchError> {
+    let path = get_cached_path(name)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporary_path = path.with_extension("rle.tmp");
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--output",
+        ])
+        .arg(&temporary_path)
+        .arg(format!("{}/{}", url.trim_end_matches('/'), file_name))
+        .output()
+        .map_err(|err| FetchError::Download(format!("cannot run curl: {}", err)))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temporary_path);
+        return Err(FetchError::Download(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let checked = std::fs::read_to_string(&temporary_path)
+        .map_err(FetchError::from)
+        .and_then(|content| Ok(Pattern::from_rle(&content)?));
+    if let Err(err) = checked {
+        let _ = std::fs::remove_file(&temporary_path);
+        return Err(err);
+    }
+    std::fs::rename(&temporary_path, &path)?;
+
+    Ok(path)
+}
@@ -0,0 +1,219 @@
+//! C API of the engine
+//!
+//! The header is generated in `include/gol.h` with
+//! `cbindgen --config cbindgen.toml --output include/gol.h`.
+//!
+//! Worlds are opaque pointers created by `gol_world_new` and released by
+//! `gol_world_free`. Cells are bytes, 0 for a dead cell and 1 for an alive
+//! one.
+//!
+//! Panics do not unwind into the caller: the functions running the engine
+//! return null or -1 instead.
+
+use crate::geometry::Point;
+use crate::pattern::Pattern;
+use crate::world::{CellState, World};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_float, c_int};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Create a world of dead cells
+///
+/// Returns null if the world cannot be allocated. The world must be released
+/// with `gol_world_free`.
+#[no_mangle]
+pub extern "C" fn gol_world_new(width: usize, height: usize) -> *mut World {
+    match catch_unwind(|| Box::new(World::new(width, height))) {
+        Ok(world) => Box::into_raw(world),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Release a world
+///
+/// # Safety
+///
+/// `world` must be null or a pointer returned by `gol_world_new` which has
+/// not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_free(world: *mut World) {
+    if !world.is_null() {
+        drop(Box::from_raw(world));
+    }
+}
+
+/// Get the width of a world
+///
+/// # Safety
+///
+/// `world` must be a valid world.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_width(world: *const World) -> usize {
+    (*world).get_width()
+}
+
+/// Get the height of a world
+///
+/// # Safety
+///
+/// `world` must be a valid world.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_height(world: *const World) -> usize {
+    (*world).get_height()
+}
+
+/// Populate a world randomly
+///
+/// Returns 0 on success, -1 if the density is not within 0 and 1.
+///
+/// # Safety
+///
+/// `world` must be a valid world.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_populate(world: *mut World, density: c_float) -> c_int {
+    if !(0.0..=1.0).contains(&density) {
+        return -1;
+    }
+
+    let world = &mut *world;
+    catch_panic(|| world.populate(&mut rand::thread_rng(), density))
+}
+
+/// Update a world to its next generation
+///
+/// Returns 0 on success, -1 if the engine failed, leaving the cells of the
+/// world unspecified.
+///
+/// # Safety
+///
+/// `world` must be a valid world.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_step(world: *mut World) -> c_int {
+    let world = &mut *world;
+    catch_panic(|| world.update())
+}
+
+/// Count the alive cells of a world
+///
+/// # Safety
+///
+/// `world` must be a valid world.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_population(world: *const World) -> usize {
+    (*world).get_population()
+}
+
+/// Get the state of a cell, 0 for the cells out of the world
+///
+/// # Safety
+///
+/// `world` must be a valid world.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_get_cell(world: *const World, x: usize, y: usize) -> u8 {
+    let world = &*world;
+    if x < world.get_width() && y < world.get_height() {
+        world.get_tile(x, y).is_alive() as u8
+    } else {
+        0
+    }
+}
+
+/// Set the state of a cell
+///
+/// Returns 0 on success, -1 if the cell is out of the world.
+///
+/// # Safety
+///
+/// `world` must be a valid world.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_set_cell(
+    world: *mut World,
+    x: usize,
+    y: usize,
+    alive: u8,
+) -> c_int {
+    let world = &mut *world;
+    if x >= world.get_width() || y >= world.get_height() {
+        return -1;
+    }
+
+    let cell_state = if alive != 0 {
+        CellState::ALIVE
+    } else {
+        CellState::DEAD
+    };
+    world.set_tile(x, y, cell_state);
+
+    0
+}
+
+/// Copy the cells of a world, row major, one byte per cell
+///
+/// Returns the number of cells copied, or 0 if the buffer cannot hold
+/// width * height cells.
+///
+/// # Safety
+///
+/// `world` must be a valid world and `buffer` must be valid for writes of
+/// `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_get_cells(
+    world: *const World,
+    buffer: *mut u8,
+    length: usize,
+) -> usize {
+    let world = &*world;
+    let count = world.get_width() * world.get_height();
+    if buffer.is_null() || length < count {
+        return 0;
+    }
+
+    let buffer = std::slice::from_raw_parts_mut(buffer, count);
+    for y in 0..world.get_height() {
+        for x in 0..world.get_width() {
+            buffer[y * world.get_width() + x] = world.get_tile(x, y).is_alive() as u8;
+        }
+    }
+
+    count
+}
+
+/// Copy a pattern in the RLE format into a world, wrapping around the edges
+///
+/// Returns 0 on success, -1 if the pattern is invalid or cannot be stamped.
+///
+/// # Safety
+///
+/// `world` must be a valid world and `rle` a valid null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn gol_world_stamp_rle(
+    world: *mut World,
+    rle: *const c_char,
+    x: usize,
+    y: usize,
+) -> c_int {
+    if rle.is_null() {
+        return -1;
+    }
+
+    let pattern = CStr::from_ptr(rle)
+        .to_str()
+        .ok()
+        .and_then(|rle| Pattern::from_rle(rle).ok());
+    match pattern {
+        Some(pattern) => {
+            let world = &mut *world;
+            catch_panic(|| world.stamp(&pattern, Point::new(x, y)))
+        }
+        None => -1,
+    }
+}
+
+/// Run some engine code, returning 0 if it finished or -1 if it panicked
+fn catch_panic<T, F: FnOnce() -> T>(f: F) -> c_int {
+    // A world may be left half updated, its cells are then unspecified
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
@@ -0,0 +1,135 @@
+//! The forest-fire model
+//!
+//! The cells are empty, trees or burning. At each step, a burning cell
+//! becomes empty, a tree catches fire when one of its neighbors burns or when
+//! struck by lightning, and a tree grows on an empty cell with the growth
+//! probability.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::Grid;
+use crate::world::CellState;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// An empty cell
+pub const EMPTY: CellState = CellState(0);
+
+/// A tree
+pub const TREE: CellState = CellState(1);
+
+/// A burning tree
+pub const BURNING: CellState = CellState(2);
+
+/// Default probability of a tree growing on an empty cell at each step
+pub const DEFAULT_GROWTH: f32 = 0.01;
+
+/// Default probability of a tree being struck by lightning at each step
+pub const DEFAULT_LIGHTNING: f32 = 0.00001;
+
+/// The forest-fire model
+pub struct ForestFire {
+    /// Probability of a tree growing on an empty cell at each step
+    growth: f32,
+    /// Probability of a tree being struck by lightning at each step
+    lightning: f32,
+    /// Random number generator deciding the growths and the lightnings
+    rng: StdRng,
+}
+
+impl ForestFire {
+    /// Create the model
+    ///
+    /// @param growth Probability of a tree growing on an empty cell at each
+    ///               step
+    /// @param lightning Probability of a tree being struck by lightning at
+    ///                  each step
+    /// @param seed Seed of the random number generator
+    pub fn new(growth: f32, lightning: f32, seed: u64) -> Self {
+        Self {
+            growth,
+            lightning,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for ForestFire {
+    fn default() -> Self {
+        Self::new(DEFAULT_GROWTH, DEFAULT_LIGHTNING, 0)
+    }
+}
+
+impl Automaton for ForestFire {
+    fn name(&self) -> String {
+        format!(
+            "forest fire growth {} lightning {}",
+            self.growth, self.lightning
+        )
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = grid.get_tiles().to_vec();
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                match grid.get_tile(x, y) {
+                    BURNING => {
+                        *new_cell = EMPTY;
+                        stats.deaths += 1;
+                    }
+                    TREE => {
+                        let neighbor_burns = [
+                            (-1, -1),
+                            (0, -1),
+                            (1, -1),
+                            (-1, 0),
+                            (1, 0),
+                            (-1, 1),
+                            (0, 1),
+                            (1, 1),
+                        ]
+                        .iter()
+                        // The fire does not spread beyond the edges which do not wrap
+                        .filter_map(|(dx, dy)| grid.get_neighbor(x, y, *dx, *dy))
+                        .any(|(x, y)| grid.get_tile(x, y) == BURNING);
+                        if neighbor_burns || self.rng.gen::<f32>() < self.lightning {
+                            *new_cell = BURNING;
+                        }
+                    }
+                    _ => {
+                        if self.rng.gen::<f32>() < self.growth {
+                            *new_cell = TREE;
+                            stats.births += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        3
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![
+            [0.2, 0.15, 0.1, 1.0],
+            [0.1, 0.55, 0.15, 1.0],
+            [1.0, 0.5, 0.0, 1.0],
+        ]
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        vec![
+            "empty".to_string(),
+            "trees".to_string(),
+            "burning".to_string(),
+        ]
+    }
+}
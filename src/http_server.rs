@@ -0,0 +1,417 @@
+use crate::geometry::Point;
+use crate::pattern::{Pattern, PatternSource, Placement};
+use crate::raster::Rasterizer;
+use crate::render::RenderOptions;
+use crate::rule::Rule;
+use crate::simulation::{Command, Simulation};
+use crate::world::World;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Maximum size of a request body
+const MAX_BODY_SIZE: usize = 1 << 20;
+
+/// A request waiting to be handled by the simulation loop
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Vec<u8>,
+    reply: Sender<Response>,
+}
+
+impl Request {
+    /// Get the value of a query parameter
+    fn get_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response to a request
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn new(status: u16, content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            content_type,
+            body,
+        }
+    }
+
+    fn json(body: String) -> Self {
+        Self::new(200, "application/json", body.into_bytes())
+    }
+
+    fn ok() -> Self {
+        Self::json("{\"ok\":true}".to_string())
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::new(
+            status,
+            "application/json",
+            format!("{{\"error\":{:?}}}", message).into_bytes(),
+        )
+    }
+}
+
+/// An embedded HTTP server controlling the simulation
+///
+/// Endpoints:
+///   GET  /status                        Generation, population (also by state), rule, pause and speed
+///   GET  /state.json                    Live cells as JSON
+///   GET  /state.rle                     The world as RLE
+///   GET  /state.png                     The world as a PNG image
+///   POST /pause                         Pause the simulation
+///   POST /resume                        Resume the simulation
+///   POST /speed?fps=N                   Limit the steps per second (0 for no limit)
+///   POST /reseed[?density=D]            Populate the world randomly again, D within 0..1
+///   POST /stamp?placement=name@x,y[:t]  Stamp a builtin pattern
+///   POST /stamp?x=X&y=Y                 Stamp the RLE or plaintext pattern in the body
+///   POST /rule?rule=B36/S23             Follow another rule from now on, if the rule is Life-like
+pub struct HttpServer {
+    /// Requests waiting to be handled
+    requests: Receiver<Request>,
+}
+
+impl HttpServer {
+    /// Start listening for requests
+    ///
+    /// @param address The address to listen on
+    pub fn bind<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let (sender, requests) = channel();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                std::thread::spawn(move || {
+                    if let Err(err) = handle_connection(stream, sender) {
+                        eprintln!("http connection failed: {}", err);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { requests })
+    }
+
+    /// Handle the pending requests
+    ///
+    /// Queries are answered right away, while the commands are returned to be
+    /// applied by the simulation loop.
+    ///
+    /// @param simulation The running simulation
+    pub fn poll(&self, simulation: &Simulation) -> Vec<Command> {
+        let mut commands = Vec::new();
+
+        for request in self.requests.try_iter() {
+            let response = match handle_request(&request, simulation) {
+                Ok((response, command)) => {
+                    commands.extend(command);
+                    response
+                }
+                Err(response) => response,
+            };
+            let _ = request.reply.send(response);
+        }
+
+        commands
+    }
+}
+
+/// Route a request
+fn handle_request(
+    request: &Request,
+    simulation: &Simulation,
+) -> Result<(Response, Option<Command>), Response> {
+    let world = simulation.get_world();
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => Ok((
+            Response::json(format!(
+                "{{\"generation\":{},\"population\":{},\"populations\":{},\"rule\":\"{}\",\"paused\":{},\"fps\":{}}}",
+                simulation.get_generation(),
+                world.get_population(),
+                serde_json::Value::Object(
+                    world
+                        .get_automaton()
+                        .state_names()
+                        .into_iter()
+                        .zip(world.get_state_populations())
+                        .map(|(name, population)| (name, population.into()))
+                        .collect()
+                ),
+                world.get_automaton().name(),
+                simulation.is_paused(),
+                simulation
+                    .get_speed()
+                    .map_or("null".to_string(), |speed| speed.to_string())
+            )),
+            None,
+        )),
+        ("GET", "/state.json") => Ok((
+            Response::json(world_to_json(world, simulation.get_generation())),
+            None,
+        )),
+        ("GET", "/state.rle") => Ok((
+            Response::new(
+                200,
+                "text/plain",
+                Pattern::from_world(world).to_rle().into_bytes(),
+            ),
+            None,
+        )),
+        ("GET", "/state.png") => Ok((Response::new(200, "image/png", world_to_png(world)), None)),
+        ("POST", "/pause") => Ok((Response::ok(), Some(Command::Pause))),
+        ("POST", "/resume") => Ok((Response::ok(), Some(Command::Resume))),
+        ("POST", "/speed") => {
+            let fps = request
+                .get_param("fps")
+                .and_then(|fps| fps.parse::<f64>().ok())
+                .filter(|fps| *fps >= 0.0)
+                .ok_or_else(|| Response::error(400, "missing or invalid fps"))?;
+            let speed = if fps == 0.0 { None } else { Some(fps) };
+
+            Ok((Response::ok(), Some(Command::SetSpeed(speed))))
+        }
+        ("POST", "/reseed") => {
+            let density = match request.get_param("density") {
+                Some(density) => density
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|density| (0.0..=1.0).contains(density))
+                    .ok_or_else(|| Response::error(400, "invalid density"))?,
+                None => 0.5,
+            };
+
+            Ok((Response::ok(), Some(Command::Reseed(density))))
+        }
+        ("POST", "/stamp") => {
+            let (pattern, position) = parse_stamp(request)?;
+            if !world.get_bounds().contains(position) {
+                return Err(Response::error(400, "position out of the world"));
+            }
+
+            Ok((Response::ok(), Some(Command::Stamp(pattern, position))))
+        }
+        ("POST", "/rule") => {
+            let rule = request
+                .get_param("rule")
+                .and_then(|rule| rule.parse::<Rule>().ok())
+                .ok_or_else(|| Response::error(400, "missing or invalid rule"))?;
+            if !simulation.is_life_like() {
+                return Err(Response::error(409, "the automaton does not follow a Life-like rule"));
+            }
+
+            Ok((Response::ok(), Some(Command::SetRule(rule))))
+        }
+        (_, "/status")
+        | (_, "/state.json")
+        | (_, "/state.rle")
+        | (_, "/state.png")
+        | (_, "/pause")
+        | (_, "/resume")
+        | (_, "/speed")
+        | (_, "/reseed")
+        | (_, "/stamp")
+        | (_, "/rule") => Err(Response::error(405, "method not allowed")),
+        _ => Err(Response::error(404, "not found")),
+    }
+}
+
+/// Get the pattern to stamp and its position from a request
+fn parse_stamp(request: &Request) -> Result<(Pattern, Point), Response> {
+    let bad_request = |err: &dyn std::fmt::Display| Response::error(400, &err.to_string());
+
+    if let Some(placement) = request.get_param("placement") {
+        let placement = placement
+            .parse::<Placement>()
+            .map_err(|err| bad_request(&err))?;
+
+        // Do not let remote clients read files of the server
+        if let PatternSource::File(_) = placement.source {
+            return Err(Response::error(400, "only builtin patterns can be placed"));
+        }
+        let pattern = placement.load().map_err(|err| bad_request(&err))?;
+
+        Ok((pattern, placement.position))
+    } else {
+        let x = request.get_param("x").and_then(|x| x.parse().ok());
+        let y = request.get_param("y").and_then(|y| y.parse().ok());
+        let (x, y) = x
+            .zip(y)
+            .ok_or_else(|| Response::error(400, "missing or invalid position"))?;
+
+        let content = std::str::from_utf8(&request.body)
+            .map_err(|_| Response::error(400, "pattern is not valid UTF-8"))?;
+        let pattern = if content
+            .lines()
+            .any(|line| line.trim_start().starts_with('x'))
+        {
+            Pattern::from_rle(content)
+        } else {
+            Pattern::from_plaintext(content)
+        }
+        .map_err(|err| bad_request(&err))?;
+
+        Ok((pattern, Point::new(x, y)))
+    }
+}
+
+/// Encode the world as JSON, with its generation
+fn world_to_json(world: &World, generation: usize) -> String {
+    let mut value = world.to_json_value();
+    value["generation"] = generation.into();
+
+    value.to_string()
+}
+
+/// Encode the world as a PNG image, one pixel per cell
+fn world_to_png(world: &World) -> Vec<u8> {
+    let pixels = Rasterizer::new(&RenderOptions::default())
+        .rasterize(world, world.get_bounds(), 1, 1.0)
+        .to_rgb();
+
+    let mut png = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(
+            &mut png,
+            world.get_width() as u32,
+            world.get_height() as u32,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+    }
+
+    png
+}
+
+/// Read a request, hand it to the simulation loop and write back the response
+fn handle_connection(stream: TcpStream, sender: Sender<Request>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("/").to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let response = if content_length > MAX_BODY_SIZE {
+        Response::error(413, "request body too large")
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path.to_string(), parse_query(query)),
+            None => (target, Vec::new()),
+        };
+
+        let (reply, response) = channel();
+        let request = Request {
+            method,
+            path,
+            query,
+            body,
+            reply,
+        };
+        if sender.send(request).is_err() {
+            return Ok(());
+        }
+        match response.recv() {
+            Ok(response) => response,
+            Err(_) => return Ok(()),
+        }
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+
+    Ok(())
+}
+
+/// Split a query string in decoded key/value pairs
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (percent_decode(key), percent_decode(value)),
+            None => (percent_decode(pair), String::new()),
+        })
+        .collect()
+}
+
+/// Decode a percent encoded URL component
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' if index + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[index + 1..index + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
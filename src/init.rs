@@ -0,0 +1,127 @@
+//! Setups of the world written on the command line
+//!
+//! A setup is a list of operations separated by `;`, applied in order:
+//!
+//! - `pattern@x,y [transform...]` places a pattern, as `gun.rle@0,0 rot90`
+//! - `soup WxH@x,y [d=density]` fills a rectangle randomly, as
+//!   `soup 64x64@100,100 d=0.4` (default density 0.5)
+//!
+//! so `glider@10,10; gun.rle@0,0 rot90; soup 64x64@100,100 d=0.4`
+//! reproduces a complex setup from a single shell command.
+
+use crate::geometry::{Point, Rect};
+use crate::pattern::{PatternError, Placement};
+use crate::world::{CellState, World};
+use rand::Rng;
+use std::str::FromStr;
+
+/// Density of the soups not giving one
+const DEFAULT_SOUP_DENSITY: f32 = 0.5;
+
+/// A rectangle filled randomly
+#[derive(Clone, Debug, PartialEq)]
+pub struct Soup {
+    /// The rectangle to fill
+    pub area: Rect,
+    /// Probability of a cell to be alive
+    pub density: f32,
+}
+
+/// An operation setting the world up
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitOperation {
+    /// Place a pattern
+    Place(Placement),
+    /// Fill a rectangle randomly
+    Soup(Soup),
+}
+
+impl InitOperation {
+    /// Apply the operation to a world, the positions wrapping around the
+    /// edges of the world which wrap, the cells beyond the others left out
+    ///
+    /// @param world The world to set up
+    /// @param rng The random number generator filling the soups
+    pub fn apply<R: Rng>(&self, world: &mut World, rng: &mut R) -> Result<(), PatternError> {
+        match self {
+            InitOperation::Place(placement) => world.stamp(&placement.load()?, placement.position),
+            InitOperation::Soup(soup) => {
+                let seed_states = world.get_automaton().seed_states();
+                for point in soup.area.get_points() {
+                    let cell_state = if rng.gen::<f32>() >= soup.density {
+                        CellState::DEAD
+                    } else {
+                        seed_states[rng.gen_range(0, seed_states.len())]
+                    };
+                    if let Some(point) =
+                        world.translate(Point::default(), point.x as isize, point.y as isize)
+                    {
+                        world.set_tile(point.x, point.y, cell_state);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for InitOperation {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PatternError::InvalidPlacement(s.to_string());
+
+        let mut words = s.split_whitespace();
+        match words.next().ok_or_else(invalid)? {
+            "soup" => {
+                let area = words.next().ok_or_else(invalid)?;
+                let (size, position) = area.split_once('@').ok_or_else(invalid)?;
+                let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+                let (x, y) = position.split_once(',').ok_or_else(invalid)?;
+                let density = match words.next() {
+                    Some(density) => density
+                        .strip_prefix("d=")
+                        .and_then(|density| density.parse::<f32>().ok())
+                        .filter(|density| (0.0..=1.0).contains(density))
+                        .ok_or_else(invalid)?,
+                    None => DEFAULT_SOUP_DENSITY,
+                };
+                if words.next().is_some() {
+                    return Err(invalid());
+                }
+
+                Ok(InitOperation::Soup(Soup {
+                    area: Rect::new(
+                        x.parse().map_err(|_| invalid())?,
+                        y.parse().map_err(|_| invalid())?,
+                        width.parse().map_err(|_| invalid())?,
+                        height.parse().map_err(|_| invalid())?,
+                    ),
+                    density,
+                }))
+            }
+            placement => {
+                // The transformations follow the position as for --place
+                let mut placement = placement.parse::<Placement>()?;
+                for transform in words {
+                    placement.transforms.push(transform.parse()?);
+                }
+
+                Ok(InitOperation::Place(placement))
+            }
+        }
+    }
+}
+
+/// Parse a setup, a list of operations separated by `;`
+///
+/// @param s The setup, as `glider@10,10; soup 64x64@100,100 d=0.4`
+pub fn parse_init(s: &str) -> Result<Vec<InitOperation>, PatternError> {
+    s.split(';')
+        .map(str::trim)
+        // Allow a trailing separator
+        .filter(|operation| !operation.is_empty())
+        .map(|operation| operation.parse::<InitOperation>())
+        .collect()
+}
@@ -0,0 +1,299 @@
+//! Kernels computing the next states of the bit-packed rows of the mapped
+//! worlds
+//!
+//! The same portable code is compiled several times, with the instructions
+//! of each set enabled, and the compiler vectorizes each copy with the
+//! instructions it may use. The best one supported by the CPU running the
+//! program is picked at runtime, so a single binary makes use of the wider
+//! vectors of the machines having them. Choosing a kernel only hints at the
+//! code generation, all of them compute the same states.
+//!
+//! There are no hand-written intrinsics. SSE2 and NEON belong to the baseline
+//! of x86-64 and aarch64, so the scalar kernel is already vectorized with
+//! them, and only AVX2 widens the vectors. The kernels only compute the
+//! worlds of `gol mapped`: the bit-packed engine of the other commands lives
+//! in `gol-core`, which cannot detect the features of the CPU without the
+//! standard library.
+
+use crate::rule::Rule;
+use std::fmt;
+use std::str::FromStr;
+
+/// An error occurring while parsing the name of a kernel
+#[derive(Debug)]
+pub struct ParseKernelError(String);
+
+impl fmt::Display for ParseKernelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown kernel {} (available {})",
+            self.0,
+            Kernel::all()
+                .iter()
+                .map(|kernel| kernel.get_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseKernelError {}
+
+/// An instruction set the code of the kernel is compiled with
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kernel {
+    /// The instructions every CPU of the target supports
+    Scalar,
+    /// The 256-bit vectors of the x86 CPUs since 2013
+    Avx2,
+    /// The 128-bit vectors of the ARM CPUs
+    Neon,
+}
+
+impl Kernel {
+    /// Get all the kernels, supported or not
+    pub fn all() -> Vec<Kernel> {
+        vec![Kernel::Scalar, Kernel::Avx2, Kernel::Neon]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Kernel::Scalar => "scalar",
+            Kernel::Avx2 => "avx2",
+            Kernel::Neon => "neon",
+        }
+    }
+
+    /// Whether the CPU running the program supports the kernel
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Kernel::Scalar => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Kernel::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "aarch64")]
+            Kernel::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Get the fastest kernel supported by the CPU running the program
+    pub fn detect() -> Kernel {
+        [Kernel::Avx2, Kernel::Neon]
+            .iter()
+            .copied()
+            .find(|kernel| kernel.is_supported())
+            .unwrap_or(Kernel::Scalar)
+    }
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+
+impl FromStr for Kernel {
+    type Err = ParseKernelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Kernel::all()
+            .into_iter()
+            .find(|kernel| kernel.get_name() == s)
+            .ok_or_else(|| ParseKernelError(s.to_string()))
+    }
+}
+
+impl fmt::Display for Kernel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+/// Compute the next states of bit-packed rows, 64 cells per word
+///
+/// Bit x % 64 of word x / 64 of a row is the cell of column x. The rows wrap
+/// around their left and right edges.
+pub struct RowKernel {
+    /// The instruction set used
+    kernel: Kernel,
+    /// Whether a dead cell with this count of alive neighbors is born
+    births: [bool; 9],
+    /// Whether an alive cell with this count of alive neighbors survives
+    survivals: [bool; 9],
+    /// Width of the rows
+    width: usize,
+    /// The rows above, current and below shifted to the west and to the
+    /// east, reused from row to row
+    shifted_rows: [Vec<u64>; 6],
+}
+
+impl RowKernel {
+    /// Create a kernel
+    ///
+    /// Falls back to the scalar kernel if the CPU does not support the one
+    /// asked for.
+    ///
+    /// @param rule The rule
+    /// @param width Width of the rows, at least 1
+    /// @param kernel The instruction set to use
+    pub fn new(rule: &Rule, width: usize, kernel: Kernel) -> Self {
+        let mut births = [false; 9];
+        let mut survivals = [false; 9];
+        for count in 0..9 {
+            births[count] = rule.is_birth(count);
+            survivals[count] = rule.is_survival(count);
+        }
+
+        Self {
+            kernel: if kernel.is_supported() {
+                kernel
+            } else {
+                Kernel::Scalar
+            },
+            births,
+            survivals,
+            width,
+            shifted_rows: Default::default(),
+        }
+    }
+
+    pub fn get_kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    /// Compute the next states of a row
+    ///
+    /// @param above The row above
+    /// @param current The row
+    /// @param below The row below
+    /// @param new_row Where the next states are written
+    pub fn next_row(&mut self, above: &[u64], current: &[u64], below: &[u64], new_row: &mut [u64]) {
+        let [above_west, above_east, west, east, below_west, below_east] = &mut self.shifted_rows;
+        for (row, row_west, row_east) in [
+            (above, &mut *above_west, &mut *above_east),
+            (current, &mut *west, &mut *east),
+            (below, &mut *below_west, &mut *below_east),
+        ] {
+            row_west.resize(row.len(), 0);
+            row_east.resize(row.len(), 0);
+            shift(row, self.width, row_west, row_east);
+        }
+        let [above_west, above_east, west, east, below_west, below_east] = &self.shifted_rows;
+        let neighbors = [
+            &above_west[..],
+            above,
+            &above_east[..],
+            &west[..],
+            &east[..],
+            &below_west[..],
+            below,
+            &below_east[..],
+        ];
+
+        match self.kernel {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            // Checked when the kernel was created
+            Kernel::Avx2 => unsafe { self.next_words_avx2(&neighbors, current, new_row) },
+            #[cfg(target_arch = "aarch64")]
+            Kernel::Neon => unsafe { self.next_words_neon(&neighbors, current, new_row) },
+            _ => self.next_words(&neighbors, current, new_row),
+        }
+
+        // Keep the bits beyond the last column dead
+        if !self.width.is_multiple_of(64) {
+            new_row[self.width / 64] &= (1 << (self.width % 64)) - 1;
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[target_feature(enable = "avx2")]
+    unsafe fn next_words_avx2(
+        &self,
+        neighbors: &[&[u64]; 8],
+        current: &[u64],
+        new_row: &mut [u64],
+    ) {
+        self.next_words(neighbors, current, new_row)
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    #[target_feature(enable = "neon")]
+    unsafe fn next_words_neon(
+        &self,
+        neighbors: &[&[u64]; 8],
+        current: &[u64],
+        new_row: &mut [u64],
+    ) {
+        self.next_words(neighbors, current, new_row)
+    }
+
+    /// Compute the next states of the words of a row
+    ///
+    /// The neighbors of the cells are counted with bitwise adders, the bit n
+    /// of the counters holding the bit n of the count of each of the 64
+    /// cells. Inlined in the functions compiled for each instruction set,
+    /// which the compiler vectorizes with their own instructions.
+    #[inline(always)]
+    fn next_words(&self, neighbors: &[&[u64]; 8], current: &[u64], new_row: &mut [u64]) {
+        for (i, new_word) in new_row.iter_mut().enumerate() {
+            let mut counters = [0u64; 4];
+            for neighbor_row in neighbors.iter() {
+                let mut carry = neighbor_row[i];
+                for counter in counters.iter_mut() {
+                    let next_carry = *counter & carry;
+                    *counter ^= carry;
+                    carry = next_carry;
+                }
+            }
+
+            let center = current[i];
+            let mut next = 0;
+            for count in 0..9 {
+                if !self.births[count] && !self.survivals[count] {
+                    continue;
+                }
+
+                let mut matching = !0u64;
+                for (bit, counter) in counters.iter().enumerate() {
+                    matching &= if count >> bit & 1 == 1 {
+                        *counter
+                    } else {
+                        !*counter
+                    };
+                }
+                if self.births[count] {
+                    next |= matching & !center;
+                }
+                if self.survivals[count] {
+                    next |= matching & center;
+                }
+            }
+            *new_word = next;
+        }
+    }
+}
+
+/// Shift a row one cell to the east and to the west, wrapping around the
+/// edges
+///
+/// @param row The row
+/// @param width Width of the row, at least 1
+/// @param west Where the row shifted to the west is written, as long as it
+/// @param east Where the row shifted to the east is written, as long as it
+fn shift(row: &[u64], width: usize, west: &mut [u64], east: &mut [u64]) {
+    let last = width - 1;
+    let cell = |x: usize| (row[x / 64] >> (x % 64)) & 1;
+
+    // Bit x of the western row is the cell of column x - 1
+    for (i, word) in west.iter_mut().enumerate() {
+        *word = row[i] << 1 | if i > 0 { row[i - 1] >> 63 } else { 0 };
+    }
+    west[0] |= cell(last);
+    // Bit x of the eastern row is the cell of column x + 1
+    for (i, word) in east.iter_mut().enumerate() {
+        *word = row[i] >> 1 | row.get(i + 1).map_or(0, |word| word << 63);
+    }
+    east[last / 64] |= cell(0) << (last % 64);
+}
@@ -0,0 +1,171 @@
+//! Independent Life-like worlds sharing a grid
+//!
+//! Each layer is a Life-like world with its own rule, stored as a bit of the
+//! state of the cells: the state of a cell is the set of the layers in which
+//! it is alive. The layers start from the same soup and are drawn as blended
+//! color channels, so their dynamics are compared at a glance.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::Grid;
+use crate::rule::Rule;
+use crate::world::CellState;
+use std::fmt;
+
+/// Maximum number of layers, one per color channel
+pub const MAX_LAYERS: usize = 3;
+
+/// Color of the cells alive in each layer: red, blue then green
+const LAYER_COLORS: [Color; MAX_LAYERS] = [
+    [1.0, 0.0, 0.0, 1.0],
+    [0.0, 0.0, 1.0, 1.0],
+    [0.0, 1.0, 0.0, 1.0],
+];
+
+/// An error occurring while creating layers
+#[derive(Debug)]
+pub struct LayersError(usize);
+
+impl fmt::Display for LayersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} layers, between 1 and {} are supported",
+            self.0, MAX_LAYERS
+        )
+    }
+}
+
+impl std::error::Error for LayersError {}
+
+/// Life-like worlds with their own rules, one per bit of the cell states
+pub struct Layers {
+    /// The rule of each layer
+    rules: Vec<Rule>,
+}
+
+impl Layers {
+    /// Create layers
+    ///
+    /// @param rules The rule of each layer
+    pub fn new(rules: Vec<Rule>) -> Result<Self, LayersError> {
+        if rules.is_empty() || rules.len() > MAX_LAYERS {
+            return Err(LayersError(rules.len()));
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn get_rules(&self) -> &[Rule] {
+        &self.rules
+    }
+}
+
+impl Automaton for Layers {
+    fn name(&self) -> String {
+        self.rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = vec![CellState::DEAD; grid.get_tiles().len()];
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                let cell_state = grid.get_tile(x, y).0;
+                let neighbors: Vec<u8> = [
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (-1, 0),
+                    (1, 0),
+                    (-1, 1),
+                    (0, 1),
+                    (1, 1),
+                ]
+                .iter()
+                // The neighbors beyond the edges which do not wrap are dead
+                .filter_map(|(dx, dy)| grid.get_neighbor(x, y, *dx, *dy))
+                .map(|(x, y)| grid.get_tile(x, y).0)
+                .collect();
+
+                // Each layer follows its rule, ignoring the others
+                let mut new_state = 0;
+                for (layer, rule) in self.rules.iter().enumerate() {
+                    let bit = 1 << layer;
+                    let neighbors_count = neighbors
+                        .iter()
+                        .filter(|neighbor| *neighbor & bit != 0)
+                        .count();
+                    let alive = cell_state & bit != 0;
+                    if alive && rule.is_survival(neighbors_count) {
+                        new_state |= bit;
+                    } else if !alive && rule.is_birth(neighbors_count) {
+                        new_state |= bit;
+                        stats.births += 1;
+                    } else if alive {
+                        stats.deaths += 1;
+                    }
+                }
+                *new_cell = CellState(new_state);
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        1 << self.rules.len()
+    }
+
+    /// Blend the colors of the layers in which the cells are alive, on black
+    fn palette(&self) -> Vec<Color> {
+        (0..self.states())
+            .map(|state| {
+                let mut color = [0.0, 0.0, 0.0, 1.0];
+                for (layer, layer_color) in LAYER_COLORS.iter().enumerate() {
+                    if state & (1 << layer) != 0 {
+                        for (component, layer_component) in
+                            color.iter_mut().zip(layer_color.iter()).take(3)
+                        {
+                            *component = (*component + layer_component).min(1.0);
+                        }
+                    }
+                }
+
+                color
+            })
+            .collect()
+    }
+
+    /// Name the states after the rules of the layers in which the cells are
+    /// alive
+    fn state_names(&self) -> Vec<String> {
+        (0..self.states())
+            .map(|state| {
+                if state == 0 {
+                    return "dead".to_string();
+                }
+
+                self.rules
+                    .iter()
+                    .enumerate()
+                    .filter(|(layer, _)| state & (1 << layer) != 0)
+                    .map(|(_, rule)| rule.to_string())
+                    .collect::<Vec<_>>()
+                    .join("+")
+            })
+            .collect()
+    }
+
+    /// Every layer starts from the same soup
+    fn seed_states(&self) -> Vec<CellState> {
+        vec![CellState((self.states() - 1) as u8)]
+    }
+}
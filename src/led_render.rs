@@ -0,0 +1,131 @@
+use crate::raster::Rasterizer;
+use crate::render::{ColorOptions, LedOptions, Render, RenderError, RenderOptions};
+use crate::world::World;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+/// The SPI device the LEDs are wired to
+const SPI_DEVICE: &str = "/dev/spidev0.0";
+
+/// Clock of the SPI bus, three SPI bits encoding one bit of the LEDs
+const SPI_SPEED_HZ: u32 = 2_400_000;
+
+/// Zero bytes sent after a frame to latch it, longer than 50 µs
+const RESET_BYTES: usize = 40;
+
+/// A render driving a matrix of WS2812 RGB LEDs over the SPI bus of a
+/// Raspberry Pi
+///
+/// Each cell is an LED, chained row by row from the top left corner. The
+/// frames of the bigger matrices exceed the default spidev buffer of 4096
+/// bytes (9 bytes per LED), the `spidev.bufsiz` kernel parameter must be
+/// raised for them.
+pub struct LedRenderType {
+    /// The SPI device
+    device: File,
+    /// Brightness, gamma and layout of the matrix
+    options: LedOptions,
+    /// Colors of the cells
+    rasterizer: Rasterizer,
+}
+
+impl LedRenderType {
+    /// Open the SPI device
+    ///
+    /// @param options Options of the render, for the brightness, gamma and
+    /// layout of the matrix and the colors of the cells
+    pub fn new(options: &RenderOptions) -> Result<Self, RenderError> {
+        let device = OpenOptions::new()
+            .write(true)
+            .open(SPI_DEVICE)
+            .map_err(|err| RenderError::Device(format!("cannot open {}: {}", SPI_DEVICE, err)))?;
+        set_speed(&device, SPI_SPEED_HZ).map_err(|err| {
+            RenderError::Device(format!("cannot set the speed of {}: {}", SPI_DEVICE, err))
+        })?;
+
+        Ok(Self {
+            device,
+            options: options.led,
+            rasterizer: Rasterizer::new(options),
+        })
+    }
+
+    /// Encode the world as the SPI bits of the LEDs
+    fn encode(&mut self, world: &World) -> Vec<u8> {
+        self.rasterizer.update(world);
+        let image = self.rasterizer.rasterize(world, world.get_bounds(), 1, 1.0);
+        // Correct the colors for the eye, then dim them
+        let component = |value: u8| {
+            ((value as f32 / 255.0).powf(self.options.gamma)
+                * self.options.brightness.clamp(0.0, 1.0)
+                * 255.0)
+                .round() as u8
+        };
+
+        let width = world.get_width();
+        let mut data = Vec::with_capacity(world.get_tiles().len() * 9 + RESET_BYTES);
+        for y in 0..world.get_height() {
+            for column in 0..width {
+                // Serpentine matrices chain the odd rows from right to left
+                let x = if self.options.serpentine && y % 2 == 1 {
+                    width - 1 - column
+                } else {
+                    column
+                };
+                let [red, green, blue, _] = image.get_pixel(x, y);
+                for value in [green, red, blue] {
+                    data.extend_from_slice(&encode_byte(component(value)));
+                }
+            }
+        }
+        data.extend(std::iter::repeat_n(0, RESET_BYTES));
+
+        data
+    }
+}
+
+impl Render for LedRenderType {
+    fn render(&mut self, world: &World) -> Result<(), RenderError> {
+        let data = self.encode(world);
+        self.device
+            .write_all(&data)
+            .map_err(|err| RenderError::Device(format!("cannot write to {}: {}", SPI_DEVICE, err)))
+    }
+
+    fn set_colors(&mut self, colors: ColorOptions) {
+        self.rasterizer.set_colors(colors);
+    }
+}
+
+/// Encode a byte as the 24 SPI bits of its 8 bits, 100 for a 0 and 110 for
+/// a 1, most significant bit first
+fn encode_byte(byte: u8) -> [u8; 3] {
+    let mut bits = 0u32;
+    for bit in (0..8).rev() {
+        bits = bits << 3 | if byte >> bit & 1 == 1 { 0b110 } else { 0b100 };
+    }
+
+    [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8]
+}
+
+/// Set the clock of an SPI device
+#[cfg(target_os = "linux")]
+fn set_speed(device: &File, speed: u32) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // _IOW('k', 4, u32)
+    const SPI_IOC_WR_MAX_SPEED_HZ: libc::c_ulong = 0x4004_6b04;
+
+    let result = unsafe { libc::ioctl(device.as_raw_fd(), SPI_IOC_WR_MAX_SPEED_HZ as _, &speed) };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Set the clock of an SPI device, left to the system outside Linux
+#[cfg(not(target_os = "linux"))]
+fn set_speed(_device: &File, _speed: u32) -> std::io::Result<()> {
+    Ok(())
+}
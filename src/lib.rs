@@ -1,4 +1,77 @@
+// The grid, the rules and the automata live in gol-core, which builds
+// without the standard library
+pub use gol_core::{
+    automaton, cyclic, generations, geometry, grid, life, rule, species, threads, wireworld,
+};
+
+pub mod apng_render;
+pub mod art;
+pub mod auto_pause;
+pub mod automaton_type;
+pub mod autosave;
+pub mod batch;
+pub mod bench;
+pub mod census;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config;
+pub mod consistency;
+pub mod delta;
+pub mod density_map;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod distributed;
+pub mod duration;
+pub mod engine;
+pub mod explore;
+pub mod fetch;
+pub mod ffi;
+pub mod forest_fire;
+pub mod http_server;
+pub mod init;
+pub mod kernel;
+pub mod layers;
+#[cfg(all(feature = "led", not(target_arch = "wasm32")))]
+pub mod led_render;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mapped_world;
 pub mod none_render;
+pub mod observer;
+pub mod osc_output;
+pub mod pattern;
+#[cfg(all(feature = "piston", not(target_arch = "wasm32")))]
 pub mod piston_render;
+pub mod plot;
+#[cfg(all(feature = "plugin", not(target_arch = "wasm32")))]
+pub mod plugin;
+pub mod population_graph;
+pub mod predecessor;
+pub mod raster;
+pub mod rawvideo_render;
+pub mod remote;
 pub mod render;
+pub mod repl;
+pub mod replay;
+pub mod rng;
+pub mod rule_colors;
+#[cfg(all(feature = "script", not(target_arch = "wasm32")))]
+pub mod script;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod shm_output;
+pub mod simulation;
+pub mod sir;
+pub mod snapshot;
+pub mod speed;
+pub mod stability;
+pub mod summary;
+pub mod sweep;
+pub mod telemetry;
+#[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+pub mod terminal_render;
+pub mod timings;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
 pub mod world;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ws_server;
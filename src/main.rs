@@ -1,54 +1,693 @@
-use gol::render::RenderType;
-use gol::world::CellState;
+use gol::art::ArtPalette;
+use gol::auto_pause::{AutoPause, PauseTrigger};
+use gol::automaton_type::{AutomatonOptions, AutomatonType};
+use gol::autosave::{self, Autosave};
+use gol::batch::{run_batch, BatchConfig, BatchReport};
+use gol::bench::{bench, format_bytes, BenchConfig};
+use gol::config::{Config, ConfigError};
+use gol::consistency::{fingerprint, EngineCheck};
+use gol::density_map::{self, DensityMapWriter};
+use gol::distributed::{run_worker, Master};
+use gol::duration::parse_duration;
+use gol::engine::Engine;
+use gol::explore::{explore, write_scores};
+use gol::fetch::{fetch, DEFAULT_URL};
+use gol::geometry::Point;
+use gol::http_server::HttpServer;
+use gol::init::{parse_init, InitOperation};
+use gol::kernel::Kernel;
+use gol::layers::{Layers, MAX_LAYERS};
+use gol::mapped_world::MappedWorld;
+use gol::none_render::NoneRenderType;
+use gol::osc_output::OscOutput;
+use gol::pattern::{PatternMetadata, PatternSource, Placement};
+use gol::plot::PopulationPlot;
+#[cfg(feature = "plugin")]
+use gol::plugin::PluginRule;
+use gol::population_graph::PopulationGraph;
+use gol::predecessor::find_predecessor;
+use gol::remote::{RemoteServer, RemoteViewer};
+use gol::render::{parse_color, Render, RenderOptions, RenderType};
+use gol::repl::Repl;
+use gol::replay::{Recorder, Replay};
+use gol::rng::{parse_seed, RngKind, SessionRng};
+use gol::rule::{Rule, RulePool};
+use gol::rule_colors::RuleColors;
+#[cfg(feature = "script")]
+use gol::script::Script;
+use gol::shm_output::SharedMemoryOutput;
+use gol::simulation::{Command, Simulation, DEFAULT_HISTORY_SIZE};
+use gol::snapshot;
+use gol::speed::SpeedMeter;
+use gol::stability::StabilityDetector;
+use gol::summary::SummaryTracker;
+use gol::sweep::{sweep, SweepParameter};
+use gol::telemetry::{TelemetrySample, TelemetryWriter};
+use gol::threads::ThreadOptions;
+use gol::timings::{Phase, Timings};
+use gol::verify::verify;
+use gol::watch::FileWatcher;
 use gol::world::World;
+use gol::ws_server::WsServer;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long to wait between two polls of the commands while paused
+const PAUSE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// How long to wait between two frames while waiting for the next step, and
+/// between two frames skipping the generations without a target frame rate
+const FRAME_INTERVAL: std::time::Duration = std::time::Duration::from_millis(16);
+
+/// How long to wait between two updates of the heads-up information
+const HUD_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// How long to wait between two checks of the config file
+const CONFIG_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How long to wait between two checks of the pattern files
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// The keys of the config file applied without restarting
+const LIVE_CONFIG_KEYS: &[&str] = &[
+    "fg",
+    "bg",
+    "dead-color",
+    "colors",
+    "target-fps",
+    "render-every",
+    "frame-skip",
+    "osc",
+];
+
+/// How long to wait between two updates of the title
+const TITLE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The title of the window when no format is given
+const DEFAULT_TITLE_FORMAT: &str =
+    "Game of Life - {width}x{height} - {rule} - generation {generation} - population {population} - {gps} gen/s";
+
+/// Number of generations after which the screensaver reseeds the world even
+/// if it is still evolving
+const SCREENSAVER_MAX_GENERATIONS: usize = 10_000;
+
+/// Most states whose populations are shown in the heads-up information
+const HUD_MAX_STATES: usize = 4;
+
+/// Default maximum number of steps of the worlds of a batch
+const BATCH_MAX_STEPS: usize = 1000;
+
+/// Default number of steps run by each engine of a benchmark
+const BENCH_STEPS: usize = 100;
+
+/// Number of steps run on a mapped world when no maximum is given
+const MAPPED_STEPS: usize = 100;
+
+/// Print a progress message, to stderr when stdout carries the frames, the
+/// answers of the console or the final world, not at all when the frames are
+/// drawn in the terminal
+macro_rules! progress {
+    ($settings:expr, $($arg:tt)*) => {
+        if $settings.render_type.uses_terminal() {
+            // Nowhere to print without garbling the frames
+        } else if $settings.render_type.writes_to_stdout()
+            || $settings.repl
+            || $settings.print_hash
+            || ($settings.dump_final_ascii && $settings.ascii_dump_path.is_none())
+        {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// What the program does
+#[derive(PartialEq)]
+enum Mode {
+    /// Run the simulation locally
+    Run,
+    /// Run the simulation headless and stream it to remote viewers
+    Serve,
+    /// Render a simulation running on a remote server
+    View,
+    /// Run many random worlds and report statistics about them
+    Batch,
+    /// Run batches of worlds for several values of a parameter
+    Sweep,
+    /// Score random rules to find the interesting ones
+    Explore,
+    /// Search a predecessor of a pattern
+    Predecessor,
+    /// Compare the speed of the engines
+    Bench,
+    /// Run a world stored in a memory-mapped file
+    Mapped,
+    /// Run a world split between workers
+    Distribute,
+    /// Simulate bands of worlds for a master
+    Worker,
+    /// Check the engines on canonical patterns
+    Verify,
+    /// Download patterns of the LifeWiki
+    Fetch,
+    /// Describe a pattern
+    ShowPattern,
+}
 
 struct Settings {
+    mode: Mode,
     world_width: usize,
     world_height: usize,
-    population_density: f32,
+    population_density: Option<f32>,
+    init_operations: Vec<InitOperation>,
+    rule: Rule,
+    rule_cycle: Vec<Rule>,
+    layers: Vec<Rule>,
+    automaton_type: AutomatonType,
+    automaton_options: AutomatonOptions,
     run_steps_max: Option<usize>,
+    max_time: Option<Duration>,
     render_type: RenderType,
+    render_options: RenderOptions,
+    render_every: usize,
+    frame_skip: bool,
+    target_fps: Option<f64>,
     display_help: bool,
+    config_path: Option<PathBuf>,
+    ws_address: Option<String>,
+    http_address: Option<String>,
+    tcp_address: Option<String>,
+    shm_name: Option<String>,
+    osc_address: Option<String>,
+    view_url: Option<String>,
+    mapped_path: Option<PathBuf>,
+    worker_addresses: Vec<String>,
+    worker_address: Option<String>,
+    fetch_names: Vec<String>,
+    fetch_url: String,
+    checkpoint_every: Option<usize>,
+    kernel: Option<Kernel>,
+    seed: Option<u64>,
+    rng: RngKind,
+    record_path: Option<PathBuf>,
+    replay_path: Option<PathBuf>,
+    history_size: Option<usize>,
+    load_path: Option<PathBuf>,
+    save_path: Option<PathBuf>,
+    autosave_interval: Option<Duration>,
+    autosave_generations: Option<usize>,
+    autosave_keep: usize,
+    autosave_path: PathBuf,
+    dump_final_ascii: bool,
+    print_hash: bool,
+    ascii_dump_path: Option<PathBuf>,
+    batch_runs: usize,
+    threads: ThreadOptions,
+    sweep_parameter: Option<SweepParameter>,
+    explore_samples: usize,
+    output_path: PathBuf,
+    pattern_source: Option<PatternSource>,
+    wrap_x: bool,
+    wrap_y: bool,
+    engines: Vec<Engine>,
+    engine: Option<Engine>,
+    verify_against: Option<Engine>,
+    timings_json_path: Option<PathBuf>,
+    telemetry_path: Option<PathBuf>,
+    density_map_path: Option<PathBuf>,
+    density_map_size: usize,
+    density_map_every: usize,
+    plot_path: Option<PathBuf>,
+    summary: bool,
+    summary_json_path: Option<PathBuf>,
+    screensaver: bool,
+    pause_triggers: Vec<PauseTrigger>,
+    watch: bool,
+    repl: bool,
+    title_format: Option<String>,
+    script_path: Option<PathBuf>,
+    rule_plugin_path: Option<PathBuf>,
+    random_rules: Option<RulePool>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            mode: Mode::Run,
             world_width: 320,
             world_height: 240,
-            population_density: 0.5,
+            population_density: None,
+            init_operations: Vec::new(),
+            rule: Rule::default(),
+            rule_cycle: Vec::new(),
+            layers: Vec::new(),
+            automaton_type: AutomatonType::default(),
+            automaton_options: AutomatonOptions::default(),
             run_steps_max: None,
-            render_type: RenderType::Piston,
+            max_time: None,
+            render_type: RenderType::default(),
+            render_options: RenderOptions::default(),
+            render_every: 1,
+            frame_skip: false,
+            target_fps: None,
             display_help: false,
+            config_path: None,
+            ws_address: None,
+            http_address: None,
+            tcp_address: None,
+            shm_name: None,
+            osc_address: None,
+            view_url: None,
+            mapped_path: None,
+            worker_addresses: Vec::new(),
+            worker_address: None,
+            fetch_names: Vec::new(),
+            fetch_url: DEFAULT_URL.to_string(),
+            checkpoint_every: None,
+            kernel: None,
+            seed: None,
+            rng: RngKind::default(),
+            record_path: None,
+            replay_path: None,
+            history_size: None,
+            load_path: None,
+            save_path: None,
+            autosave_interval: None,
+            autosave_generations: None,
+            autosave_keep: autosave::DEFAULT_KEEP,
+            autosave_path: PathBuf::from("autosave.gols"),
+            dump_final_ascii: false,
+            print_hash: false,
+            ascii_dump_path: None,
+            batch_runs: 100,
+            threads: ThreadOptions::default(),
+            sweep_parameter: None,
+            explore_samples: 50,
+            output_path: PathBuf::from("rules.csv"),
+            pattern_source: None,
+            wrap_x: true,
+            wrap_y: true,
+            engines: Engine::all(),
+            engine: None,
+            verify_against: None,
+            timings_json_path: None,
+            telemetry_path: None,
+            density_map_path: None,
+            density_map_size: density_map::DEFAULT_SIZE,
+            density_map_every: 1,
+            plot_path: None,
+            summary: false,
+            summary_json_path: None,
+            screensaver: false,
+            pause_triggers: Vec::new(),
+            watch: false,
+            repl: false,
+            title_format: None,
+            script_path: None,
+            rule_plugin_path: None,
+            random_rules: None,
         }
     }
 }
 
 fn usage() {
-    println!("Usage: gol [--help] [--width width] [--height height] [--max-steps steps]");
-    println!("");
+    println!(
+        "Usage: gol [run|serve] [--help] [--width width] [--height height] [--max-steps steps]"
+    );
+    println!("       gol view [--render type] url");
+    println!(
+        "       gol batch [--runs runs] [--threads threads] [--pin-threads] [--max-steps steps]"
+    );
+    println!("       gol sweep (--density values | --rule values | --size values) [--runs-per-point runs]");
+    println!("       gol explore [--samples samples] [--runs runs] [--output path]");
+    println!("       gol predecessor [--rule rule] pattern");
+    println!("       gol bench [--engines engines] [--max-steps steps] [--threads threads] [--pin-threads]");
+    println!("       gol verify [--engines engines]");
+    println!("       gol mapped [--width width] [--height height] [--max-steps steps] path");
+    println!(
+        "       gol distribute --workers addresses [--max-time duration] [--checkpoint-every generations --save path]"
+    );
+    println!("       gol worker address");
+    println!("       gol fetch [--url url] name...");
+    println!("       gol patterns show pattern");
+    println!();
+    println!("Commands");
+    println!("    run                Run the simulation locally (default)");
+    println!("    serve              Run the simulation headless and stream it to remote viewers");
+    println!("    view url           Render the simulation of a server (url is tcp://host:port)");
+    println!("    batch              Run many random worlds and report statistics about them");
+    println!("    sweep              Run batches for several values of a parameter, output as CSV");
+    println!("                       densities are start:end:step or a,b,c, rules B3/S23,B36/S23");
+    println!("                       and sizes 32x32,64x64");
+    println!(
+        "    explore            Score random rules on 64x64 soups (8 runs per rule by default)"
+    );
+    println!(
+        "    predecessor pattern  Search a predecessor of a pattern, a Garden of Eden has none"
+    );
+    println!("    bench              Run the same world with every engine and compare their speed");
+    println!(
+        "    verify             Check the engines on canonical patterns, such as the period of the"
+    );
+    println!(
+        "                       blinker or the population of the R-pentomino at generation 1103"
+    );
+    println!(
+        "    mapped path        Run a bit-packed Life-like world stored in a memory-mapped file,"
+    );
+    println!(
+        "                       for worlds larger than the memory (created and seeded if missing,"
+    );
+    println!("                       resumed otherwise, 100 steps by default)");
+    println!("    distribute         Split the world in bands simulated by workers, exchanging their edges");
+    println!("    worker address     Simulate bands for gol distribute, listening on address (host:port)");
+    println!("    fetch name...      Download patterns of the LifeWiki, as gosperglidergun, to place them as");
+    println!("                       cached:name (needs curl)");
+    println!("    patterns show pattern  Print the name, author and comments of a pattern, and its cells");
+    println!();
     println!("Options");
     println!("    --help             Display this message");
     println!("    --width width      Define the size of the world (default 320)");
     println!("    --height height    Define the height of the world (default 240)");
-    println!("    --density density  Define the initial density of population of the world (default 0.5)");
+    println!("    --density density  Define the initial density of population of the world (default 0.5, 0 if patterns are placed)");
+    println!(
+        "    --place placement  Place a pattern, as pattern@x,y[:transform...] (can be repeated)"
+    );
+    println!("                       pattern is builtin:name, file:path, a builtin name or a path");
+    println!("                       transform is rot90, rot180, rot270, flipx or flipy");
+    println!("    --init setup       Set the world up with operations separated by ;, applied in order (can be repeated)");
+    println!("                       pattern@x,y [transform...] places a pattern, soup WxH@x,y [d=density]");
+    println!("                       fills a rectangle randomly, as \"glider@10,10; gun.rle@0,0 rot90; soup 64x64@100,100 d=0.4\"");
+    println!("    --seed seed        Seed of the random number generator, a number or a text hashed into one (default random)");
+    println!(
+        "    --rng rng          The random number generator (default {}) (available {})",
+        RngKind::default(),
+        RngKind::all()
+            .iter()
+            .map(|kind| kind.get_name())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    println!("    --rule rule        The rule of the world, as B3/S23 or 23/3 (default B3/S23)");
+    println!("    --rule-plugin path  Compute the generations with a WebAssembly module exporting");
+    println!(
+        "                       next_state(neighborhood) or next_state_count(alive, neighbors)"
+    );
+    println!(
+        "    --wrap-x, --no-wrap-x  Connect the left and right edges of the world (default on)"
+    );
+    println!(
+        "    --wrap-y, --no-wrap-y  Connect the top and bottom edges of the world (default on)"
+    );
+    println!("    --rules rules      The rules cycled through with the R key, as B3/S23,B36/S23");
+    println!("                       (default the rule, HighLife, Day & Night and Seeds)");
+    println!(
+        "    --ca automaton     The cellular automaton, life, species, immigration, sir, fire, cyclic,"
+    );
+    println!("                       generations or wireworld (default life)");
+    println!(
+        "                       species has red and blue cells following the rule, born of the"
+    );
+    println!("                       majority species and dying when outnumbered by the other one");
+    println!("                       immigration has them keep their species until they die");
+    println!("                       sir is an epidemic, the soup being the first infected cells");
+    println!("    --infection probability  Probability of catching the disease from an infected neighbor (sir only) (default 0.25)");
+    println!("    --recovery probability  Probability of recovering at each step (sir only) (default 0.1)");
+    println!(
+        "                       fire is the forest-fire model, the soup being the first trees"
+    );
+    println!("    --growth probability  Probability of a tree growing on an empty cell (fire only) (default 0.01)");
+    println!("    --lightning probability  Probability of a tree being struck by lightning (fire only) (default 0.00001)");
+    println!("                       cyclic advances the cells to the next state when a neighbor is in it, best with --density 1");
+    println!(
+        "    --states states    The number of states, between 2 and 256 (cyclic and generations only) (default 14)"
+    );
+    println!("                       generations has the cells which do not survive go through the dying states,");
+    println!("                       as Brian's Brain with --rule B2/S --states 3");
+    println!("                       wireworld runs electrons along wires, the soup being wires with a few electrons");
+    println!("    --layers rules     Run up to 3 worlds with their own rules from the same soup, as B3/S23,B36/S23");
+    println!("                       drawn blended in red, blue and green");
     println!("    --max-steps steps  The number of steps to run of the simulation (default 0)");
     println!("    --loop             Run the simulation forever (enabled by default)");
-    println!("    --render type   The render to use (default piston) (available piston none");
+    println!("    --max-time duration  Stop the simulation after a wall-clock duration, as 90s, 10m or 2h");
+    println!(
+        "                       then print the final statistics (and save the world with --save)"
+    );
+    println!(
+        "    --render type   The render to use (default {}) (available {})",
+        RenderType::default(),
+        RenderType::all()
+            .iter()
+            .map(|render_type| render_type.get_name())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    println!("                       rawvideo writes RGB frames to stdout, for ffmpeg -f rawvideo -pixel_format rgb24 -video_size WxH -i -");
+    println!("                       ppm writes PPM frames to stdout, for ffmpeg -f image2pipe -i - or mpv -");
+    println!("                       terminal draws two cells per character with 24-bit colors, e edits the rule");
+    println!("                       led drives WS2812 LEDs on /dev/spidev0.0, a cell per LED");
+    println!("                       apng writes an animated PNG with 24-bit colors at the end of the run");
+    println!(
+        "    --render-output path  Where the render writes its file (apng only) (default gol.png)"
+    );
+    println!(
+        "    --render-every generations  Only render and stream every Nth generation (default 1)"
+    );
+    println!(
+        "    --frame-skip       Only render the latest generation when the render falls behind,"
+    );
+    println!(
+        "                       at most --target-fps frames per second or one every 16 ms, a render slower"
+    );
+    println!(
+        "                       than half a frame leaving as long to the simulation before the next one,"
+    );
+    println!("                       the skipped generations being shown");
+    println!("    --led-brightness brightness  Brightness of the LEDs, between 0 and 1 (led only) (default 0.25)");
+    println!("    --led-gamma gamma  Gamma correction of the LEDs (led only) (default 2.2)");
+    println!("    --led-serpentine   Chain the odd rows of LEDs from right to left (led only)");
+    println!("    --config path      Read options from a TOML file, as rule = \"B36/S23\" for --rule B36/S23,");
+    println!(
+        "                       the colors, --target-fps, --render-every, --frame-skip and --osc"
+    );
+    println!("                       applied as soon as the file changes");
+    println!("    --fg color         The color of the alive cells, as #00ff00 (default the color of the automaton)");
+    println!("    --bg color         The color around the world, and of the dead cells unless --dead-color is given");
+    println!(
+        "    --dead-color color  The color of the dead cells (default the color of the automaton)"
+    );
+    println!("    --colors path      Color the states as a Golly .colors, .icons or .rule file, under --fg, --bg and --dead-color");
+    println!("    --art [palette]    Color the alive cells after their age, cycling through a palette at each generation");
+    println!(
+        "                       (piston, terminal, rawvideo and ppm only) (available {}) (default {})",
+        ArtPalette::all()
+            .iter()
+            .map(|palette| palette.get_name())
+            .collect::<Vec<_>>()
+            .join(", "),
+        ArtPalette::default()
+    );
+    println!("    --title-format format  The title of the window, updated every second, where {{width}}, {{height}},");
+    println!("                       {{rule}}, {{generation}}, {{population}}, {{gps}} (generations per second) and");
+    println!(
+        "                       {{hud}} (the timings) are replaced (piston only) (default \"{}\")",
+        DEFAULT_TITLE_FORMAT
+    );
+    println!("    --smooth           Fade the cells between generations (piston only)");
+    println!("    --rulers           Show rulers along the edges and a crosshair on the cell under the cursor,");
+    println!("                       its coordinates in the title, toggled with C (piston only)");
+    println!("    --view-offset x,y  Show the cell x,y at the top left corner, along the edges which wrap around,");
+    println!("                       scrolled with Ctrl and the arrows (piston) or the arrows (terminal)");
+    println!("    --trails generations  Fade the dead cells out during some generations");
+    println!("    --watch            Start over when a pattern file of --place or --init changes on disk");
+    println!("    --screensaver      Run fullscreen, reseed the world when it settles and exit on any input");
+    println!("    --pause-on trigger  Pause when the population drops below N (population<N), the world settles");
+    println!("                       (period), an object appears (pattern=PATTERN) or a generation is reached");
+    println!("                       (generation=N) (can be repeated)");
+    println!(
+        "    --random-rule [pool]  Pick a random rule each time the world is seeded, from the pool"
+    );
+    println!("                       curated (famous rules, default) or uniform (any rule)");
+    println!("    --ws address       Stream the world over WebSocket on this address (serve only)");
+    println!(
+        "    --tcp address      Stream the world to gol view clients on this address (serve only)"
+    );
+    println!("    --workers addresses  The workers simulating the bands, as host:port,host:port (distribute only)");
+    println!("    --force-kernel kernel  The instruction set the kernel is compiled with, scalar, avx2 or neon, a hint");
+    println!(
+        "                       for the code generation computing the same states (mapped only, the bitpacked"
+    );
+    println!("                       engine of the other commands is always scalar)");
+    println!("                       (default the fastest the CPU supports)");
+    println!(
+        "    --url url          Where to download the patterns from (fetch only, default {})",
+        DEFAULT_URL
+    );
+    println!("    --checkpoint-every generations  Save a snapshot to the --save path every N generations (distribute only)");
+    println!(
+        "    --shm name         Write the world to a shared memory region in /dev/shm (or a path)"
+    );
+    println!(
+        "    --osc address      Send the stats of each generation as OSC messages to this address"
+    );
+    println!("    --http address     Serve the HTTP control API on this address");
+    println!(
+        "    --script path      Run the on_init, on_step and on_finish hooks of a rhai script"
+    );
+    println!("    --repl             Read commands on stdin, as pause, step 50 or save out.rle (help lists them)");
+    println!("    --runs runs        The number of worlds to run (batch only) (default 100)");
+    println!("    --runs-per-point runs  The number of worlds to run per value (sweep only) (default 100)");
+    println!(
+        "    --samples samples  The number of random rules to score (explore only) (default 50)"
+    );
+    println!(
+        "    --output path      Where to write the scored rules (explore only) (default rules.csv)"
+    );
+    println!("    --threads threads  The number of threads running the worlds (batch) or the tiles (parallel engine)");
+    println!("                       (default all cores)");
+    println!("    --pin-threads      Pin each thread to one of the cores the process may run on, as set by taskset");
+    println!(
+        "    --engines engines  The engines to compare, as naive,flat,bitpacked (bench and verify only) (default all)"
+    );
+    println!("    --engine engine    The engine computing the generations of the Life-like rules (default naive)");
+    println!("    --verify-against engine  Step a reference engine, as naive, along with the simulation and stop");
+    println!("                       with a report of the differing cells at the first generation they disagree on");
+    println!("    --record path      Record the commands of the session to a file");
+    println!("    --replay path      Replay the commands of a recorded session");
+    println!("    --history n        Number of previous generations kept to step back (default {} if the render", DEFAULT_HISTORY_SIZE);
+    println!("                       or the replayed session steps back, 0 otherwise)");
+    println!(
+        "    --load path        Start from a snapshot of a world (JSON if path ends with .json)"
+    );
+    println!("                       resuming its session: topology, random numbers and pending commands");
+    println!("    --timings-json path  Write the percentiles of the durations of the update, render and I/O to a file at the end");
+    println!("    --telemetry-out path  Write the durations of the update, render and I/O and the depths of the");
+    println!("                       queues of every generation to a CSV file (JSON Lines if path ends with .jsonl)");
+    println!("    --density-map path  Write the density of the population over NxN blocks of the world every few");
+    println!("                       generations to a CSV file (NumPy array shaped (maps, N, N) if path ends with .npy)");
+    println!(
+        "    --density-map-size blocks  The number N of blocks along each axis of the density maps (default {})",
+        density_map::DEFAULT_SIZE
+    );
+    println!(
+        "    --density-map-every generations  Write a density map every Nth generation (default 1)"
+    );
+    println!("    --plot path        Draw the population of every generation as a chart when the run ends");
+    println!("                       (SVG if path ends with .svg, PNG otherwise)");
+    println!("    --summary          Print a summary when the run ends: generations and their rate, peak and final");
+    println!("                       population, generation and period of stabilization, most common objects");
+    println!("    --summary-json path  Write the summary to a JSON file when the run ends");
+    println!("    --dump-final-ascii [path]  Write the final generation as . and # to a file or stdout when the run ends");
+    println!("    --print-hash       Print the fingerprint of the final generation, the same on every platform");
+    println!("    --save path        Save a snapshot of the world and its session when the simulation ends");
+    println!("                       (JSON if path ends with .json, the world only)");
+    println!("    --autosave duration  Save a snapshot of the session every interval of wall clock time, as 5m");
+    println!("    --autosave-gens generations  Save a snapshot of the session every N generations");
+    println!("    --autosave-keep count  The number of autosaved snapshots to keep, the oldest removed (default 3)");
+    println!("    --autosave-path path  Where to autosave, named after the generation as autosave-1200.gols");
+    println!("                       (default autosave.gols)");
 }
 
 enum ParseArgsError {
     MissingValue(String),
     InvalidValue(String, String),
     UnknowArg(String),
+    InvalidConfig(PathBuf, ConfigError),
+}
+
+impl std::fmt::Display for ParseArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseArgsError::MissingValue(arg) => write!(f, "missing value for {}", arg),
+            ParseArgsError::InvalidValue(arg, value) => {
+                write!(f, "invalid value {} for {}", value, arg)
+            }
+            ParseArgsError::UnknowArg(arg) => write!(f, "unknown argument {}", arg),
+            ParseArgsError::InvalidConfig(path, err) => {
+                write!(f, "cannot load {}: {}", path.display(), err)
+            }
+        }
+    }
 }
 
 fn parse_args() -> Result<Settings, ParseArgsError> {
     let mut settings = Settings::default();
 
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
     let mut arg_index = 1;
 
+    if args.len() > 1 && args[1] == "run" {
+        settings.mode = Mode::Run;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "serve" {
+        settings.mode = Mode::Serve;
+        settings.render_type = RenderType::None;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "view" {
+        settings.mode = Mode::View;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "batch" {
+        settings.mode = Mode::Batch;
+        settings.render_type = RenderType::None;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "sweep" {
+        settings.mode = Mode::Sweep;
+        settings.render_type = RenderType::None;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "predecessor" {
+        settings.mode = Mode::Predecessor;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "bench" {
+        settings.mode = Mode::Bench;
+        settings.render_type = RenderType::None;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "mapped" {
+        settings.mode = Mode::Mapped;
+        settings.render_type = RenderType::None;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "distribute" {
+        settings.mode = Mode::Distribute;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "worker" {
+        settings.mode = Mode::Worker;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "verify" {
+        settings.mode = Mode::Verify;
+        settings.render_type = RenderType::None;
+        arg_index += 1;
+    } else if args.len() > 1 && args[1] == "fetch" {
+        settings.mode = Mode::Fetch;
+        arg_index += 1;
+    } else if args.len() > 2 && args[1] == "patterns" && args[2] == "show" {
+        settings.mode = Mode::ShowPattern;
+        arg_index += 2;
+    } else if args.len() > 1 && args[1] == "explore" {
+        // Many rules are scored, keep the soups small
+        settings.mode = Mode::Explore;
+        settings.render_type = RenderType::None;
+        settings.world_width = 64;
+        settings.world_height = 64;
+        settings.batch_runs = 8;
+        arg_index += 1;
+    }
+
+    // The options of the config file come first, so the ones of the command
+    // line override them
+    let config_path = args
+        .iter()
+        .position(|arg| arg == "--config")
+        .and_then(|index| args.get(index + 1))
+        .map(PathBuf::from);
+    if let Some(path) = config_path {
+        let config_args = Config::load(&path)
+            .and_then(|config| config.to_args())
+            .map_err(|err| ParseArgsError::InvalidConfig(path, err))?;
+        args.splice(arg_index..arg_index, config_args);
+    }
+
     while arg_index < args.len() {
         let current_arg = &args[arg_index];
         let next_arg = if arg_index + 1 == args.len() {
@@ -76,6 +715,30 @@ fn parse_args() -> Result<Settings, ParseArgsError> {
             if let Some(height) = next_arg {
                 settings.world_height = height.parse::<usize>().unwrap();
 
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if settings.mode == Mode::Sweep
+            && (current_arg == "--density" || current_arg == "--rule" || current_arg == "--size")
+        {
+            if let Some(values) = next_arg {
+                let parameter = match current_arg.as_str() {
+                    "--density" => SweepParameter::parse_densities(values),
+                    "--rule" => SweepParameter::parse_rules(values),
+                    _ => SweepParameter::parse_sizes(values),
+                };
+                match parameter {
+                    Ok(parameter) => settings.sweep_parameter = Some(parameter),
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            values.to_string(),
+                        ))
+                    }
+                }
+
                 // Consume the arg
                 arg_index += 1;
             } else {
@@ -83,35 +746,42 @@ fn parse_args() -> Result<Settings, ParseArgsError> {
             }
         } else if current_arg == "--density" {
             if let Some(density) = next_arg {
-                settings.population_density = density.parse::<f32>().unwrap();
+                settings.population_density = Some(density.parse::<f32>().unwrap());
 
                 // Consume the arg
                 arg_index += 1;
             } else {
                 return Err(ParseArgsError::MissingValue(current_arg.to_string()));
             }
-        } else if current_arg == "--max-steps" {
-            if let Some(max_steps) = next_arg {
-                settings.run_steps_max = Some(max_steps.parse::<usize>().unwrap());
+        } else if current_arg == "--place" {
+            if let Some(placement) = next_arg {
+                match placement.parse::<Placement>() {
+                    Ok(placement) => settings
+                        .init_operations
+                        .push(InitOperation::Place(placement)),
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            placement.to_string(),
+                        ))
+                    }
+                }
 
                 // Consume the arg
                 arg_index += 1;
             } else {
                 return Err(ParseArgsError::MissingValue(current_arg.to_string()));
             }
-        } else if current_arg == "--loop" {
-            settings.run_steps_max = None;
-        } else if current_arg == "--render" {
-            if let Some(render) = next_arg {
-                if render == "none" {
-                    settings.render_type = RenderType::None;
-                } else if render == "piston" {
-                    settings.render_type = RenderType::Piston;
-                } else {
-                    return Err(ParseArgsError::InvalidValue(
-                        current_arg.to_string(),
-                        render.to_string(),
-                    ));
+        } else if current_arg == "--init" {
+            if let Some(init) = next_arg {
+                match parse_init(init) {
+                    Ok(operations) => settings.init_operations.extend(operations),
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            init.to_string(),
+                        ))
+                    }
                 }
 
                 // Consume the arg
@@ -119,106 +789,2460 @@ fn parse_args() -> Result<Settings, ParseArgsError> {
             } else {
                 return Err(ParseArgsError::MissingValue(current_arg.to_string()));
             }
-        } else {
-            return Err(ParseArgsError::UnknowArg(current_arg.to_string()));
-        }
+        } else if current_arg == "--seed" {
+            if let Some(seed) = next_arg {
+                settings.seed = Some(parse_seed(seed));
 
-        arg_index += 1;
-    }
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--rng" {
+            if let Some(rng) = next_arg {
+                match rng.parse::<RngKind>() {
+                    Ok(rng) => settings.rng = rng,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            rng.to_string(),
+                        ))
+                    }
+                }
 
-    Ok(settings)
-}
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--runs" || current_arg == "--runs-per-point" {
+            if let Some(runs) = next_arg {
+                match runs.parse::<usize>() {
+                    Ok(runs) => settings.batch_runs = runs,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            runs.to_string(),
+                        ))
+                    }
+                }
 
-fn main() {
-    // Parse the args
-    let settings = parse_args().ok().unwrap();
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--samples" {
+            if let Some(samples) = next_arg {
+                match samples.parse::<usize>() {
+                    Ok(samples) => settings.explore_samples = samples,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            samples.to_string(),
+                        ))
+                    }
+                }
 
-    // Display the help if asked
-    if settings.display_help {
-        usage();
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--output" {
+            if let Some(path) = next_arg {
+                settings.output_path = PathBuf::from(path);
 
-        return;
-    }
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--threads" {
+            if let Some(threads) = next_arg {
+                match threads.parse::<usize>() {
+                    Ok(threads) if threads > 0 => settings.threads.count = Some(threads),
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            threads.to_string(),
+                        ))
+                    }
+                }
 
-    // Create the world
-    let mut world = World::new(settings.world_width, settings.world_height);
-    world.populate(settings.population_density);
-
-    // Create the window if needed
-    let mut window: Option<piston_window::PistonWindow> = match settings.render_type {
-        RenderType::Piston => Some(
-            piston_window::WindowSettings::new(
-                "Game of Life",
-                [settings.world_width as u32, settings.world_height as u32],
-            )
-            .exit_on_esc(true)
-            .build()
-            .unwrap(),
-        ),
-        _ => None,
-    };
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--pin-threads" {
+            settings.threads.pin = true;
+        } else if current_arg == "--record" {
+            if let Some(path) = next_arg {
+                settings.record_path = Some(PathBuf::from(path));
 
-    // Main loop
-    let mut current_step = 0;
-    //while let Some(event) = window.next() {
-    loop {
-        println!("running step {}...", current_step);
-        let step_start = std::time::SystemTime::now();
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--replay" {
+            if let Some(path) = next_arg {
+                settings.replay_path = Some(PathBuf::from(path));
 
-        if let Some(max_steps) = settings.run_steps_max {
-            if current_step >= max_steps {
-                break;
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
             }
-        }
+        } else if current_arg == "--history" {
+            if let Some(history_size) = next_arg {
+                match history_size.parse::<usize>() {
+                    Ok(history_size) => settings.history_size = Some(history_size),
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            history_size.to_string(),
+                        ))
+                    }
+                }
 
-        // Update the world
-        {
-            println!("update world...");
-            let update_start = std::time::SystemTime::now();
-            world.update();
-            let update_end = std::time::SystemTime::now();
-            let update_duration = update_end.duration_since(update_start).unwrap();
-            println!("update done, took {:?}", update_duration);
-        }
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--load" {
+            if let Some(path) = next_arg {
+                settings.load_path = Some(PathBuf::from(path));
 
-        // Render the world
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--timings-json" {
+            if let Some(path) = next_arg {
+                settings.timings_json_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--telemetry-out" {
+            if let Some(path) = next_arg {
+                settings.telemetry_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--density-map" {
+            if let Some(path) = next_arg {
+                settings.density_map_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--density-map-size" || current_arg == "--density-map-every" {
+            if let Some(value) = next_arg {
+                match value.parse::<usize>() {
+                    Ok(value) if value > 0 => {
+                        if current_arg == "--density-map-size" {
+                            settings.density_map_size = value;
+                        } else {
+                            settings.density_map_every = value;
+                        }
+                    }
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            value.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--plot" {
+            if let Some(path) = next_arg {
+                settings.plot_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--summary" {
+            settings.summary = true;
+        } else if current_arg == "--summary-json" {
+            if let Some(path) = next_arg {
+                settings.summary_json_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--save" {
+            if let Some(path) = next_arg {
+                settings.save_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--rule" {
+            if let Some(rule) = next_arg {
+                match rule.parse::<Rule>() {
+                    Ok(rule) => settings.rule = rule,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            rule.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--rules" {
+            if let Some(rules) = next_arg {
+                match rules
+                    .split(',')
+                    .map(|rule| rule.parse::<Rule>())
+                    .collect::<Result<Vec<_>, _>>()
+                {
+                    Ok(rules) => settings.rule_cycle = rules,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            rules.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--ca" {
+            if let Some(automaton_type) = next_arg {
+                match automaton_type.parse::<AutomatonType>() {
+                    Ok(automaton_type) => settings.automaton_type = automaton_type,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            automaton_type.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--infection"
+            || current_arg == "--recovery"
+            || current_arg == "--growth"
+            || current_arg == "--lightning"
         {
-            println!("render world...");
-            let render_start = std::time::SystemTime::now();
-            if let Some(window_) = window.as_mut() {
-                if let Some(event) = window_.next() {
-                    window_.draw_2d(&event, |context, graphics, _device| {
-                        piston_window::clear([1.0; 4], graphics);
-
-                        for y in 0..world.get_height() {
-                            for x in 0..world.get_width() {
-                                let cell_state = world.get_tile(x, y);
-                                if cell_state == CellState::Alive {
-                                    piston_window::rectangle(
-                                        [0.0, 0.0, 0.0, 1.0],
-                                        [x as f64, y as f64, 1.0, 1.0],
-                                        context.transform,
-                                        graphics,
-                                    );
-                                }
-                            }
+            if let Some(probability) = next_arg {
+                match probability.parse::<f32>() {
+                    Ok(probability) if (0.0..=1.0).contains(&probability) => {
+                        let options = &mut settings.automaton_options;
+                        match current_arg.as_str() {
+                            "--infection" => options.infection = probability,
+                            "--recovery" => options.recovery = probability,
+                            "--growth" => options.growth = probability,
+                            _ => options.lightning = probability,
                         }
-                    });
+                    }
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            probability.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--states" {
+            if let Some(states) = next_arg {
+                match states.parse::<usize>() {
+                    Ok(states) if (2..=256).contains(&states) => {
+                        settings.automaton_options.states = states
+                    }
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            states.to_string(),
+                        ))
+                    }
                 }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
             }
-            let render_end = std::time::SystemTime::now();
-            let render_duration = render_end.duration_since(render_start).unwrap();
-            println!("render done, took {:?}", render_duration);
-        }
+        } else if current_arg == "--layers" {
+            if let Some(rules) = next_arg {
+                match rules
+                    .split(',')
+                    .map(|rule| rule.parse::<Rule>())
+                    .collect::<Result<Vec<_>, _>>()
+                {
+                    Ok(rules) if rules.len() <= MAX_LAYERS => settings.layers = rules,
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            rules.to_string(),
+                        ))
+                    }
+                }
 
-        let step_end = std::time::SystemTime::now();
-        let step_duration = step_end.duration_since(step_start).unwrap();
-        println!(
-            "step done, took {:?} ({:.0} FPS)",
-            step_duration,
-            1.0 / step_duration.as_secs_f64()
-        );
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--max-steps" {
+            if let Some(max_steps) = next_arg {
+                settings.run_steps_max = Some(max_steps.parse::<usize>().unwrap());
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--max-time" {
+            if let Some(max_time) = next_arg {
+                match parse_duration(max_time) {
+                    Some(max_time) => settings.max_time = Some(max_time),
+                    None => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            max_time.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--wrap-x" {
+            settings.wrap_x = true;
+        } else if current_arg == "--no-wrap-x" {
+            settings.wrap_x = false;
+        } else if current_arg == "--wrap-y" {
+            settings.wrap_y = true;
+        } else if current_arg == "--no-wrap-y" {
+            settings.wrap_y = false;
+        } else if current_arg == "--loop" {
+            settings.run_steps_max = None;
+        } else if current_arg == "--engines" {
+            if let Some(engines) = next_arg {
+                match engines
+                    .split(',')
+                    .map(|engine| engine.trim().parse::<Engine>())
+                    .collect::<Result<Vec<_>, _>>()
+                {
+                    Ok(engines) => settings.engines = engines,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            engines.to_string(),
+                        ))
+                    }
+                }
 
-        current_step += 1;
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--engine" || current_arg == "--verify-against" {
+            if let Some(engine) = next_arg {
+                match engine.parse::<Engine>() {
+                    Ok(engine) if current_arg == "--engine" => settings.engine = Some(engine),
+                    Ok(engine) => settings.verify_against = Some(engine),
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            engine.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--render-every" {
+            if let Some(render_every) = next_arg {
+                match render_every.parse::<usize>() {
+                    Ok(render_every) if render_every > 0 => settings.render_every = render_every,
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            render_every.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--target-fps" {
+            if let Some(fps) = next_arg {
+                match fps.parse::<f64>() {
+                    Ok(fps) if fps > 0.0 => settings.target_fps = Some(fps),
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            fps.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--frame-skip" {
+            settings.frame_skip = true;
+        } else if current_arg == "--led-brightness" || current_arg == "--led-gamma" {
+            if let Some(value) = next_arg {
+                match value.parse::<f32>() {
+                    Ok(value) if value >= 0.0 => {
+                        if current_arg == "--led-brightness" {
+                            settings.render_options.led.brightness = value;
+                        } else {
+                            settings.render_options.led.gamma = value;
+                        }
+                    }
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            value.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--led-serpentine" {
+            settings.render_options.led.serpentine = true;
+        } else if current_arg == "--watch" {
+            settings.watch = true;
+        } else if current_arg == "--pause-on" {
+            if let Some(trigger) = next_arg {
+                match trigger.parse::<PauseTrigger>() {
+                    Ok(trigger) => settings.pause_triggers.push(trigger),
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            trigger.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--screensaver" {
+            settings.screensaver = true;
+            settings.render_options.fullscreen = true;
+            settings.render_options.exit_on_input = true;
+        } else if current_arg == "--rule-plugin" {
+            if let Some(path) = next_arg {
+                settings.rule_plugin_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--script" {
+            if let Some(path) = next_arg {
+                settings.script_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--repl" {
+            settings.repl = true;
+        } else if current_arg == "--random-rule" {
+            // The pool is optional
+            match next_arg.map(|pool| pool.parse::<RulePool>()) {
+                Some(Ok(pool)) => {
+                    settings.random_rules = Some(pool);
+
+                    // Consume the arg
+                    arg_index += 1;
+                }
+                _ => settings.random_rules = Some(RulePool::Curated),
+            }
+        } else if current_arg == "--art" {
+            // The palette is optional
+            match next_arg.map(|palette| palette.parse::<ArtPalette>()) {
+                Some(Ok(palette)) => {
+                    settings.render_options.art = Some(palette);
+
+                    // Consume the arg
+                    arg_index += 1;
+                }
+                _ => settings.render_options.art = Some(ArtPalette::default()),
+            }
+        } else if current_arg == "--render-output" {
+            if let Some(path) = next_arg {
+                settings.render_options.output_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--print-hash" {
+            settings.print_hash = true;
+        } else if current_arg == "--dump-final-ascii" {
+            settings.dump_final_ascii = true;
+
+            // The path is optional, stdout by default
+            if let Some(path) = next_arg.filter(|path| !path.starts_with("--")) {
+                settings.ascii_dump_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            }
+        } else if current_arg == "--title-format" {
+            if let Some(title_format) = next_arg {
+                settings.title_format = Some(title_format.to_string());
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--fg" || current_arg == "--bg" || current_arg == "--dead-color" {
+            if let Some(color) = next_arg {
+                match parse_color(color) {
+                    Ok(color) => {
+                        let colors = &mut settings.render_options.colors;
+                        match current_arg.as_str() {
+                            "--fg" => colors.alive = Some(color),
+                            "--bg" => colors.background = Some(color),
+                            _ => colors.dead = Some(color),
+                        }
+                    }
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            color.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--colors" {
+            if let Some(path) = next_arg {
+                match RuleColors::load(&PathBuf::from(path)) {
+                    Ok(colors) => settings.render_options.colors.rule = Some(colors),
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            path.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--smooth" {
+            settings.render_options.smooth = true;
+        } else if current_arg == "--view-offset" {
+            if let Some(offset) = next_arg {
+                let parsed = offset
+                    .split_once(',')
+                    .and_then(|(x, y)| Some(Point::new(x.parse().ok()?, y.parse().ok()?)));
+                match parsed {
+                    Some(offset) => settings.render_options.view_offset = offset,
+                    None => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            offset.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--rulers" {
+            settings.render_options.rulers = true;
+        } else if current_arg == "--trails" {
+            if let Some(trails) = next_arg {
+                match trails.parse::<usize>() {
+                    Ok(trails) => settings.render_options.trails = trails,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            trails.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--ws" {
+            if let Some(address) = next_arg {
+                settings.ws_address = Some(address.to_string());
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--tcp" {
+            if let Some(address) = next_arg {
+                settings.tcp_address = Some(address.to_string());
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--workers" {
+            if let Some(addresses) = next_arg {
+                settings.worker_addresses = addresses.split(',').map(str::to_string).collect();
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--url" {
+            if let Some(url) = next_arg {
+                settings.fetch_url = url.to_string();
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--checkpoint-every" {
+            if let Some(generations) = next_arg {
+                match generations.parse::<usize>() {
+                    Ok(generations) if generations > 0 => {
+                        settings.checkpoint_every = Some(generations)
+                    }
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            generations.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--autosave" {
+            if let Some(interval) = next_arg {
+                match parse_duration(interval) {
+                    Some(interval) if !interval.is_zero() => {
+                        settings.autosave_interval = Some(interval)
+                    }
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            interval.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--autosave-gens" {
+            if let Some(generations) = next_arg {
+                match generations.parse::<usize>() {
+                    Ok(generations) if generations > 0 => {
+                        settings.autosave_generations = Some(generations)
+                    }
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            generations.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--autosave-keep" {
+            if let Some(keep) = next_arg {
+                match keep.parse::<usize>() {
+                    Ok(keep) if keep > 0 => settings.autosave_keep = keep,
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            keep.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--autosave-path" {
+            if let Some(path) = next_arg {
+                settings.autosave_path = PathBuf::from(path);
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--force-kernel" {
+            if let Some(kernel) = next_arg {
+                match kernel.parse::<Kernel>() {
+                    Ok(kernel) if kernel.is_supported() => settings.kernel = Some(kernel),
+                    _ => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            kernel.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--shm" {
+            if let Some(name) = next_arg {
+                settings.shm_name = Some(name.to_string());
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--config" {
+            if let Some(path) = next_arg {
+                settings.config_path = Some(PathBuf::from(path));
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--osc" {
+            if let Some(address) = next_arg {
+                settings.osc_address = Some(address.to_string());
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--http" {
+            if let Some(address) = next_arg {
+                settings.http_address = Some(address.to_string());
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if current_arg == "--render" {
+            if let Some(render) = next_arg {
+                match render.parse::<RenderType>() {
+                    Ok(render_type) => settings.render_type = render_type,
+                    Err(_) => {
+                        return Err(ParseArgsError::InvalidValue(
+                            current_arg.to_string(),
+                            render.to_string(),
+                        ))
+                    }
+                }
+
+                // Consume the arg
+                arg_index += 1;
+            } else {
+                return Err(ParseArgsError::MissingValue(current_arg.to_string()));
+            }
+        } else if settings.mode == Mode::View
+            && settings.view_url.is_none()
+            && !current_arg.starts_with("--")
+        {
+            settings.view_url = Some(current_arg.to_string());
+        } else if settings.mode == Mode::Mapped
+            && settings.mapped_path.is_none()
+            && !current_arg.starts_with("--")
+        {
+            settings.mapped_path = Some(PathBuf::from(current_arg));
+        } else if settings.mode == Mode::Worker
+            && settings.worker_address.is_none()
+            && !current_arg.starts_with("--")
+        {
+            settings.worker_address = Some(current_arg.to_string());
+        } else if settings.mode == Mode::Fetch && !current_arg.starts_with("--") {
+            settings.fetch_names.push(current_arg.to_string());
+        } else if (settings.mode == Mode::Predecessor || settings.mode == Mode::ShowPattern)
+            && settings.pattern_source.is_none()
+            && !current_arg.starts_with("--")
+        {
+            match current_arg.parse::<PatternSource>() {
+                Ok(pattern_source) => settings.pattern_source = Some(pattern_source),
+                Err(_) => {
+                    return Err(ParseArgsError::InvalidValue(
+                        "pattern".to_string(),
+                        current_arg.to_string(),
+                    ))
+                }
+            }
+        } else {
+            return Err(ParseArgsError::UnknowArg(current_arg.to_string()));
+        }
+
+        arg_index += 1;
+    }
+
+    if settings.mode == Mode::Serve
+        && settings.ws_address.is_none()
+        && settings.tcp_address.is_none()
+    {
+        return Err(ParseArgsError::MissingValue("--ws or --tcp".to_string()));
+    }
+    if settings.mode == Mode::Sweep && settings.sweep_parameter.is_none() {
+        return Err(ParseArgsError::MissingValue(
+            "--density, --rule or --size".to_string(),
+        ));
+    }
+    if (settings.mode == Mode::Predecessor || settings.mode == Mode::ShowPattern)
+        && settings.pattern_source.is_none()
+    {
+        return Err(ParseArgsError::MissingValue("pattern".to_string()));
+    }
+    if settings.mode == Mode::View && settings.view_url.is_none() {
+        return Err(ParseArgsError::MissingValue("url".to_string()));
+    }
+    if settings.mode == Mode::Mapped && settings.mapped_path.is_none() {
+        return Err(ParseArgsError::MissingValue("path".to_string()));
+    }
+    if settings.mode == Mode::Distribute && settings.worker_addresses.is_empty() {
+        return Err(ParseArgsError::MissingValue("--workers".to_string()));
+    }
+    if settings.checkpoint_every.is_some() && settings.save_path.is_none() {
+        return Err(ParseArgsError::MissingValue("--save".to_string()));
+    }
+    if settings.watch
+        && !settings
+            .init_operations
+            .iter()
+            .any(|operation| matches!(operation, InitOperation::Place(placement) if placement.source.get_path().is_some()))
+    {
+        return Err(ParseArgsError::MissingValue(
+            "--place or --init with a pattern file".to_string(),
+        ));
+    }
+    if settings.repl && settings.render_type.uses_terminal() {
+        // The frames and the console would share the terminal
+        return Err(ParseArgsError::InvalidValue(
+            "--render".to_string(),
+            settings.render_type.to_string(),
+        ));
+    }
+    if settings.mode == Mode::Worker && settings.worker_address.is_none() {
+        return Err(ParseArgsError::MissingValue("address".to_string()));
+    }
+    if settings.mode == Mode::Fetch && settings.fetch_names.is_empty() {
+        return Err(ParseArgsError::MissingValue("name".to_string()));
+    }
+    #[cfg(not(feature = "plugin"))]
+    if settings.rule_plugin_path.is_some() {
+        // Built without the WebAssembly runtime
+        return Err(ParseArgsError::UnknowArg("--rule-plugin".to_string()));
+    }
+    #[cfg(not(feature = "script"))]
+    if settings.script_path.is_some() {
+        // Built without the scripting engine
+        return Err(ParseArgsError::UnknowArg("--script".to_string()));
+    }
+
+    Ok(settings)
+}
+
+/// Download patterns of the LifeWiki into the cache
+fn fetch_patterns(settings: &Settings) {
+    for name in settings.fetch_names.iter() {
+        match fetch(name, &settings.fetch_url) {
+            Ok(path) => println!("fetched {} to {}", name, path.display()),
+            Err(err) => {
+                eprintln!("error: {}: {}", name, err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Print what a pattern file tells about its pattern, and its cells
+fn show_pattern(settings: &Settings) {
+    let pattern = settings
+        .pattern_source
+        .as_ref()
+        .unwrap()
+        .load()
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        });
+
+    let metadata = pattern.get_metadata();
+    if let Some(name) = metadata.name.as_ref() {
+        println!("name: {}", name);
+    }
+    if let Some(author) = metadata.author.as_ref() {
+        println!("author: {}", author);
+    }
+    for comment in metadata.comments.iter() {
+        println!("comment: {}", comment);
+    }
+    println!("size: {}x{}", pattern.get_width(), pattern.get_height());
+    println!();
+    print!(
+        "{}",
+        pattern
+            .with_metadata(PatternMetadata::default())
+            .to_plaintext()
+    );
+}
+
+/// Get the name and the author of the first placed pattern which has one
+///
+/// @param operations The operations initializing the world
+fn get_pattern_title(operations: &[InitOperation]) -> Option<String> {
+    operations
+        .iter()
+        .filter_map(|operation| match operation {
+            InitOperation::Place(placement) => placement.load().ok(),
+            InitOperation::Soup(_) => None,
+        })
+        .find_map(|pattern| pattern.get_metadata().get_title())
+}
+
+/// Search a predecessor of a pattern and print it
+fn predecessor(settings: &Settings) {
+    let pattern = settings
+        .pattern_source
+        .as_ref()
+        .unwrap()
+        .load()
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        });
+
+    match find_predecessor(&pattern, &settings.rule) {
+        Ok(Some(predecessor)) => {
+            println!("predecessor found, the pattern is at (1, 1) in it:");
+            print!("{}", predecessor.to_rle());
+        }
+        Ok(None) => println!("no predecessor, the pattern is a Garden of Eden orphan"),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Get the settings of the batches from the args
+fn batch_config(settings: &Settings) -> BatchConfig {
+    BatchConfig {
+        width: settings.world_width,
+        height: settings.world_height,
+        density: settings.population_density.unwrap_or(0.5),
+        rule: settings.rule,
+        runs: settings.batch_runs,
+        max_steps: settings.run_steps_max.unwrap_or(BATCH_MAX_STEPS),
+        seed: settings.seed.unwrap_or_else(rand::random),
+        threads: settings.threads,
+    }
+}
+
+/// Run many random worlds and print statistics about them
+fn batch(settings: &Settings) {
+    let config = batch_config(settings);
+    println!("seed {}", config.seed);
+
+    let outcomes = run_batch(&config);
+    print!("{}", BatchReport::new(&outcomes));
+}
+
+/// Run the same world with several engines and print how fast they are
+fn run_bench(settings: &Settings) {
+    let config = BenchConfig {
+        width: settings.world_width,
+        height: settings.world_height,
+        density: settings.population_density.unwrap_or(0.5),
+        rule: settings.rule,
+        steps: settings.run_steps_max.unwrap_or(BENCH_STEPS),
+        seed: settings.seed.unwrap_or_else(rand::random),
+        threads: settings.threads,
+    };
+    println!(
+        "seed {}, {}x{} world, {} steps",
+        config.seed, config.width, config.height, config.steps
+    );
+
+    let report = bench(&config, &settings.engines);
+    print!("{}", report);
+    if !report.get_mismatches().is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Run a world stored in a memory-mapped file, printing its statistics at
+/// each step
+fn run_mapped(settings: &Settings) {
+    let path = settings.mapped_path.as_ref().unwrap();
+    let result = if path.exists() {
+        MappedWorld::open(path, settings.rule)
+    } else {
+        let seed = settings.seed.unwrap_or_else(rand::random);
+        println!("seed {}", seed);
+        MappedWorld::create(
+            path,
+            settings.world_width,
+            settings.world_height,
+            settings.rule,
+        )
+        .and_then(|mut world| {
+            world.populate(
+                &mut SessionRng::with_kind(settings.rng, seed),
+                settings.population_density.unwrap_or(0.5),
+            )?;
+            Ok(world)
+        })
+    };
+    let mut world = result.unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", path.display(), err);
+        std::process::exit(1);
+    });
+    if let Some(kernel) = settings.kernel {
+        world.set_kernel(kernel);
+    }
+
+    let mut population = world.get_population();
+    println!(
+        "{}x{} world ({}, {} kernel), generation {}, population {}",
+        world.get_width(),
+        world.get_height(),
+        format_bytes(world.get_width().div_ceil(64) * 8 * world.get_height()),
+        world.get_kernel(),
+        world.get_generation(),
+        population
+    );
+    for _ in 0..settings.run_steps_max.unwrap_or(MAPPED_STEPS) {
+        let start = Instant::now();
+        let stats = world.step().unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        population = population + stats.births - stats.deaths;
+        println!(
+            "generation {}, population {}, births {}, deaths {}, {:.2?}",
+            world.get_generation(),
+            population,
+            stats.births,
+            stats.deaths,
+            start.elapsed()
+        );
+    }
+
+    if let Err(err) = world.flush() {
+        eprintln!("error: {}: {}", path.display(), err);
+        std::process::exit(1);
+    }
+}
+
+/// Run a world split between workers, rendering the bands they send back
+fn distribute(settings: &Settings) {
+    let (mut world, mut generation) = match settings.load_path.as_ref() {
+        Some(path) if is_json(path) => {
+            let world = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|json| World::from_json(&json).map_err(|err| err.to_string()))
+                .unwrap_or_else(|err| {
+                    eprintln!("error: cannot load {}: {}", path.display(), err);
+                    std::process::exit(1);
+                });
+
+            (world, 0)
+        }
+        Some(path) => match snapshot::load(path) {
+            Ok(snapshot) => (snapshot.world, snapshot.generation),
+            Err(err) => {
+                eprintln!("error: cannot load {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let seed = settings.seed.unwrap_or_else(rand::random);
+            progress!(settings, "seed {}", seed);
+            let mut world = World::new(settings.world_width, settings.world_height);
+            world.set_wrapping(settings.wrap_x, settings.wrap_y);
+            world.populate(
+                &mut SessionRng::with_kind(settings.rng, seed),
+                settings.population_density.unwrap_or(0.5),
+            );
+
+            (world, 0)
+        }
+    };
+
+    let mut master = Master::connect(&settings.worker_addresses, &world, settings.rule)
+        .unwrap_or_else(|err| {
+            eprintln!("error: cannot start the workers: {}", err);
+            std::process::exit(1);
+        });
+    progress!(
+        settings,
+        "{}x{} world split between {} workers",
+        world.get_width(),
+        world.get_height(),
+        master.get_worker_count()
+    );
+
+    let exit_on_error = |err: std::io::Error| -> ! {
+        eprintln!("error: lost a worker: {}", err);
+        std::process::exit(1);
+    };
+    let save = |world: &World, generation: usize| {
+        let path = settings.save_path.as_ref().unwrap();
+        let result = if is_json(path) {
+            std::fs::write(path, world.to_json()).map_err(|err| err.to_string())
+        } else {
+            snapshot::save(path, world, generation).map_err(|err| err.to_string())
+        };
+        if let Err(err) = result {
+            eprintln!("error: cannot save {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut render = create_render(settings, world.get_width(), world.get_height());
+    render_world(&mut render, &world);
+    let mut steps = 0;
+    let start = Instant::now();
+    while settings
+        .run_steps_max
+        .is_none_or(|max_steps| steps < max_steps)
+        && settings
+            .max_time
+            .is_none_or(|max_time| start.elapsed() < max_time)
+        && !render.is_closed()
+    {
+        let start = Instant::now();
+        master.step().unwrap_or_else(|err| exit_on_error(err));
+        generation += 1;
+        steps += 1;
+
+        let render_due = generation % settings.render_every == 0;
+        let checkpoint_due = settings
+            .checkpoint_every
+            .is_some_and(|generations| generation % generations == 0);
+        if render_due || checkpoint_due {
+            world = master.fetch().unwrap_or_else(|err| exit_on_error(err));
+        }
+        if render_due {
+            render.set_hud(&format!(
+                "generation {} - population {} - {:.2?} per step",
+                generation,
+                world.get_population(),
+                start.elapsed()
+            ));
+            render_world(&mut render, &world);
+        }
+        if checkpoint_due {
+            save(&world, generation);
+        }
+    }
+
+    world = master.fetch().unwrap_or_else(|err| exit_on_error(err));
+    drop(render);
+    progress!(
+        settings,
+        "generation {}, population {}",
+        generation,
+        world.get_population()
+    );
+    if settings.save_path.is_some() {
+        save(&world, generation);
+    }
+}
+
+/// Get the rules cycled through by default: the rule, then some famous ones
+fn default_rule_cycle(rule: Rule) -> Vec<Rule> {
+    let mut rules = vec![rule];
+    for famous_rule in ["B36/S23", "B3678/S34678", "B2/S"] {
+        let famous_rule = famous_rule.parse::<Rule>().unwrap();
+        if !rules.contains(&famous_rule) {
+            rules.push(famous_rule);
+        }
+    }
+
+    rules
+}
+
+/// Replace the {name} fields of a title by their values
+///
+/// @param format The title, as "generation {generation}"
+/// @param fields The names of the fields and their values
+fn format_title(format: &str, fields: &[(&str, String)]) -> String {
+    fields
+        .iter()
+        .fold(format.to_string(), |title, (name, value)| {
+            title.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// Whether a world file is in the JSON format rather than a snapshot
+fn is_json(path: &std::path::Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+/// Save a snapshot of a simulation and of its session
+///
+/// @param path Path of the snapshot
+/// @param simulation The simulation
+/// @param replay The replay, for its commands not applied yet
+/// @param commands The commands to apply at the current generation
+fn save_session(
+    path: &std::path::Path,
+    simulation: &Simulation,
+    replay: Option<&Replay>,
+    commands: &[Command],
+) -> Result<(), snapshot::SnapshotError> {
+    let mut pending_commands = commands
+        .iter()
+        .map(|command| (simulation.get_generation(), command))
+        .collect::<Vec<_>>();
+    pending_commands.extend(replay.iter().flat_map(|replay| {
+        replay
+            .get_pending_commands()
+            .map(|(generation, command)| (*generation, command))
+    }));
+
+    snapshot::save_session(
+        path,
+        simulation.get_world(),
+        simulation.get_generation(),
+        Some(simulation.get_rng()),
+        &pending_commands,
+    )
+}
+
+/// Create the render of the settings, falling back to no render when it
+/// cannot be opened
+fn create_render(settings: &Settings, width: usize, height: usize) -> Box<dyn Render> {
+    settings
+        .render_type
+        .create(width, height, settings.render_options.clone())
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "error: cannot open the {} render: {}, running without render",
+                settings.render_type, err
+            );
+            Box::new(NoneRenderType::new())
+        })
+}
+
+/// Render a world, falling back to no render when the render fails
+fn render_world(render: &mut Box<dyn Render>, world: &World) {
+    if let Err(err) = render.render(world) {
+        eprintln!("error: render failed: {}, running without render", err);
+        *render = Box::new(NoneRenderType::new());
+    }
+}
+
+/// Render the frames received from a server until the connection or the
+/// render is closed
+fn view(settings: &Settings) {
+    let url = settings.view_url.as_ref().unwrap();
+    let mut viewer = RemoteViewer::connect(url).unwrap_or_else(|err| {
+        eprintln!("error: cannot connect to {}: {}", url, err);
+        std::process::exit(1);
+    });
+
+    // The render is created once the size of the world is known
+    let mut render: Option<Box<dyn Render>> = None;
+    let mut world = None;
+    loop {
+        match viewer.receive_timeout(PAUSE_POLL_INTERVAL) {
+            Ok(Some(frame)) => world = Some(frame.world),
+            Ok(None) => {}
+            Err(err) => {
+                progress!(settings, "connection closed: {}", err);
+                break;
+            }
+        }
+
+        if let Some(world) = world.as_ref() {
+            let render = render.get_or_insert_with(|| {
+                create_render(settings, world.get_width(), world.get_height())
+            });
+            render_world(render, world);
+            if render.is_closed() {
+                break;
+            }
+
+            // Forward the edits to the server
+            for command in render.take_commands() {
+                if let Err(err) = viewer.send(&command) {
+                    progress!(settings, "connection closed: {}", err);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    // Parse the args
+    let mut settings = match parse_args() {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            usage();
+            std::process::exit(1);
+        }
+    };
+
+    // Display the help if asked
+    if settings.display_help {
+        usage();
+
+        return;
+    }
+
+    if settings.mode == Mode::View {
+        view(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::Batch {
+        batch(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::Fetch {
+        fetch_patterns(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::ShowPattern {
+        show_pattern(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::Predecessor {
+        predecessor(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::Bench {
+        run_bench(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::Verify {
+        let report = verify(&settings.engines);
+        print!("{}", report);
+        if !report.is_passed() {
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if settings.mode == Mode::Mapped {
+        run_mapped(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::Distribute {
+        distribute(&settings);
+
+        return;
+    }
+
+    if settings.mode == Mode::Worker {
+        let address = settings.worker_address.as_ref().unwrap();
+        if let Err(err) = run_worker(address.as_str()) {
+            eprintln!("error: cannot listen on {}: {}", address, err);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if settings.mode == Mode::Explore {
+        let config = batch_config(&settings);
+        println!("seed {}", config.seed);
+        let scores = explore(
+            &config,
+            settings.explore_samples,
+            &mut StdRng::seed_from_u64(config.seed),
+        );
+        for score in scores.iter().take(10) {
+            println!("{:.4} {}", score.score, score.rule);
+        }
+
+        let result = std::fs::File::create(&settings.output_path)
+            .and_then(|file| write_scores(std::io::BufWriter::new(file), &scores));
+        if let Err(err) = result {
+            eprintln!(
+                "error: cannot write {}: {}",
+                settings.output_path.display(),
+                err
+            );
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if settings.mode == Mode::Sweep {
+        // The seed goes to stderr to keep stdout a valid CSV
+        let config = batch_config(&settings);
+        eprintln!("seed {}", config.seed);
+        let parameter = settings.sweep_parameter.as_ref().unwrap();
+        if let Err(err) = sweep(std::io::stdout().lock(), &config, parameter) {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    // Load the session to replay
+    let mut replay = settings.replay_path.as_ref().map(|path| {
+        Replay::load(path).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        })
+    });
+
+    // Seed the random number generator, so the run can be reproduced
+    let (seed, rng_kind) = match replay.as_ref() {
+        Some(replay) => (replay.get_seed(), replay.get_rng_kind()),
+        None => (settings.seed.unwrap_or_else(rand::random), settings.rng),
+    };
+    progress!(settings, "seed {}", seed);
+    let mut rng = SessionRng::with_kind(rng_kind, seed);
+
+    // Create the world, or restore it from a snapshot with its session
+    let mut restored_rng = None;
+    let mut pending_commands = Vec::new();
+    let (mut world, generation) = match settings.load_path.as_ref() {
+        Some(path) if is_json(path) => {
+            let world = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|json| World::from_json(&json).map_err(|err| err.to_string()))
+                .unwrap_or_else(|err| {
+                    eprintln!("error: cannot load {}: {}", path.display(), err);
+                    std::process::exit(1);
+                });
+
+            (world, 0)
+        }
+        Some(path) => match snapshot::load(path) {
+            Ok(snapshot) => {
+                restored_rng = snapshot.rng;
+                pending_commands = snapshot.pending_commands;
+                (snapshot.world, snapshot.generation)
+            }
+            Err(err) => {
+                eprintln!("error: cannot load {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let mut world = World::new(settings.world_width, settings.world_height);
+            if settings.layers.is_empty() {
+                let rule = match settings.random_rules {
+                    Some(pool) => pool.pick(&mut rng),
+                    None => settings.rule,
+                };
+                world.set_automaton(settings.automaton_type.create(&AutomatonOptions {
+                    rule,
+                    seed,
+                    ..settings.automaton_options
+                }));
+            } else {
+                // The rules were checked when parsing the arguments
+                world.set_automaton(Box::new(Layers::new(settings.layers.clone()).unwrap()));
+            }
+            let default_density = if settings.init_operations.is_empty() {
+                0.5
+            } else {
+                0.0
+            };
+            world.populate(
+                &mut rng,
+                settings.population_density.unwrap_or(default_density),
+            );
+
+            (world, 0)
+        }
+    };
+
+    // The snapshots keep the topology of their session
+    if settings.load_path.as_ref().is_none_or(|path| is_json(path)) {
+        world.set_wrapping(settings.wrap_x, settings.wrap_y);
+    }
+    #[cfg(feature = "plugin")]
+    if let Some(path) = settings.rule_plugin_path.as_ref() {
+        match PluginRule::load(path) {
+            Ok(plugin_rule) => world.set_automaton(Box::new(plugin_rule)),
+            Err(err) => {
+                eprintln!("error: cannot load {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(engine) = settings.engine {
+        match world.get_automaton().name().parse::<Rule>() {
+            Ok(rule) => world.set_automaton(engine.create_with_threads(rule, settings.threads)),
+            Err(_) => {
+                eprintln!("error: --engine needs a Life-like rule");
+                std::process::exit(1);
+            }
+        }
+    }
+    if settings.random_rules.is_some() {
+        if world.get_automaton().name().parse::<Rule>().is_err() {
+            eprintln!("error: --random-rule needs a Life-like rule");
+            std::process::exit(1);
+        }
+        progress!(settings, "rule {}", world.get_automaton().name());
+    }
+    if settings.verify_against.is_some() && world.get_automaton().name().parse::<Rule>().is_err() {
+        eprintln!("error: --verify-against needs a Life-like rule");
+        std::process::exit(1);
+    }
+
+    // Place the patterns and the soups
+    for operation in settings.init_operations.iter() {
+        if let Err(err) = operation.apply(&mut world, &mut rng) {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    // Start streaming the world if asked
+    let mut ws_server = settings.ws_address.as_ref().map(|address| {
+        WsServer::bind(address).unwrap_or_else(|err| {
+            eprintln!("error: cannot listen on {}: {}", address, err);
+            std::process::exit(1);
+        })
+    });
+
+    let mut remote_server = settings.tcp_address.as_ref().map(|address| {
+        RemoteServer::bind(address, world.get_width(), world.get_height()).unwrap_or_else(|err| {
+            eprintln!("error: cannot listen on {}: {}", address, err);
+            std::process::exit(1);
+        })
+    });
+
+    let mut shm_output = settings.shm_name.as_ref().map(|name| {
+        SharedMemoryOutput::create(name, world.get_width(), world.get_height()).unwrap_or_else(
+            |err| {
+                eprintln!("error: cannot create the shared memory {}: {}", name, err);
+                std::process::exit(1);
+            },
+        )
+    });
+    if let Some(shm_output) = shm_output.as_ref() {
+        progress!(
+            settings,
+            "writing the world to {}",
+            shm_output.get_path().display()
+        );
+    }
+
+    let mut osc_output = settings.osc_address.as_ref().map(|address| {
+        OscOutput::connect(address).unwrap_or_else(|err| {
+            eprintln!("error: cannot send to {}: {}", address, err);
+            std::process::exit(1);
+        })
+    });
+
+    // Check the steps against a reference engine if asked
+    let mut engine_check = settings.verify_against.map(EngineCheck::new);
+
+    // Create the render
+    let mut render = create_render(&settings, world.get_width(), world.get_height());
+
+    // Create the simulation
+    let mut simulation = Simulation::new(world);
+    simulation.set_generation(generation);
+    simulation.set_engine(settings.engine.unwrap_or(Engine::Naive), settings.threads);
+    simulation.set_rule_cycle(if settings.rule_cycle.is_empty() {
+        default_rule_cycle(settings.rule)
+    } else {
+        settings.rule_cycle.clone()
+    });
+    if let Some(restored_rng) = restored_rng {
+        progress!(settings, "resumed seed {}", restored_rng.get_seed());
+        rng = restored_rng;
+    }
+    simulation.set_rng(rng);
+    simulation.set_random_rules(settings.random_rules);
+
+    // Apply the commands the snapshot was waiting for, unless replaying
+    if replay.is_none() && !pending_commands.is_empty() {
+        replay = Some(Replay::new(seed, rng_kind, pending_commands));
+    }
+
+    // Record the session if asked
+    let mut recorder = settings.record_path.as_ref().map(|path| {
+        Recorder::create(path, seed, rng_kind).unwrap_or_else(|err| {
+            eprintln!("error: cannot create {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    });
+
+    // Start the control API if asked
+    let http_server = settings.http_address.as_ref().map(|address| {
+        HttpServer::bind(address).unwrap_or_else(|err| {
+            eprintln!("error: cannot listen on {}: {}", address, err);
+            std::process::exit(1);
+        })
+    });
+
+    // Read commands on stdin if asked
+    let repl = settings.repl.then(Repl::start);
+
+    // Run the hooks of a script if asked, their changes are applied as the
+    // other commands
+    #[cfg(feature = "script")]
+    let mut script = settings.script_path.as_ref().map(|path| {
+        Script::load(path).unwrap_or_else(|err| {
+            eprintln!("error: cannot load {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    });
+    #[cfg(feature = "script")]
+    let mut script_commands = match script
+        .as_mut()
+        .map(|script| script.on_init(simulation.get_world()))
+    {
+        Some(Ok(commands)) => commands,
+        Some(Err(err)) => {
+            eprintln!("error: script: {}", err);
+            script = None;
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+
+    // Only copy the previous generations if something steps back to them, or
+    // if the script looks at the last one
+    let history_size = settings.history_size.unwrap_or_else(|| {
+        let replay_steps_back = replay.as_ref().is_some_and(|replay| {
+            replay
+                .get_pending_commands()
+                .any(|(_, command)| matches!(command, Command::StepBack))
+        });
+        if settings.render_type.steps_back() || replay_steps_back {
+            DEFAULT_HISTORY_SIZE
+        } else {
+            0
+        }
+    });
+    #[cfg(feature = "script")]
+    let history_size = if script
+        .as_ref()
+        .is_some_and(|script| script.reads_previous())
+    {
+        history_size.max(1)
+    } else {
+        history_size
+    };
+    simulation.set_history_size(history_size);
+
+    // Save the session periodically if asked
+    let mut autosave = (settings.autosave_interval.is_some()
+        || settings.autosave_generations.is_some())
+    .then(|| {
+        Autosave::new(
+            &settings.autosave_path,
+            settings.autosave_keep,
+            settings.autosave_interval,
+            settings.autosave_generations,
+        )
+    });
+
+    // Watch the world to reseed it once it settles, as a screensaver
+    let mut stability = settings.screensaver.then(StabilityDetector::default);
+    let mut soup_start = simulation.get_generation();
+    let mut soup_over = false;
+
+    // Main loop
+    let mut timings = Timings::default();
+    let mut population_graph = settings
+        .render_type
+        .shows_population_graph()
+        .then(PopulationGraph::default);
+    let mut plot = settings.plot_path.as_ref().map(|_| {
+        let mut plot = PopulationPlot::new();
+        plot.record(
+            simulation.get_generation(),
+            simulation.get_world().get_population(),
+        );
+        plot
+    });
+    let mut summary = (settings.summary || settings.summary_json_path.is_some())
+        .then(|| SummaryTracker::new(simulation.get_world(), simulation.get_generation()));
+    // The events going on from the start do not pause the simulation
+    let mut auto_pause = AutoPause::new(settings.pause_triggers.clone());
+    auto_pause.check(simulation.get_world(), simulation.get_generation());
+    let mut telemetry = settings.telemetry_path.as_ref().map(|path| {
+        TelemetryWriter::create(path).unwrap_or_else(|err| {
+            eprintln!("error: cannot write {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    });
+    let mut density_map = settings.density_map_path.as_ref().map(|path| {
+        DensityMapWriter::create(path, settings.density_map_size).unwrap_or_else(|err| {
+            eprintln!("error: cannot write {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    });
+    if let Some(density_map_) = density_map.as_mut().filter(|_| {
+        simulation
+            .get_generation()
+            .is_multiple_of(settings.density_map_every)
+    }) {
+        if let Err(err) = density_map_.write(simulation.get_world(), simulation.get_generation()) {
+            eprintln!("error: cannot write the density map: {}", err);
+            // Keep the maps written so far readable, as far as possible
+            let _ = density_map_.finish();
+            density_map = None;
+        }
+    }
+    let mut hud_update = Instant::now();
+    let mut hud = String::new();
+    let mut speed_meter = SpeedMeter::default();
+    let mut pattern_title = get_pattern_title(&settings.init_operations);
+    let mut title_update = Instant::now();
+    let mut config = settings
+        .config_path
+        .as_ref()
+        .and_then(|path| Config::load(path).ok());
+    let mut config_update = Instant::now();
+
+    // Follow the pattern files to start over when they change if asked
+    let mut pattern_watcher = settings.watch.then(|| {
+        FileWatcher::new(
+            settings
+                .init_operations
+                .iter()
+                .filter_map(|operation| match operation {
+                    InitOperation::Place(placement) => placement.source.get_path(),
+                    InitOperation::Soup(_) => None,
+                })
+                .map(|path| path.to_path_buf())
+                .collect(),
+        )
+    });
+    let mut watch_update = Instant::now();
+    let mut title_generation = simulation.get_generation();
+    let mut last_frame = Instant::now();
+    let mut steps_per_frame = settings.render_every;
+    let mut steps_since_frame = 0;
+    let mut skipped_generations = 0;
+    let mut last_skipped_generations = 0;
+    let run_start = Instant::now();
+    let mut out_of_time = false;
+    // The generation the steps are counted from, 0 again once restarted
+    let mut start_generation = generation;
+    loop {
+        if let Some(max_steps) = settings.run_steps_max {
+            // Stepping back may go below the generation of a loaded snapshot
+            if simulation.get_generation().saturating_sub(start_generation) >= max_steps {
+                break;
+            }
+        }
+        if settings
+            .max_time
+            .is_some_and(|max_time| run_start.elapsed() >= max_time)
+        {
+            out_of_time = true;
+            break;
+        }
+        if simulation.is_stopped() {
+            break;
+        }
+
+        let step_start = Instant::now();
+
+        // Apply the commands
+        let io_start = Instant::now();
+        let mut commands = match replay.as_mut() {
+            Some(replay) => replay.take_commands(simulation.get_generation()),
+            None => Vec::new(),
+        };
+        commands.extend(render.take_commands());
+        if let Some(http_server) = http_server.as_ref() {
+            commands.extend(http_server.poll(&simulation));
+        }
+        if let Some(repl) = repl.as_ref() {
+            commands.extend(repl.poll(&simulation));
+        }
+        #[cfg(feature = "script")]
+        commands.append(&mut script_commands);
+        if let Some(remote_server) = remote_server.as_ref() {
+            commands.extend(remote_server.poll());
+        }
+        if soup_over {
+            commands.push(Command::Reseed(settings.population_density.unwrap_or(0.5)));
+            if let Some(stability) = stability.as_mut() {
+                stability.reset();
+            }
+            soup_start = simulation.get_generation();
+            soup_over = false;
+        }
+        let command_count = commands.len();
+        let mut world_changed = !commands.is_empty();
+        for command in commands {
+            if let Some(recorder_) = recorder.as_mut() {
+                if let Err(err) = recorder_.record(simulation.get_generation(), &command) {
+                    eprintln!("error: cannot record the session: {}", err);
+                    recorder = None;
+                }
+            }
+            simulation.apply(command);
+        }
+        let mut io_duration = io_start.elapsed();
+        if simulation.is_stopped() {
+            break;
+        }
+
+        let stepped = !simulation.is_paused();
+        if stepped {
+            progress!(settings, "running step {}...", simulation.get_generation());
+
+            // Update the world
+            progress!(settings, "update world...");
+            if let Some(engine_check) = engine_check.as_mut() {
+                engine_check.prepare(simulation.get_world());
+            }
+            timings.measure(Phase::Update, || simulation.step());
+            if let Some(engine_check) = engine_check.as_mut() {
+                if let Err(divergence) =
+                    engine_check.check(simulation.get_world(), simulation.get_generation())
+                {
+                    eprintln!("error: {}", divergence);
+                    std::process::exit(1);
+                }
+            }
+            progress!(
+                settings,
+                "update done, took {:?}",
+                timings.get_last(Phase::Update).unwrap()
+            );
+
+            world_changed = true;
+            steps_since_frame += 1;
+            if let Some(population_graph) = population_graph.as_mut() {
+                population_graph.record(simulation.get_world().get_population());
+            }
+            if let Some(plot) = plot.as_mut() {
+                plot.record(
+                    simulation.get_generation(),
+                    simulation.get_world().get_population(),
+                );
+            }
+            if let Some(summary) = summary.as_mut() {
+                summary.record(simulation.get_world(), simulation.get_generation());
+            }
+
+            #[cfg(feature = "script")]
+            {
+                let result = script.as_mut().map(|script| {
+                    script.on_step(
+                        simulation.get_world(),
+                        simulation.get_previous_grid().map(|grid| grid.get_tiles()),
+                        simulation.get_generation(),
+                    )
+                });
+                match result {
+                    Some(Ok(commands)) => script_commands.extend(commands),
+                    Some(Err(err)) => {
+                        eprintln!("error: script: {}", err);
+                        script = None;
+                    }
+                    None => {}
+                }
+            }
+
+            let events = auto_pause.check(simulation.get_world(), simulation.get_generation());
+            if !events.is_empty() {
+                simulation.apply(Command::Pause);
+                for event in events {
+                    progress!(
+                        settings,
+                        "paused at generation {}: {}",
+                        simulation.get_generation(),
+                        event
+                    );
+                }
+            }
+
+            if let Some(stability) = stability.as_mut() {
+                soup_over = stability.update(simulation.get_world()).is_some()
+                    || simulation.get_generation().saturating_sub(soup_start)
+                        >= SCREENSAVER_MAX_GENERATIONS;
+            }
+        }
+
+        // Save the session if it is time to
+        if let Some(autosave) = autosave
+            .as_mut()
+            .filter(|autosave| autosave.is_due(simulation.get_generation()))
+        {
+            #[cfg(feature = "script")]
+            let commands = script_commands.as_slice();
+            #[cfg(not(feature = "script"))]
+            let commands = &[];
+            let result = autosave.save(simulation.get_generation(), |path| {
+                save_session(path, &simulation, replay.as_ref(), commands)
+            });
+            match result {
+                Ok(path) => progress!(settings, "autosaved {}", path.display()),
+                Err(err) => eprintln!("error: cannot autosave: {}", err),
+            }
+        }
+
+        // Only show every Nth generation while running, to run long
+        // simulations quickly
+        let frame_due = simulation.is_paused()
+            || match settings.target_fps {
+                Some(_) => steps_since_frame >= steps_per_frame,
+                None => simulation
+                    .get_generation()
+                    .is_multiple_of(settings.render_every),
+            };
+
+        // Stream the world
+        if world_changed && frame_due {
+            let stream_start = Instant::now();
+            if let Some(ws_server) = ws_server.as_mut() {
+                ws_server.broadcast(simulation.get_world(), simulation.get_generation());
+            }
+            if let Some(remote_server) = remote_server.as_mut() {
+                remote_server.broadcast(simulation.get_world(), simulation.get_generation());
+            }
+            if let Some(shm_output) = shm_output.as_mut() {
+                shm_output.write(simulation.get_world(), simulation.get_generation());
+            }
+            if let Some(osc_output) = osc_output.as_mut() {
+                osc_output.send(simulation.get_world(), simulation.get_generation());
+            }
+            io_duration += stream_start.elapsed();
+        }
+        timings.record(Phase::Io, io_duration);
+
+        // Show the timings
+        if hud_update.elapsed() >= HUD_INTERVAL {
+            let rule = simulation.get_world().get_automaton().name();
+            let rule = match rule.parse::<Rule>().ok().and_then(|rule| rule.get_name()) {
+                Some(name) => format!("{} ({})", name, rule),
+                None => rule,
+            };
+            hud = format!(
+                "{}{} - generation {} - {} - memory {}",
+                pattern_title
+                    .as_ref()
+                    .map_or_else(String::new, |title| format!("{} - ", title)),
+                rule,
+                simulation.get_generation(),
+                timings.get_summary(),
+                format_bytes(simulation.get_memory_usage())
+            );
+            let world = simulation.get_world();
+            speed_meter.record(
+                simulation.get_generation(),
+                world.get_width() * world.get_height(),
+            );
+            let speed = speed_meter.get_speed();
+            if let Some(speed) = speed {
+                hud.push_str(&format!(" - {}", speed));
+            }
+            render.set_speed(speed);
+            if settings.target_fps.is_some() {
+                hud.push_str(&format!(" - {} generations per frame", steps_per_frame));
+            }
+            if settings.frame_skip {
+                hud.push_str(&format!(" - skipped {}", last_skipped_generations));
+            }
+
+            // The population of each state, when there are more than dead
+            // and alive but few enough to fit
+            let automaton = simulation.get_world().get_automaton();
+            if automaton.states() > 2 && automaton.states() <= HUD_MAX_STATES {
+                let populations = simulation.get_world().get_state_populations();
+                for (name, population) in automaton.state_names().iter().zip(populations) {
+                    hud.push_str(&format!(" - {} {}", name, population));
+                }
+            }
+            render.set_hud(&hud);
+            hud_update = Instant::now();
+        }
+
+        // Show the progress in the title
+        if title_update.elapsed() >= TITLE_INTERVAL {
+            let world = simulation.get_world();
+            let gps = simulation.get_generation().saturating_sub(title_generation) as f64
+                / title_update.elapsed().as_secs_f64();
+            let title = format_title(
+                settings
+                    .title_format
+                    .as_deref()
+                    .unwrap_or(DEFAULT_TITLE_FORMAT),
+                &[
+                    ("width", world.get_width().to_string()),
+                    ("height", world.get_height().to_string()),
+                    ("rule", world.get_automaton().name()),
+                    ("generation", simulation.get_generation().to_string()),
+                    ("population", world.get_population().to_string()),
+                    ("gps", format!("{:.1}", gps)),
+                    ("hud", hud.clone()),
+                ],
+            );
+            render.set_title(&title);
+            title_update = Instant::now();
+            title_generation = simulation.get_generation();
+        }
+
+        // Apply the changes of the config file which do not need a restart
+        if config_update.elapsed() >= CONFIG_POLL_INTERVAL {
+            let modified_config = config
+                .as_mut()
+                .and_then(|config| config.check_modified().then_some(config));
+            if let Some(config) = modified_config {
+                let path = config.get_path().to_path_buf();
+                let reloaded = Config::load(&path)
+                    .map_err(|err| ParseArgsError::InvalidConfig(path.clone(), err))
+                    .and_then(|reloaded| Ok((reloaded, parse_args()?)));
+                match reloaded {
+                    Ok((reloaded, reloaded_settings)) => {
+                        let mut applied = Vec::new();
+                        for key in config.get_changed_keys(&reloaded) {
+                            if LIVE_CONFIG_KEYS.contains(&key.as_str()) {
+                                applied.push(key);
+                            } else {
+                                eprintln!(
+                                    "{}: {} changed, restart to apply it",
+                                    path.display(),
+                                    key
+                                );
+                            }
+                        }
+                        *config = reloaded;
+
+                        settings.render_options.colors = reloaded_settings.render_options.colors;
+                        render.set_colors(settings.render_options.colors.clone());
+                        settings.target_fps = reloaded_settings.target_fps;
+                        settings.render_every = reloaded_settings.render_every;
+                        if settings.target_fps.is_none() {
+                            steps_per_frame = settings.render_every;
+                        }
+                        settings.frame_skip = reloaded_settings.frame_skip;
+                        if settings.osc_address != reloaded_settings.osc_address {
+                            settings.osc_address = reloaded_settings.osc_address;
+                            osc_output = settings.osc_address.as_ref().and_then(|address| {
+                                OscOutput::connect(address)
+                                    .map_err(|err| {
+                                        eprintln!("error: cannot send to {}: {}", address, err)
+                                    })
+                                    .ok()
+                            });
+                        }
+                        if !applied.is_empty() {
+                            progress!(
+                                settings,
+                                "{}: applied {}",
+                                path.display(),
+                                applied.join(", ")
+                            );
+                        }
+                    }
+                    Err(err) => eprintln!("error: {}", err),
+                }
+            }
+            config_update = Instant::now();
+        }
+
+        // Start over from the new version of the pattern files
+        if watch_update.elapsed() >= WATCH_INTERVAL {
+            let modified_paths = pattern_watcher
+                .as_mut()
+                .map_or_else(Vec::new, |watcher| watcher.take_modified());
+            if !modified_paths.is_empty() {
+                // Keep the world while the patterns do not load, as when
+                // saved halfway through an edit
+                let loaded =
+                    settings
+                        .init_operations
+                        .iter()
+                        .try_for_each(|operation| match operation {
+                            InitOperation::Place(placement) => placement.load().map(|_| ()),
+                            InitOperation::Soup(_) => Ok(()),
+                        });
+                match loaded {
+                    Ok(()) => {
+                        // The soups are filled as when the run started
+                        let mut rng = SessionRng::with_kind(rng_kind, seed);
+                        pattern_title = get_pattern_title(&settings.init_operations);
+                        if let Some(population_graph) = population_graph.as_mut() {
+                            population_graph.clear();
+                        }
+                        let world = simulation.restart();
+                        start_generation = 0;
+                        if let Some(density) = settings.population_density {
+                            world.populate(&mut rng, density);
+                        }
+                        for operation in settings.init_operations.iter() {
+                            if let Err(err) = operation.apply(world, &mut rng) {
+                                eprintln!("error: {}", err);
+                            }
+                        }
+                        if let Some(plot) = plot.as_mut() {
+                            plot.clear();
+                            plot.record(0, world.get_population());
+                        }
+                        if let Some(summary) = summary.as_mut() {
+                            summary.restart(world, 0);
+                        }
+                        auto_pause.reset();
+                        auto_pause.check(world, 0);
+                        for path in modified_paths.iter() {
+                            progress!(settings, "reloaded {}", path.display());
+                        }
+                        render_world(&mut render, simulation.get_world());
+                    }
+                    Err(err) => eprintln!("error: {}", err),
+                }
+            }
+            watch_update = Instant::now();
+        }
+
+        // Skip the frames until the next one is due at the target frame
+        // rate, showing the latest generation rather than slowing the
+        // simulation down to draw them all. A render slower than half a frame
+        // leaves the simulation as long as it took before the next one.
+        let frame_duration = settings
+            .target_fps
+            .map_or(FRAME_INTERVAL, |fps| Duration::from_secs_f64(1.0 / fps));
+        let render_duration = timings.get_last(Phase::Render).unwrap_or_default();
+        let render_due = frame_due
+            && (!settings.frame_skip
+                || simulation.is_paused()
+                || last_frame.elapsed() >= frame_duration.max(render_duration * 2));
+        if frame_due && !render_due {
+            skipped_generations += 1;
+        }
+
+        // Render the world
+        if render_due {
+            // Run more generations per frame when the frames are quicker
+            // than asked, fewer when they are slower, by steps to stay smooth
+            if let Some(fps) = settings.target_fps {
+                if !simulation.is_paused() {
+                    let ratio = (1.0 / fps) / last_frame.elapsed().as_secs_f64().max(f64::EPSILON);
+                    steps_per_frame =
+                        ((steps_per_frame as f64 * ratio.clamp(0.5, 2.0)).round() as usize).max(1);
+                }
+                steps_since_frame = 0;
+            }
+
+            last_frame = Instant::now();
+            last_skipped_generations = std::mem::take(&mut skipped_generations);
+            if !simulation.is_paused() {
+                progress!(settings, "render world...");
+            }
+            if let Some(population_graph) = population_graph.as_ref() {
+                render.set_population_graph(population_graph);
+            }
+            timings.measure(Phase::Render, || {
+                render_world(&mut render, simulation.get_world())
+            });
+            if !simulation.is_paused() {
+                progress!(
+                    settings,
+                    "render done, took {:?}",
+                    timings.get_last(Phase::Render).unwrap()
+                );
+            }
+        }
+
+        // Record the timings of the generation if asked
+        if let Some(telemetry_) = telemetry.as_mut().filter(|_| stepped) {
+            let pending_commands = replay
+                .as_ref()
+                .map_or(0, |replay| replay.get_pending_commands().count());
+            #[cfg(feature = "script")]
+            let pending_commands = pending_commands + script_commands.len();
+            let viewer_queue = ws_server
+                .as_ref()
+                .map_or(0, |ws_server| ws_server.get_queue_depth())
+                .max(
+                    remote_server
+                        .as_ref()
+                        .map_or(0, |remote_server| remote_server.get_queue_depth()),
+                );
+            let sample = TelemetrySample {
+                generation: simulation.get_generation(),
+                update: timings.get_last(Phase::Update).unwrap_or_default(),
+                render: render_due
+                    .then(|| timings.get_last(Phase::Render))
+                    .flatten(),
+                io: timings.get_last(Phase::Io).unwrap_or_default(),
+                commands: command_count,
+                pending_commands,
+                viewer_queue,
+            };
+            if let Err(err) = telemetry_.write(&sample) {
+                eprintln!("error: cannot write the telemetry: {}", err);
+                telemetry = None;
+            }
+        }
+
+        // Sample the density of the population if asked
+        if let Some(density_map_) = density_map.as_mut().filter(|_| {
+            stepped
+                && simulation
+                    .get_generation()
+                    .is_multiple_of(settings.density_map_every)
+        }) {
+            if let Err(err) =
+                density_map_.write(simulation.get_world(), simulation.get_generation())
+            {
+                eprintln!("error: cannot write the density map: {}", err);
+                // Keep the maps written so far readable, as far as possible
+                let _ = density_map_.finish();
+                density_map = None;
+            }
+        }
+
+        if render.is_closed() {
+            break;
+        }
+
+        if simulation.is_paused() {
+            // Do not spin while waiting to be resumed
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+            continue;
+        }
+
+        // Honor the speed limit
+        if let Some(min_step_duration) = simulation.get_step_duration() {
+            // Keep rendering while waiting, for the animations and the inputs
+            let mut step_duration = step_start.elapsed();
+            while step_duration < min_step_duration && !render.is_closed() {
+                std::thread::sleep((min_step_duration - step_duration).min(FRAME_INTERVAL));
+                if frame_due {
+                    render_world(&mut render, simulation.get_world());
+                }
+                step_duration = step_start.elapsed();
+            }
+        }
+
+        let step_duration = step_start.elapsed();
+        progress!(
+            settings,
+            "step done, took {:?} ({:.0} FPS)",
+            step_duration,
+            1.0 / step_duration.as_secs_f64()
+        );
+    }
+
+    // Close the render, the renders writing a file write it now
+    drop(render);
+
+    // Let the script see the last generation, and change it before it is
+    // saved
+    #[cfg(feature = "script")]
+    if let Some(script) = script.as_mut() {
+        match script.on_finish(simulation.get_world()) {
+            Ok(commands) => commands
+                .into_iter()
+                .for_each(|command| simulation.apply(command)),
+            Err(err) => eprintln!("error: script: {}", err),
+        }
+    }
+
+    // Tell where the time limit stopped the simulation
+    if out_of_time {
+        eprintln!(
+            "time limit reached after {:.2?}: generation {}, population {}",
+            run_start.elapsed(),
+            simulation.get_generation(),
+            simulation.get_world().get_population()
+        );
+    }
+
+    // Summarize the run if asked, on stderr so it never mixes with what is
+    // written to stdout
+    if let Some(summary) = summary.as_ref() {
+        let summary = summary.get_summary(
+            simulation.get_world(),
+            simulation.get_generation(),
+            run_start.elapsed(),
+        );
+        if settings.summary {
+            eprintln!("{}", summary);
+        }
+        if let Some(path) = settings.summary_json_path.as_ref() {
+            if let Err(err) = std::fs::write(path, summary.to_json_value().to_string()) {
+                eprintln!("error: cannot write {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Print the fingerprint of the final generation if asked
+    if settings.print_hash {
+        println!("{:016x}", fingerprint(simulation.get_world()));
+    }
+
+    // Dump the final generation as text if asked
+    if settings.dump_final_ascii {
+        let ascii = simulation.get_world().to_ascii();
+        match settings.ascii_dump_path.as_ref() {
+            Some(path) => {
+                if let Err(err) = std::fs::write(path, ascii) {
+                    eprintln!("error: cannot write {}: {}", path.display(), err);
+                    std::process::exit(1);
+                }
+            }
+            None => print!("{}", ascii),
+        }
+    }
+
+    // Write the last lines of the telemetry
+    if let Some(mut telemetry) = telemetry {
+        if let Err(err) = telemetry.flush() {
+            eprintln!("error: cannot write the telemetry: {}", err);
+        }
+    }
+
+    // Write the last density maps and their number
+    if let Some(mut density_map) = density_map {
+        if let Err(err) = density_map.finish() {
+            eprintln!("error: cannot write the density map: {}", err);
+        }
+    }
+
+    // Draw the population chart if asked
+    if let (Some(path), Some(plot)) = (settings.plot_path.as_ref(), plot.as_ref()) {
+        if let Err(err) = plot.save(path) {
+            eprintln!("error: cannot write {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    }
+
+    // Dump the timings if asked
+    if let Some(path) = settings.timings_json_path.as_ref() {
+        if let Err(err) = std::fs::write(path, timings.to_json_value().to_string()) {
+            eprintln!("error: cannot write {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    }
+
+    // Save the world if asked
+    if let Some(path) = settings.save_path.as_ref() {
+        let result = if is_json(path) {
+            std::fs::write(path, simulation.get_world().to_json()).map_err(|err| err.to_string())
+        } else {
+            // Save the session, so resuming it goes on as if it never stopped
+            #[cfg(feature = "script")]
+            let commands = script_commands.as_slice();
+            #[cfg(not(feature = "script"))]
+            let commands = &[];
+            save_session(path, &simulation, replay.as_ref(), commands)
+                .map_err(|err| err.to_string())
+        };
+        if let Err(err) = result {
+            eprintln!("error: cannot save {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
     }
 }
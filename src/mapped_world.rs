@@ -0,0 +1,332 @@
+//! Worlds stored in memory-mapped files
+//!
+//! The cells are packed as bits in a file mapped in memory, so the worlds
+//! can be larger than the memory: a 100000x100000 world takes 1.25 GB. The
+//! update streams through the mapping in bands of rows, only keeping three
+//! rows in memory, and asks the kernel to write each band back once it is
+//! updated, so the pages of the rows already updated can be evicted.
+
+use crate::automaton::UpdateStats;
+use crate::kernel::{Kernel, RowKernel};
+use crate::rule::Rule;
+use memmap::MmapMut;
+use rand::Rng;
+use std::convert::TryInto;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::Path;
+
+/// The magic starting the file
+const MAGIC: &[u8; 4] = b"GOLB";
+
+/// The version of the layout
+const VERSION: u32 = 1;
+
+/// Size of the header of the file
+const HEADER_SIZE: usize = 32;
+
+/// Offset of the generation in the header
+const GENERATION_OFFSET: usize = 24;
+
+/// Number of rows updated before writing them back to the file
+const BAND_ROWS: usize = 1024;
+
+/// An error occurring while creating or opening a mapped world
+#[derive(Debug)]
+pub enum MappedWorldError {
+    /// The file cannot be created, read or mapped
+    Io(std::io::Error),
+    /// The file is not a mapped world
+    InvalidHeader,
+    /// The file is smaller than the world its header describes
+    Truncated,
+}
+
+impl fmt::Display for MappedWorldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MappedWorldError::Io(err) => write!(f, "{}", err),
+            MappedWorldError::InvalidHeader => write!(f, "not a mapped world"),
+            MappedWorldError::Truncated => write!(f, "truncated mapped world"),
+        }
+    }
+}
+
+impl std::error::Error for MappedWorldError {}
+
+impl From<std::io::Error> for MappedWorldError {
+    fn from(err: std::io::Error) -> Self {
+        MappedWorldError::Io(err)
+    }
+}
+
+/// A toroidal Life-like world whose cells live in a memory-mapped file
+///
+/// The file starts with a header made of the magic `GOLB`, the version of
+/// the layout as u32, the width, the height and the generation as u64, in
+/// little endian. The rows follow, each one packed in as many u64 as needed
+/// in little endian, bit x % 64 of word x / 64 being the cell of column x.
+pub struct MappedWorld {
+    /// The mapped file
+    map: MmapMut,
+    /// Width of the world
+    width: usize,
+    /// Height of the world
+    height: usize,
+    /// Number of words of a row
+    words: usize,
+    /// The rule of the world
+    rule: Rule,
+    /// The instruction set computing the rows
+    kernel: Kernel,
+}
+
+impl MappedWorld {
+    /// Create a file holding an empty world
+    ///
+    /// @param path Path of the file, replaced if it exists
+    /// @param width Width of the world
+    /// @param height Height of the world
+    /// @param rule The rule of the world
+    pub fn create(
+        path: &Path,
+        width: usize,
+        height: usize,
+        rule: Rule,
+    ) -> Result<Self, MappedWorldError> {
+        let words = width.div_ceil(64);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        // The file is sparse, the pages are allocated on the first write
+        file.set_len((HEADER_SIZE + words * 8 * height) as u64)?;
+
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[0..4].copy_from_slice(MAGIC);
+        map[4..8].copy_from_slice(&VERSION.to_le_bytes());
+        map[8..16].copy_from_slice(&(width as u64).to_le_bytes());
+        map[16..24].copy_from_slice(&(height as u64).to_le_bytes());
+
+        Ok(Self {
+            map,
+            width,
+            height,
+            words,
+            rule,
+            kernel: Kernel::detect(),
+        })
+    }
+
+    /// Open a file holding a world
+    ///
+    /// @param path Path of the file
+    /// @param rule The rule of the world
+    pub fn open(path: &Path, rule: Rule) -> Result<Self, MappedWorldError> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        if map.len() < HEADER_SIZE
+            || &map[0..4] != MAGIC
+            || u32::from_le_bytes(map[4..8].try_into().unwrap()) != VERSION
+        {
+            return Err(MappedWorldError::InvalidHeader);
+        }
+
+        let width = u64::from_le_bytes(map[8..16].try_into().unwrap()) as usize;
+        let height = u64::from_le_bytes(map[16..24].try_into().unwrap()) as usize;
+        let words = width.div_ceil(64);
+        if map.len() < HEADER_SIZE + words * 8 * height {
+            return Err(MappedWorldError::Truncated);
+        }
+
+        Ok(Self {
+            map,
+            width,
+            height,
+            words,
+            rule,
+            kernel: Kernel::detect(),
+        })
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    pub fn get_kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    /// Use another instruction set, the scalar one if the CPU does not
+    /// support it
+    ///
+    /// @param kernel The instruction set
+    pub fn set_kernel(&mut self, kernel: Kernel) {
+        self.kernel = if kernel.is_supported() {
+            kernel
+        } else {
+            Kernel::Scalar
+        };
+    }
+
+    pub fn get_generation(&self) -> usize {
+        u64::from_le_bytes(
+            self.map[GENERATION_OFFSET..GENERATION_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        ) as usize
+    }
+
+    fn set_generation(&mut self, generation: usize) {
+        self.map[GENERATION_OFFSET..GENERATION_OFFSET + 8]
+            .copy_from_slice(&(generation as u64).to_le_bytes());
+    }
+
+    /// Whether a cell is alive
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    pub fn is_alive(&self, x: usize, y: usize) -> bool {
+        let offset = self.row_offset(y) + x / 64 * 8;
+        let word = u64::from_le_bytes(self.map[offset..offset + 8].try_into().unwrap());
+
+        (word >> (x % 64)) & 1 == 1
+    }
+
+    /// Make a cell alive or dead
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    /// @param alive Whether the cell is alive
+    pub fn set_alive(&mut self, x: usize, y: usize, alive: bool) {
+        let offset = self.row_offset(y) + x / 64 * 8;
+        let mut word = u64::from_le_bytes(self.map[offset..offset + 8].try_into().unwrap());
+        if alive {
+            word |= 1 << (x % 64);
+        } else {
+            word &= !(1 << (x % 64));
+        }
+        self.map[offset..offset + 8].copy_from_slice(&word.to_le_bytes());
+    }
+
+    /// Count the alive cells, reading the whole file
+    pub fn get_population(&self) -> usize {
+        self.map[HEADER_SIZE..]
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()).count_ones() as usize)
+            .sum()
+    }
+
+    /// Fill the world with random cells, a band of rows at a time
+    ///
+    /// @param rng The random number generator
+    /// @param density Probability of a cell to be alive
+    pub fn populate(&mut self, rng: &mut impl Rng, density: f32) -> std::io::Result<()> {
+        let mut row = vec![0u64; self.words];
+        for y in 0..self.height {
+            row.iter_mut().for_each(|word| *word = 0);
+            for x in 0..self.width {
+                if rng.gen::<f32>() < density {
+                    row[x / 64] |= 1 << (x % 64);
+                }
+            }
+            self.write_row(y, &row);
+            self.end_band(y)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compute the next generation, a band of rows at a time
+    ///
+    /// The rows are updated in place: the old states of the previous, the
+    /// current and the first rows are kept aside, the next row is still
+    /// untouched in the file.
+    pub fn step(&mut self) -> std::io::Result<UpdateStats> {
+        let mut stats = UpdateStats::default();
+        if self.width == 0 || self.height == 0 {
+            return Ok(stats);
+        }
+
+        let mut kernel = RowKernel::new(&self.rule, self.width, self.kernel);
+        let first = self.read_row(0);
+        let mut above = self.read_row(self.height - 1);
+        let mut current = first.clone();
+        let mut new_row = vec![0u64; self.words];
+        for y in 0..self.height {
+            let below = if y + 1 < self.height {
+                self.read_row(y + 1)
+            } else {
+                first.clone()
+            };
+
+            kernel.next_row(&above, &current, &below, &mut new_row);
+            for (old, new) in current.iter().zip(new_row.iter()) {
+                stats.births += (new & !old).count_ones() as usize;
+                stats.deaths += (old & !new).count_ones() as usize;
+            }
+            self.write_row(y, &new_row);
+            self.end_band(y)?;
+
+            above = std::mem::replace(&mut current, below);
+        }
+
+        let generation = self.get_generation();
+        self.set_generation(generation + 1);
+
+        Ok(stats)
+    }
+
+    /// Write the changes back to the file, waiting for the end of the writes
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.map.flush()
+    }
+
+    fn row_offset(&self, y: usize) -> usize {
+        HEADER_SIZE + y * self.words * 8
+    }
+
+    fn read_row(&self, y: usize) -> Vec<u64> {
+        let offset = self.row_offset(y);
+        self.map[offset..offset + self.words * 8]
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .collect()
+    }
+
+    fn write_row(&mut self, y: usize, row: &[u64]) {
+        let offset = self.row_offset(y);
+        for (bytes, word) in self.map[offset..offset + self.words * 8]
+            .chunks_exact_mut(8)
+            .zip(row.iter())
+        {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    /// Start writing back the band ending at a row, if it is complete
+    fn end_band(&self, y: usize) -> std::io::Result<()> {
+        if (y + 1).is_multiple_of(BAND_ROWS) || y + 1 == self.height {
+            let start = y - y % BAND_ROWS;
+            let offset = self.row_offset(start);
+            self.map
+                .flush_async_range(offset, self.row_offset(y + 1) - offset)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,17 @@
-use crate::render::Render;
+use crate::render::{Render, RenderError};
 use crate::world::World;
 
-struct NoneRenderType {}
+#[derive(Default)]
+pub struct NoneRenderType {}
 
 impl NoneRenderType {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {}
     }
 }
 
 impl Render for NoneRenderType {
-    fn render(&mut self, _: &World) {}
+    fn render(&mut self, _: &World) -> Result<(), RenderError> {
+        Ok(())
+    }
 }
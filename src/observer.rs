@@ -0,0 +1,43 @@
+use crate::world::World;
+
+/// Statistics of a generation
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StepStats {
+    /// The generation reached by the step
+    pub generation: usize,
+    /// Number of alive cells
+    pub population: usize,
+    /// Number of cells born during the step
+    pub births: usize,
+    /// Number of cells which died during the step
+    pub deaths: usize,
+}
+
+/// What the simulation should do after notifying an observer
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StepControl {
+    /// Keep running
+    Continue,
+    /// Stop the simulation
+    Stop,
+}
+
+/// An observer notified after each generation of a simulation
+///
+/// Closures taking the stats and the world are observers too.
+pub trait StepObserver {
+    /// Called after each generation
+    ///
+    /// @param stats The statistics of the generation
+    /// @param world The world after the step
+    fn on_step(&mut self, stats: &StepStats, world: &World) -> StepControl;
+}
+
+impl<F> StepObserver for F
+where
+    F: FnMut(&StepStats, &World) -> StepControl,
+{
+    fn on_step(&mut self, stats: &StepStats, world: &World) -> StepControl {
+        self(stats, world)
+    }
+}
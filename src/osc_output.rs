@@ -0,0 +1,143 @@
+use crate::world::{CellState, World};
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// Number of regions along each axis of the world, for the activity
+const ACTIVITY_REGIONS: usize = 4;
+
+/// An argument of an OSC message
+enum OscArgument {
+    Int(i32),
+    Float(f32),
+}
+
+/// An output sending the statistics of each generation as OSC messages over
+/// UDP, to drive live-coding environments such as SuperCollider
+///
+/// Each generation sends:
+/// - `/gol/stats` with the generation, the population, the births and the
+///   deaths as int32
+/// - `/gol/activity` with the fraction of the cells which changed in each
+///   region of a 4x4 split of the world as float32, row major
+pub struct OscOutput {
+    /// The socket, connected to the receiver
+    socket: UdpSocket,
+    /// The world as it was sent at the previous generation
+    previous_tiles: Vec<CellState>,
+}
+
+impl OscOutput {
+    /// Create an output sending to a receiver
+    ///
+    /// @param address The address of the receiver, such as 127.0.0.1:57120
+    pub fn connect<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(address)?;
+
+        Ok(Self {
+            socket,
+            previous_tiles: Vec::new(),
+        })
+    }
+
+    /// Send the statistics of a generation
+    ///
+    /// Messages which cannot be sent, such as when nothing listens, are
+    /// dropped.
+    ///
+    /// @param world The world
+    /// @param generation The generation of the world
+    pub fn send(&mut self, world: &World, generation: usize) {
+        let width = world.get_width();
+        let height = world.get_height();
+        let tiles = world.get_tiles();
+        if tiles.len() != self.previous_tiles.len() {
+            self.previous_tiles = vec![CellState::DEAD; tiles.len()];
+        }
+
+        let mut population = 0;
+        let mut births = 0;
+        let mut deaths = 0;
+        let mut changes = [0usize; ACTIVITY_REGIONS * ACTIVITY_REGIONS];
+        for (index, (tile, previous_tile)) in tiles.iter().zip(&self.previous_tiles).enumerate() {
+            if tile.is_alive() {
+                population += 1;
+            }
+            if tile != previous_tile {
+                if tile.is_alive() {
+                    births += 1;
+                } else {
+                    deaths += 1;
+                }
+
+                let region_x = (index % width) * ACTIVITY_REGIONS / width;
+                let region_y = (index / width) * ACTIVITY_REGIONS / height;
+                changes[region_y * ACTIVITY_REGIONS + region_x] += 1;
+            }
+        }
+        self.previous_tiles.copy_from_slice(tiles);
+
+        let stats = [generation, population, births, deaths]
+            .iter()
+            .map(|value| OscArgument::Int(*value as i32))
+            .collect::<Vec<_>>();
+        let activity = changes
+            .iter()
+            .enumerate()
+            .map(|(region, changes)| {
+                let region_x = region % ACTIVITY_REGIONS;
+                let region_y = region / ACTIVITY_REGIONS;
+                let region_width = region_size(width, region_x);
+                let region_height = region_size(height, region_y);
+                OscArgument::Float(*changes as f32 / (region_width * region_height).max(1) as f32)
+            })
+            .collect::<Vec<_>>();
+
+        let _ = self.socket.send(&osc_message("/gol/stats", &stats));
+        let _ = self.socket.send(&osc_message("/gol/activity", &activity));
+    }
+}
+
+/// Get the number of cells of a world along an axis falling in a region
+///
+/// @param size Size of the world along the axis
+/// @param region Index of the region along the axis
+fn region_size(size: usize, region: usize) -> usize {
+    // The cells of coordinate c fall in the region c * REGIONS / size
+    let start = (region * size).div_ceil(ACTIVITY_REGIONS);
+    let end = ((region + 1) * size).div_ceil(ACTIVITY_REGIONS);
+
+    end - start
+}
+
+/// Encode an OSC message
+///
+/// @param address The address pattern of the message
+/// @param arguments The arguments of the message
+fn osc_message(address: &str, arguments: &[OscArgument]) -> Vec<u8> {
+    let mut message = Vec::new();
+    write_osc_string(&mut message, address);
+
+    let type_tags: String = std::iter::once(',')
+        .chain(arguments.iter().map(|argument| match argument {
+            OscArgument::Int(_) => 'i',
+            OscArgument::Float(_) => 'f',
+        }))
+        .collect();
+    write_osc_string(&mut message, &type_tags);
+
+    for argument in arguments {
+        match argument {
+            OscArgument::Int(value) => message.extend_from_slice(&value.to_be_bytes()),
+            OscArgument::Float(value) => message.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+
+    message
+}
+
+/// Write an OSC string: null terminated and padded to a multiple of 4 bytes
+fn write_osc_string(buffer: &mut Vec<u8>, s: &str) {
+    buffer.extend_from_slice(s.as_bytes());
+    let padding = 4 - s.len() % 4;
+    buffer.extend(std::iter::repeat_n(0, padding));
+}
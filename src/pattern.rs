@@ -0,0 +1,666 @@
+use crate::fetch::get_cached_path;
+use crate::geometry::{Point, Rect};
+use crate::world::{CellState, World};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Largest width or height of a pattern, which may come from remote clients
+pub const MAX_PATTERN_SIZE: usize = 1 << 16;
+
+/// Largest number of cells of a pattern
+pub const MAX_PATTERN_CELLS: usize = 1 << 26;
+
+/// Builtin patterns, in plaintext format
+const BUILTINS: &[(&str, &str)] = &[
+    ("block", "OO\nOO"),
+    ("beehive", ".OO.\nO..O\n.OO."),
+    ("loaf", ".OO.\nO..O\n.O.O\n..O."),
+    ("boat", "OO.\nO.O\n.O."),
+    ("blinker", "OOO"),
+    ("toad", ".OOO\nOOO."),
+    ("beacon", "OO..\nOO..\n..OO\n..OO"),
+    ("glider", ".O.\n..O\nOOO"),
+    ("lwss", ".O..O\nO....\nO...O\nOOOO."),
+    ("r-pentomino", ".OO\nOO.\n.O."),
+    ("diehard", "......O.\nOO......\n.O...OOO"),
+    ("acorn", ".O.....\n...O...\nOO..OOO"),
+    (
+        "gun",
+        "........................O...........\n\
+         ......................O.O...........\n\
+         ............OO......OO............OO\n\
+         ...........O...O....OO............OO\n\
+         OO........O.....O...OO..............\n\
+         OO........O...O.OO....O.O...........\n\
+         ..........O.....O.......O...........\n\
+         ...........O...O....................\n\
+         ............OO......................",
+    ),
+];
+
+/// An error occurring while loading or placing a pattern
+#[derive(Debug)]
+pub enum PatternError {
+    /// The pattern file could not be read
+    Io(PathBuf, std::io::Error),
+    /// The pattern content is malformed
+    Parse(String),
+    /// No builtin pattern has this name
+    UnknownBuiltin(String),
+    /// The placement specification is malformed
+    InvalidPlacement(String),
+    /// The pattern was not downloaded with `gol fetch`
+    NotCached(String),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::Io(path, err) => write!(f, "cannot read {}: {}", path.display(), err),
+            PatternError::Parse(msg) => write!(f, "invalid pattern: {}", msg),
+            PatternError::UnknownBuiltin(name) => write!(f, "unknown builtin pattern {}", name),
+            PatternError::InvalidPlacement(spec) => write!(f, "invalid placement {}", spec),
+            PatternError::NotCached(name) => write!(
+                f,
+                "pattern {0} is not cached, download it with gol fetch {0}",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// A transformation applied to a pattern
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    /// Rotate by 90 degrees clockwise
+    Rot90,
+    /// Rotate by 180 degrees
+    Rot180,
+    /// Rotate by 270 degrees clockwise
+    Rot270,
+    /// Mirror along the vertical axis
+    FlipX,
+    /// Mirror along the horizontal axis
+    FlipY,
+}
+
+impl FromStr for Transform {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rot90" => Ok(Transform::Rot90),
+            "rot180" => Ok(Transform::Rot180),
+            "rot270" => Ok(Transform::Rot270),
+            "flipx" => Ok(Transform::FlipX),
+            "flipy" => Ok(Transform::FlipY),
+            _ => Err(PatternError::InvalidPlacement(s.to_string())),
+        }
+    }
+}
+
+/// What a pattern file tells about its pattern
+///
+/// Read from the `#N`, `#O` and `#C` lines of the RLE files and the
+/// `!Name:`, `!Author:` and other `!` lines of the plaintext files.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct PatternMetadata {
+    /// Name of the pattern
+    pub name: Option<String>,
+    /// Who found the pattern
+    pub author: Option<String>,
+    /// The comments, one per line
+    pub comments: Vec<String>,
+}
+
+impl PatternMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.author.is_none() && self.comments.is_empty()
+    }
+
+    /// Get the name of the pattern and its author, as `Glider by Richard K.
+    /// Guy`, None without a name
+    pub fn get_title(&self) -> Option<String> {
+        let name = self.name.as_ref()?;
+
+        Some(match self.author.as_ref() {
+            Some(author) => format!("{} by {}", name, author),
+            None => name.clone(),
+        })
+    }
+
+    /// Read a comment line of a RLE file, ignoring the other kinds
+    ///
+    /// @param line The line, starting with `#`
+    fn read_rle_line(&mut self, line: &str) {
+        let value = line.get(2..).unwrap_or("").trim();
+        match line.get(..2) {
+            Some("#N") if !value.is_empty() => self.name = Some(value.to_string()),
+            Some("#O") if !value.is_empty() => self.author = Some(value.to_string()),
+            Some("#C") | Some("#c") => self.comments.push(value.to_string()),
+            _ => {}
+        }
+    }
+
+    /// Read a comment line of a plaintext file
+    ///
+    /// @param line The line, starting with `!`
+    fn read_plaintext_line(&mut self, line: &str) {
+        let value = line[1..].trim();
+        if let Some(name) = value.strip_prefix("Name:") {
+            self.name = Some(name.trim().to_string()).filter(|name| !name.is_empty());
+        } else if let Some(author) = value.strip_prefix("Author:") {
+            self.author = Some(author.trim().to_string()).filter(|author| !author.is_empty());
+        } else {
+            self.comments.push(value.to_string());
+        }
+    }
+}
+
+/// A rectangular pattern of cells
+#[derive(Clone)]
+pub struct Pattern {
+    /// Width of the pattern
+    width: usize,
+    /// Height of the pattern
+    height: usize,
+    /// Tiles of the pattern
+    tiles: Vec<Vec<CellState>>,
+    /// What the file of the pattern tells about it
+    metadata: PatternMetadata,
+}
+
+impl Pattern {
+    /// Create a pattern from rows of tiles
+    ///
+    /// Rows shorter than the longest one are padded with dead cells.
+    pub fn from_rows(mut tiles: Vec<Vec<CellState>>) -> Self {
+        let width = tiles.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in tiles.iter_mut() {
+            row.resize(width, CellState::DEAD);
+        }
+
+        Self {
+            width,
+            height: tiles.len(),
+            tiles,
+            metadata: PatternMetadata::default(),
+        }
+    }
+
+    /// Attach metadata to the pattern
+    ///
+    /// @param metadata The metadata
+    pub fn with_metadata(mut self, metadata: PatternMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Parse a pattern in the plaintext (.cells) format
+    ///
+    /// @param content The content of the pattern
+    pub fn from_plaintext(content: &str) -> Result<Self, PatternError> {
+        let mut tiles = Vec::new();
+        let mut metadata = PatternMetadata::default();
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.starts_with('!') {
+                metadata.read_plaintext_line(line);
+                continue;
+            }
+
+            let mut row = Vec::with_capacity(line.len());
+            for c in line.chars() {
+                row.push(match c {
+                    '.' => CellState::DEAD,
+                    'O' | '*' => CellState::ALIVE,
+                    _ => return Err(PatternError::Parse(format!("unexpected character {:?}", c))),
+                });
+            }
+            tiles.push(row);
+        }
+        check_size(&tiles)?;
+
+        Ok(Self::from_rows(tiles).with_metadata(metadata))
+    }
+
+    /// Parse a pattern in the run length encoded (.rle) format
+    ///
+    /// @param content The content of the pattern
+    pub fn from_rle(content: &str) -> Result<Self, PatternError> {
+        let mut tiles = vec![Vec::new()];
+        let mut run_count: usize = 0;
+        // Number of cells of the rows, before they are padded
+        let mut cell_count = 0;
+        let mut header_seen = false;
+        let mut metadata = PatternMetadata::default();
+
+        'lines: for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                metadata.read_rle_line(line);
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+
+            if !header_seen && line.starts_with('x') {
+                header_seen = true;
+                // As `x = 3, y = 3, rule = B3/S23`, only the size is checked
+                for item in line.split(',') {
+                    if let Some((key, value)) = item.split_once('=') {
+                        if matches!(key.trim(), "x" | "y") {
+                            let size = value.trim().parse::<usize>().map_err(|_| {
+                                PatternError::Parse(format!("invalid size {}", value.trim()))
+                            })?;
+                            if size > MAX_PATTERN_SIZE {
+                                return Err(too_large_error());
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+
+            for c in line.chars() {
+                match c {
+                    '0'..='9' => {
+                        run_count = run_count
+                            .checked_mul(10)
+                            .and_then(|count| count.checked_add(c.to_digit(10).unwrap() as usize))
+                            .filter(|count| *count <= MAX_PATTERN_SIZE)
+                            .ok_or_else(too_large_error)?;
+                        continue;
+                    }
+                    'b' | '.' | 'o' | 'A'..='X' => {
+                        let cell_state = if matches!(c, 'b' | '.') {
+                            CellState::DEAD
+                        } else {
+                            CellState::ALIVE
+                        };
+                        let row = tiles.last_mut().unwrap();
+                        cell_count += run_count.max(1);
+                        if row.len() + run_count.max(1) > MAX_PATTERN_SIZE
+                            || cell_count > MAX_PATTERN_CELLS
+                        {
+                            return Err(too_large_error());
+                        }
+                        row.resize(row.len() + run_count.max(1), cell_state);
+                    }
+                    '$' => {
+                        if tiles.len() + run_count.max(1) > MAX_PATTERN_SIZE {
+                            return Err(too_large_error());
+                        }
+                        for _ in 0..run_count.max(1) {
+                            tiles.push(Vec::new());
+                        }
+                    }
+                    '!' => break 'lines,
+                    c if c.is_whitespace() => {}
+                    _ => return Err(PatternError::Parse(format!("unexpected character {:?}", c))),
+                }
+                run_count = 0;
+            }
+        }
+        check_size(&tiles)?;
+
+        Ok(Self::from_rows(tiles).with_metadata(metadata))
+    }
+
+    /// Get a builtin pattern by name
+    ///
+    /// @param name The name of the pattern
+    pub fn builtin(name: &str) -> Result<Self, PatternError> {
+        BUILTINS
+            .iter()
+            .find(|(builtin_name, _)| *builtin_name == name)
+            .ok_or_else(|| PatternError::UnknownBuiltin(name.to_string()))
+            .and_then(|(_, content)| Self::from_plaintext(content))
+    }
+
+    /// Get the names of the builtin patterns
+    pub fn builtin_names() -> Vec<&'static str> {
+        BUILTINS.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Load a pattern from a file
+    ///
+    /// The format is guessed from the extension, falling back to the content.
+    ///
+    /// @param path Path of the pattern file
+    pub fn load(path: &Path) -> Result<Self, PatternError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| PatternError::Io(path.to_path_buf(), err))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("rle") => Self::from_rle(&content),
+            Some("cells") => Self::from_plaintext(&content),
+            _ => Self::parse(&content),
+        }
+    }
+
+    /// Parse a pattern, the format being guessed from the content
+    ///
+    /// @param content The pattern in the RLE or the plaintext format
+    pub fn parse(content: &str) -> Result<Self, PatternError> {
+        let is_rle = content
+            .lines()
+            .any(|line| line.trim_start().starts_with("x ") || line.starts_with("x="));
+
+        if is_rle {
+            Self::from_rle(content)
+        } else {
+            Self::from_plaintext(content)
+        }
+    }
+
+    /// Create a pattern holding the whole world
+    ///
+    /// @param world The world to copy
+    pub fn from_world(world: &World) -> Self {
+        Self::from_rows(
+            (0..world.get_height())
+                .map(|y| {
+                    (0..world.get_width())
+                        .map(|x| world.get_tile(x, y))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Encode the pattern in the plaintext (.cells) format
+    pub fn to_plaintext(&self) -> String {
+        let mut plaintext = String::with_capacity((self.width + 1) * self.height);
+        if let Some(name) = self.metadata.name.as_ref() {
+            plaintext.push_str(&format!("!Name: {}\n", name));
+        }
+        if let Some(author) = self.metadata.author.as_ref() {
+            plaintext.push_str(&format!("!Author: {}\n", author));
+        }
+        for comment in self.metadata.comments.iter() {
+            plaintext.push_str(&format!("!{}\n", comment));
+        }
+        plaintext.push_str(&self.to_plaintext_rows());
+
+        plaintext
+    }
+
+    /// Encode the cells of the pattern in the plaintext (.cells) format,
+    /// without its metadata
+    pub fn to_plaintext_rows(&self) -> String {
+        let mut plaintext = String::with_capacity((self.width + 1) * self.height);
+        for row in self.tiles.iter() {
+            for tile in row.iter() {
+                plaintext.push(if *tile == CellState::ALIVE { 'O' } else { '.' });
+            }
+            plaintext.push('\n');
+        }
+
+        plaintext
+    }
+
+    /// Encode the pattern in the run length encoded (.rle) format
+    pub fn to_rle(&self) -> String {
+        let mut items = Vec::new();
+        let mut pending_rows = 0;
+        for row in self.tiles.iter() {
+            // Trailing dead cells are implicit
+            let length = row
+                .iter()
+                .rposition(|tile| *tile == CellState::ALIVE)
+                .map_or(0, |position| position + 1);
+            if length == 0 {
+                pending_rows += 1;
+                continue;
+            }
+
+            if !items.is_empty() {
+                items.push(rle_item(pending_rows + 1, '$'));
+            } else if pending_rows > 0 {
+                items.push(rle_item(pending_rows, '$'));
+            }
+            pending_rows = 0;
+
+            let mut x = 0;
+            while x < length {
+                let run_length = row[x..length]
+                    .iter()
+                    .take_while(|tile| **tile == row[x])
+                    .count();
+                let tag = if row[x] == CellState::ALIVE { 'o' } else { 'b' };
+                items.push(rle_item(run_length, tag));
+                x += run_length;
+            }
+        }
+        items.push("!".to_string());
+
+        let mut rle = String::new();
+        if let Some(name) = self.metadata.name.as_ref() {
+            rle.push_str(&format!("#N {}\n", name));
+        }
+        if let Some(author) = self.metadata.author.as_ref() {
+            rle.push_str(&format!("#O {}\n", author));
+        }
+        for comment in self.metadata.comments.iter() {
+            rle.push_str(&format!("#C {}\n", comment));
+        }
+
+        // Lines of RLE files should not exceed 70 characters
+        rle.push_str(&format!(
+            "x = {}, y = {}, rule = B3/S23\n",
+            self.width, self.height
+        ));
+        let mut line_length = 0;
+        for item in items {
+            if line_length + item.len() > 70 {
+                rle.push('\n');
+                line_length = 0;
+            }
+            line_length += item.len();
+            rle.push_str(&item);
+        }
+        rle.push('\n');
+
+        rle
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the rectangle covering the pattern, from its top left corner
+    pub fn get_bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    pub fn get_tile(&self, x: usize, y: usize) -> CellState {
+        self.tiles[y][x]
+    }
+
+    pub fn get_metadata(&self) -> &PatternMetadata {
+        &self.metadata
+    }
+
+    /// Get a transformed copy of the pattern
+    ///
+    /// @param transform The transformation to apply
+    pub fn transform(&self, transform: Transform) -> Self {
+        let (width, height) = match transform {
+            Transform::Rot90 | Transform::Rot270 => (self.height, self.width),
+            _ => (self.width, self.height),
+        };
+
+        let tiles = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| match transform {
+                        Transform::Rot90 => self.tiles[self.height - 1 - x][y],
+                        Transform::Rot180 => self.tiles[self.height - 1 - y][self.width - 1 - x],
+                        Transform::Rot270 => self.tiles[x][self.width - 1 - y],
+                        Transform::FlipX => self.tiles[y][self.width - 1 - x],
+                        Transform::FlipY => self.tiles[self.height - 1 - y][x],
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            tiles,
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+/// Get the error of a pattern too large to be loaded
+fn too_large_error() -> PatternError {
+    PatternError::Parse(format!(
+        "larger than {0}x{0} or {1} cells",
+        MAX_PATTERN_SIZE, MAX_PATTERN_CELLS
+    ))
+}
+
+/// Check rows of cells fit in a pattern once padded to the longest one
+///
+/// @param tiles The rows
+fn check_size(tiles: &[Vec<CellState>]) -> Result<(), PatternError> {
+    let width = tiles.iter().map(|row| row.len()).max().unwrap_or(0);
+    if width > MAX_PATTERN_SIZE
+        || tiles.len() > MAX_PATTERN_SIZE
+        || width * tiles.len() > MAX_PATTERN_CELLS
+    {
+        return Err(too_large_error());
+    }
+
+    Ok(())
+}
+
+/// Format a run of a RLE pattern
+fn rle_item(run_length: usize, tag: char) -> String {
+    if run_length == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", run_length, tag)
+    }
+}
+
+/// Where a pattern comes from
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatternSource {
+    /// A builtin pattern
+    Builtin(String),
+    /// A pattern file
+    File(PathBuf),
+}
+
+impl PatternSource {
+    /// Load the pattern
+    pub fn load(&self) -> Result<Pattern, PatternError> {
+        match self {
+            PatternSource::Builtin(name) => Pattern::builtin(name),
+            PatternSource::File(path) => Pattern::load(path),
+        }
+    }
+
+    /// Get the path of the pattern file, None for the builtin patterns
+    pub fn get_path(&self) -> Option<&Path> {
+        match self {
+            PatternSource::Builtin(_) => None,
+            PatternSource::File(path) => Some(path),
+        }
+    }
+}
+
+impl FromStr for PatternSource {
+    type Err = PatternError;
+
+    /// Parse `builtin:name`, `file:path`, `cached:name` for a pattern
+    /// downloaded with `gol fetch`, or a bare name which is looked up in the
+    /// builtins before being treated as a path
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(name) = s.strip_prefix("builtin:") {
+            Ok(PatternSource::Builtin(name.to_string()))
+        } else if let Some(name) = s.strip_prefix("cached:") {
+            match get_cached_path(name) {
+                Ok(path) if path.is_file() => Ok(PatternSource::File(path)),
+                _ => Err(PatternError::NotCached(name.to_string())),
+            }
+        } else if let Some(path) = s.strip_prefix("file:") {
+            Ok(PatternSource::File(PathBuf::from(path)))
+        } else if Pattern::builtin_names().contains(&s) {
+            Ok(PatternSource::Builtin(s.to_string()))
+        } else {
+            Ok(PatternSource::File(PathBuf::from(s)))
+        }
+    }
+}
+
+/// A pattern placed at a position of the world
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+    /// The pattern to place
+    pub source: PatternSource,
+    /// Position of the top left corner of the pattern
+    pub position: Point,
+    /// Transformations to apply, in order
+    pub transforms: Vec<Transform>,
+}
+
+impl Placement {
+    /// Load the pattern and apply the transformations
+    pub fn load(&self) -> Result<Pattern, PatternError> {
+        let pattern = self.source.load()?;
+
+        Ok(self
+            .transforms
+            .iter()
+            .fold(pattern, |pattern, transform| pattern.transform(*transform)))
+    }
+}
+
+impl FromStr for Placement {
+    type Err = PatternError;
+
+    /// Parse `pattern@x,y[:transform...]`, e.g. `glider@10,10:rot90`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PatternError::InvalidPlacement(s.to_string());
+
+        let at = s.rfind('@').ok_or_else(invalid)?;
+        let source = s[..at].parse::<PatternSource>()?;
+
+        let mut parts = s[at + 1..].split(':');
+        let position = parts.next().ok_or_else(invalid)?;
+        let mut coords = position.split(',');
+        let x = coords
+            .next()
+            .and_then(|x| x.parse().ok())
+            .ok_or_else(invalid)?;
+        let y = coords
+            .next()
+            .and_then(|y| y.parse().ok())
+            .ok_or_else(invalid)?;
+        if coords.next().is_some() {
+            return Err(invalid());
+        }
+
+        let transforms = parts
+            .map(|transform| transform.parse::<Transform>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            source,
+            position: Point::new(x, y),
+            transforms,
+        })
+    }
+}
@@ -1,14 +1,777 @@
-use crate::render::Render;
-use crate::world::World;
+use crate::geometry::{Point, Rect};
+use crate::grid::RegionStats;
+use crate::pattern::Pattern;
+use crate::population_graph::PopulationGraph;
+use crate::raster::Rasterizer;
+use crate::render::{ColorOptions, Render, RenderError, RenderOptions};
+use crate::simulation::Command;
+use crate::world::{CellState, World};
+use piston_window::texture::{CreateTexture, Format, UpdateTexture};
+use piston_window::{
+    AdvancedWindow, Button, Filter, G2dTexture, G2dTextureContext, ImageSize, Key, MouseButton,
+    MouseCursorEvent, PistonWindow, PressEvent, ReleaseEvent, Texture, TextureSettings,
+    Transformed, WindowSettings,
+};
+use std::time::{Duration, Instant};
 
-struct PistonRenderType {}
+/// Longest fade between two generations, for the slow or paused simulations
+const MAX_FADE_DURATION: Duration = Duration::from_secs(1);
+
+/// How far the mouse must move to close the render, with the exit on input
+/// option, so the jitter of the pointer is ignored
+const EXIT_MOUSE_DISTANCE: f64 = 10.0;
+
+/// Fraction of the size of the world the view scrolls by at each press of
+/// an arrow
+const SCROLL_FRACTION: usize = 64;
+
+/// Title of the window
+const TITLE: &str = "Game of Life";
+
+/// Opacity of the pasted pattern following the cursor
+const STAMP_PREVIEW_ALPHA: f32 = 0.5;
+
+/// Size of the population graph, in pixels
+const GRAPH_SIZE: [f64; 2] = [200.0, 48.0];
+
+/// Space between the population graph and the corner of the window, in
+/// pixels
+const GRAPH_MARGIN: f64 = 8.0;
+
+/// Width of a column of the population graph, in pixels
+const GRAPH_COLUMN_WIDTH: f64 = 2.0;
+
+/// Color behind the population graph
+const GRAPH_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+
+/// Thickness of the rulers, in pixels
+const RULER_SIZE: f64 = 16.0;
+
+/// Smallest space between two ticks of the rulers, in pixels
+const RULER_MIN_SPACING: f64 = 6.0;
+
+/// Lengths of the ticks of the rulers, every tick and every tenth, in pixels
+const RULER_TICK_LENGTHS: [f64; 2] = [4.0, 10.0];
+
+/// Color behind the rulers
+const RULER_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+
+/// Color of the ticks of the rulers
+const RULER_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+
+/// Color of the selection over the selected cells
+const SELECTION_COLOR: [f32; 4] = [0.3, 0.6, 1.0, 0.3];
+
+/// Color of the crosshair over the row and the column of the cell under the
+/// cursor
+const CROSSHAIR_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.2];
+
+/// A render drawing the world in a window
+///
+/// Cells can be painted alive with the left mouse button and dead with the
+/// right one. The left arrow key pauses and steps back one generation. The R
+/// key switches to the next rule of the rule cycle. The I
+/// key toggles the inspection of the cell under the cursor, whose position,
+/// state, age and number of live neighbors are shown in the title. The
+/// title shows the progress of the simulation, or the heads-up information
+/// until it is given, otherwise.
+///
+/// Ctrl+V pastes an RLE or plaintext pattern from the clipboard: it follows
+/// the cursor until the left mouse button stamps it, or the right one drops
+/// it.
+///
+/// Shift and the left mouse button select a rectangle of the world, whose
+/// population, and births, deaths and activity since the previous generation
+/// drawn, are shown in the title. Shift and the right mouse button clear the
+/// selection.
+///
+/// Ctrl and the arrow keys scroll the view along the edges of the world
+/// which wrap around, so the structures crossing them show in one piece.
+///
+/// With the rulers option, or after pressing the C key, rulers along the top
+/// and left edges of the window tick the cells, a longer tick every ten, and
+/// a crosshair highlights the row and the column of the cell under the
+/// cursor, whose coordinates are shown in the title.
+///
+/// With the smooth option, the cells born or dead fade in and out during
+/// the time between two generations, as measured on the previous ones. With
+/// the trails option, the cells fade out during the generations following
+/// their death.
+///
+/// When the population graph is given, it is drawn in the bottom left corner
+/// of the window.
+///
+/// With the fullscreen option, the world is scaled to fit the screen, keeping
+/// the cells square. With the exit on input option, the window closes on any
+/// key press, click or move of the mouse, as a screensaver does.
+pub struct PistonRenderType {
+    /// The window
+    window: PistonWindow,
+    /// Whether the window has been closed
+    closed: bool,
+    /// Position of the mouse cursor, in cells
+    cursor: [f64; 2],
+    /// First position of the mouse cursor seen, in pixels, when exiting on
+    /// input
+    cursor_origin: Option<[f64; 2]>,
+    /// Size of a cell, in pixels
+    scale: f64,
+    /// Position of the world in the window, in pixels
+    offset: [f64; 2],
+    /// State painted while a mouse button is held
+    painting: Option<CellState>,
+    /// Whether a Ctrl key is held
+    ctrl: bool,
+    /// Whether a Shift key is held
+    shift: bool,
+    /// The cell where the selection started, while a mouse button is held
+    selection_start: Option<Point>,
+    /// The selected cells
+    selection: Option<Rect>,
+    /// The statistics of the selected cells, shown in the title
+    selection_stats: Option<RegionStats>,
+    /// Pattern pasted from the clipboard, following the cursor until stamped
+    stamping: Option<Pattern>,
+    /// Commands issued by the user
+    commands: Vec<Command>,
+    /// Options of the render
+    options: RenderOptions,
+    /// Colors of the cells
+    rasterizer: Rasterizer,
+    /// Where the textures are uploaded to the GPU
+    texture_context: G2dTextureContext,
+    /// The world as an image, one pixel per cell, None before the first frame
+    texture: Option<G2dTexture>,
+    /// Whether the cell under the cursor is inspected
+    inspecting: bool,
+    /// Whether the rulers and the crosshair are shown
+    rulers: bool,
+    /// The cell under the cursor, when showing the rulers
+    hovered: Option<Point>,
+    /// Generations since each cell is in its state, when inspecting
+    cell_ages: Vec<usize>,
+    /// Current title of the window
+    title: String,
+    /// Heads-up information shown in the title when not inspecting
+    hud: String,
+    /// Progress of the simulation, shown in the title rather than the
+    /// heads-up information when given
+    progress_title: Option<String>,
+    /// When the current generation was first rendered
+    generation_start: Instant,
+    /// Time between the last two generations
+    generation_duration: Duration,
+    /// Heights of the columns of the population graph, between 0 and 1,
+    /// oldest first
+    population_columns: Vec<f32>,
+}
 
 impl PistonRenderType {
-    fn new() -> Self {
-        Self {}
+    /// Open the window
+    ///
+    /// @param width Width of the window
+    /// @param height Height of the window
+    /// @param options Options of the render
+    pub fn new(width: usize, height: usize, options: RenderOptions) -> Result<Self, RenderError> {
+        let mut window: PistonWindow = WindowSettings::new(TITLE, [width as u32, height as u32])
+            .exit_on_esc(true)
+            .fullscreen(options.fullscreen)
+            .build()
+            .map_err(|err| RenderError::Device(format!("cannot open the window: {}", err)))?;
+        let texture_context = window.create_texture_context();
+        let rasterizer = Rasterizer::new(&options);
+        let rulers = options.rulers;
+
+        Ok(Self {
+            window,
+            closed: false,
+            cursor: [0.0, 0.0],
+            cursor_origin: None,
+            scale: 1.0,
+            offset: [0.0, 0.0],
+            painting: None,
+            ctrl: false,
+            shift: false,
+            selection_start: None,
+            selection: None,
+            selection_stats: None,
+            stamping: None,
+            commands: Vec::new(),
+            options,
+            rasterizer,
+            texture_context,
+            texture: None,
+            inspecting: false,
+            rulers,
+            hovered: None,
+            cell_ages: Vec::new(),
+            title: TITLE.to_string(),
+            hud: String::new(),
+            progress_title: None,
+            generation_start: Instant::now(),
+            generation_duration: Duration::from_secs(1),
+            population_columns: Vec::new(),
+        })
+    }
+
+    /// Track the generations of the world to fade between them and to age
+    /// the cells
+    ///
+    /// Returns how far the fade from the previous generation is, between 0
+    /// and 1.
+    fn track_generation(&mut self, world: &World) -> f32 {
+        if self.rasterizer.update(world) {
+            let now = Instant::now();
+            self.generation_duration = now
+                .duration_since(self.generation_start)
+                .min(MAX_FADE_DURATION);
+            self.generation_start = now;
+
+            if self.inspecting {
+                self.age_cells(world);
+            }
+        }
+        if self.rasterizer.get_previous_tiles().len() != world.get_tiles().len() {
+            return 1.0;
+        }
+
+        (self.generation_start.elapsed().as_secs_f32()
+            / self.generation_duration.as_secs_f32().max(f32::EPSILON))
+        .min(1.0)
+    }
+
+    /// Age the cells which kept their state since the previous generation
+    fn age_cells(&mut self, world: &World) {
+        let (previous_tiles, current_tiles) =
+            (self.rasterizer.get_previous_tiles(), world.get_tiles());
+        self.cell_ages.resize(current_tiles.len(), 0);
+        for (index, age) in self.cell_ages.iter_mut().enumerate() {
+            let previous_state = previous_tiles.get(index);
+            *age = if previous_state == Some(&current_tiles[index]) {
+                *age + 1
+            } else {
+                0
+            };
+        }
+    }
+
+    /// Get the cell of the world under the cursor, None if it is outside
+    fn get_cursor_cell(&self, world: &World) -> Option<Point> {
+        let [x, y] = self.cursor;
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+
+        Some(Point::new(x as usize, y as usize))
+            .filter(|point| world.get_bounds().contains(*point))
+            .map(|point| self.rasterizer.get_world_point(world, point))
+    }
+
+    /// Show the cell under the cursor in the title of the window
+    fn inspect(&mut self, world: &World) {
+        let title = if let Some(Point { x, y }) = self.get_cursor_cell(world) {
+            let neighbors_count = world.get_live_neighbor_count(x, y);
+            let age = self
+                .cell_ages
+                .get(y * world.get_width() + x)
+                .copied()
+                .unwrap_or(0);
+
+            format!(
+                "{} - ({}, {}) state {} age {} neighbors {}",
+                TITLE,
+                x,
+                y,
+                world.get_tile(x, y).0,
+                age,
+                neighbors_count
+            )
+        } else {
+            format!("{} - inspecting", TITLE)
+        };
+
+        self.show_title(title);
+    }
+
+    /// Get the title of the window when not inspecting
+    fn get_hud_title(&self) -> String {
+        let title = if let Some(progress_title) = self.progress_title.as_ref() {
+            progress_title.clone()
+        } else if self.hud.is_empty() {
+            TITLE.to_string()
+        } else {
+            format!("{} - {}", TITLE, self.hud)
+        };
+
+        let title = match self.selection_stats {
+            Some(stats) => format!("{} - {}", stats, title),
+            None => title,
+        };
+
+        match self.hovered {
+            Some(point) => format!("{} - {}", point, title),
+            None => title,
+        }
+    }
+
+    /// Extend the selection to the cell under the cursor, if selecting
+    fn select(&mut self, world: &World) {
+        if let (Some(start), Some(end)) = (self.selection_start, self.get_cursor_cell(world)) {
+            let (x, y) = (start.x.min(end.x), start.y.min(end.y));
+            self.selection = Some(Rect::new(
+                x,
+                y,
+                start.x.max(end.x) - x + 1,
+                start.y.max(end.y) - y + 1,
+            ));
+        }
+    }
+
+    /// Change the title of the window if needed
+    fn show_title(&mut self, title: String) {
+        if title != self.title {
+            self.window.set_title(title.clone());
+            self.title = title;
+        }
+    }
+
+    /// Parse the pattern in the clipboard and make it follow the cursor
+    fn paste(&mut self) {
+        let text = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text());
+        match text {
+            Ok(text) => match Pattern::parse(&text) {
+                Ok(pattern) => self.stamping = Some(pattern),
+                Err(err) => eprintln!("error: cannot paste: {}", err),
+            },
+            Err(err) => eprintln!("error: cannot read the clipboard: {}", err),
+        }
+    }
+
+    /// Scroll the view around the world, by a 64th of its size
+    ///
+    /// @param world The world
+    /// @param dx Direction to scroll along the columns, -1, 0 or 1
+    /// @param dy Direction to scroll along the rows, -1, 0 or 1
+    fn scroll(&mut self, world: &World, dx: isize, dy: isize) {
+        let step = |size: usize| (size / SCROLL_FRACTION).max(1) as isize;
+        self.rasterizer.scroll(
+            world,
+            dx * step(world.get_width()),
+            dy * step(world.get_height()),
+        );
+    }
+
+    /// Paint the cell under the cursor if a mouse button is held
+    fn paint(&mut self, world: &World) {
+        if let Some(cell_state) = self.painting {
+            if let Some(point) = self.get_cursor_cell(world) {
+                self.commands.push(Command::SetCell(point, cell_state));
+            }
+        }
     }
 }
 
 impl Render for PistonRenderType {
-    fn render(&mut self, _: &World) {}
+    fn render(&mut self, world: &World) -> Result<(), RenderError> {
+        if let Some(event) = self.window.next() {
+            if self.options.exit_on_input {
+                if event.press_args().is_some() {
+                    self.closed = true;
+                    return Ok(());
+                }
+                if let Some(cursor) = event.mouse_cursor_args() {
+                    // The first position is where the mouse rests
+                    let [origin_x, origin_y] = *self.cursor_origin.get_or_insert(cursor);
+                    if (cursor[0] - origin_x).hypot(cursor[1] - origin_y) > EXIT_MOUSE_DISTANCE {
+                        self.closed = true;
+                        return Ok(());
+                    }
+                }
+            }
+
+            if let Some([x, y]) = event.mouse_cursor_args() {
+                self.cursor = [
+                    (x - self.offset[0]) / self.scale,
+                    (y - self.offset[1]) / self.scale,
+                ];
+                self.paint(world);
+                self.select(world);
+            }
+            if let Some(Button::Mouse(button)) = event.press_args() {
+                if self.shift {
+                    match button {
+                        MouseButton::Left => {
+                            self.selection_start = self.get_cursor_cell(world);
+                            self.select(world);
+                        }
+                        MouseButton::Right => self.selection = None,
+                        _ => {}
+                    }
+                } else if let Some(pattern) = self.stamping.take() {
+                    let [x, y] = self.cursor;
+                    if button == MouseButton::Left && x >= 0.0 && y >= 0.0 {
+                        let origin = self
+                            .rasterizer
+                            .get_world_point(world, Point::new(x as usize, y as usize));
+                        self.commands.push(Command::Stamp(pattern, origin));
+                    }
+                } else {
+                    self.painting = match button {
+                        MouseButton::Left => Some(CellState::ALIVE),
+                        MouseButton::Right => Some(CellState::DEAD),
+                        _ => None,
+                    };
+                    self.paint(world);
+                }
+            }
+            match event.press_args() {
+                Some(Button::Keyboard(Key::LCtrl)) | Some(Button::Keyboard(Key::RCtrl)) => {
+                    self.ctrl = true
+                }
+                Some(Button::Keyboard(Key::V)) if self.ctrl => self.paste(),
+                _ => {}
+            }
+            if let Some(Button::Keyboard(Key::LCtrl)) | Some(Button::Keyboard(Key::RCtrl)) =
+                event.release_args()
+            {
+                self.ctrl = false;
+            }
+            match event.press_args() {
+                Some(Button::Keyboard(Key::LShift)) | Some(Button::Keyboard(Key::RShift)) => {
+                    self.shift = true
+                }
+                _ => {}
+            }
+            if let Some(Button::Keyboard(Key::LShift)) | Some(Button::Keyboard(Key::RShift)) =
+                event.release_args()
+            {
+                self.shift = false;
+            }
+            // Ctrl and the arrows scroll the view around the world
+            match event.press_args() {
+                Some(Button::Keyboard(Key::Left)) if self.ctrl => self.scroll(world, -1, 0),
+                Some(Button::Keyboard(Key::Right)) if self.ctrl => self.scroll(world, 1, 0),
+                Some(Button::Keyboard(Key::Up)) if self.ctrl => self.scroll(world, 0, -1),
+                Some(Button::Keyboard(Key::Down)) if self.ctrl => self.scroll(world, 0, 1),
+                Some(Button::Keyboard(Key::Left)) => self.commands.push(Command::StepBack),
+                _ => {}
+            }
+            if let Some(Button::Keyboard(Key::R)) = event.press_args() {
+                self.commands.push(Command::NextRule);
+            }
+            if let Some(Button::Keyboard(Key::I)) = event.press_args() {
+                self.inspecting = !self.inspecting;
+                if !self.inspecting {
+                    self.show_title(self.get_hud_title());
+                }
+            }
+            if let Some(Button::Keyboard(Key::C)) = event.press_args() {
+                self.rulers = !self.rulers;
+            }
+            if let Some(Button::Mouse(_)) = event.release_args() {
+                self.painting = None;
+                self.selection_start = None;
+            }
+
+            let generation_fade = self.track_generation(world);
+            let fade = if self.options.smooth {
+                generation_fade
+            } else {
+                1.0
+            };
+            // Against the previous generation drawn, the one the cells fade from
+            let selection_stats = self.selection.map(|selection| {
+                world.get_region_stats(selection, Some(self.rasterizer.get_previous_tiles()))
+            });
+            if self.inspecting {
+                self.inspect(world);
+            } else {
+                let hovered = self.get_cursor_cell(world).filter(|_| self.rulers);
+                if hovered != self.hovered || selection_stats != self.selection_stats {
+                    self.hovered = hovered;
+                    self.selection_stats = selection_stats;
+                    self.show_title(self.get_hud_title());
+                }
+            }
+
+            // Upload the world as a texture, one texel per cell
+            let image = self
+                .rasterizer
+                .rasterize(world, world.get_bounds(), 1, fade);
+            let size = [image.get_width() as u32, image.get_height() as u32];
+            let texture_context = &mut self.texture_context;
+            let uploaded = match self.texture.as_mut() {
+                Some(texture) if texture.get_size() == (size[0], size[1]) => UpdateTexture::update(
+                    texture,
+                    texture_context,
+                    Format::Rgba8,
+                    image.get_pixels(),
+                    [0, 0],
+                    size,
+                ),
+                // An empty world has nothing to show
+                _ if size[0] == 0 || size[1] == 0 => Ok(()),
+                _ => Texture::create(
+                    texture_context,
+                    Format::Rgba8,
+                    image.get_pixels(),
+                    size,
+                    // Keep the cells sharp when scaling them up
+                    &TextureSettings::new().filter(Filter::Nearest),
+                )
+                .map(|texture| self.texture = Some(texture)),
+            };
+            uploaded
+                .map_err(|err| RenderError::Device(format!("cannot upload the frame: {}", err)))?;
+
+            let rulers = self.rulers;
+            // The crosshair is where the hovered cell is shown
+            let hovered = self
+                .hovered
+                .map(|point| self.rasterizer.get_view_point(world, point));
+            let view_offset = self.rasterizer.get_wrapped_offset(world);
+            let selection = self.selection.map(|selection| {
+                let origin = self
+                    .rasterizer
+                    .get_view_point(world, selection.get_origin());
+                Rect::at(origin, selection.width, selection.height)
+            });
+            // The ticks follow the cells of the world across the scrolled view
+            let get_tick_position = |tick: usize, offset: usize, size: usize| {
+                if offset == 0 {
+                    tick
+                } else {
+                    (tick + size - offset) % size
+                }
+            };
+            let palette = self.rasterizer.get_palette(world);
+            let background = self.rasterizer.get_background(world);
+            let (world_width, world_height) = (world.get_width() as f64, world.get_height() as f64);
+            let texture = self.texture.as_ref();
+            let texture_context = &mut self.texture_context;
+            let stamping = self.stamping.as_ref();
+            let cursor = self.cursor;
+            let fullscreen = self.options.fullscreen;
+            let (mut scale, mut offset) = (self.scale, self.offset);
+            let population_columns = &self.population_columns;
+            self.window.draw_2d(&event, |context, graphics, device| {
+                texture_context.encoder.flush(device);
+                piston_window::clear(background, graphics);
+
+                // Fit the world in the screen, centered
+                if fullscreen {
+                    let [view_width, view_height] = context.get_view_size();
+                    scale = (view_width / world_width).min(view_height / world_height);
+                    offset = [
+                        (view_width - world_width * scale) / 2.0,
+                        (view_height - world_height * scale) / 2.0,
+                    ];
+                }
+                let transform = context
+                    .transform
+                    .trans(offset[0], offset[1])
+                    .scale(scale, scale);
+
+                if let Some(texture) = texture {
+                    piston_window::image(texture, transform, graphics);
+                }
+
+                // The pasted pattern, where it would be stamped
+                if let Some(pattern) = stamping {
+                    let [red, green, blue, _] = palette[CellState::ALIVE.0 as usize];
+                    let color = [red, green, blue, STAMP_PREVIEW_ALPHA];
+                    let [x, y] = [cursor[0].floor(), cursor[1].floor()];
+                    for pattern_y in 0..pattern.get_height() {
+                        for pattern_x in 0..pattern.get_width() {
+                            if pattern.get_tile(pattern_x, pattern_y).is_alive() {
+                                piston_window::rectangle(
+                                    color,
+                                    [x + pattern_x as f64, y + pattern_y as f64, 1.0, 1.0],
+                                    transform,
+                                    graphics,
+                                );
+                            }
+                        }
+                    }
+                }
+
+                // The selection, over the world
+                if let Some(selection) = selection {
+                    piston_window::rectangle(
+                        SELECTION_COLOR,
+                        [
+                            selection.x as f64,
+                            selection.y as f64,
+                            selection.width as f64,
+                            selection.height as f64,
+                        ],
+                        transform,
+                        graphics,
+                    );
+                }
+
+                // The crosshair and the rulers, over the world
+                if rulers {
+                    if let Some(Point { x, y }) = hovered {
+                        piston_window::rectangle(
+                            CROSSHAIR_COLOR,
+                            [x as f64, 0.0, 1.0, world_height],
+                            transform,
+                            graphics,
+                        );
+                        piston_window::rectangle(
+                            CROSSHAIR_COLOR,
+                            [0.0, y as f64, world_width, 1.0],
+                            transform,
+                            graphics,
+                        );
+                    }
+
+                    let [view_width, view_height] = context.get_view_size();
+                    piston_window::rectangle(
+                        RULER_BACKGROUND,
+                        [0.0, 0.0, view_width, RULER_SIZE],
+                        context.transform,
+                        graphics,
+                    );
+                    piston_window::rectangle(
+                        RULER_BACKGROUND,
+                        [0.0, RULER_SIZE, RULER_SIZE, view_height - RULER_SIZE],
+                        context.transform,
+                        graphics,
+                    );
+
+                    let step = get_ruler_step(scale);
+                    for tick in (0..=world_width as usize).step_by(step) {
+                        let length = RULER_TICK_LENGTHS[(tick % (10 * step) == 0) as usize];
+                        let tick_x = get_tick_position(tick, view_offset.x, world_width as usize);
+                        let x = offset[0] + tick_x as f64 * scale;
+                        piston_window::rectangle(
+                            RULER_COLOR,
+                            [x, RULER_SIZE - length, 1.0, length],
+                            context.transform,
+                            graphics,
+                        );
+                    }
+                    for tick in (0..=world_height as usize).step_by(step) {
+                        let length = RULER_TICK_LENGTHS[(tick % (10 * step) == 0) as usize];
+                        let tick_y = get_tick_position(tick, view_offset.y, world_height as usize);
+                        let y = offset[1] + tick_y as f64 * scale;
+                        piston_window::rectangle(
+                            RULER_COLOR,
+                            [RULER_SIZE - length, y, length, 1.0],
+                            context.transform,
+                            graphics,
+                        );
+                    }
+
+                    // Where the cell under the cursor is on the rulers
+                    if let Some(Point { x, y }) = hovered {
+                        piston_window::rectangle(
+                            CROSSHAIR_COLOR,
+                            [
+                                offset[0] + x as f64 * scale,
+                                0.0,
+                                scale.max(1.0),
+                                RULER_SIZE,
+                            ],
+                            context.transform,
+                            graphics,
+                        );
+                        piston_window::rectangle(
+                            CROSSHAIR_COLOR,
+                            [
+                                0.0,
+                                offset[1] + y as f64 * scale,
+                                RULER_SIZE,
+                                scale.max(1.0),
+                            ],
+                            context.transform,
+                            graphics,
+                        );
+                    }
+                }
+
+                // The population graph, over the world
+                if !population_columns.is_empty() {
+                    let [_, view_height] = context.get_view_size();
+                    let [left, top] = [GRAPH_MARGIN, view_height - GRAPH_MARGIN - GRAPH_SIZE[1]];
+                    piston_window::rectangle(
+                        GRAPH_BACKGROUND,
+                        [left, top, GRAPH_SIZE[0], GRAPH_SIZE[1]],
+                        context.transform,
+                        graphics,
+                    );
+                    let color = palette[CellState::ALIVE.0 as usize];
+                    for (column, height) in population_columns.iter().enumerate() {
+                        // Never empty, so the lowest population still shows
+                        let height = (*height as f64 * GRAPH_SIZE[1]).max(1.0);
+                        piston_window::rectangle(
+                            color,
+                            [
+                                left + column as f64 * GRAPH_COLUMN_WIDTH,
+                                top + GRAPH_SIZE[1] - height,
+                                GRAPH_COLUMN_WIDTH,
+                                height,
+                            ],
+                            context.transform,
+                            graphics,
+                        );
+                    }
+                }
+            });
+            self.scale = scale;
+            self.offset = offset;
+        } else {
+            self.closed = true;
+        }
+
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn take_commands(&mut self) -> Vec<Command> {
+        std::mem::take(&mut self.commands)
+    }
+
+    fn set_hud(&mut self, hud: &str) {
+        self.hud = hud.to_string();
+        if !self.inspecting {
+            self.show_title(self.get_hud_title());
+        }
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.progress_title = Some(title.to_string());
+        if !self.inspecting {
+            self.show_title(self.get_hud_title());
+        }
+    }
+
+    fn set_colors(&mut self, colors: ColorOptions) {
+        self.rasterizer.set_colors(colors);
+    }
+
+    fn set_population_graph(&mut self, graph: &PopulationGraph) {
+        self.population_columns = graph.get_columns((GRAPH_SIZE[0] / GRAPH_COLUMN_WIDTH) as usize);
+    }
+}
+
+/// Get the cells between two ticks of the rulers, 1, 2 or 5 times a power
+/// of ten, so the ticks are not closer than the smallest spacing
+///
+/// @param scale Size of a cell, in pixels
+fn get_ruler_step(scale: f64) -> usize {
+    let mut magnitude = 1;
+    loop {
+        for factor in [1, 2, 5] {
+            if (factor * magnitude) as f64 * scale >= RULER_MIN_SPACING {
+                return factor * magnitude;
+            }
+        }
+        magnitude *= 10;
+    }
 }
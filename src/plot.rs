@@ -0,0 +1,453 @@
+//! A chart of the population along the generations, written to a file
+//!
+//! Unlike the graph shown while running, which only keeps the last
+//! generations, the plot records every generation of the run and is written
+//! once it ends, as an SVG drawing if its path ends with `.svg`, as a PNG
+//! image otherwise. The population axis starts from zero so the charts of
+//! different runs compare at a glance. The PNG image only labels the ticks
+//! with their values, the SVG drawing also names the axes.
+//!
+//! A long run has more generations than the chart has pixels, so each column
+//! of pixels only keeps the lowest and the highest population of its
+//! generations, which keeps the oscillations and the spikes visible.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+
+/// Width of the chart, in pixels
+const WIDTH: usize = 800;
+/// Height of the chart, in pixels
+const HEIGHT: usize = 480;
+/// Space left around the plotting area for the labels: left, right, top and
+/// bottom
+const MARGINS: (usize, usize, usize, usize) = (72, 24, 16, 48);
+/// About how many ticks to put on an axis
+const TICKS: usize = 5;
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const GRID: [u8; 3] = [225, 225, 225];
+const AXIS: [u8; 3] = [0, 0, 0];
+const LINE: [u8; 3] = [31, 119, 180];
+
+/// The digits drawn on the PNG image, 3 by 5 pixels, the top row in the
+/// highest bits
+const DIGITS: [u16; 10] = [
+    0b111_101_101_101_111,
+    0b010_110_010_010_111,
+    0b111_001_111_100_111,
+    0b111_001_111_001_111,
+    0b101_101_111_001_001,
+    0b111_100_111_001_111,
+    0b111_100_111_101_111,
+    0b111_001_001_001_001,
+    0b111_101_111_101_111,
+    0b111_101_111_001_111,
+];
+/// Size of a pixel of a digit on the PNG image
+const DIGIT_SCALE: usize = 2;
+
+/// A generation and its population
+type Point = (usize, usize);
+
+/// The population of every generation of a run
+#[derive(Clone, Default, Debug)]
+pub struct PopulationPlot {
+    /// The generations and their population, in order
+    points: Vec<Point>,
+}
+
+impl PopulationPlot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the population of a generation
+    ///
+    /// @param generation The generation
+    /// @param population Its population
+    pub fn record(&mut self, generation: usize, population: usize) {
+        self.points.push((generation, population));
+    }
+
+    /// Forget the populations, as when the world starts over
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Write the chart, as SVG if the path ends with `.svg`, as PNG otherwise
+    ///
+    /// @param path Path of the file
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if path.extension().is_some_and(|extension| extension == "svg") {
+            std::fs::write(path, self.to_svg())
+        } else {
+            let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            file.write_all(&self.to_png()?)?;
+            file.flush()
+        }
+    }
+
+    /// Draw the chart as an SVG drawing
+    pub fn to_svg(&self) -> String {
+        let axes = Axes::new(&self.points);
+        let (left, _, top, _) = MARGINS;
+        let (right, bottom) = (WIDTH - MARGINS.1, HEIGHT - MARGINS.3);
+
+        let mut svg = String::new();
+        // Writing to a string cannot fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif" font-size="12">"#,
+            WIDTH, HEIGHT
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="{}" height="{}" fill="{}"/>"#,
+            WIDTH,
+            HEIGHT,
+            to_hex(BACKGROUND)
+        );
+
+        for tick in axes.get_x_ticks() {
+            let x = axes.get_x(tick);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{0:.1}" y1="{1}" x2="{0:.1}" y2="{2}" stroke="{3}"/>"#,
+                x,
+                top,
+                bottom,
+                to_hex(GRID)
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#,
+                x,
+                bottom + 16,
+                tick
+            );
+        }
+        for tick in axes.get_y_ticks() {
+            let y = axes.get_y(tick);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{1}" y1="{0:.1}" x2="{2}" y2="{0:.1}" stroke="{3}"/>"#,
+                y,
+                left,
+                right,
+                to_hex(GRID)
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{:.1}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+                left - 6,
+                y,
+                tick
+            );
+        }
+
+        let _ = writeln!(
+            svg,
+            r#"<path d="M{0} {1}V{2}H{3}" fill="none" stroke="{4}"/>"#,
+            left,
+            top,
+            bottom,
+            right,
+            to_hex(AXIS)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="middle">generation</text>"#,
+            (left + right) / 2,
+            HEIGHT - 8
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text transform="translate(16 {}) rotate(-90)" text-anchor="middle">population</text>"#,
+            (top + bottom) / 2
+        );
+
+        let points = axes
+            .get_line(&self.points)
+            .iter()
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#,
+            points,
+            to_hex(LINE)
+        );
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+
+    /// Draw the chart as a PNG image
+    pub fn to_png(&self) -> std::io::Result<Vec<u8>> {
+        let axes = Axes::new(&self.points);
+        let (left, _, top, _) = MARGINS;
+        let (right, bottom) = (WIDTH - MARGINS.1, HEIGHT - MARGINS.3);
+        let mut canvas = Canvas::new(WIDTH, HEIGHT, BACKGROUND);
+
+        for tick in axes.get_x_ticks() {
+            let x = axes.get_x(tick);
+            canvas.draw_line((x, top as f64), (x, bottom as f64), GRID);
+            let label = tick.to_string();
+            let label_width = get_label_width(&label);
+            canvas.draw_label(
+                (x.round() as usize).saturating_sub(label_width / 2),
+                bottom + 8,
+                &label,
+            );
+        }
+        for tick in axes.get_y_ticks() {
+            let y = axes.get_y(tick);
+            canvas.draw_line((left as f64, y), (right as f64, y), GRID);
+            let label = tick.to_string();
+            canvas.draw_label(
+                (left - 8).saturating_sub(get_label_width(&label)),
+                (y.round() as usize).saturating_sub(5 * DIGIT_SCALE / 2),
+                &label,
+            );
+        }
+
+        canvas.draw_line(
+            (left as f64, top as f64),
+            (left as f64, bottom as f64),
+            AXIS,
+        );
+        canvas.draw_line(
+            (left as f64, bottom as f64),
+            (right as f64, bottom as f64),
+            AXIS,
+        );
+
+        let line = axes.get_line(&self.points);
+        for segment in line.windows(2) {
+            canvas.draw_line(segment[0], segment[1], LINE);
+        }
+        if let [point] = line.as_slice() {
+            canvas.draw_line(*point, *point, LINE);
+        }
+
+        let mut png = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png, WIDTH as u32, HEIGHT as u32);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&canvas.pixels)?;
+        }
+
+        Ok(png)
+    }
+}
+
+/// The ranges of the axes and how they map to the chart
+struct Axes {
+    /// First and last generation
+    generations: (usize, usize),
+    /// Highest population shown, at the top of the chart
+    population: usize,
+    /// Generations between two ticks
+    x_step: usize,
+    /// Population between two ticks
+    y_step: usize,
+}
+
+impl Axes {
+    fn new(points: &[Point]) -> Self {
+        // Going back in the history plots the generations again
+        let first = points.iter().map(|(generation, _)| *generation).min();
+        let first = first.unwrap_or(0);
+        let last = points.iter().map(|(generation, _)| *generation).max();
+        let last = last.unwrap_or(0);
+        let last = last.max(first + 1);
+        let highest = points
+            .iter()
+            .map(|(_, population)| *population)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        // Up to the tick above the highest population, so it has a label
+        let y_step = get_step(highest);
+        let population = highest.div_ceil(y_step) * y_step;
+
+        Self {
+            generations: (first, last),
+            population,
+            x_step: get_step(last - first),
+            y_step,
+        }
+    }
+
+    /// Get the generations with a tick, multiples of the step
+    fn get_x_ticks(&self) -> Vec<usize> {
+        let (first, last) = self.generations;
+
+        (first.div_ceil(self.x_step)..=last / self.x_step)
+            .map(|tick| tick * self.x_step)
+            .collect()
+    }
+
+    /// Get the populations with a tick, from zero
+    fn get_y_ticks(&self) -> Vec<usize> {
+        (0..=self.population / self.y_step)
+            .map(|tick| tick * self.y_step)
+            .collect()
+    }
+
+    /// Get the abscissa of a generation on the chart
+    fn get_x(&self, generation: usize) -> f64 {
+        let (first, last) = self.generations;
+        let width = (WIDTH - MARGINS.0 - MARGINS.1) as f64;
+
+        MARGINS.0 as f64 + (generation - first) as f64 * width / (last - first) as f64
+    }
+
+    /// Get the ordinate of a population on the chart
+    fn get_y(&self, population: usize) -> f64 {
+        let height = (HEIGHT - MARGINS.2 - MARGINS.3) as f64;
+
+        (HEIGHT - MARGINS.3) as f64 - population as f64 * height / self.population as f64
+    }
+
+    /// Get the points of the line of the populations, keeping the lowest and
+    /// the highest population of each column of pixels, in the order they
+    /// were reached
+    fn get_line(&self, points: &[Point]) -> Vec<(f64, f64)> {
+        let mut line = Vec::new();
+        let mut column: Option<(usize, Point, Point)> = None;
+        let mut flush = |column: (usize, Point, Point)| {
+            let (_, lowest, highest) = column;
+            let (first, second) = if lowest.0 <= highest.0 {
+                (lowest, highest)
+            } else {
+                (highest, lowest)
+            };
+            line.push((self.get_x(first.0), self.get_y(first.1)));
+            if second != first {
+                line.push((self.get_x(second.0), self.get_y(second.1)));
+            }
+        };
+
+        for point in points.iter().copied() {
+            let x = self.get_x(point.0).round() as usize;
+            column = match column {
+                Some((column_x, lowest, highest)) if column_x == x => Some((
+                    x,
+                    if point.1 < lowest.1 { point } else { lowest },
+                    if point.1 > highest.1 { point } else { highest },
+                )),
+                previous => {
+                    if let Some(previous) = previous {
+                        flush(previous);
+                    }
+                    Some((x, point, point))
+                }
+            };
+        }
+        if let Some(column) = column {
+            flush(column);
+        }
+
+        line
+    }
+}
+
+/// Get the step between the ticks of an axis spanning a range, 1, 2 or 5
+/// times a power of ten
+fn get_step(range: usize) -> usize {
+    let rough = (range / TICKS).max(1);
+    let mut magnitude = 1;
+    while magnitude * 10 <= rough {
+        magnitude *= 10;
+    }
+
+    [1, 2, 5, 10]
+        .iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10 * magnitude)
+}
+
+/// Get the width of a number drawn on the PNG image, in pixels
+fn get_label_width(label: &str) -> usize {
+    (label.len() * 4).saturating_sub(1) * DIGIT_SCALE
+}
+
+fn to_hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// The pixels of the PNG image
+struct Canvas {
+    width: usize,
+    height: usize,
+    /// The pixels, row by row, 3 bytes each
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize, background: [u8; 3]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: background.repeat(width * height),
+        }
+    }
+
+    /// Color a pixel, nothing if outside of the image
+    fn set_pixel(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        if x < self.width && y < self.height {
+            let index = (y * self.width + x) * 3;
+            self.pixels[index..index + 3].copy_from_slice(&color);
+        }
+    }
+
+    /// Draw a line one pixel wide
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), color: [u8; 3]) {
+        let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).ceil() as usize;
+        for step in 0..=steps {
+            let t = if steps == 0 {
+                0.0
+            } else {
+                step as f64 / steps as f64
+            };
+            let x = from.0 + (to.0 - from.0) * t;
+            let y = from.1 + (to.1 - from.1) * t;
+            self.set_pixel(x.round() as usize, y.round() as usize, color);
+        }
+    }
+
+    /// Draw a number, its top left corner at a pixel
+    fn draw_label(&mut self, x: usize, y: usize, label: &str) {
+        for (index, digit) in label.bytes().enumerate() {
+            let bits = DIGITS[(digit - b'0') as usize];
+            let digit_x = x + index * 4 * DIGIT_SCALE;
+            for row in 0..5 {
+                for column in 0..3 {
+                    if bits & (1 << (14 - row * 3 - column)) == 0 {
+                        continue;
+                    }
+                    for dy in 0..DIGIT_SCALE {
+                        for dx in 0..DIGIT_SCALE {
+                            self.set_pixel(
+                                digit_x + column * DIGIT_SCALE + dx,
+                                y + row * DIGIT_SCALE + dy,
+                                AXIS,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Rules supplied as WebAssembly plugins
+//!
+//! A plugin is a WebAssembly module, binary or text, exporting one of:
+//!
+//! - `next_state(neighborhood: i32) -> i32`, given the 3x3 neighborhood as 9
+//!   bits, bit `3 * row + column` being the cell at this row and column of
+//!   the neighborhood, so bit 4 is the cell itself
+//! - `next_state_count(alive: i32, neighbors: i32) -> i32`, given whether the
+//!   cell is alive and its number of alive neighbors
+//!
+//! returning a non-zero value if the cell is alive at the next generation.
+//!
+//! The module is given no imports, so it cannot reach anything outside its
+//! own memory, and each call is limited in fuel, so it cannot hang the
+//! simulation. It is called once per neighborhood when loading it, the
+//! generations then only look the answers up.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::Grid;
+use crate::world::CellState;
+use std::fmt;
+use std::path::Path;
+use wasmi::{Config, Engine, Linker, Module, Store};
+
+/// Number of 3x3 neighborhoods
+const NEIGHBORHOODS: usize = 1 << 9;
+
+/// Bit of the cell itself in a neighborhood
+const CENTER: usize = 1 << 4;
+
+/// Fuel given to each call of the plugin, about as many instructions
+const CALL_FUEL: u64 = 1_000_000;
+
+/// An error occurring while loading a plugin
+#[derive(Debug)]
+pub enum PluginError {
+    /// The file cannot be read
+    Io(std::io::Error),
+    /// The module is invalid, needs imports or trapped
+    Wasm(wasmi::Error),
+    /// The module exports none of the transition functions
+    MissingExport,
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginError::Io(err) => write!(f, "{}", err),
+            PluginError::Wasm(err) => write!(f, "{}", err),
+            PluginError::MissingExport => write!(
+                f,
+                "the module exports neither next_state(i32) -> i32 nor next_state_count(i32, i32) -> i32"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+impl From<std::io::Error> for PluginError {
+    fn from(err: std::io::Error) -> Self {
+        PluginError::Io(err)
+    }
+}
+
+impl From<wasmi::Error> for PluginError {
+    fn from(err: wasmi::Error) -> Self {
+        PluginError::Wasm(err)
+    }
+}
+
+/// A two-state rule computed by a WebAssembly plugin
+pub struct PluginRule {
+    /// Name of the plugin, the name of its file
+    name: String,
+    /// Whether the cell is alive at the next generation, indexed by
+    /// neighborhood
+    alive: Vec<bool>,
+}
+
+impl PluginRule {
+    /// Load a plugin and ask it the next state of every neighborhood
+    ///
+    /// @param path Path of the module
+    pub fn load(path: &Path) -> Result<Self, PluginError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, std::fs::read(path)?)?;
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(CALL_FUEL)?;
+        let instance = Linker::<()>::new(&engine).instantiate_and_start(&mut store, &module)?;
+
+        let mut alive = vec![false; NEIGHBORHOODS];
+        if let Ok(next_state) = instance.get_typed_func::<i32, i32>(&store, "next_state") {
+            for (neighborhood, alive) in alive.iter_mut().enumerate() {
+                store.set_fuel(CALL_FUEL)?;
+                *alive = next_state.call(&mut store, neighborhood as i32)? != 0;
+            }
+        } else if let Ok(next_state) =
+            instance.get_typed_func::<(i32, i32), i32>(&store, "next_state_count")
+        {
+            // Ask once per state and count, the neighborhoods share the answers
+            let mut answers = [[false; 9]; 2];
+            for (state, answers) in answers.iter_mut().enumerate() {
+                for (neighbors, answer) in answers.iter_mut().enumerate() {
+                    store.set_fuel(CALL_FUEL)?;
+                    *answer = next_state.call(&mut store, (state as i32, neighbors as i32))? != 0;
+                }
+            }
+            for (neighborhood, alive) in alive.iter_mut().enumerate() {
+                let state = (neighborhood & CENTER != 0) as usize;
+                let neighbors = (neighborhood & !CENTER).count_ones() as usize;
+                *alive = answers[state][neighbors];
+            }
+        } else {
+            return Err(PluginError::MissingExport);
+        }
+
+        let name = path.file_stem().map_or_else(
+            || "plugin".to_string(),
+            |stem| stem.to_string_lossy().into_owned(),
+        );
+
+        Ok(Self { name, alive })
+    }
+}
+
+impl Automaton for PluginRule {
+    fn name(&self) -> String {
+        format!("plugin {}", self.name)
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = grid.get_tiles().to_vec();
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                let mut neighborhood = 0;
+                for (bit, (dx, dy)) in (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                    .enumerate()
+                {
+                    // The neighbors beyond the edges which do not wrap are dead
+                    let alive = grid
+                        .get_neighbor(x, y, dx, dy)
+                        .is_some_and(|(x, y)| grid.get_tile(x, y) != CellState::DEAD);
+                    neighborhood |= (alive as usize) << bit;
+                }
+
+                let was_alive = neighborhood & CENTER != 0;
+                let is_alive = self.alive[neighborhood];
+                if is_alive && !was_alive {
+                    stats.births += 1;
+                } else if was_alive && !is_alive {
+                    stats.deaths += 1;
+                }
+                *new_cell = if is_alive {
+                    CellState::ALIVE
+                } else {
+                    CellState::DEAD
+                };
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        2
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![[1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]]
+    }
+}
@@ -0,0 +1,124 @@
+//! The population of the last generations, drawn as a graph
+//!
+//! The graph is scaled between the lowest and the highest population of the
+//! generations it keeps, so the oscillations stand out even on a large
+//! population, and a collapse shows as a drop to the bottom.
+
+use std::collections::VecDeque;
+
+/// Number of generations kept when not given
+pub const DEFAULT_GENERATIONS: usize = 256;
+
+/// The characters drawing a column of a line, from empty to full by eighths
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The population of the last generations
+#[derive(Clone, Debug)]
+pub struct PopulationGraph {
+    /// Number of generations kept
+    generations: usize,
+    /// The populations, oldest first
+    populations: VecDeque<usize>,
+}
+
+impl PopulationGraph {
+    /// Create an empty graph
+    ///
+    /// @param generations Number of generations kept
+    pub fn new(generations: usize) -> Self {
+        Self {
+            generations: generations.max(1),
+            populations: VecDeque::new(),
+        }
+    }
+
+    /// Add the population of a generation, forgetting the oldest one if the
+    /// graph is full
+    ///
+    /// @param population The population
+    pub fn record(&mut self, population: usize) {
+        if self.populations.len() == self.generations {
+            self.populations.pop_front();
+        }
+        self.populations.push_back(population);
+    }
+
+    /// Forget the populations, as when the world starts over
+    pub fn clear(&mut self) {
+        self.populations.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.populations.is_empty()
+    }
+
+    /// Get the population of the last generation
+    pub fn get_last(&self) -> Option<usize> {
+        self.populations.back().copied()
+    }
+
+    /// Get the lowest and the highest population
+    pub fn get_range(&self) -> Option<(usize, usize)> {
+        let min = self.populations.iter().min()?;
+        let max = self.populations.iter().max()?;
+
+        Some((*min, *max))
+    }
+
+    /// Get the heights of the columns of the graph, oldest first, between 0
+    /// for the lowest population and 1 for the highest
+    ///
+    /// The generations are shared evenly between the columns, each showing
+    /// the last one of its share. There are fewer columns than asked while
+    /// there are fewer generations.
+    ///
+    /// @param columns Number of columns
+    pub fn get_columns(&self, columns: usize) -> Vec<f32> {
+        let (min, max) = match self.get_range() {
+            Some(range) => range,
+            None => return Vec::new(),
+        };
+        let count = self.populations.len();
+        let columns = columns.min(count);
+
+        (0..columns)
+            .map(|column| {
+                let population = self.populations[(column + 1) * count / columns - 1];
+                if max == min {
+                    0.5
+                } else {
+                    (population - min) as f32 / (max - min) as f32
+                }
+            })
+            .collect()
+    }
+
+    /// Draw the graph as lines of block characters, the top line first
+    ///
+    /// @param columns Number of characters of a line
+    /// @param rows Number of lines
+    pub fn to_lines(&self, columns: usize, rows: usize) -> Vec<String> {
+        let heights = self.get_columns(columns);
+
+        (0..rows)
+            .map(|row| {
+                // Eighths of the column below the top of this line
+                let floor = (rows - 1 - row) * 8;
+                heights
+                    .iter()
+                    .map(|height| {
+                        // Never empty, so the lowest population still shows
+                        let eighths = ((height * (rows * 8) as f32).round() as usize).max(1);
+                        BLOCKS[eighths.saturating_sub(floor).min(8)]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Default for PopulationGraph {
+    fn default() -> Self {
+        Self::new(DEFAULT_GENERATIONS)
+    }
+}
@@ -0,0 +1,401 @@
+//! Search of the predecessors of a pattern
+//!
+//! A predecessor of a pattern is a pattern one cell larger on every side
+//! which evolves into the pattern in one generation. Cells outside the
+//! pattern are not constrained, so a pattern without predecessor cannot
+//! appear in any world except as the initial state: it is a Garden of Eden
+//! orphan.
+//!
+//! The search goes row by row, keeping every pair of consecutive rows of the
+//! predecessor consistent with the rows of the pattern seen so far, and
+//! fills each new row column by column, backtracking as soon as a cell of
+//! the pattern cannot be produced. It is exhaustive, so not finding a
+//! predecessor proves there is none.
+
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::world::{CellState, World};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Maximum size of the narrowest side of a searched pattern
+pub const MAX_NARROW_SIDE: usize = 8;
+
+/// Maximum size of the widest side of a searched pattern
+pub const MAX_WIDE_SIDE: usize = 30;
+
+/// Minimum size of the sides of a world searched for a predecessor
+pub const MIN_WORLD_SIDE: usize = 3;
+
+/// Maximum number of partial predecessors of a world kept by the search
+pub const MAX_WORLD_STATES: usize = 1 << 22;
+
+/// An error occurring while searching a predecessor
+#[derive(Debug)]
+pub enum PredecessorError {
+    /// The pattern is too large to be searched
+    TooLarge(usize, usize),
+    /// The world is too small to be searched
+    TooSmall(usize, usize),
+    /// The world has too many partial predecessors to be searched
+    TooComplex,
+    /// The world does not follow a Life-like rule
+    UnsupportedAutomaton(String),
+    /// The world does not wrap around both axes
+    UnsupportedTopology,
+}
+
+impl fmt::Display for PredecessorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PredecessorError::TooLarge(width, height) => write!(
+                f,
+                "pattern of {}x{} cells too large, sides must not exceed {} and {}",
+                width, height, MAX_NARROW_SIDE, MAX_WIDE_SIDE
+            ),
+            PredecessorError::TooSmall(width, height) => write!(
+                f,
+                "world of {}x{} cells too small, sides must be at least {}",
+                width, height, MIN_WORLD_SIDE
+            ),
+            PredecessorError::TooComplex => write!(f, "too many partial predecessors"),
+            PredecessorError::UnsupportedAutomaton(name) => {
+                write!(f, "automaton {} is not Life-like", name)
+            }
+            PredecessorError::UnsupportedTopology => {
+                write!(f, "only worlds wrapping around both axes are supported")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PredecessorError {}
+
+/// Search a predecessor of a pattern
+///
+/// Returns the predecessor, with the pattern at (1, 1) in it, or None if the
+/// pattern has no predecessor.
+///
+/// @param pattern The pattern
+/// @param rule The rule of the world
+pub fn find_predecessor(
+    pattern: &Pattern,
+    rule: &Rule,
+) -> Result<Option<Pattern>, PredecessorError> {
+    // The rules are isotropic, search along the longest side to keep the
+    // rows short
+    let transposed = pattern.get_width() > pattern.get_height();
+    let (width, height) = if transposed {
+        (pattern.get_height(), pattern.get_width())
+    } else {
+        (pattern.get_width(), pattern.get_height())
+    };
+    if width > MAX_NARROW_SIDE || height > MAX_WIDE_SIDE {
+        return Err(PredecessorError::TooLarge(
+            pattern.get_width(),
+            pattern.get_height(),
+        ));
+    }
+    let is_alive = |x: usize, y: usize| {
+        let cell_state = if transposed {
+            pattern.get_tile(y, x)
+        } else {
+            pattern.get_tile(x, y)
+        };
+        cell_state == CellState::ALIVE
+    };
+
+    // The rows of the pattern and of the predecessor are bitmasks, least
+    // significant bit first
+    let targets: Vec<u32> = (0..height)
+        .map(|y| (0..width).fold(0, |row, x| row | ((is_alive(x, y) as u32) << x)))
+        .collect();
+    let searcher = RowSearcher {
+        rule,
+        width: width + 2,
+        wrap: false,
+    };
+
+    // Every layer maps the last two rows of the partial predecessors to the
+    // row before them, to rebuild a whole predecessor at the end
+    let mut layers: Vec<HashMap<(u32, u32), u32>> = Vec::new();
+    for (y, target) in targets.iter().enumerate() {
+        let mut layer = HashMap::new();
+        let mut add = |rows: [u32; 3]| {
+            layer.entry((rows[1], rows[2])).or_insert(rows[0]);
+        };
+        if y == 0 {
+            searcher.search(*target, None, &mut add);
+        } else {
+            for (first, second) in layers[y - 1].keys() {
+                searcher.search(*target, Some((*first, *second)), &mut add);
+            }
+        }
+
+        if layer.is_empty() {
+            return Ok(None);
+        }
+        layers.push(layer);
+    }
+
+    // Rebuild a predecessor from the last rows
+    let mut rows = vec![0; height + 2];
+    let (mut first, mut second) = match layers.last() {
+        Some(layer) => *layer.keys().next().unwrap(),
+        // An empty pattern is its own predecessor
+        None => return Ok(Some(Pattern::from_rows(Vec::new()))),
+    };
+    rows[height + 1] = second;
+    rows[height] = first;
+    for y in (0..height).rev() {
+        let previous = layers[y][&(first, second)];
+        rows[y] = previous;
+        second = first;
+        first = previous;
+    }
+
+    let predecessor_tile = |x: usize, y: usize| {
+        if (rows[y] >> x) & 1 != 0 {
+            CellState::ALIVE
+        } else {
+            CellState::DEAD
+        }
+    };
+    let tiles = if transposed {
+        (0..width + 2)
+            .map(|y| (0..height + 2).map(|x| predecessor_tile(y, x)).collect())
+            .collect()
+    } else {
+        (0..height + 2)
+            .map(|y| (0..width + 2).map(|x| predecessor_tile(x, y)).collect())
+            .collect()
+    };
+
+    Ok(Some(Pattern::from_rows(tiles)))
+}
+
+/// Search a previous state of a world
+///
+/// Only narrow worlds wrapping around both axes can be searched, see
+/// `MAX_NARROW_SIDE`.
+///
+/// Returns the previous state, or None if the world has no predecessor.
+///
+/// @param world The world
+pub fn find_world_predecessor(world: &World) -> Result<Option<World>, PredecessorError> {
+    let grid = world.get_grid();
+    if !grid.is_wrapping_x() || !grid.is_wrapping_y() {
+        return Err(PredecessorError::UnsupportedTopology);
+    }
+
+    let name = world.get_automaton().name();
+    let rule = name
+        .parse::<Rule>()
+        .map_err(|_| PredecessorError::UnsupportedAutomaton(name.clone()))?;
+
+    let transposed = world.get_width() > world.get_height();
+    let (width, height) = if transposed {
+        (world.get_height(), world.get_width())
+    } else {
+        (world.get_width(), world.get_height())
+    };
+    if width < MIN_WORLD_SIDE {
+        return Err(PredecessorError::TooSmall(
+            world.get_width(),
+            world.get_height(),
+        ));
+    }
+    if width > MAX_NARROW_SIDE {
+        return Err(PredecessorError::TooLarge(
+            world.get_width(),
+            world.get_height(),
+        ));
+    }
+    let is_alive = |x: usize, y: usize| {
+        let cell_state = if transposed {
+            world.get_tile(y, x)
+        } else {
+            world.get_tile(x, y)
+        };
+        cell_state == CellState::ALIVE
+    };
+    let targets: Vec<u32> = (0..height)
+        .map(|y| (0..width).fold(0, |row, x| row | ((is_alive(x, y) as u32) << x)))
+        .collect();
+    let searcher = RowSearcher {
+        rule: &rule,
+        width,
+        wrap: true,
+    };
+
+    // The rows wrap around too, so the partial predecessors remember their
+    // last row and first row, which must match the rows found at the end.
+    // Layer y maps the states (last row, first row, row y, row y + 1) to
+    // row y - 1.
+    let key = |rows: [u32; 4]| {
+        rows.iter()
+            .fold(0_u64, |key, row| (key << MAX_NARROW_SIDE) | *row as u64)
+    };
+    let mut layers: Vec<HashMap<u64, ([u32; 4], u32)>> = Vec::new();
+    let mut first_layer = HashMap::new();
+    searcher.search(targets[0], None, &mut |rows| {
+        first_layer
+            .entry(key([rows[0], rows[1], rows[1], rows[2]]))
+            .or_insert(([rows[0], rows[1], rows[1], rows[2]], rows[0]));
+    });
+    layers.push(first_layer);
+
+    for (y, target) in targets.iter().enumerate().take(height - 1).skip(1) {
+        let mut layer = HashMap::new();
+        for (state, _) in layers[y - 1].values() {
+            let [last, first, previous, current] = *state;
+            searcher.search(*target, Some((previous, current)), &mut |rows| {
+                let state = [last, first, current, rows[2]];
+                layer.entry(key(state)).or_insert((state, previous));
+            });
+        }
+        if layer.len() > MAX_WORLD_STATES {
+            return Err(PredecessorError::TooComplex);
+        }
+        layers.push(layer);
+    }
+
+    // Close the loop: the last target row is produced by the last two rows
+    // and the first one
+    let mut closing = None;
+    for (state, _) in layers[height - 2].values() {
+        let [last, first, previous, current] = *state;
+        if current != last {
+            continue;
+        }
+        searcher.search(
+            targets[height - 1],
+            Some((previous, current)),
+            &mut |rows| {
+                if rows[2] == first {
+                    closing = Some(*state);
+                }
+            },
+        );
+        if closing.is_some() {
+            break;
+        }
+    }
+    let mut state = match closing {
+        Some(state) => state,
+        None => return Ok(None),
+    };
+
+    // Rebuild the predecessor from the last rows
+    let mut rows = vec![0; height];
+    for y in (0..height - 1).rev() {
+        rows[y + 1] = state[3];
+        rows[y] = state[2];
+        let previous = layers[y][&key(state)].1;
+        state = [state[0], state[1], previous, state[2]];
+    }
+
+    let mut predecessor = World::new(world.get_width(), world.get_height());
+    predecessor.set_rule(rule);
+    for (y, row) in rows.iter().enumerate() {
+        for x in (0..width).filter(|x| (row >> x) & 1 != 0) {
+            if transposed {
+                predecessor.set_tile(y, x, CellState::ALIVE);
+            } else {
+                predecessor.set_tile(x, y, CellState::ALIVE);
+            }
+        }
+    }
+
+    Ok(Some(predecessor))
+}
+
+/// Searches the rows of a predecessor producing a row of a pattern
+struct RowSearcher<'a> {
+    /// The rule of the world
+    rule: &'a Rule,
+    /// Width of the rows of the predecessor
+    width: usize,
+    /// Whether the rows wrap around, the predecessor being as wide as the
+    /// pattern, rather than one cell larger on both sides
+    wrap: bool,
+}
+
+impl RowSearcher<'_> {
+    /// Search the rows producing a row of the pattern
+    ///
+    /// @param target The row of the pattern
+    /// @param known The first two rows, if known
+    /// @param found Called with every consistent three rows
+    fn search(&self, target: u32, known: Option<(u32, u32)>, found: &mut dyn FnMut([u32; 3])) {
+        let rows = match known {
+            Some((first, second)) => [first, second, 0],
+            None => [0; 3],
+        };
+        self.search_column(target, known.is_some(), rows, 0, found);
+    }
+
+    /// Fill a column of the rows, then the next ones
+    fn search_column(
+        &self,
+        target: u32,
+        known: bool,
+        rows: [u32; 3],
+        x: usize,
+        found: &mut dyn FnMut([u32; 3]),
+    ) {
+        if x == self.width {
+            // The cells on the edges have their neighbors on the other side
+            if self.wrap
+                && !(self.produces(target, &rows, 0) && self.produces(target, &rows, x - 1))
+            {
+                return;
+            }
+            found(rows);
+            return;
+        }
+
+        // Only the last row is free if the first two are known
+        let choices: &[u32] = if known {
+            &[0b000, 0b100]
+        } else {
+            &[0b000, 0b001, 0b010, 0b011, 0b100, 0b101, 0b110, 0b111]
+        };
+        for choice in choices {
+            let mut rows = rows;
+            for (index, row) in rows.iter_mut().enumerate() {
+                *row |= ((choice >> index) & 1) << x;
+            }
+
+            // The cell left of the column now has all its neighbors
+            if x >= 2 && !self.produces(target, &rows, x - 1) {
+                continue;
+            }
+            self.search_column(target, known, rows, x + 1, found);
+        }
+    }
+
+    /// Whether the middle row produces the cell of the pattern in a column
+    ///
+    /// @param target The row of the pattern
+    /// @param rows The three rows of the predecessor
+    /// @param x The column in the predecessor
+    fn produces(&self, target: u32, rows: &[u32; 3], x: usize) -> bool {
+        let is_alive = |row: u32, x: usize| (row >> (x % self.width)) & 1 != 0;
+        let alive = is_alive(rows[1], x);
+        let neighbors_count = rows
+            .iter()
+            .flat_map(|row| (0..3).map(move |dx| is_alive(*row, x + self.width + dx - 1)))
+            .filter(|alive| *alive)
+            .count()
+            - alive as usize;
+        let next_alive = if alive {
+            self.rule.is_survival(neighbors_count)
+        } else {
+            self.rule.is_birth(neighbors_count)
+        };
+
+        let target_x = if self.wrap { x } else { x - 1 };
+        next_alive == ((target >> target_x) & 1 != 0)
+    }
+}
@@ -0,0 +1,360 @@
+//! Conversion of the worlds into images
+//!
+//! The renders all color the cells the same way: after the palette of the
+//! automaton with the colors of the options replaced, the gradient of the art
+//! mode, the trails of the dead cells and the fade between two generations.
+//! The rasterizer computes these colors once per frame into an RGBA image,
+//! the renders then only copy its pixels to where they show them.
+
+use crate::art::ArtColors;
+use crate::automaton::Color;
+use crate::geometry::{Point, Rect};
+use crate::render::{ColorOptions, RenderOptions};
+use crate::world::{CellState, World};
+
+/// How close to the alive color the trail of a cell which just died is
+const TRAIL_INTENSITY: f32 = 0.5;
+
+/// Age of the cells alive or not dead recently, for the trails
+const NO_TRAIL: usize = usize::MAX;
+
+/// An image, in 8-bit RGBA pixels, row major
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the pixels, four bytes each
+    pub fn get_pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Get a pixel as its RGBA components
+    ///
+    /// @param x Column of the pixel
+    /// @param y Row of the pixel
+    pub fn get_pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let index = (y * self.width + x) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[index..index + 4]);
+
+        pixel
+    }
+
+    /// Get the pixels without their alpha, three bytes each
+    pub fn to_rgb(&self) -> Vec<u8> {
+        self.pixels
+            .chunks_exact(4)
+            .flat_map(|pixel| pixel[..3].iter().copied())
+            .collect()
+    }
+}
+
+/// Converts the worlds into images, following their generations for the
+/// colors depending on the past ones
+pub struct Rasterizer {
+    /// Colors replacing the ones of the automaton
+    colors: ColorOptions,
+    /// Number of generations during which the dead cells fade out
+    trails: usize,
+    /// Colors of the alive cells after their age, in art mode
+    art: Option<ArtColors>,
+    /// Tiles of the previous generation
+    previous_tiles: Vec<CellState>,
+    /// Tiles of the current generation
+    current_tiles: Vec<CellState>,
+    /// Generations since each cell died, when trailing
+    death_ages: Vec<usize>,
+    /// Cell of the world shown at the top left corner, along the axes
+    /// wrapping around
+    view_offset: Point,
+}
+
+impl Rasterizer {
+    /// Create a rasterizer
+    ///
+    /// @param options Options of the render, for the colors, the trails and
+    /// the art mode
+    pub fn new(options: &RenderOptions) -> Self {
+        Self {
+            colors: options.colors.clone(),
+            trails: options.trails,
+            art: options.art.map(ArtColors::new),
+            previous_tiles: Vec::new(),
+            current_tiles: Vec::new(),
+            death_ages: Vec::new(),
+            view_offset: options.view_offset,
+        }
+    }
+
+    /// Replace the colors of the options
+    ///
+    /// @param colors The colors
+    pub fn set_colors(&mut self, colors: ColorOptions) {
+        self.colors = colors;
+    }
+
+    pub fn get_view_offset(&self) -> Point {
+        self.view_offset
+    }
+
+    /// Show another cell of the world at the top left corner
+    ///
+    /// The offset only applies along the axes wrapping around, so the
+    /// structures crossing their edges show in one piece.
+    ///
+    /// @param view_offset The cell shown at the top left corner
+    pub fn set_view_offset(&mut self, view_offset: Point) {
+        self.view_offset = view_offset;
+    }
+
+    /// Scroll the view along the axes of a world wrapping around
+    ///
+    /// @param world The world
+    /// @param dx Number of columns to scroll right, negative to scroll left
+    /// @param dy Number of rows to scroll down, negative to scroll up
+    pub fn scroll(&mut self, world: &World, dx: isize, dy: isize) {
+        let offset = self.get_wrapped_offset(world);
+        let scroll = |offset: usize, delta: isize, size: usize| {
+            (offset as isize + delta).rem_euclid(size.max(1) as isize) as usize
+        };
+
+        self.view_offset = Point::new(
+            scroll(offset.x, dx, world.get_width()),
+            scroll(offset.y, dy, world.get_height()),
+        );
+    }
+
+    /// Get the offset of the view in a world, 0 along the axes which do not
+    /// wrap around
+    ///
+    /// @param world The world
+    pub fn get_wrapped_offset(&self, world: &World) -> Point {
+        let wrap = |offset: usize, size: usize, wrapping: bool| {
+            if wrapping && size > 0 {
+                offset % size
+            } else {
+                0
+            }
+        };
+
+        Point::new(
+            wrap(
+                self.view_offset.x,
+                world.get_width(),
+                world.get_grid().is_wrapping_x(),
+            ),
+            wrap(
+                self.view_offset.y,
+                world.get_height(),
+                world.get_grid().is_wrapping_y(),
+            ),
+        )
+    }
+
+    /// Get the cell of a world shown at a position of the view
+    ///
+    /// @param world The world
+    /// @param point The position in the view, in cells
+    pub fn get_world_point(&self, world: &World, point: Point) -> Point {
+        let offset = self.get_wrapped_offset(world);
+
+        Point::new(
+            (point.x + offset.x) % world.get_width().max(1),
+            (point.y + offset.y) % world.get_height().max(1),
+        )
+    }
+
+    /// Get the position in the view of a cell of a world
+    ///
+    /// @param world The world
+    /// @param point The cell
+    pub fn get_view_point(&self, world: &World, point: Point) -> Point {
+        let offset = self.get_wrapped_offset(world);
+        let (width, height) = (world.get_width().max(1), world.get_height().max(1));
+
+        Point::new(
+            (point.x + width - offset.x) % width,
+            (point.y + height - offset.y) % height,
+        )
+    }
+
+    /// Follow the generations of a world, to call before rasterizing each
+    /// frame
+    ///
+    /// Returns whether the world changed since the last frame.
+    ///
+    /// @param world The world about to be rendered
+    pub fn update(&mut self, world: &World) -> bool {
+        if let Some(art) = self.art.as_mut() {
+            art.update(world);
+        }
+        if self.current_tiles == world.get_tiles() {
+            return false;
+        }
+
+        self.previous_tiles =
+            std::mem::replace(&mut self.current_tiles, world.get_tiles().to_vec());
+        if self.trails > 0 {
+            self.age_dead_cells();
+        }
+
+        true
+    }
+
+    /// Get the tiles of the generation before the last one rendered, empty
+    /// before the second one
+    pub fn get_previous_tiles(&self) -> &[CellState] {
+        &self.previous_tiles
+    }
+
+    /// Get the palette of a world, with the colors of the options
+    ///
+    /// @param world The world
+    pub fn get_palette(&self, world: &World) -> Vec<Color> {
+        self.colors.get_palette(world)
+    }
+
+    /// Get the color around a world
+    ///
+    /// @param world The world
+    pub fn get_background(&self, world: &World) -> Color {
+        self.colors.get_background(&self.get_palette(world))
+    }
+
+    /// Age the cells dead since the previous generation
+    fn age_dead_cells(&mut self) {
+        self.death_ages.resize(self.current_tiles.len(), NO_TRAIL);
+        for (index, age) in self.death_ages.iter_mut().enumerate() {
+            let was_alive = self
+                .previous_tiles
+                .get(index)
+                .is_some_and(|cell_state| *cell_state != CellState::DEAD);
+            *age = if self.current_tiles[index] != CellState::DEAD {
+                NO_TRAIL
+            } else if was_alive {
+                1
+            } else if *age < self.trails {
+                *age + 1
+            } else {
+                NO_TRAIL
+            };
+        }
+    }
+
+    /// Convert a part of a world into an image
+    ///
+    /// @param world The world, as given to the last update
+    /// @param viewport The part of the view, in cells, clipped to the size of
+    /// the world
+    /// @param scale Size of a cell, in pixels
+    /// @param fade How far the fade from the previous generation is, between
+    /// 0 and 1, 1 to show the current generation only
+    pub fn rasterize(&self, world: &World, viewport: Rect, scale: usize, fade: f32) -> Image {
+        let palette = self.get_palette(world);
+        let dead_color = palette[CellState::DEAD.0 as usize];
+        let alive_color = palette[CellState::ALIVE.0 as usize];
+        let trails = self.trails;
+        let cell_color = |cell_state: CellState, age: usize| {
+            if cell_state != CellState::DEAD {
+                palette[cell_state.0 as usize]
+            } else if age <= trails {
+                let intensity = 1.0 - age as f32 / (trails + 1) as f32;
+                blend(dead_color, alive_color, TRAIL_INTENSITY * intensity)
+            } else {
+                dead_color
+            }
+        };
+        // A world changing size has no previous generation to fade from
+        let fade = if self.previous_tiles.len() == world.get_tiles().len() {
+            fade
+        } else {
+            1.0
+        };
+
+        let area = viewport
+            .get_intersection(&world.get_bounds())
+            .unwrap_or_default();
+        let (width, height) = (area.width * scale, area.height * scale);
+        let mut pixels = vec![0; width * height * 4];
+
+        for view_point in area.get_points() {
+            let point = self.get_world_point(world, view_point);
+            let index = point.get_index(world.get_width());
+            let cell_state = world.get_tile(point.x, point.y);
+            let age = self.death_ages.get(index).copied().unwrap_or(NO_TRAIL);
+            let art_color = self.art.as_ref().and_then(|art| art.get_color(index));
+            let color = art_color.unwrap_or_else(|| cell_color(cell_state, age));
+            let color = if fade >= 1.0 {
+                color
+            } else if art_color.is_some() && self.previous_tiles[index] != CellState::DEAD {
+                // Alive at both generations, the color only cycles
+                color
+            } else {
+                let previous_age = if age == NO_TRAIL { age } else { age - 1 };
+                blend(
+                    cell_color(self.previous_tiles[index], previous_age),
+                    color,
+                    fade,
+                )
+            };
+
+            let pixel = to_rgba8(color);
+            let (pixel_x, pixel_y) = (
+                (view_point.x - area.x) * scale,
+                (view_point.y - area.y) * scale,
+            );
+            for row in pixel_y..pixel_y + scale {
+                let start = (row * width + pixel_x) * 4;
+                for chunk in pixels[start..start + scale * 4].chunks_exact_mut(4) {
+                    chunk.copy_from_slice(&pixel);
+                }
+            }
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// Convert a color to 8-bit RGBA components
+///
+/// @param color The color
+pub fn to_rgba8(color: Color) -> [u8; 4] {
+    let component = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    [
+        component(color[0]),
+        component(color[1]),
+        component(color[2]),
+        component(color[3]),
+    ]
+}
+
+/// Blend two colors
+///
+/// @param from The color at 0
+/// @param to The color at 1
+/// @param ratio How far from the first color
+pub fn blend(from: Color, to: Color, ratio: f32) -> Color {
+    let mut color = from;
+    for (component, to) in color.iter_mut().zip(to.iter()) {
+        *component += (to - *component) * ratio;
+    }
+
+    color
+}
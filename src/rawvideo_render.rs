@@ -0,0 +1,81 @@
+use crate::raster::Rasterizer;
+use crate::render::{ColorOptions, Render, RenderError, RenderOptions};
+use crate::world::World;
+use std::io::{BufWriter, ErrorKind, Stdout, Write};
+
+/// A render writing the frames to stdout, to pipe them into other tools
+///
+/// Each cell is a pixel colored after the palette of the automaton. Raw
+/// frames are the rows of RGB pixels (one byte per component), without any
+/// header, for `ffmpeg -f rawvideo -pixel_format rgb24 -video_size WxH -i -`.
+/// PPM frames are the same pixels prefixed by a binary PPM header
+/// (`P6\nW H\n255\n`), a stream `ffmpeg -f image2pipe -i -` and `mpv -`
+/// read as is.
+pub struct RawVideoRenderType {
+    /// Where to write the frames
+    writer: BufWriter<Stdout>,
+    /// Whether the frames are prefixed by a PPM header
+    ppm: bool,
+    /// Whether stdout has been closed by the reader
+    closed: bool,
+    /// Colors of the cells
+    rasterizer: Rasterizer,
+}
+
+impl RawVideoRenderType {
+    /// Create a render writing to stdout
+    ///
+    /// @param ppm Whether the frames are prefixed by a PPM header
+    /// @param options Options of the render, for the colors of the cells
+    pub fn new(ppm: bool, options: &RenderOptions) -> Self {
+        Self {
+            writer: BufWriter::new(std::io::stdout()),
+            ppm,
+            closed: false,
+            rasterizer: Rasterizer::new(options),
+        }
+    }
+
+    /// Write a frame
+    fn write_frame(&mut self, world: &World) -> std::io::Result<()> {
+        if self.ppm {
+            write!(
+                self.writer,
+                "P6\n{} {}\n255\n",
+                world.get_width(),
+                world.get_height()
+            )?;
+        }
+
+        self.rasterizer.update(world);
+        let image = self.rasterizer.rasterize(world, world.get_bounds(), 1, 1.0);
+        self.writer.write_all(&image.to_rgb())?;
+
+        self.writer.flush()
+    }
+}
+
+impl Render for RawVideoRenderType {
+    fn render(&mut self, world: &World) -> Result<(), RenderError> {
+        if self.closed {
+            return Ok(());
+        }
+
+        match self.write_frame(world) {
+            // The reader went away, such as a closed player
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => {
+                self.closed = true;
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn set_colors(&mut self, colors: ColorOptions) {
+        self.rasterizer.set_colors(colors);
+    }
+}
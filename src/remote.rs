@@ -0,0 +1,464 @@
+use crate::delta::{self, DeltaEncoder};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::pattern::Pattern;
+use crate::simulation::Command;
+use crate::world::{CellState, World};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::{BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Number of frames buffered for a viewer before frames are dropped
+const CLIENT_QUEUE_SIZE: usize = 4;
+
+/// Room left in the messages for the metadata of a stamped pattern
+const MAX_METADATA_SIZE: usize = 1 << 16;
+
+/// Kind of a message holding a whole frame
+const MESSAGE_FRAME: u8 = 0;
+/// Kind of a message setting the state of a cell
+const MESSAGE_SET_CELL: u8 = 1;
+/// Kind of a message stamping a pattern
+const MESSAGE_STAMP: u8 = 2;
+/// Kind of a message holding the changes since the previous frame
+const MESSAGE_DIFF: u8 = 3;
+
+/// Size of the header of a frame or diff message
+const FRAME_HEADER_SIZE: usize = 1 + 8 + 4 + 4;
+
+/// A connected viewer
+struct Client {
+    /// Queue of the messages to send
+    sender: SyncSender<Arc<Vec<u8>>>,
+    /// Whether the viewer missed a frame and needs the whole world
+    needs_frame: bool,
+    /// Number of messages in the queue
+    queued: Arc<AtomicUsize>,
+}
+
+/// A generation of the world received from a server
+pub struct Frame {
+    /// The generation of the world
+    pub generation: usize,
+    /// The world
+    pub world: World,
+}
+
+/// A server sending the world to remote viewers over TCP
+///
+/// Every message is prefixed by its length as a little endian u32, then
+/// starts with its kind as u8. All the integers are little endian.
+///
+/// The server sends frame messages (kind 0) made of the generation as u64,
+/// the width and the height as u32, followed by the world packed as a bitmap
+/// (row major, least significant bit first). The following generations are
+/// sent as diff messages (kind 3) with the same header, followed by the cells
+/// toggled since the previous message, encoded as described in `delta` and
+/// compressed with zlib. Viewers which missed a message are sent a frame
+/// again.
+///
+/// Viewers may edit the world by sending set cell messages (kind 1) made of
+/// the position as two u32 followed by the state as u8 (0 dead, 1 alive), and
+/// stamp messages (kind 2) made of the position as two u32 followed by the
+/// pattern in the RLE format. The edits of all the viewers are merged in the
+/// world, which is sent back to everyone.
+///
+/// Viewers too slow to keep up miss frames rather than slowing down the
+/// simulation. Viewers sending a message larger than the world are
+/// disconnected.
+pub struct RemoteServer {
+    /// The connected viewers
+    clients: Arc<Mutex<Vec<Client>>>,
+    /// The edits received from the viewers
+    commands: Receiver<Command>,
+    /// The deltas between the generations sent
+    delta_encoder: DeltaEncoder,
+}
+
+impl RemoteServer {
+    /// Start listening for viewers
+    ///
+    /// @param address The address to listen on
+    /// @param width The width of the world
+    /// @param height The height of the world
+    pub fn bind<A: ToSocketAddrs>(
+        address: A,
+        width: usize,
+        height: usize,
+    ) -> std::io::Result<Self> {
+        let max_message_size = get_max_message_size(width, height);
+        let listener = TcpListener::bind(address)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let (command_sender, commands) = channel();
+
+        let clients_ = clients.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let peer = stream.peer_addr().ok();
+                eprintln!("viewer {:?} connected", peer);
+
+                if let Ok(reader) = stream.try_clone() {
+                    let command_sender = command_sender.clone();
+                    std::thread::spawn(move || {
+                        read_commands(reader, command_sender, max_message_size)
+                    });
+                }
+
+                let (sender, receiver) = sync_channel::<Arc<Vec<u8>>>(CLIENT_QUEUE_SIZE);
+                let queued = Arc::new(AtomicUsize::new(0));
+                clients_.lock().unwrap().push(Client {
+                    sender,
+                    needs_frame: true,
+                    queued: queued.clone(),
+                });
+
+                std::thread::spawn(move || {
+                    for message in receiver {
+                        queued.fetch_sub(1, Ordering::Relaxed);
+                        if stream.write_all(&message).is_err() {
+                            eprintln!("viewer {:?} disconnected", peer);
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            clients,
+            commands,
+            delta_encoder: DeltaEncoder::new(),
+        })
+    }
+
+    /// Take the edits received from the viewers since the last call
+    pub fn poll(&self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+
+    /// Get the number of messages waiting to be sent to the most lagging
+    /// viewer
+    pub fn get_queue_depth(&self) -> usize {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|client| client.queued.load(Ordering::Relaxed))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Send the world to the viewers
+    ///
+    /// @param world The world to send
+    /// @param generation The generation of the world
+    pub fn broadcast(&mut self, world: &World, generation: usize) {
+        // New viewers are sent a frame first, so the deltas need not follow
+        // the world while nobody watches
+        let mut clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+        let delta = self.delta_encoder.encode(world);
+
+        let header = |kind: u8| {
+            let mut header = vec![kind];
+            header.extend_from_slice(&(generation as u64).to_le_bytes());
+            header.extend_from_slice(&(world.get_width() as u32).to_le_bytes());
+            header.extend_from_slice(&(world.get_height() as u32).to_le_bytes());
+            header
+        };
+        let mut encoder = ZlibEncoder::new(header(MESSAGE_DIFF), Compression::fast());
+        encoder.write_all(&delta.toggled).unwrap();
+        let diff_message = Arc::new(length_prefixed(encoder.finish().unwrap()));
+        let mut frame_message = None;
+
+        clients.retain_mut(|client| {
+            let message = if client.needs_frame {
+                frame_message
+                    .get_or_insert_with(|| {
+                        let mut message = header(MESSAGE_FRAME);
+                        message.extend_from_slice(&world.to_bitmap());
+                        Arc::new(length_prefixed(message))
+                    })
+                    .clone()
+            } else {
+                diff_message.clone()
+            };
+
+            // Counted before being sent, the viewer may take it right away
+            client.queued.fetch_add(1, Ordering::Relaxed);
+            match client.sender.try_send(message) {
+                Ok(()) => {
+                    client.needs_frame = false;
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    client.queued.fetch_sub(1, Ordering::Relaxed);
+                    // The viewer is lagging, it will need a frame to catch up
+                    client.needs_frame = true;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+/// A connection to a remote server
+pub struct RemoteViewer {
+    /// Frames received from the server
+    frames: Receiver<std::io::Result<Frame>>,
+    /// Stream used to send edits to the server
+    stream: TcpStream,
+}
+
+impl RemoteViewer {
+    /// Connect to a server
+    ///
+    /// @param url The address of the server, as tcp://host:port or host:port
+    pub fn connect(url: &str) -> std::io::Result<Self> {
+        let address = url.strip_prefix("tcp://").unwrap_or(url);
+        let stream = TcpStream::connect(address)?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (sender, frames) = channel();
+        std::thread::spawn(move || {
+            let mut grid = None;
+            loop {
+                let frame = read_frame(&mut reader, &mut grid);
+                let failed = frame.is_err();
+                if sender.send(frame).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self { frames, stream })
+    }
+
+    /// Wait for the next frames
+    ///
+    /// Returns the most recent frame received, or None if no frame came in
+    /// time.
+    ///
+    /// @param timeout How long to wait for a frame
+    pub fn receive_timeout(&mut self, timeout: Duration) -> std::io::Result<Option<Frame>> {
+        let mut frame = match self.frames.recv_timeout(timeout) {
+            Ok(frame) => frame?,
+            Err(RecvTimeoutError::Timeout) => return Ok(None),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(std::io::ErrorKind::ConnectionAborted.into())
+            }
+        };
+
+        // Only keep the most recent frame
+        for next_frame in self.frames.try_iter() {
+            frame = next_frame?;
+        }
+
+        Ok(Some(frame))
+    }
+
+    /// Send an edit to the server
+    ///
+    /// Only the commands editing cells are supported, the others are ignored.
+    ///
+    /// @param command The edit to send
+    pub fn send(&mut self, command: &Command) -> std::io::Result<()> {
+        let mut message = Vec::new();
+        match command {
+            Command::SetCell(position, cell_state) => {
+                message.push(MESSAGE_SET_CELL);
+                message.extend_from_slice(&(position.x as u32).to_le_bytes());
+                message.extend_from_slice(&(position.y as u32).to_le_bytes());
+                message.push(cell_state.is_alive() as u8);
+            }
+            Command::Stamp(pattern, position) => {
+                message.push(MESSAGE_STAMP);
+                message.extend_from_slice(&(position.x as u32).to_le_bytes());
+                message.extend_from_slice(&(position.y as u32).to_le_bytes());
+                message.extend_from_slice(pattern.to_rle().as_bytes());
+            }
+            _ => return Ok(()),
+        }
+
+        self.stream
+            .write_all(&(message.len() as u32).to_le_bytes())?;
+        self.stream.write_all(&message)
+    }
+}
+
+/// Get the size of the largest message about a world
+///
+/// It holds the whole world, as a frame or as a diff, or a pattern as large
+/// as the world, as RLE of at most a tag per cell and a line break per row.
+///
+/// @param width The width of the world
+/// @param height The height of the world
+fn get_max_message_size(width: usize, height: usize) -> usize {
+    width
+        .saturating_add(1)
+        .saturating_mul(height)
+        .saturating_mul(2)
+        .saturating_add(FRAME_HEADER_SIZE + MAX_METADATA_SIZE)
+}
+
+/// Read the length prefixing a message
+fn read_length<R: Read>(reader: &mut R) -> std::io::Result<usize> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+
+    Ok(u32::from_le_bytes(length) as usize)
+}
+
+/// Read a length prefixed message
+///
+/// @param reader Where to read the message
+/// @param max_size The size above which the message is rejected
+fn read_message<R: Read>(reader: &mut R, max_size: usize) -> std::io::Result<Vec<u8>> {
+    let length = read_length(reader)?;
+    if length > max_size {
+        return Err(invalid_data("message too large"));
+    }
+
+    let mut message = vec![0; length];
+    reader.read_exact(&mut message)?;
+
+    Ok(message)
+}
+
+/// Wait for the next frame or diff message
+///
+/// Diffs are applied to the previous frame, and skipped until a frame is
+/// received.
+///
+/// @param reader Where to read the messages
+/// @param grid The cells of the previous frame
+fn read_frame<R: Read>(reader: &mut R, grid: &mut Option<Grid>) -> std::io::Result<Frame> {
+    loop {
+        let length = read_length(reader)?;
+        if length == 0 {
+            continue;
+        }
+        let mut message = vec![0; FRAME_HEADER_SIZE];
+        reader.read_exact(&mut message[..1])?;
+
+        // Skip the messages this viewer does not know about
+        let kind = message[0];
+        if kind != MESSAGE_FRAME && kind != MESSAGE_DIFF {
+            std::io::copy(&mut reader.take(length as u64 - 1), &mut std::io::sink())?;
+            continue;
+        }
+        if length < FRAME_HEADER_SIZE {
+            return Err(invalid_data("truncated frame"));
+        }
+        reader.read_exact(&mut message[1..])?;
+
+        let generation = u64::from_le_bytes(read_array(&message[1..9])) as usize;
+        let width = u32::from_le_bytes(read_array(&message[9..13])) as usize;
+        let height = u32::from_le_bytes(read_array(&message[13..17])) as usize;
+        if length > get_max_message_size(width, height) {
+            return Err(invalid_data("message too large"));
+        }
+        message.resize(length, 0);
+        reader.read_exact(&mut message[FRAME_HEADER_SIZE..])?;
+
+        let world = if kind == MESSAGE_FRAME {
+            World::from_bitmap(width, height, &message[FRAME_HEADER_SIZE..])
+                .ok_or_else(|| invalid_data("truncated frame"))?
+        } else {
+            let mut world = World::new(width, height);
+            match grid.take() {
+                Some(grid) if grid.get_width() == width && grid.get_height() == height => {
+                    world.set_grid(grid)
+                }
+                _ => continue,
+            }
+
+            let mut toggled = Vec::new();
+            ZlibDecoder::new(&message[FRAME_HEADER_SIZE..]).read_to_end(&mut toggled)?;
+            delta::apply(&mut world, &toggled).ok_or_else(|| invalid_data("invalid diff"))?;
+            world
+        };
+        *grid = Some(world.get_grid().clone());
+
+        return Ok(Frame { generation, world });
+    }
+}
+
+/// Read the edits sent by a viewer until it disconnects
+///
+/// @param stream The connection to the viewer
+/// @param sender Where to send the edits
+/// @param max_message_size The size above which the viewer is disconnected
+fn read_commands(stream: TcpStream, sender: Sender<Command>, max_message_size: usize) {
+    let mut reader = BufReader::new(stream);
+    while let Ok(message) = read_message(&mut reader, max_message_size) {
+        if message.len() < 9 {
+            continue;
+        }
+
+        let position = Point::new(
+            u32::from_le_bytes(read_array(&message[1..5])) as usize,
+            u32::from_le_bytes(read_array(&message[5..9])) as usize,
+        );
+        let command = match message[0] {
+            MESSAGE_SET_CELL => {
+                let cell_state = if message.get(9) == Some(&1) {
+                    CellState::ALIVE
+                } else {
+                    CellState::DEAD
+                };
+                Command::SetCell(position, cell_state)
+            }
+            MESSAGE_STAMP => {
+                let pattern = std::str::from_utf8(&message[9..])
+                    .ok()
+                    .and_then(|rle| Pattern::from_rle(rle).ok());
+                match pattern {
+                    Some(pattern) => Command::Stamp(pattern, position),
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+
+        if sender.send(command).is_err() {
+            break;
+        }
+    }
+
+    // Drop the connection, so a viewer sending messages too large is not
+    // sent frames either
+    let _ = reader.get_ref().shutdown(Shutdown::Both);
+}
+
+/// Prefix a message with its length
+fn length_prefixed(message: Vec<u8>) -> Vec<u8> {
+    let mut prefixed = Vec::with_capacity(4 + message.len());
+    prefixed.extend_from_slice(&(message.len() as u32).to_le_bytes());
+    prefixed.extend(message);
+
+    prefixed
+}
+
+fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// Copy a slice into an array of the same size
+fn read_array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut array = [0; N];
+    array.copy_from_slice(bytes);
+
+    array
+}
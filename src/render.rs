@@ -1,10 +1,418 @@
-use crate::world::World;
+use crate::apng_render::ApngRenderType;
+use crate::art::ArtPalette;
+use crate::automaton::Color;
+use crate::geometry::Point;
+#[cfg(all(feature = "led", not(target_arch = "wasm32")))]
+use crate::led_render::LedRenderType;
+use crate::none_render::NoneRenderType;
+#[cfg(all(feature = "piston", not(target_arch = "wasm32")))]
+use crate::piston_render::PistonRenderType;
+use crate::population_graph::PopulationGraph;
+use crate::rawvideo_render::RawVideoRenderType;
+use crate::rule_colors::RuleColors;
+use crate::simulation::Command;
+use crate::speed::Speed;
+#[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+use crate::terminal_render::TerminalRenderType;
+use crate::world::{CellState, World};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
+/// Default path of the animations written by the APNG render
+pub const DEFAULT_APNG_PATH: &str = "gol.png";
+
+/// Options of the renders, ignored by the renders not supporting them
+#[derive(Clone, Default, Debug)]
+pub struct RenderOptions {
+    /// Cross-fade the cells between generations
+    pub smooth: bool,
+    /// Number of generations during which the dead cells fade out, 0 for no
+    /// trails
+    pub trails: usize,
+    /// Cover the whole screen, scaling the world to fit
+    pub fullscreen: bool,
+    /// Close the render on any key press, click or move of the mouse
+    pub exit_on_input: bool,
+    /// Show rulers along the edges of the world and a crosshair on the cell
+    /// under the cursor
+    pub rulers: bool,
+    /// Options of the LED matrices
+    pub led: LedOptions,
+    /// Path of the file written by the renders writing to a file
+    pub output_path: Option<PathBuf>,
+    /// Cell of the world shown at the top left corner, along the axes
+    /// wrapping around
+    pub view_offset: Point,
+    /// Colors replacing the ones of the automaton
+    pub colors: ColorOptions,
+    /// Gradient coloring the alive cells after their age, None to color them
+    /// after their state
+    pub art: Option<ArtPalette>,
+}
+
+/// Colors replacing the ones of the palette of the automaton
+#[derive(Clone, Default, Debug)]
+pub struct ColorOptions {
+    /// Colors of the states read from the files of a rule, replaced in turn
+    /// by the colors below
+    pub rule: Option<RuleColors>,
+    /// Color of the alive cells
+    pub alive: Option<Color>,
+    /// Color of the dead cells, the background color if not given
+    pub dead: Option<Color>,
+    /// Color around the world, the color of the dead cells if not given
+    pub background: Option<Color>,
+}
+
+impl ColorOptions {
+    /// Get the palette of the automaton of a world with the colors replaced
+    ///
+    /// @param world The world
+    pub fn get_palette(&self, world: &World) -> Vec<Color> {
+        let mut palette = world.get_automaton().palette();
+        if let Some(rule) = &self.rule {
+            rule.apply(&mut palette);
+        }
+        if let Some(dead) = self.dead.or(self.background) {
+            palette[CellState::DEAD.0 as usize] = dead;
+        }
+        if let Some(alive) = self.alive {
+            palette[CellState::ALIVE.0 as usize] = alive;
+        }
+
+        palette
+    }
+
+    /// Get the color around the world
+    ///
+    /// @param palette The palette of the world, with the colors replaced
+    pub fn get_background(&self, palette: &[Color]) -> Color {
+        self.background
+            .unwrap_or(palette[CellState::DEAD.0 as usize])
+    }
+}
+
+/// An error occurring while parsing a color
+#[derive(Debug)]
+pub struct ParseColorError(String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid color {} (expected #rrggbb)", self.0)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Parse a color written as #rrggbb or #rrggbbaa in hexadecimal
+///
+/// @param s The color, the # being optional
+pub fn parse_color(s: &str) -> Result<Color, ParseColorError> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return Err(ParseColorError(s.to_string()));
+    }
+
+    let mut color = [1.0; 4];
+    for (component, index) in color.iter_mut().zip((0..hex.len()).step_by(2)) {
+        *component = u8::from_str_radix(&hex[index..index + 2], 16)
+            .map_err(|_| ParseColorError(s.to_string()))? as f32
+            / 255.0;
+    }
+
+    Ok(color)
+}
+
+/// Options of the LED matrices
+#[derive(Clone, Copy, Debug)]
+pub struct LedOptions {
+    /// Brightness of the LEDs, between 0 and 1
+    pub brightness: f32,
+    /// Gamma correcting the colors for the eye, 1 for none
+    pub gamma: f32,
+    /// Whether the odd rows are chained from right to left
+    pub serpentine: bool,
+}
+
+impl Default for LedOptions {
+    fn default() -> Self {
+        Self {
+            brightness: 0.25,
+            gamma: 2.2,
+            serpentine: false,
+        }
+    }
+}
+
+/// An error occurring while opening a render or rendering a frame
+#[derive(Debug)]
+pub enum RenderError {
+    /// Writing the frames failed
+    Io(std::io::Error),
+    /// The window or the device cannot be opened or was lost
+    Device(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::Io(err) => write!(f, "{}", err),
+            RenderError::Device(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<std::io::Error> for RenderError {
+    fn from(err: std::io::Error) -> Self {
+        RenderError::Io(err)
+    }
+}
+
+/// An error occurring while parsing the name of a render
+#[derive(Debug)]
+pub struct ParseRenderTypeError(String);
+
+impl fmt::Display for ParseRenderTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown render {} (available {})",
+            self.0,
+            RenderType::all()
+                .iter()
+                .map(|render_type| render_type.get_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseRenderTypeError {}
+
+/// A kind of render
+///
+/// The renders pulling in heavy dependencies or talking to the hardware are
+/// only available with their Cargo feature: `piston`, `terminal` and `led`,
+/// all enabled by default. Build with `--no-default-features` for a
+/// window-less simulator.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum RenderType {
     None,
+    #[cfg(feature = "piston")]
     Piston,
+    /// Raw RGB frames written to stdout
+    RawVideo,
+    /// PPM frames written to stdout
+    Ppm,
+    /// WS2812 LED matrix wired to the SPI bus
+    #[cfg(feature = "led")]
+    Led,
+    /// Half blocks with 24-bit colors in the terminal
+    #[cfg(feature = "terminal")]
+    Terminal,
+    /// Animated PNG written to a file at the end of the run
+    Apng,
+}
+
+impl RenderType {
+    /// Get the renders built in
+    pub fn all() -> Vec<RenderType> {
+        vec![
+            #[cfg(feature = "piston")]
+            RenderType::Piston,
+            RenderType::None,
+            RenderType::RawVideo,
+            RenderType::Ppm,
+            #[cfg(feature = "led")]
+            RenderType::Led,
+            #[cfg(feature = "terminal")]
+            RenderType::Terminal,
+            RenderType::Apng,
+        ]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            RenderType::None => "none",
+            #[cfg(feature = "piston")]
+            RenderType::Piston => "piston",
+            RenderType::RawVideo => "rawvideo",
+            RenderType::Ppm => "ppm",
+            #[cfg(feature = "led")]
+            RenderType::Led => "led",
+            #[cfg(feature = "terminal")]
+            RenderType::Terminal => "terminal",
+            RenderType::Apng => "apng",
+        }
+    }
+
+    /// Create a render of this type
+    ///
+    /// @param width Width of the world to render
+    /// @param height Height of the world to render
+    /// @param options Options of the render
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn create(
+        &self,
+        width: usize,
+        height: usize,
+        options: RenderOptions,
+    ) -> Result<Box<dyn Render>, RenderError> {
+        let render: Box<dyn Render> = match self {
+            RenderType::None => Box::new(NoneRenderType::new()),
+            #[cfg(all(feature = "piston", not(target_arch = "wasm32")))]
+            RenderType::Piston => Box::new(PistonRenderType::new(width, height, options)?),
+            #[cfg(all(feature = "piston", target_arch = "wasm32"))]
+            RenderType::Piston => Box::new(NoneRenderType::new()),
+            RenderType::RawVideo => Box::new(RawVideoRenderType::new(false, &options)),
+            RenderType::Ppm => Box::new(RawVideoRenderType::new(true, &options)),
+            #[cfg(all(feature = "led", not(target_arch = "wasm32")))]
+            RenderType::Led => Box::new(LedRenderType::new(&options)?),
+            #[cfg(all(feature = "led", target_arch = "wasm32"))]
+            RenderType::Led => Box::new(NoneRenderType::new()),
+            #[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+            RenderType::Terminal => Box::new(TerminalRenderType::new(&options)?),
+            #[cfg(all(feature = "terminal", target_arch = "wasm32"))]
+            RenderType::Terminal => Box::new(NoneRenderType::new()),
+            RenderType::Apng => Box::new(ApngRenderType::new(
+                options
+                    .output_path
+                    .as_deref()
+                    .unwrap_or(Path::new(DEFAULT_APNG_PATH)),
+                width,
+                height,
+                &options,
+            )),
+        };
+
+        Ok(render)
+    }
+
+    /// Whether the render writes to stdout, which must then be left alone
+    pub fn writes_to_stdout(&self) -> bool {
+        matches!(self, RenderType::RawVideo | RenderType::Ppm) || self.uses_terminal()
+    }
+
+    /// Whether the render shows the population graph
+    pub fn shows_population_graph(&self) -> bool {
+        match self {
+            #[cfg(feature = "piston")]
+            RenderType::Piston => true,
+            #[cfg(feature = "terminal")]
+            RenderType::Terminal => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the user can step back from the render, which then needs the
+    /// previous generations
+    pub fn steps_back(&self) -> bool {
+        match self {
+            #[cfg(feature = "piston")]
+            RenderType::Piston => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the render draws in the terminal, which must then be left
+    /// alone too
+    pub fn uses_terminal(&self) -> bool {
+        match self {
+            #[cfg(feature = "terminal")]
+            RenderType::Terminal => true,
+            _ => false,
+        }
+    }
+}
+
+/// The window when it is built in, no render otherwise
+impl Default for RenderType {
+    fn default() -> Self {
+        #[cfg(feature = "piston")]
+        return RenderType::Piston;
+        #[cfg(not(feature = "piston"))]
+        return RenderType::None;
+    }
+}
+
+impl FromStr for RenderType {
+    type Err = ParseRenderTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RenderType::all()
+            .into_iter()
+            .find(|render_type| render_type.get_name() == s)
+            .ok_or_else(|| ParseRenderTypeError(s.to_string()))
+    }
+}
+
+impl fmt::Display for RenderType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
 }
 
 pub trait Render {
-    fn render(&mut self, world: &World);
+    /// Show a frame
+    ///
+    /// Fails when the render cannot go on, such as a lost device or an
+    /// output which cannot be written anymore.
+    ///
+    /// @param world The world to show
+    fn render(&mut self, world: &World) -> Result<(), RenderError>;
+
+    /// Whether the render has been closed by the user
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Take the commands issued by the user since the last call
+    fn take_commands(&mut self) -> Vec<Command> {
+        Vec::new()
+    }
+
+    /// Show a line of information about the simulation, such as its speed
+    ///
+    /// @param hud The information
+    fn set_hud(&mut self, _hud: &str) {}
+
+    /// Show the progress of the simulation in the title of the window, for
+    /// the renders having one
+    ///
+    /// @param title The title
+    fn set_title(&mut self, _title: &str) {}
+
+    /// Change the colors replacing the ones of the automaton, for the
+    /// renders showing colors
+    ///
+    /// @param colors The colors
+    fn set_colors(&mut self, _colors: ColorOptions) {}
+
+    /// Show the population of the last generations as a graph, for the
+    /// renders having room for it
+    ///
+    /// @param graph The populations
+    fn set_population_graph(&mut self, _graph: &PopulationGraph) {}
+
+    /// Show the speed of the simulation over the last seconds, for the
+    /// renders not showing the HUD
+    ///
+    /// @param speed The speed, None until measured
+    fn set_speed(&mut self, _speed: Option<Speed>) {}
+}
+
+/// Convert a color to 8-bit RGB components
+///
+/// @param color The color
+pub fn to_rgb8(color: Color) -> [u8; 3] {
+    let component = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    [
+        component(color[0]),
+        component(color[1]),
+        component(color[2]),
+    ]
 }
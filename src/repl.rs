@@ -0,0 +1,183 @@
+//! Commands typed on stdin while the simulation runs
+//!
+//! Each line is a command, answered on stdout, so the simulation can be
+//! driven from scripts and terminal multiplexers without a window:
+//!
+//! ```text
+//! pause
+//! step 50
+//! set 10 10 alive
+//! load gun.rle 5 5
+//! save out.rle
+//! stats
+//! ```
+
+use crate::geometry::Point;
+use crate::pattern::{Pattern, PatternSource};
+use crate::simulation::{Command, Simulation, MAX_COMMAND_STEPS};
+use crate::snapshot;
+use crate::world::{CellState, World};
+use std::io::BufRead;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
+
+/// Description of the commands, printed by `help`
+const HELP: &str = "\
+pause                 stop updating the world
+resume                update the world again
+step [n]              pause and run n generations (default 1, at most 10000)
+set x y state         set a cell to alive, dead or a state number
+load pattern x y      stamp a pattern file or builtin at a position
+save path             save the world as .rle, .cells, .json or a snapshot
+stats                 print the generation, the population and the rule
+rule rule             follow another rule, as B36/S23
+speed fps|none        limit the steps per second
+reseed [density]      populate the world randomly again (default 0.5)
+quit                  stop the simulation";
+
+/// A console reading commands from stdin
+pub struct Repl {
+    /// The lines read from stdin
+    lines: Receiver<String>,
+}
+
+impl Repl {
+    /// Start reading stdin
+    pub fn start() -> Self {
+        let (sender, lines) = channel();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { lines }
+    }
+
+    /// Handle the lines typed since the last call, up to the first command
+    ///
+    /// Queries are answered right away, while the command is returned to be
+    /// applied by the simulation loop. The lines after it wait for the next
+    /// call, so they see its effect.
+    ///
+    /// @param simulation The running simulation
+    pub fn poll(&self, simulation: &Simulation) -> Vec<Command> {
+        for line in self.lines.try_iter() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match handle_command(&words, simulation) {
+                Ok(Some(command)) => return vec![command],
+                Ok(None) => {}
+                Err(err) => println!("error: {}", err),
+            }
+        }
+
+        Vec::new()
+    }
+}
+
+/// Answer a query or parse a command
+fn handle_command(words: &[&str], simulation: &Simulation) -> Result<Option<Command>, String> {
+    let parse_usize = |word: &str| {
+        word.parse::<usize>()
+            .map_err(|_| format!("invalid number {}", word))
+    };
+    let world = simulation.get_world();
+
+    let command = match words {
+        [] => return Ok(None),
+        ["help"] => {
+            println!("{}", HELP);
+            return Ok(None);
+        }
+        ["stats"] => {
+            println!(
+                "generation {}, population {}, rule {}, {}",
+                simulation.get_generation(),
+                world.get_population(),
+                world.get_automaton().name(),
+                if simulation.is_paused() {
+                    "paused"
+                } else {
+                    "running"
+                }
+            );
+            return Ok(None);
+        }
+        ["save", path] => {
+            save(Path::new(path), world, simulation.get_generation())?;
+            println!("saved {}", path);
+            return Ok(None);
+        }
+        ["pause"] => Command::Pause,
+        ["resume"] => Command::Resume,
+        ["quit"] => Command::Stop,
+        ["step"] => Command::Step(1),
+        ["step", steps] => {
+            let steps = parse_usize(steps)?;
+            if steps > MAX_COMMAND_STEPS {
+                return Err(format!("at most {} steps at once", MAX_COMMAND_STEPS));
+            }
+            Command::Step(steps)
+        }
+        ["set", x, y, state] => {
+            let cell_state = match *state {
+                "alive" => CellState::ALIVE,
+                "dead" => CellState::DEAD,
+                _ => state
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|state| (*state as usize) < world.get_automaton().states())
+                    .map(CellState)
+                    .ok_or_else(|| format!("invalid state {}", state))?,
+            };
+            Command::SetCell(Point::new(parse_usize(x)?, parse_usize(y)?), cell_state)
+        }
+        ["load", pattern, x, y] => {
+            let pattern = pattern
+                .parse::<PatternSource>()
+                .and_then(|source| source.load())
+                .map_err(|err| err.to_string())?;
+            Command::Stamp(pattern, Point::new(parse_usize(x)?, parse_usize(y)?))
+        }
+        ["rule", rule] => {
+            if !simulation.is_life_like() {
+                return Err("the automaton does not follow a Life-like rule".to_string());
+            }
+            Command::SetRule(rule.parse().map_err(|_| format!("invalid rule {}", rule))?)
+        }
+        ["speed", "none"] => Command::SetSpeed(None),
+        ["speed", speed] => Command::SetSpeed(Some(
+            speed
+                .parse()
+                .map_err(|_| format!("invalid speed {}", speed))?,
+        )),
+        ["reseed"] => Command::Reseed(0.5),
+        ["reseed", density] => Command::Reseed(
+            density
+                .parse()
+                .map_err(|_| format!("invalid density {}", density))?,
+        ),
+        _ => return Err(format!("unknown command {} (try help)", words.join(" "))),
+    };
+
+    Ok(Some(command))
+}
+
+/// Save the world in the format given by the extension of the path
+fn save(path: &Path, world: &World, generation: usize) -> Result<(), String> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    let result = match extension {
+        Some("rle") => std::fs::write(path, Pattern::from_world(world).to_rle()),
+        Some("cells") => std::fs::write(path, Pattern::from_world(world).to_plaintext()),
+        Some("json") => std::fs::write(path, world.to_json()),
+        _ => return snapshot::save(path, world, generation).map_err(|err| err.to_string()),
+    };
+
+    result.map_err(|err| format!("cannot write {}: {}", path.display(), err))
+}
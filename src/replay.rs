@@ -0,0 +1,290 @@
+//! Recording and replay of the commands of a session
+//!
+//! A session file is a text file starting with a `golrec 1` line, a
+//! `seed <seed>` line and a `rng <generator>` line, followed by one command
+//! per line prefixed by the generation at which it was applied:
+//!
+//! ```text
+//! golrec 1
+//! seed 42
+//! rng xoshiro256++
+//! 12 pause
+//! 12 set 10 20 1
+//! 12 stamp 5 5 .O./..O/OOO
+//! 12 resume
+//! 40 speed 30
+//! 80 speed none
+//! 95 reseed 0.3
+//! 120 back
+//! 150 rule B36/S23
+//! 180 next-rule
+//! 200 step 10
+//! 210 stop
+//! ```
+//!
+//! Replaying a session with the same settings, seed and generator reproduces
+//! the run. The files without a `rng` line were recorded with ChaCha20.
+
+use crate::geometry::Point;
+use crate::pattern::{Pattern, PatternError};
+use crate::rng::RngKind;
+use crate::simulation::Command;
+use crate::world::CellState;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// The header of a session file
+const HEADER: &str = "golrec 1";
+
+/// An error occurring while loading a session
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The session file could not be read
+    Io(std::io::Error),
+    /// The session file is malformed, at the given line
+    Parse(usize, String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "cannot read session: {}", err),
+            ReplayError::Parse(line, msg) => write!(f, "invalid session at line {}: {}", line, msg),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(err: std::io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+/// Records the commands of a session to a file
+pub struct Recorder {
+    file: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Create a session file
+    ///
+    /// @param path Path of the session file
+    /// @param seed Seed of the random number generator of the session
+    /// @param rng_kind The random number generator of the session
+    pub fn create(path: &Path, seed: u64, rng_kind: RngKind) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", HEADER)?;
+        writeln!(file, "seed {}", seed)?;
+        writeln!(file, "rng {}", rng_kind)?;
+        file.flush()?;
+
+        Ok(Self { file })
+    }
+
+    /// Record a command
+    ///
+    /// @param generation The generation at which the command is applied
+    /// @param command The command
+    pub fn record(&mut self, generation: usize, command: &Command) -> std::io::Result<()> {
+        writeln!(self.file, "{} {}", generation, format_command(command))?;
+
+        // Keep the file usable if the program is killed
+        self.file.flush()
+    }
+}
+
+/// Write a command as a line of a session file, without its generation
+///
+/// @param command The command
+pub fn format_command(command: &Command) -> String {
+    match command {
+        Command::Pause => "pause".to_string(),
+        Command::Resume => "resume".to_string(),
+        Command::SetSpeed(Some(speed)) => format!("speed {}", speed),
+        Command::SetSpeed(None) => "speed none".to_string(),
+        Command::Reseed(density) => format!("reseed {}", density),
+        Command::SetCell(position, cell_state) => {
+            format!(
+                "set {} {} {}",
+                position.x,
+                position.y,
+                cell_state.is_alive() as u8
+            )
+        }
+        Command::StepBack => "back".to_string(),
+        Command::SetRule(rule) => format!("rule {}", rule),
+        Command::NextRule => "next-rule".to_string(),
+        Command::Step(steps) => format!("step {}", steps),
+        Command::Stop => "stop".to_string(),
+        Command::Stamp(pattern, position) => format!(
+            "stamp {} {} {}",
+            position.x,
+            position.y,
+            // The metadata would not fit on the line
+            pattern.to_plaintext_rows().trim_end().replace('\n', "/")
+        ),
+    }
+}
+
+/// The commands of a recorded session
+pub struct Replay {
+    /// Seed of the random number generator of the session
+    seed: u64,
+    /// The random number generator of the session
+    rng_kind: RngKind,
+    /// Commands not replayed yet, with the generation at which to apply them
+    commands: VecDeque<(usize, Command)>,
+}
+
+impl Replay {
+    /// Create a replay of some commands
+    ///
+    /// @param seed Seed of the random number generator of the session
+    /// @param rng_kind The random number generator of the session
+    /// @param commands The commands, with the generation at which to apply
+    /// them, in order
+    pub fn new(seed: u64, rng_kind: RngKind, commands: Vec<(usize, Command)>) -> Self {
+        Self {
+            seed,
+            rng_kind,
+            commands: commands.into(),
+        }
+    }
+
+    /// Load a session file
+    ///
+    /// @param path Path of the session file
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut seed = None;
+        // The sessions recorded before the generator was chosen used ChaCha20
+        let mut rng_kind = RngKind::ChaCha;
+        let mut commands = VecDeque::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_number = index + 1;
+            let error = |msg: &str| ReplayError::Parse(line_number, msg.to_string());
+
+            if index == 0 {
+                if line.trim() != HEADER {
+                    return Err(error("not a session file"));
+                }
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => {}
+                ["seed", value] => {
+                    seed = Some(value.parse().map_err(|_| error("invalid seed"))?);
+                }
+                ["rng", value] => {
+                    rng_kind = value
+                        .parse()
+                        .map_err(|_| error("invalid random number generator"))?;
+                }
+                [generation, words @ ..] => {
+                    let generation = generation
+                        .parse::<usize>()
+                        .map_err(|_| error("invalid generation"))?;
+                    let command = parse_command(words).map_err(|msg| error(&msg))?;
+                    commands.push_back((generation, command));
+                }
+            }
+        }
+
+        Ok(Self {
+            seed: seed.ok_or_else(|| ReplayError::Parse(0, "missing seed".to_string()))?,
+            rng_kind,
+            commands,
+        })
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_rng_kind(&self) -> RngKind {
+        self.rng_kind
+    }
+
+    /// Get the commands not replayed yet, with the generation at which to
+    /// apply them
+    pub fn get_pending_commands(&self) -> impl Iterator<Item = &(usize, Command)> {
+        self.commands.iter()
+    }
+
+    /// Take the commands to apply up to the given generation
+    ///
+    /// @param generation The current generation
+    pub fn take_commands(&mut self, generation: usize) -> Vec<Command> {
+        let mut commands = Vec::new();
+        while self
+            .commands
+            .front()
+            .is_some_and(|(command_generation, _)| *command_generation <= generation)
+        {
+            commands.push(self.commands.pop_front().unwrap().1);
+        }
+
+        commands
+    }
+}
+
+/// Parse the words of a recorded command, without its generation
+///
+/// @param words The words of the command
+pub fn parse_command(words: &[&str]) -> Result<Command, String> {
+    let parse_usize = |word: &str| {
+        word.parse::<usize>()
+            .map_err(|_| format!("invalid number {}", word))
+    };
+
+    match words {
+        ["pause"] => Ok(Command::Pause),
+        ["resume"] => Ok(Command::Resume),
+        ["back"] => Ok(Command::StepBack),
+        ["next-rule"] => Ok(Command::NextRule),
+        ["stop"] => Ok(Command::Stop),
+        ["step", steps] => Ok(Command::Step(parse_usize(steps)?)),
+        ["rule", rule] => rule
+            .parse()
+            .map(Command::SetRule)
+            .map_err(|_| format!("invalid rule {}", rule)),
+        ["speed", "none"] => Ok(Command::SetSpeed(None)),
+        ["speed", speed] => speed
+            .parse()
+            .map(|speed| Command::SetSpeed(Some(speed)))
+            .map_err(|_| format!("invalid speed {}", speed)),
+        ["reseed", density] => density
+            .parse()
+            .map(Command::Reseed)
+            .map_err(|_| format!("invalid density {}", density)),
+        ["set", x, y, state] => {
+            let cell_state = match *state {
+                "0" => CellState::DEAD,
+                "1" => CellState::ALIVE,
+                _ => return Err(format!("invalid state {}", state)),
+            };
+            Ok(Command::SetCell(
+                Point::new(parse_usize(x)?, parse_usize(y)?),
+                cell_state,
+            ))
+        }
+        ["stamp", x, y, rows] => {
+            let pattern = Pattern::from_plaintext(&rows.replace('/', "\n"))
+                .map_err(|err: PatternError| err.to_string())?;
+            Ok(Command::Stamp(
+                pattern,
+                Point::new(parse_usize(x)?, parse_usize(y)?),
+            ))
+        }
+        _ => Err(format!("unknown command {}", words.join(" "))),
+    }
+}
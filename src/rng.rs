@@ -0,0 +1,300 @@
+//! Random number generator whose state can be saved
+//!
+//! Three generators are available:
+//!
+//! - `chacha`, the default, is the one behind `StdRng`, ChaCha20, so a run
+//!   seeded with it draws the same numbers
+//! - `xoshiro256++` is much faster but not cryptographic, which does not
+//!   matter to fill soups
+//! - `thread` is the generator of the thread, seeded by the system, so the
+//!   runs cannot be reproduced
+//!
+//! The whole state of the seeded generators is their seed and the number of
+//! words drawn so far, which the snapshots save to resume a session exactly
+//! where it stopped.
+//!
+//! A seed may be given as a text, hashed into the integer seeding the
+//! generator, so `--seed glider-party` reproduces a run as well as a number.
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::fmt;
+use std::str::FromStr;
+
+/// An error occurring while parsing the name of a generator
+#[derive(Debug)]
+pub struct ParseRngKindError(String);
+
+impl fmt::Display for ParseRngKindError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown random number generator {} (available {})",
+            self.0,
+            RngKind::all()
+                .iter()
+                .map(|kind| kind.get_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseRngKindError {}
+
+/// A random number generator
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RngKind {
+    /// ChaCha20, as `StdRng`
+    #[default]
+    ChaCha,
+    /// xoshiro256++, seeded by SplitMix64
+    Xoshiro256PlusPlus,
+    /// The generator of the thread, seeded by the system
+    Thread,
+}
+
+impl RngKind {
+    /// Get all the available generators
+    pub fn all() -> Vec<RngKind> {
+        vec![
+            RngKind::Xoshiro256PlusPlus,
+            RngKind::ChaCha,
+            RngKind::Thread,
+        ]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            RngKind::ChaCha => "chacha",
+            RngKind::Xoshiro256PlusPlus => "xoshiro256++",
+            RngKind::Thread => "thread",
+        }
+    }
+
+    /// Whether the generator draws the same numbers from the same seed
+    pub fn is_reproducible(&self) -> bool {
+        *self != RngKind::Thread
+    }
+}
+
+impl fmt::Display for RngKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+impl FromStr for RngKind {
+    type Err = ParseRngKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RngKind::all()
+            .into_iter()
+            .find(|kind| kind.get_name() == s)
+            .ok_or_else(|| ParseRngKindError(s.to_string()))
+    }
+}
+
+/// Get the seed given as a number or as a text
+///
+/// A text is hashed with FNV-1a, which is stable across platforms and
+/// versions, so it gives the same seed everywhere.
+///
+/// @param seed The seed, as `42` or `glider-party`
+pub fn parse_seed(seed: &str) -> u64 {
+    seed.parse().unwrap_or_else(|_| {
+        seed.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    })
+}
+
+/// The xoshiro256++ generator, counting the words it draws
+#[derive(Clone, Debug)]
+struct Xoshiro256PlusPlus {
+    /// The state of the generator
+    state: [u64; 4],
+    /// The number of 64-bit words drawn so far
+    position: u128,
+}
+
+impl Xoshiro256PlusPlus {
+    /// Create a generator, its state drawn from the seed by SplitMix64
+    ///
+    /// @param seed The seed
+    fn new(seed: u64) -> Self {
+        let mut seed = seed;
+        let mut state = [0; 4];
+        for word in state.iter_mut() {
+            seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            *word = z ^ (z >> 31);
+        }
+
+        Self { state, position: 0 }
+    }
+}
+
+impl RngCore for Xoshiro256PlusPlus {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        self.position += 1;
+
+        result
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+
+        Ok(())
+    }
+}
+
+/// The generators behind a session generator
+#[derive(Clone, Debug)]
+enum Generator {
+    /// Boxed, its state being much larger than the others
+    ChaCha(Box<ChaCha20Rng>),
+    Xoshiro256PlusPlus(Xoshiro256PlusPlus),
+    /// The generator of the thread, looked up at each draw so the session
+    /// generator can be sent to another thread
+    Thread,
+}
+
+/// A seeded random number generator, knowing its position in its stream
+#[derive(Clone, Debug)]
+pub struct SessionRng {
+    /// The seed of the generator
+    seed: u64,
+    /// The generator
+    generator: Generator,
+}
+
+impl SessionRng {
+    /// Create a ChaCha20 generator
+    ///
+    /// @param seed The seed
+    pub fn new(seed: u64) -> Self {
+        Self::with_kind(RngKind::ChaCha, seed)
+    }
+
+    /// Create a generator
+    ///
+    /// @param kind The generator
+    /// @param seed The seed, ignored by the generator of the thread
+    pub fn with_kind(kind: RngKind, seed: u64) -> Self {
+        let generator = match kind {
+            RngKind::ChaCha => Generator::ChaCha(Box::new(ChaCha20Rng::seed_from_u64(seed))),
+            RngKind::Xoshiro256PlusPlus => {
+                Generator::Xoshiro256PlusPlus(Xoshiro256PlusPlus::new(seed))
+            }
+            RngKind::Thread => Generator::Thread,
+        };
+
+        Self { seed, generator }
+    }
+
+    /// Create a ChaCha20 generator with a random seed
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    /// Create a generator at a given position of its stream
+    ///
+    /// @param kind The generator
+    /// @param seed The seed
+    /// @param position The number of words drawn so far, see `get_position`
+    pub fn resume(kind: RngKind, seed: u64, position: u128) -> Self {
+        let mut rng = Self::with_kind(kind, seed);
+        match &mut rng.generator {
+            Generator::ChaCha(generator) => generator.set_word_pos(position),
+            // There is no jump to an arbitrary position, the words are drawn
+            // again, a few nanoseconds each
+            Generator::Xoshiro256PlusPlus(generator) => {
+                while generator.position < position {
+                    generator.next_u64();
+                }
+            }
+            Generator::Thread => {}
+        }
+
+        rng
+    }
+
+    pub fn get_kind(&self) -> RngKind {
+        match self.generator {
+            Generator::ChaCha(_) => RngKind::ChaCha,
+            Generator::Xoshiro256PlusPlus(_) => RngKind::Xoshiro256PlusPlus,
+            Generator::Thread => RngKind::Thread,
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Get the number of words drawn so far, 32-bit ones for ChaCha20 and
+    /// 64-bit ones for xoshiro256++, always 0 for the generator of the thread
+    pub fn get_position(&self) -> u128 {
+        match &self.generator {
+            Generator::ChaCha(generator) => generator.get_word_pos(),
+            Generator::Xoshiro256PlusPlus(generator) => generator.position,
+            Generator::Thread => 0,
+        }
+    }
+}
+
+impl RngCore for SessionRng {
+    fn next_u32(&mut self) -> u32 {
+        match &mut self.generator {
+            Generator::ChaCha(generator) => generator.next_u32(),
+            Generator::Xoshiro256PlusPlus(generator) => generator.next_u32(),
+            Generator::Thread => rand::thread_rng().next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match &mut self.generator {
+            Generator::ChaCha(generator) => generator.next_u64(),
+            Generator::Xoshiro256PlusPlus(generator) => generator.next_u64(),
+            Generator::Thread => rand::thread_rng().next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match &mut self.generator {
+            Generator::ChaCha(generator) => generator.fill_bytes(dest),
+            Generator::Xoshiro256PlusPlus(generator) => generator.fill_bytes(dest),
+            Generator::Thread => rand::thread_rng().fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match &mut self.generator {
+            Generator::ChaCha(generator) => generator.try_fill_bytes(dest),
+            Generator::Xoshiro256PlusPlus(generator) => generator.try_fill_bytes(dest),
+            Generator::Thread => rand::thread_rng().try_fill_bytes(dest),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Colors of the states read from the rule files of Golly
+//!
+//! Three kinds of files are read:
+//!
+//! - `.colors` files, with `color = STATE R G B` lines giving the color of a
+//!   state and `gradient = R G B R G B` lines spreading a gradient over the
+//!   states from 1 to the last one
+//! - `.icons` files, XPM images of the icons of the states stacked from the
+//!   state 1 down, each state taking the average color of the pixels of its
+//!   icon which are not black, as Golly does when a rule has no colors
+//! - `.rule` files, whose `@COLORS` section has the lines of a `.colors` file
+//!   without their `color =` and `gradient =`, and whose `@ICONS` section is
+//!   an `.icons` file
+//!
+//! The components are between 0 and 255. The colors of the states given
+//! override the ones of the icons, which override the gradient.
+
+use crate::automaton::Color;
+use crate::world::CellState;
+use std::fmt;
+use std::path::Path;
+
+/// An error occurring while loading the colors of a rule
+#[derive(Debug)]
+pub enum RuleColorsError {
+    /// The file cannot be read
+    Io(std::io::Error),
+    /// A line is invalid, with its number, from 1
+    InvalidLine(usize, String),
+    /// The icons are not a valid XPM image
+    InvalidIcons(String),
+    /// The file gives no color
+    NoColors,
+}
+
+impl fmt::Display for RuleColorsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleColorsError::Io(err) => write!(f, "{}", err),
+            RuleColorsError::InvalidLine(number, line) => {
+                write!(f, "invalid color at line {}: {}", number, line)
+            }
+            RuleColorsError::InvalidIcons(reason) => write!(f, "invalid icons: {}", reason),
+            RuleColorsError::NoColors => write!(f, "no colors found"),
+        }
+    }
+}
+
+impl std::error::Error for RuleColorsError {}
+
+impl From<std::io::Error> for RuleColorsError {
+    fn from(err: std::io::Error) -> Self {
+        RuleColorsError::Io(err)
+    }
+}
+
+/// The colors of the states of a rule
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct RuleColors {
+    /// Colors of the states, the last one of a state winning
+    states: Vec<(CellState, Color)>,
+    /// Colors of the state 1 and of the last state, the states between them
+    /// being interpolated
+    gradient: Option<(Color, Color)>,
+}
+
+impl RuleColors {
+    /// Load the colors of a `.colors`, `.icons` or `.rule` file
+    ///
+    /// @param path Path of the file
+    pub fn load(path: &Path) -> Result<Self, RuleColorsError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse the colors of a `.colors`, `.icons` or `.rule` file
+    ///
+    /// @param content Content of the file
+    pub fn parse(content: &str) -> Result<Self, RuleColorsError> {
+        let lines = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+        let mut colors = RuleColors::default();
+
+        if content
+            .lines()
+            .any(|line| line.trim_start().starts_with('@'))
+        {
+            // A rule file, only its sections of colors and icons matter
+            let mut section = "";
+            let mut icons = Vec::new();
+            let mut color_lines = Vec::new();
+            for (number, line) in lines {
+                if let Some(name) = line.trim_start().strip_prefix('@') {
+                    section = name.split_whitespace().next().unwrap_or("");
+                } else if section == "COLORS" {
+                    color_lines.push((number, line));
+                } else if section == "ICONS" {
+                    icons.push(line);
+                }
+            }
+
+            if icons.iter().any(|line| line.contains('"')) {
+                colors.states.extend(parse_icons(&icons)?);
+            }
+            colors.parse_color_lines(color_lines)?;
+        } else if content.trim_start().starts_with("XPM") || content.contains("/* XPM */") {
+            colors.states = parse_icons(&content.lines().collect::<Vec<_>>())?;
+        } else {
+            colors.parse_color_lines(lines)?;
+        }
+
+        if colors.states.is_empty() && colors.gradient.is_none() {
+            return Err(RuleColorsError::NoColors);
+        }
+
+        Ok(colors)
+    }
+
+    /// Parse the lines of a `.colors` file or of a `@COLORS` section
+    ///
+    /// @param lines The lines, with their number
+    fn parse_color_lines<'a>(
+        &mut self,
+        lines: impl IntoIterator<Item = (usize, &'a str)>,
+    ) -> Result<(), RuleColorsError> {
+        for (number, line) in lines {
+            let invalid = || RuleColorsError::InvalidLine(number, line.trim().to_string());
+
+            let content = line.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let (key, values) = match content.split_once('=') {
+                Some((key, values)) => (Some(key.trim()), values),
+                None => (None, content),
+            };
+            let values = values
+                .split_whitespace()
+                .map(|value| value.parse::<u8>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+
+            match (key, values.as_slice()) {
+                (None | Some("color"), [state, r, g, b]) => {
+                    self.states.push((CellState(*state), to_color(*r, *g, *b)))
+                }
+                (None | Some("gradient"), [r1, g1, b1, r2, g2, b2]) => {
+                    self.gradient = Some((to_color(*r1, *g1, *b1), to_color(*r2, *g2, *b2)))
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the colors of a palette by the ones of the rule
+    ///
+    /// The states the rule gives no color keep theirs, the states the
+    /// palette does not have are ignored.
+    ///
+    /// @param palette The palette of the automaton
+    pub fn apply(&self, palette: &mut [Color]) {
+        if let Some((first, last)) = self.gradient {
+            let steps = palette.len().saturating_sub(2).max(1) as f32;
+            for (index, color) in palette.iter_mut().enumerate().skip(1) {
+                let t = (index - 1) as f32 / steps;
+                for ((component, first), last) in color.iter_mut().zip(first).zip(last) {
+                    *component = first + (last - first) * t;
+                }
+            }
+        }
+
+        for (state, color) in self.states.iter() {
+            if let Some(entry) = palette.get_mut(state.0 as usize) {
+                *entry = *color;
+            }
+        }
+    }
+}
+
+/// Get a color from its components between 0 and 255
+fn to_color(r: u8, g: u8, b: u8) -> Color {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+}
+
+/// Get the colors of the states from the first XPM image of icons
+///
+/// The icons are square and stacked from the state 1 down, so an image as
+/// high as 3 times its width has the icons of the states 1 to 3.
+///
+/// @param lines The lines of the image
+fn parse_icons(lines: &[&str]) -> Result<Vec<(CellState, Color)>, RuleColorsError> {
+    let invalid = |reason: &str| RuleColorsError::InvalidIcons(reason.to_string());
+
+    // The image is the quoted strings, the first one its header
+    let mut strings = lines.iter().filter_map(|line| line.split('"').nth(1));
+    let header = strings
+        .next()
+        .ok_or_else(|| invalid("missing header"))?
+        .split_whitespace()
+        .map(|value| value.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid("invalid header"))?;
+    let [width, height, color_count, chars_per_pixel] = header[..] else {
+        return Err(invalid("invalid header"));
+    };
+    if width == 0 || chars_per_pixel == 0 {
+        return Err(invalid("invalid header"));
+    }
+
+    // The colors of the pixels, None for the transparent and black ones,
+    // which do not count in the color of a state
+    let mut pixel_colors = Vec::with_capacity(color_count);
+    for _ in 0..color_count {
+        let definition = strings.next().ok_or_else(|| invalid("missing colors"))?;
+        let key = definition
+            .get(..chars_per_pixel)
+            .ok_or_else(|| invalid("invalid color"))?;
+        let mut words = definition[chars_per_pixel..].split_whitespace();
+        let value = loop {
+            match words.next() {
+                Some("c") => break words.next().ok_or_else(|| invalid("invalid color"))?,
+                Some(_) => continue,
+                None => return Err(invalid("invalid color")),
+            }
+        };
+        pixel_colors.push((key, parse_xpm_color(value).filter(|color| *color != [0; 3])));
+    }
+
+    let mut states = Vec::new();
+    let mut sums = [0u64; 3];
+    let mut count = 0;
+    for row in 0..height {
+        let pixels = strings.next().ok_or_else(|| invalid("missing pixels"))?;
+        for column in 0..width {
+            let key = pixels
+                .get(column * chars_per_pixel..(column + 1) * chars_per_pixel)
+                .ok_or_else(|| invalid("row too short"))?;
+            let (_, color) = pixel_colors
+                .iter()
+                .find(|(pixel_key, _)| *pixel_key == key)
+                .ok_or_else(|| invalid("unknown pixel"))?;
+            if let Some(color) = color {
+                for (sum, component) in sums.iter_mut().zip(color) {
+                    *sum += *component as u64;
+                }
+                count += 1;
+            }
+        }
+
+        // The last row of an icon
+        if (row + 1) % width == 0 {
+            let state = CellState(((row + 1) / width).min(u8::MAX as usize) as u8);
+            if count > 0 {
+                let [r, g, b] = sums.map(|sum| (sum / count) as u8);
+                states.push((state, to_color(r, g, b)));
+            }
+            sums = [0; 3];
+            count = 0;
+        }
+    }
+
+    Ok(states)
+}
+
+/// Parse a color of an XPM image, as `#rrggbb` or `#rrrrggggbbbb`
+///
+/// Returns None for `None`, the transparent pixels, and for the named
+/// colors, which are not supported.
+///
+/// @param value The color
+fn parse_xpm_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#')?;
+    let digits = match hex.len() {
+        6 => 2,
+        12 => 4,
+        _ => return None,
+    };
+
+    let mut color = [0; 3];
+    for (component, index) in color.iter_mut().zip((0..hex.len()).step_by(digits)) {
+        // Keep the most significant byte of the longer components
+        *component = u8::from_str_radix(hex.get(index..index + 2)?, 16).ok()?;
+    }
+
+    Some(color)
+}
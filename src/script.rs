@@ -0,0 +1,295 @@
+//! Hooks written in the rhai scripting language
+//!
+//! A script defines some of the functions `on_init(world)`, called before the
+//! first step, `on_step(world, generation)`, called after each step, and
+//! `on_finish(world)`, called when the simulation ends:
+//!
+//! ```text
+//! fn on_step(world, generation) {
+//!     if world.population < 100 {
+//!         world.stamp("r-pentomino", world.width / 2, world.height / 2);
+//!     }
+//!     if generation == 5000 {
+//!         world.stop();
+//!     }
+//! }
+//! ```
+//!
+//! The world reads its cells with `world.get(x, y)` and its `width`,
+//! `height` and `population`. `world.region_stats(x, y, width, height)`
+//! gives the `population` of a rectangle, and the `births`, `deaths` and
+//! `activity`, the fraction of its cells which changed, since the previous
+//! generation, e.g. to watch the output lane of a gun. Its changes, `set(x, y, alive)`,
+//! `stamp(pattern, x, y)`, `pause()`, `resume()`, `speed(fps)` and `stop()`,
+//! become commands applied by the simulation, so they are recorded as the
+//! other inputs.
+
+use crate::geometry::{Point, Rect};
+use crate::grid::Grid;
+use crate::pattern::PatternSource;
+use crate::simulation::Command;
+use crate::world::{CellState, World};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+/// An error occurring while loading or running a script
+#[derive(Debug)]
+pub struct ScriptError(Box<EvalAltResult>);
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(err: Box<EvalAltResult>) -> Self {
+        ScriptError(err)
+    }
+}
+
+/// What a hook sees of the world
+struct ScriptState {
+    /// A copy of the cells, updated by the changes of the hook
+    grid: Grid,
+    /// A copy of the cells of the previous generation, if the script looks
+    /// at them and the simulation kept them
+    previous: Option<Vec<CellState>>,
+    /// The changes of the hook, to apply to the simulation
+    commands: Vec<Command>,
+}
+
+impl ScriptState {
+    /// Get the position of a cell, None if it is out of the world
+    fn get_point(&self, x: i64, y: i64) -> Option<Point> {
+        let point = Point::new(usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+
+        self.grid.get_bounds().contains(point).then_some(point)
+    }
+}
+
+/// The world handed to the hooks
+#[derive(Clone)]
+struct ScriptWorld(Rc<RefCell<ScriptState>>);
+
+impl ScriptWorld {
+    fn new(world: &World, previous: Option<&[CellState]>) -> Self {
+        ScriptWorld(Rc::new(RefCell::new(ScriptState {
+            grid: world.get_grid().clone(),
+            previous: previous.map(|previous| previous.to_vec()),
+            commands: Vec::new(),
+        })))
+    }
+
+    fn push(&mut self, command: Command) {
+        self.0.borrow_mut().commands.push(command);
+    }
+
+    fn get(&mut self, x: i64, y: i64) -> bool {
+        let state = self.0.borrow();
+        state
+            .get_point(x, y)
+            .is_some_and(|point| state.grid.get_tile(point.x, point.y) != CellState::DEAD)
+    }
+
+    fn set(&mut self, x: i64, y: i64, alive: bool) {
+        let mut state = self.0.borrow_mut();
+        // Like the commands of the other inputs, the cells out of the world
+        // are ignored
+        if let Some(point) = state.get_point(x, y) {
+            let cell_state = if alive {
+                CellState::ALIVE
+            } else {
+                CellState::DEAD
+            };
+            state.grid.set_tile(point.x, point.y, cell_state);
+            state.commands.push(Command::SetCell(point, cell_state));
+        }
+    }
+
+    fn region_stats(&mut self, x: i64, y: i64, width: i64, height: i64) -> Map {
+        let state = self.0.borrow();
+        // The parts of the rectangle out of the world are left out
+        let (x, width) = (x.max(0), (width + x.min(0)).max(0));
+        let (y, height) = (y.max(0), (height + y.min(0)).max(0));
+        let area = Rect::new(x as usize, y as usize, width as usize, height as usize);
+        let stats = state.grid.get_region_stats(area, state.previous.as_deref());
+
+        let mut map = Map::new();
+        map.insert("population".into(), (stats.population as i64).into());
+        map.insert("births".into(), (stats.births as i64).into());
+        map.insert("deaths".into(), (stats.deaths as i64).into());
+        map.insert("activity".into(), (stats.activity as f64).into());
+
+        map
+    }
+
+    fn stamp(&mut self, pattern: &str, x: i64, y: i64) -> Result<(), Box<EvalAltResult>> {
+        let pattern = pattern
+            .parse::<PatternSource>()
+            .and_then(|source| source.load())
+            .map_err(|err| err.to_string())?;
+        let x = usize::try_from(x).map_err(|_| format!("invalid column {}", x))?;
+        let y = usize::try_from(y).map_err(|_| format!("invalid row {}", y))?;
+        self.push(Command::Stamp(pattern, Point::new(x, y)));
+
+        Ok(())
+    }
+}
+
+/// A loaded script
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    /// The variables of the top level of the script
+    scope: Scope<'static>,
+    /// Whether the script calls `region_stats`, the only one needing the
+    /// previous generation
+    reads_previous: bool,
+}
+
+impl Script {
+    /// Load a script and run its top level
+    ///
+    /// @param path Path of the script
+    pub fn load(path: &Path) -> Result<Self, ScriptError> {
+        let mut engine = Engine::new();
+        engine
+            .register_type_with_name::<ScriptWorld>("World")
+            .register_get("width", |world: &mut ScriptWorld| {
+                world.0.borrow().grid.get_width() as i64
+            })
+            .register_get("height", |world: &mut ScriptWorld| {
+                world.0.borrow().grid.get_height() as i64
+            })
+            .register_get("population", |world: &mut ScriptWorld| {
+                world.0.borrow().grid.get_population() as i64
+            })
+            .register_fn("get", ScriptWorld::get)
+            .register_fn("set", ScriptWorld::set)
+            .register_fn("stamp", ScriptWorld::stamp)
+            .register_fn("region_stats", ScriptWorld::region_stats)
+            .register_fn("pause", |world: &mut ScriptWorld| {
+                world.push(Command::Pause)
+            })
+            .register_fn("resume", |world: &mut ScriptWorld| {
+                world.push(Command::Resume)
+            })
+            .register_fn("speed", |world: &mut ScriptWorld, fps: f64| {
+                world.push(Command::SetSpeed(Some(fps)))
+            })
+            .register_fn("speed", |world: &mut ScriptWorld, fps: i64| {
+                world.push(Command::SetSpeed(Some(fps as f64)))
+            })
+            .register_fn("unlimited_speed", |world: &mut ScriptWorld| {
+                world.push(Command::SetSpeed(None))
+            })
+            .register_fn("stop", |world: &mut ScriptWorld| world.push(Command::Stop));
+
+        let ast = engine.compile_file(path.to_path_buf())?;
+        let mut scope = Scope::new();
+        engine.run_ast_with_scope(&mut scope, &ast)?;
+        // Copying the previous generation is not free either, only do it for
+        // the scripts which may look at it
+        let reads_previous =
+            std::fs::read_to_string(path).is_ok_and(|source| source.contains("region_stats"));
+
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            reads_previous,
+        })
+    }
+
+    /// Whether the script looks at the previous generation
+    pub fn reads_previous(&self) -> bool {
+        self.reads_previous
+    }
+
+    /// Call `on_init(world)` if the script defines it
+    ///
+    /// Returns the changes of the hook.
+    ///
+    /// @param world The world before the first step
+    pub fn on_init(&mut self, world: &World) -> Result<Vec<Command>, ScriptError> {
+        self.call("on_init", world, None, None)
+    }
+
+    /// Call `on_step(world, generation)` if the script defines it
+    ///
+    /// Returns the changes of the hook.
+    ///
+    /// @param world The world after the step
+    /// @param previous The tiles of the previous generation, None if the
+    /// simulation did not keep them
+    /// @param generation The generation of the world
+    pub fn on_step(
+        &mut self,
+        world: &World,
+        previous: Option<&[CellState]>,
+        generation: usize,
+    ) -> Result<Vec<Command>, ScriptError> {
+        self.call("on_step", world, previous, Some(generation))
+    }
+
+    /// Call `on_finish(world)` if the script defines it
+    ///
+    /// Returns the changes of the hook.
+    ///
+    /// @param world The world at the end of the simulation
+    pub fn on_finish(&mut self, world: &World) -> Result<Vec<Command>, ScriptError> {
+        self.call("on_finish", world, None, None)
+    }
+
+    /// Call a hook with the world, its previous generation if given, and the
+    /// generation if given
+    fn call(
+        &mut self,
+        name: &str,
+        world: &World,
+        previous: Option<&[CellState]>,
+        generation: Option<usize>,
+    ) -> Result<Vec<Command>, ScriptError> {
+        let arity = if generation.is_some() { 2 } else { 1 };
+        if !self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == name && function.params.len() == arity)
+        {
+            // Copying the cells is not free, skip the hooks not defined
+            return Ok(Vec::new());
+        }
+
+        let script_world = ScriptWorld::new(world, previous.filter(|_| self.reads_previous));
+        // The top level already ran when loading the script
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = match generation {
+            Some(generation) => self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut self.scope,
+                &self.ast,
+                name,
+                (script_world.clone(), generation as i64),
+            ),
+            None => self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut self.scope,
+                &self.ast,
+                name,
+                (script_world.clone(),),
+            ),
+        };
+        // The value returned by the hook is ignored
+        let _ = result?;
+
+        let commands = std::mem::take(&mut script_world.0.borrow_mut().commands);
+        Ok(commands)
+    }
+}
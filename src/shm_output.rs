@@ -0,0 +1,98 @@
+use crate::world::World;
+use memmap::MmapMut;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The magic starting the region
+const MAGIC: &[u8; 4] = b"GOLM";
+
+/// The version of the layout
+const VERSION: u32 = 1;
+
+/// Size of the header of the region
+const HEADER_SIZE: usize = 32;
+
+/// Offset of the sequence number in the region
+const SEQUENCE_OFFSET: usize = 8;
+
+/// Where the named regions live
+const SHM_DIRECTORY: &str = "/dev/shm";
+
+/// An output writing the world into a shared memory region
+///
+/// Other processes map the same file to read the world without copies. The
+/// region starts with a header made of the magic `GOLM`, the version of the
+/// layout as u32, a sequence number as u64, the generation as u64 and the
+/// width and the height as u32, in the native byte order. The cells follow,
+/// one byte per cell holding its state, row major.
+///
+/// The sequence number is odd while a frame is being written, and increased
+/// to the next even number once the frame is complete. Readers copy what
+/// they need, then check the sequence number did not change and is even.
+pub struct SharedMemoryOutput {
+    /// The mapped region
+    map: MmapMut,
+    /// Path of the file backing the region
+    path: PathBuf,
+}
+
+impl SharedMemoryOutput {
+    /// Create the region
+    ///
+    /// @param name Name of the region, created in /dev/shm, or path of the
+    ///             file backing it if it contains a `/`
+    /// @param width Width of the world
+    /// @param height Height of the world
+    pub fn create(name: &str, width: usize, height: usize) -> std::io::Result<Self> {
+        let path = if name.contains('/') {
+            PathBuf::from(name)
+        } else {
+            Path::new(SHM_DIRECTORY).join(name)
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.set_len((HEADER_SIZE + width * height) as u64)?;
+
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[0..4].copy_from_slice(MAGIC);
+        map[4..8].copy_from_slice(&VERSION.to_ne_bytes());
+        map[24..28].copy_from_slice(&(width as u32).to_ne_bytes());
+        map[28..32].copy_from_slice(&(height as u32).to_ne_bytes());
+
+        Ok(Self { map, path })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the world into the region
+    ///
+    /// The world must have the size the region was created for.
+    ///
+    /// @param world The world
+    /// @param generation The generation of the world
+    pub fn write(&mut self, world: &World, generation: usize) {
+        let cells = world.get_tiles();
+        assert_eq!(cells.len(), self.map.len() - HEADER_SIZE);
+
+        // The region is page aligned, so is the sequence number
+        let sequence = unsafe { &*(self.map.as_ptr().add(SEQUENCE_OFFSET) as *const AtomicU64) };
+        let start = sequence.load(Ordering::Relaxed) | 1;
+        sequence.store(start, Ordering::Release);
+        std::sync::atomic::fence(Ordering::Release);
+
+        self.map[16..24].copy_from_slice(&(generation as u64).to_ne_bytes());
+        for (byte, cell_state) in self.map[HEADER_SIZE..].iter_mut().zip(cells) {
+            *byte = cell_state.0;
+        }
+
+        let sequence = unsafe { &*(self.map.as_ptr().add(SEQUENCE_OFFSET) as *const AtomicU64) };
+        sequence.store(start + 1, Ordering::Release);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::engine::Engine;
+use crate::geometry::{Point, Rect};
+use crate::grid::{Grid, RegionStats};
+use crate::observer::{StepControl, StepObserver, StepStats};
+use crate::pattern::Pattern;
+use crate::predecessor::PredecessorError;
+use crate::rng::SessionRng;
+use crate::rule::{Rule, RulePool};
+use crate::threads::ThreadOptions;
+use crate::world::{CellState, World};
+use std::collections::VecDeque;
+
+/// Default number of previous generations kept to step back
+pub const DEFAULT_HISTORY_SIZE: usize = 100;
+
+/// Most generations run by a step command, the simulation handling no other
+/// command and drawing no frame meanwhile
+pub const MAX_COMMAND_STEPS: usize = 10_000;
+
+/// A command altering a running simulation
+pub enum Command {
+    /// Stop updating the world
+    Pause,
+    /// Resume updating the world
+    Resume,
+    /// Limit the number of steps per second (None for no limit)
+    SetSpeed(Option<f64>),
+    /// Populate the world randomly again with the given density, with a new
+    /// rule if random rules are picked
+    Reseed(f32),
+    /// Copy a pattern into the world at the given position
+    Stamp(Pattern, Point),
+    /// Set the state of the cell at the given position
+    SetCell(Point, CellState),
+    /// Pause and go back to the previous generation
+    StepBack,
+    /// Follow a Life-like rule from now on
+    SetRule(Rule),
+    /// Follow the next rule of the rule cycle from now on
+    NextRule,
+    /// Pause and run the given number of generations, up to
+    /// `MAX_COMMAND_STEPS`, until the simulation is stopped
+    Step(usize),
+    /// Stop the simulation
+    Stop,
+}
+
+/// A running simulation
+pub struct Simulation {
+    /// The simulated world
+    world: World,
+    /// The number of steps done
+    generation: usize,
+    /// Whether the world is updated
+    paused: bool,
+    /// Maximum number of steps per second (None for no limit)
+    speed: Option<f64>,
+    /// Observers notified after each step
+    observers: Vec<Box<dyn StepObserver>>,
+    /// Whether an observer asked to stop the simulation
+    stopped: bool,
+    /// Random number generator used to reseed the world
+    rng: SessionRng,
+    /// Previous generations of the world, oldest first
+    history: VecDeque<Grid>,
+    /// Maximum number of previous generations kept
+    history_size: usize,
+    /// Rules cycled through by the NextRule command
+    rule_cycle: Vec<Rule>,
+    /// Engine computing the generations of the rules set by the commands
+    engine: Engine,
+    /// Threads of the parallel engine
+    threads: ThreadOptions,
+    /// Pool of the random rule picked on each reseed, None to keep the rule
+    random_rules: Option<RulePool>,
+}
+
+impl Simulation {
+    /// Create a new simulation
+    ///
+    /// @param world The world to simulate
+    pub fn new(world: World) -> Self {
+        Self {
+            world,
+            generation: 0,
+            paused: false,
+            speed: None,
+            observers: Vec::new(),
+            stopped: false,
+            rng: SessionRng::from_entropy(),
+            history: VecDeque::new(),
+            history_size: DEFAULT_HISTORY_SIZE,
+            rule_cycle: Vec::new(),
+            engine: Engine::Naive,
+            threads: ThreadOptions::default(),
+            random_rules: None,
+        }
+    }
+
+    pub fn get_world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Resume counting the steps from a given generation, e.g. when the
+    /// world comes from a snapshot
+    ///
+    /// @param generation The generation of the world
+    pub fn set_generation(&mut self, generation: usize) {
+        self.generation = generation;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn get_speed(&self) -> Option<f64> {
+        self.speed
+    }
+
+    /// Use a given random number generator to reseed the world
+    ///
+    /// Runs using generators in the same state reseed the world the same way.
+    ///
+    /// @param rng The random number generator
+    pub fn set_rng(&mut self, rng: SessionRng) {
+        self.rng = rng;
+    }
+
+    /// Get the random number generator reseeding the world, to save it
+    pub fn get_rng(&self) -> &SessionRng {
+        &self.rng
+    }
+
+    /// Get the number of bytes taken by the world and the previous
+    /// generations kept to step back
+    pub fn get_memory_usage(&self) -> usize {
+        self.world.get_memory_usage()
+            + self
+                .history
+                .iter()
+                .map(|grid| grid.get_memory_usage())
+                .sum::<usize>()
+    }
+
+    /// Set how many previous generations are kept to step back
+    ///
+    /// @param history_size The number of generations, 0 to keep none
+    pub fn set_history_size(&mut self, history_size: usize) {
+        self.history_size = history_size;
+        while self.history.len() > history_size {
+            self.history.pop_front();
+        }
+    }
+
+    /// Set the rules cycled through by the NextRule command
+    ///
+    /// @param rules The rules, in order
+    pub fn set_rule_cycle(&mut self, rules: Vec<Rule>) {
+        self.rule_cycle = rules;
+    }
+
+    /// Set the engine computing the generations of the rules set by the
+    /// commands
+    ///
+    /// @param engine The engine
+    /// @param threads The threads of the parallel engine
+    pub fn set_engine(&mut self, engine: Engine, threads: ThreadOptions) {
+        self.engine = engine;
+        self.threads = threads;
+    }
+
+    /// Whether the world follows a Life-like rule, which the rule commands
+    /// can replace
+    pub fn is_life_like(&self) -> bool {
+        self.world.get_automaton().name().parse::<Rule>().is_ok()
+    }
+
+    /// Follow another Life-like rule with the engine of the simulation
+    ///
+    /// The other automata are left alone.
+    ///
+    /// @param rule The rule
+    fn set_rule(&mut self, rule: Rule) {
+        if self.is_life_like() {
+            self.world
+                .set_automaton(self.engine.create_with_threads(rule, self.threads));
+        }
+    }
+
+    /// Pick a new random rule each time the world is reseeded
+    ///
+    /// @param pool The pool of the rules, None to keep the rule
+    pub fn set_random_rules(&mut self, pool: Option<RulePool>) {
+        self.random_rules = pool;
+    }
+
+    /// Whether an observer asked to stop the simulation
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Start over from a world of dead cells, at generation 0 and without
+    /// previous generations to step back to
+    ///
+    /// Returns the world, to set it up again.
+    pub fn restart(&mut self) -> &mut World {
+        self.world.get_tiles_mut().fill(CellState::DEAD);
+        self.generation = 0;
+        self.history.clear();
+
+        &mut self.world
+    }
+
+    /// Get the generation before the last step, None if it is not kept
+    pub fn get_previous_grid(&self) -> Option<&Grid> {
+        self.history.back()
+    }
+
+    /// Get the statistics of a region of the world, the births, the deaths
+    /// and the activity since the previous generation if it is kept
+    ///
+    /// @param area The region, clipped to the world
+    pub fn get_region_stats(&self, area: Rect) -> RegionStats {
+        self.world
+            .get_region_stats(area, self.get_previous_grid().map(|grid| grid.get_tiles()))
+    }
+
+    /// Register an observer notified after each step
+    ///
+    /// @param observer The observer
+    pub fn add_observer(&mut self, observer: Box<dyn StepObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Update the world to the next generation
+    pub fn step(&mut self) {
+        if self.history_size > 0 {
+            if self.history.len() == self.history_size {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.world.get_grid().clone());
+        }
+
+        let update_stats = self.world.update();
+        self.generation += 1;
+
+        if self.observers.is_empty() {
+            return;
+        }
+
+        let stats = StepStats {
+            generation: self.generation,
+            population: update_stats
+                .population
+                .unwrap_or_else(|| self.world.get_population()),
+            births: update_stats.births,
+            deaths: update_stats.deaths,
+        };
+        for observer in self.observers.iter_mut() {
+            if observer.on_step(&stats, &self.world) == StepControl::Stop {
+                self.stopped = true;
+            }
+        }
+    }
+
+    /// Go back to the previous generation
+    ///
+    /// The previous generations kept in the history are restored first, then
+    /// the previous generations are searched with `World::reverse_step`.
+    ///
+    /// Returns false if the world has no previous generation.
+    pub fn step_back(&mut self) -> Result<bool, PredecessorError> {
+        if let Some(grid) = self.history.pop_back() {
+            self.world.set_grid(grid);
+        } else if !self.world.reverse_step()? {
+            return Ok(false);
+        }
+        self.generation = self.generation.saturating_sub(1);
+
+        Ok(true)
+    }
+
+    /// Apply a command
+    ///
+    /// @param command The command to apply
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::Pause => self.paused = true,
+            Command::Resume => self.paused = false,
+            Command::SetSpeed(speed) => self.speed = speed,
+            Command::Reseed(density) => {
+                if let Some(pool) = self.random_rules {
+                    let rule = pool.pick(&mut self.rng);
+                    self.set_rule(rule);
+                }
+                self.world.populate(&mut self.rng, density);
+            }
+            Command::Stamp(pattern, position) => self.world.stamp(&pattern, position),
+            Command::SetCell(position, cell_state) => {
+                // Commands may come from remote clients, ignore the cells out of the world
+                if self.world.get_bounds().contains(position) {
+                    self.world.set_tile(position.x, position.y, cell_state);
+                }
+            }
+            Command::SetRule(rule) => self.set_rule(rule),
+            Command::NextRule => {
+                // Start from the first rule if the current one is not in the
+                // cycle
+                let name = self.world.get_automaton().name();
+                let next = self
+                    .rule_cycle
+                    .iter()
+                    .position(|rule| rule.to_string() == name)
+                    .map_or(0, |position| position + 1);
+                if let Some(rule) = self.rule_cycle.get(next % self.rule_cycle.len().max(1)) {
+                    self.set_rule(*rule);
+                }
+            }
+            Command::Step(steps) => {
+                self.paused = true;
+                for _ in 0..steps.min(MAX_COMMAND_STEPS) {
+                    self.step();
+                    if self.stopped {
+                        break;
+                    }
+                }
+            }
+            Command::Stop => self.stopped = true,
+            Command::StepBack => {
+                self.paused = true;
+
+                // Worlds too large to search simply stay where they are
+                let _ = self.step_back();
+            }
+        }
+    }
+
+    /// Get the minimal duration of a step according to the speed limit
+    pub fn get_step_duration(&self) -> Option<std::time::Duration> {
+        self.speed
+            .filter(|speed| *speed > 0.0)
+            .map(|speed| std::time::Duration::from_secs_f64(1.0 / speed))
+    }
+}
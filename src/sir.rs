@@ -0,0 +1,142 @@
+//! An epidemic spreading between neighbors
+//!
+//! The cells are susceptible, infected or recovered. At each step, a
+//! susceptible cell catches the disease from each of its infected neighbors
+//! with the infection probability, and an infected cell recovers with the
+//! recovery probability. Recovered cells are immune for good.
+
+use crate::automaton::{Automaton, Color, UpdateStats};
+use crate::grid::Grid;
+use crate::world::CellState;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A susceptible cell, the state of the empty worlds
+pub const SUSCEPTIBLE: CellState = CellState(0);
+
+/// An infected cell
+pub const INFECTED: CellState = CellState(1);
+
+/// A recovered cell
+pub const RECOVERED: CellState = CellState(2);
+
+/// Default probability of catching the disease from an infected neighbor
+pub const DEFAULT_INFECTION: f32 = 0.25;
+
+/// Default probability of recovering at each step
+pub const DEFAULT_RECOVERY: f32 = 0.1;
+
+/// A susceptible, infected, recovered epidemic model
+pub struct Sir {
+    /// Probability of catching the disease from an infected neighbor
+    infection: f32,
+    /// Probability of recovering at each step
+    recovery: f32,
+    /// Random number generator deciding the infections and recoveries
+    rng: StdRng,
+}
+
+impl Sir {
+    /// Create the model
+    ///
+    /// @param infection Probability of catching the disease from an infected
+    ///                  neighbor, at each step
+    /// @param recovery Probability of recovering at each step
+    /// @param seed Seed of the random number generator
+    pub fn new(infection: f32, recovery: f32, seed: u64) -> Self {
+        Self {
+            infection,
+            recovery,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for Sir {
+    fn default() -> Self {
+        Self::new(DEFAULT_INFECTION, DEFAULT_RECOVERY, 0)
+    }
+}
+
+impl Automaton for Sir {
+    fn name(&self) -> String {
+        format!(
+            "sir infection {} recovery {}",
+            self.infection, self.recovery
+        )
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> UpdateStats {
+        let width = grid.get_width();
+        let mut stats = UpdateStats::default();
+        let mut new_tiles = grid.get_tiles().to_vec();
+
+        for (y, new_row) in new_tiles.chunks_mut(width.max(1)).enumerate() {
+            for (x, new_cell) in new_row.iter_mut().enumerate() {
+                match grid.get_tile(x, y) {
+                    SUSCEPTIBLE => {
+                        let infected_count = [
+                            (-1, -1),
+                            (0, -1),
+                            (1, -1),
+                            (-1, 0),
+                            (1, 0),
+                            (-1, 1),
+                            (0, 1),
+                            (1, 1),
+                        ]
+                        .iter()
+                        // The neighbors beyond the edges which do not wrap are healthy
+                        .filter_map(|(dx, dy)| grid.get_neighbor(x, y, *dx, *dy))
+                        .filter(|(x, y)| grid.get_tile(*x, *y) == INFECTED)
+                        .count();
+
+                        // Only draw for the exposed cells, most are not
+                        if infected_count > 0 {
+                            let escape = (1.0 - self.infection).powi(infected_count as i32);
+                            if self.rng.gen::<f32>() >= escape {
+                                *new_cell = INFECTED;
+                                stats.births += 1;
+                            }
+                        }
+                    }
+                    // Still counted in the population, which is the cells ever
+                    // infected
+                    INFECTED if self.rng.gen::<f32>() < self.recovery => {
+                        *new_cell = RECOVERED;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        grid.set_tiles(new_tiles);
+
+        stats
+    }
+
+    fn states(&self) -> usize {
+        3
+    }
+
+    fn palette(&self) -> Vec<Color> {
+        vec![
+            [1.0, 1.0, 1.0, 1.0],
+            [0.85, 0.1, 0.1, 1.0],
+            [0.35, 0.65, 0.35, 1.0],
+        ]
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        vec![
+            "susceptible".to_string(),
+            "infected".to_string(),
+            "recovered".to_string(),
+        ]
+    }
+
+    /// The soups are the first infected cells
+    fn seed_states(&self) -> Vec<CellState> {
+        vec![INFECTED]
+    }
+}
@@ -0,0 +1,318 @@
+//! Compressed snapshots of a world
+//!
+//! A snapshot starts with a header made of the magic `GOLS`, the version of
+//! the format as u8, the generation as u64, the width and the height as u32
+//! and the rule as a u8 length followed by the rule in the B/S notation.
+//!
+//! Since the version 2, the header goes on with the session: a u8 of flags,
+//! bit 0 and 1 set if the world wraps around its columns and rows, bit 2 set
+//! if the random number generator follows as its seed as u64 and its
+//! position as u128, then the number of pending commands as u32, each made
+//! of its generation as u64 and its line of a session file (see `replay`) as
+//! a u16 length followed by the text. A session resumed from a snapshot draws
+//! the same random numbers and applies the same commands as if it was never
+//! interrupted.
+//!
+//! Since the version 3, bit 3 of the flags is set if the random number
+//! generator is xoshiro256++ rather than ChaCha20, its position then
+//! counting 64-bit words. The generator of the thread is never saved.
+//!
+//! All the integers are little endian. The header is followed by the world
+//! packed as a bitmap (row major, least significant bit first) compressed
+//! with zlib. Worlds are mostly made of dead cells, so snapshots are much
+//! smaller than raw dumps.
+
+use crate::replay::{format_command, parse_command};
+use crate::rng::{RngKind, SessionRng};
+use crate::rule::Rule;
+use crate::simulation::Command;
+use crate::world::World;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// The magic starting a snapshot
+const MAGIC: &[u8; 4] = b"GOLS";
+
+/// The version of the format
+const VERSION: u8 = 3;
+
+/// Flag of the worlds wrapping around their columns
+const WRAP_X: u8 = 1;
+
+/// Flag of the worlds wrapping around their rows
+const WRAP_Y: u8 = 1 << 1;
+
+/// Flag of the snapshots saving the random number generator
+const HAS_RNG: u8 = 1 << 2;
+
+/// Flag of the snapshots saving a xoshiro256++ random number generator
+const RNG_XOSHIRO: u8 = 1 << 3;
+
+/// An error occurring while saving or loading a snapshot
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The snapshot could not be read or written
+    Io(std::io::Error),
+    /// The snapshot is not a snapshot
+    InvalidMagic,
+    /// The snapshot was saved with an unknown version of the format
+    UnsupportedVersion(u8),
+    /// The rule of the snapshot is not a Life-like rule
+    InvalidRule(String),
+    /// The cells of the snapshot do not match its dimensions
+    InvalidCells,
+    /// A pending command of the snapshot is malformed
+    InvalidCommand(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "{}", err),
+            SnapshotError::InvalidMagic => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::InvalidRule(rule) => write!(f, "invalid rule {}", rule),
+            SnapshotError::InvalidCells => write!(f, "truncated cells"),
+            SnapshotError::InvalidCommand(err) => write!(f, "invalid pending command: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+/// A world at a given generation, with the session it runs in
+pub struct Snapshot {
+    /// The generation of the world
+    pub generation: usize,
+    /// The world
+    pub world: World,
+    /// The random number generator of the session, None if not saved
+    pub rng: Option<SessionRng>,
+    /// The commands waiting to be applied, with the generation at which to
+    /// apply them, in order
+    pub pending_commands: Vec<(usize, Command)>,
+}
+
+/// Write a snapshot of a world
+///
+/// @param writer Where to write the snapshot
+/// @param world The world
+/// @param generation The generation of the world
+pub fn write<W: Write>(writer: W, world: &World, generation: usize) -> Result<(), SnapshotError> {
+    write_session(writer, world, generation, None, &[])
+}
+
+/// Write a snapshot of a world and of the session it runs in
+///
+/// @param writer Where to write the snapshot
+/// @param world The world
+/// @param generation The generation of the world
+/// @param rng The random number generator of the session, if any
+/// @param pending_commands The commands waiting to be applied, with the
+/// generation at which to apply them, in order
+pub fn write_session<W: Write>(
+    writer: W,
+    world: &World,
+    generation: usize,
+    rng: Option<&SessionRng>,
+    pending_commands: &[(usize, &Command)],
+) -> Result<(), SnapshotError> {
+    let mut writer = writer;
+    let rule = world.get_automaton().name();
+    if rule.parse::<Rule>().is_err() || rule.len() > u8::MAX as usize {
+        return Err(SnapshotError::InvalidRule(rule));
+    }
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    writer.write_all(&(generation as u64).to_le_bytes())?;
+    writer.write_all(&(world.get_width() as u32).to_le_bytes())?;
+    writer.write_all(&(world.get_height() as u32).to_le_bytes())?;
+    writer.write_all(&[rule.len() as u8])?;
+    writer.write_all(rule.as_bytes())?;
+
+    let grid = world.get_grid();
+    let mut flags = 0;
+    if grid.is_wrapping_x() {
+        flags |= WRAP_X;
+    }
+    if grid.is_wrapping_y() {
+        flags |= WRAP_Y;
+    }
+    // The generator of the thread cannot be resumed
+    let rng = rng.filter(|rng| rng.get_kind().is_reproducible());
+    if let Some(rng) = rng {
+        flags |= HAS_RNG;
+        if rng.get_kind() == RngKind::Xoshiro256PlusPlus {
+            flags |= RNG_XOSHIRO;
+        }
+    }
+    writer.write_all(&[flags])?;
+    if let Some(rng) = rng {
+        writer.write_all(&rng.get_seed().to_le_bytes())?;
+        writer.write_all(&rng.get_position().to_le_bytes())?;
+    }
+    writer.write_all(&(pending_commands.len() as u32).to_le_bytes())?;
+    for (command_generation, command) in pending_commands.iter() {
+        let line = format_command(command);
+        if line.len() > u16::MAX as usize {
+            return Err(SnapshotError::InvalidCommand(format!(
+                "{}... too long",
+                line.chars().take(16).collect::<String>()
+            )));
+        }
+        writer.write_all(&(*command_generation as u64).to_le_bytes())?;
+        writer.write_all(&(line.len() as u16).to_le_bytes())?;
+        writer.write_all(line.as_bytes())?;
+    }
+
+    let mut encoder = ZlibEncoder::new(writer, Compression::best());
+    encoder.write_all(&world.to_bitmap())?;
+    encoder.finish()?.flush()?;
+
+    Ok(())
+}
+
+/// Read a snapshot
+///
+/// @param reader Where to read the snapshot
+pub fn read<R: Read>(reader: R) -> Result<Snapshot, SnapshotError> {
+    let mut reader = reader;
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+
+    let mut version = [0; 1];
+    reader.read_exact(&mut version)?;
+    let version = version[0];
+    if version == 0 || version > VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let mut generation = [0; 8];
+    reader.read_exact(&mut generation)?;
+    let mut width = [0; 4];
+    reader.read_exact(&mut width)?;
+    let mut height = [0; 4];
+    reader.read_exact(&mut height)?;
+    let mut rule_length = [0; 1];
+    reader.read_exact(&mut rule_length)?;
+    let mut rule = vec![0; rule_length[0] as usize];
+    reader.read_exact(&mut rule)?;
+    let rule = String::from_utf8_lossy(&rule).into_owned();
+    let rule = rule
+        .parse::<Rule>()
+        .map_err(|_| SnapshotError::InvalidRule(rule))?;
+
+    // The snapshots before the version 2 only saved the world, wrapping
+    let mut flags = [WRAP_X | WRAP_Y];
+    let mut rng = None;
+    let mut pending_commands = Vec::new();
+    if version >= 2 {
+        reader.read_exact(&mut flags)?;
+        if flags[0] & HAS_RNG != 0 {
+            let mut seed = [0; 8];
+            reader.read_exact(&mut seed)?;
+            let mut position = [0; 16];
+            reader.read_exact(&mut position)?;
+            let kind = if flags[0] & RNG_XOSHIRO != 0 {
+                RngKind::Xoshiro256PlusPlus
+            } else {
+                RngKind::ChaCha
+            };
+            rng = Some(SessionRng::resume(
+                kind,
+                u64::from_le_bytes(seed),
+                u128::from_le_bytes(position),
+            ));
+        }
+
+        let mut count = [0; 4];
+        reader.read_exact(&mut count)?;
+        for _ in 0..u32::from_le_bytes(count) {
+            let mut command_generation = [0; 8];
+            reader.read_exact(&mut command_generation)?;
+            let mut length = [0; 2];
+            reader.read_exact(&mut length)?;
+            let mut line = vec![0; u16::from_le_bytes(length) as usize];
+            reader.read_exact(&mut line)?;
+            let line = String::from_utf8_lossy(&line);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let command = parse_command(&words).map_err(SnapshotError::InvalidCommand)?;
+            pending_commands.push((u64::from_le_bytes(command_generation) as usize, command));
+        }
+    }
+
+    let mut bitmap = Vec::new();
+    ZlibDecoder::new(reader).read_to_end(&mut bitmap)?;
+    let mut world = World::from_bitmap(
+        u32::from_le_bytes(width) as usize,
+        u32::from_le_bytes(height) as usize,
+        &bitmap,
+    )
+    .ok_or(SnapshotError::InvalidCells)?;
+    world.set_rule(rule);
+    world.set_wrapping(flags[0] & WRAP_X != 0, flags[0] & WRAP_Y != 0);
+
+    Ok(Snapshot {
+        generation: u64::from_le_bytes(generation) as usize,
+        world,
+        rng,
+        pending_commands,
+    })
+}
+
+/// Save a snapshot of a world to a file
+///
+/// @param path Path of the file
+/// @param world The world
+/// @param generation The generation of the world
+pub fn save(path: &Path, world: &World, generation: usize) -> Result<(), SnapshotError> {
+    write(BufWriter::new(File::create(path)?), world, generation)
+}
+
+/// Save a snapshot of a world and of the session it runs in to a file
+///
+/// @param path Path of the file
+/// @param world The world
+/// @param generation The generation of the world
+/// @param rng The random number generator of the session, if any
+/// @param pending_commands The commands waiting to be applied, with the
+/// generation at which to apply them, in order
+pub fn save_session(
+    path: &Path,
+    world: &World,
+    generation: usize,
+    rng: Option<&SessionRng>,
+    pending_commands: &[(usize, &Command)],
+) -> Result<(), SnapshotError> {
+    write_session(
+        BufWriter::new(File::create(path)?),
+        world,
+        generation,
+        rng,
+        pending_commands,
+    )
+}
+
+/// Load a snapshot from a file
+///
+/// @param path Path of the file
+pub fn load(path: &Path) -> Result<Snapshot, SnapshotError> {
+    read(BufReader::new(File::open(path)?))
+}
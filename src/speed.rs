@@ -0,0 +1,131 @@
+//! Speed of the simulation over the last seconds
+//!
+//! The speed is measured over a sliding window rather than since the start,
+//! so changing the engine, the threads or the generations per frame shows in
+//! a few seconds. The cells per second count the cells of the world each
+//! generation computes, to compare worlds of different sizes.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Duration over which the speed is measured when not given
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(3);
+
+/// The speed of the simulation
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Speed {
+    /// Number of generations computed per second
+    pub generations_per_second: f64,
+    /// Number of cells computed per second
+    pub cells_per_second: f64,
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} gen/s {} cells/s",
+            self.generations_per_second,
+            format_count(self.cells_per_second)
+        )
+    }
+}
+
+/// Measures the speed of the simulation over the last seconds
+pub struct SpeedMeter {
+    /// Duration over which the speed is measured
+    window: Duration,
+    /// When the generations were reached, the generation and the number of
+    /// cells computed so far, the oldest first
+    samples: VecDeque<(Instant, usize, u64)>,
+}
+
+impl SpeedMeter {
+    /// Create a meter
+    ///
+    /// @param window Duration over which the speed is measured
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record the generation reached
+    ///
+    /// Going back to a previous generation, as when stepping back or starting
+    /// over, starts measuring again.
+    ///
+    /// @param generation The generation
+    /// @param cells Number of cells of the world
+    pub fn record(&mut self, generation: usize, cells: usize) {
+        let now = Instant::now();
+        let computed = match self.samples.back() {
+            Some((_, last_generation, _)) if generation < *last_generation => {
+                self.samples.clear();
+                0
+            }
+            Some((_, last_generation, computed)) => {
+                computed + ((generation - last_generation) * cells) as u64
+            }
+            None => 0,
+        };
+        self.samples.push_back((now, generation, computed));
+
+        // Keep a sample as old as the window, so it is covered whole
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|(time, _, _)| now.duration_since(*time) >= self.window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Start measuring again
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Get the speed over the window, None until measured
+    pub fn get_speed(&self) -> Option<Speed> {
+        let (first_time, first_generation, first_computed) = self.samples.front()?;
+        let (last_time, last_generation, last_computed) = self.samples.back()?;
+        let seconds = last_time.duration_since(*first_time).as_secs_f64();
+        if seconds <= 0.0 {
+            return None;
+        }
+
+        Some(Speed {
+            generations_per_second: (last_generation - first_generation) as f64 / seconds,
+            cells_per_second: (last_computed - first_computed) as f64 / seconds,
+        })
+    }
+}
+
+impl Default for SpeedMeter {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+/// Format a count with a metric prefix, as `12.3M`
+///
+/// @param count The count
+pub fn format_count(count: f64) -> String {
+    const PREFIXES: [&str; 5] = ["", "k", "M", "G", "T"];
+
+    let mut count = count;
+    let mut prefix = 0;
+    while count >= 1000.0 && prefix + 1 < PREFIXES.len() {
+        count /= 1000.0;
+        prefix += 1;
+    }
+
+    if prefix == 0 {
+        format!("{:.0}", count)
+    } else {
+        format!("{:.1}{}", count, PREFIXES[prefix])
+    }
+}
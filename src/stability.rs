@@ -0,0 +1,79 @@
+//! Detection of the worlds which stopped evolving
+//!
+//! A world has stabilized when it repeats one of its recent states, i.e.
+//! when it is made only of still lifes and oscillators of a short period, or
+//! when it went extinct.
+
+use crate::world::World;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+/// Longest period detected by default
+pub const DEFAULT_MAX_PERIOD: usize = 30;
+
+/// Detect when a world stops evolving, from the hashes of its last states
+pub struct StabilityDetector {
+    /// Longest period detected
+    max_period: usize,
+    /// Hashes of the last states, the most recent last
+    hashes: VecDeque<u64>,
+}
+
+impl StabilityDetector {
+    /// Create a detector
+    ///
+    /// @param max_period Longest period detected
+    pub fn new(max_period: usize) -> Self {
+        Self {
+            max_period: max_period.max(1),
+            hashes: VecDeque::new(),
+        }
+    }
+
+    /// Look at the next state of the world
+    ///
+    /// Returns the period of the cycle the world settled into, 1 for still
+    /// lifes and extinct worlds, None if the world is still evolving.
+    ///
+    /// @param world The world
+    pub fn update(&mut self, world: &World) -> Option<usize> {
+        if world.get_population() == 0 {
+            return Some(1);
+        }
+
+        let hash = hash_world(world);
+        let period = self
+            .hashes
+            .iter()
+            .rev()
+            .position(|previous| *previous == hash)
+            .map(|position| position + 1);
+
+        if self.hashes.len() == self.max_period {
+            self.hashes.pop_front();
+        }
+        self.hashes.push_back(hash);
+
+        period
+    }
+
+    /// Forget the previous states, after the world was changed
+    pub fn reset(&mut self) {
+        self.hashes.clear();
+    }
+}
+
+impl Default for StabilityDetector {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PERIOD)
+    }
+}
+
+/// Hash the cells of a world, whatever their number of states
+fn hash_world(world: &World) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    world.get_tiles().hash(&mut hasher);
+
+    hasher.finish()
+}
@@ -0,0 +1,214 @@
+//! Summary of a run, reported when it ends
+//!
+//! While the simulation runs, the tracker follows the population of every
+//! generation and watches for the world settling into a cycle. When it ends,
+//! the summary tells how many generations were computed and how fast, the
+//! peak and final populations, when the world stabilized and with which
+//! period, and the most common objects of the final generation.
+
+use crate::census::census;
+use crate::stability::StabilityDetector;
+use crate::world::World;
+use serde_json::json;
+use std::fmt;
+use std::time::Duration;
+
+/// Number of objects listed in the census of a summary
+const SUMMARY_CENSUS_SIZE: usize = 5;
+
+/// Follows a run to summarize it
+pub struct SummaryTracker {
+    /// Generation the run started from
+    start_generation: usize,
+    /// Highest population and its first generation
+    peak: (usize, usize),
+    /// Detects the cycle the world settles into
+    stability: StabilityDetector,
+    /// First generation of the cycle and its period, if the world settled
+    stabilized: Option<(usize, usize)>,
+}
+
+impl SummaryTracker {
+    /// Start following a run
+    ///
+    /// @param world The world the run starts from
+    /// @param generation The generation the run starts from
+    pub fn new(world: &World, generation: usize) -> Self {
+        let mut tracker = Self {
+            start_generation: generation,
+            peak: (0, generation),
+            stability: StabilityDetector::default(),
+            stabilized: None,
+        };
+        tracker.record(world, generation);
+
+        tracker
+    }
+
+    /// Look at a generation of the world
+    ///
+    /// @param world The world
+    /// @param generation Its generation
+    pub fn record(&mut self, world: &World, generation: usize) {
+        let population = world.get_population();
+        if population > self.peak.0 {
+            self.peak = (population, generation);
+        }
+
+        match self.stability.update(world) {
+            // Keep the first generation of the cycle while it lasts
+            Some(period) if self.stabilized.is_some_and(|(_, known)| known == period) => {}
+            Some(period) => {
+                // An extinct world repeats from its first empty generation
+                let start = if population == 0 {
+                    generation
+                } else {
+                    generation.saturating_sub(period)
+                };
+                self.stabilized = Some((start, period));
+            }
+            // Edited or restarted, it evolves again
+            None => self.stabilized = None,
+        }
+    }
+
+    /// Follow the run again from a new start, as when the world starts over
+    ///
+    /// @param world The world the run starts from
+    /// @param generation The generation the run starts from
+    pub fn restart(&mut self, world: &World, generation: usize) {
+        *self = Self::new(world, generation);
+    }
+
+    /// Summarize the run
+    ///
+    /// @param world The final world
+    /// @param generation The final generation
+    /// @param wall_time How long the run took
+    pub fn get_summary(&self, world: &World, generation: usize, wall_time: Duration) -> RunSummary {
+        let mut objects: Vec<(String, usize)> = census(world).into_iter().collect();
+        // The most common first, then by name
+        objects.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let object_count = objects.iter().map(|(_, count)| count).sum();
+        objects.truncate(SUMMARY_CENSUS_SIZE);
+
+        RunSummary {
+            start_generation: self.start_generation,
+            final_generation: generation,
+            wall_time,
+            peak_population: self.peak.0,
+            peak_generation: self.peak.1,
+            final_population: world.get_population(),
+            stabilized: self.stabilized,
+            object_count,
+            objects,
+        }
+    }
+}
+
+/// The summary of a run
+#[derive(Clone, Debug)]
+pub struct RunSummary {
+    /// Generation the run started from
+    pub start_generation: usize,
+    /// Generation the run ended at
+    pub final_generation: usize,
+    /// How long the run took
+    pub wall_time: Duration,
+    /// Highest population
+    pub peak_population: usize,
+    /// First generation with the highest population
+    pub peak_generation: usize,
+    /// Population of the final generation
+    pub final_population: usize,
+    /// First generation of the cycle the world settled into and its period,
+    /// if it did
+    pub stabilized: Option<(usize, usize)>,
+    /// Number of objects of the final generation
+    pub object_count: usize,
+    /// The most common objects of the final generation and their counts
+    pub objects: Vec<(String, usize)>,
+}
+
+impl RunSummary {
+    /// Get the number of generations computed
+    pub fn get_generations(&self) -> usize {
+        self.final_generation.saturating_sub(self.start_generation)
+    }
+
+    /// Get the mean number of generations computed per second
+    pub fn get_generations_per_second(&self) -> f64 {
+        let seconds = self.wall_time.as_secs_f64();
+        if seconds > 0.0 {
+            self.get_generations() as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    pub fn to_json_value(&self) -> serde_json::Value {
+        json!({
+            "start_generation": self.start_generation,
+            "final_generation": self.final_generation,
+            "generations": self.get_generations(),
+            "wall_time_s": self.wall_time.as_secs_f64(),
+            "generations_per_second": self.get_generations_per_second(),
+            "peak_population": self.peak_population,
+            "peak_generation": self.peak_generation,
+            "final_population": self.final_population,
+            "stabilized_generation": self.stabilized.map(|(generation, _)| generation),
+            "period": self.stabilized.map(|(_, period)| period),
+            "object_count": self.object_count,
+            "objects": self
+                .objects
+                .iter()
+                .map(|(name, count)| json!({ "name": name, "count": count }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "generations: {} ({} to {}) in {:.2?}, {:.1} generations/s",
+            self.get_generations(),
+            self.start_generation,
+            self.final_generation,
+            self.wall_time,
+            self.get_generations_per_second()
+        )?;
+        writeln!(
+            f,
+            "population: peak {} at generation {}, final {}",
+            self.peak_population, self.peak_generation, self.final_population
+        )?;
+        match self.stabilized {
+            Some((generation, _)) if self.final_population == 0 => {
+                writeln!(f, "extinct at generation {}", generation)?
+            }
+            Some((generation, period)) => writeln!(
+                f,
+                "stabilized at generation {} with period {}",
+                generation, period
+            )?,
+            None => writeln!(f, "not stabilized")?,
+        }
+        write!(f, "objects: {}", self.object_count)?;
+        for (index, (name, count)) in self.objects.iter().enumerate() {
+            write!(
+                f,
+                "{}{} {}",
+                if index == 0 { " (" } else { ", " },
+                name,
+                count
+            )?;
+        }
+        if !self.objects.is_empty() {
+            write!(f, ")")?;
+        }
+
+        Ok(())
+    }
+}
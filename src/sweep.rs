@@ -0,0 +1,178 @@
+//! Parameter sweeps
+//!
+//! A sweep runs a batch of worlds for each value of a parameter and outputs
+//! the outcomes as CSV, one line per value, to plot how the outcomes depend
+//! on the parameter.
+
+use crate::batch::{run_batch, BatchConfig, BatchReport};
+use crate::rule::Rule;
+use std::fmt;
+use std::io::Write;
+
+/// An error occurring while parsing the values of a parameter
+#[derive(Debug)]
+pub struct ParseSweepError(String);
+
+impl fmt::Display for ParseSweepError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid sweep values {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseSweepError {}
+
+/// The values taken by the swept parameter
+#[derive(Clone, Debug)]
+pub enum SweepParameter {
+    /// Initial population densities
+    Density(Vec<f32>),
+    /// Rules
+    Rule(Vec<Rule>),
+    /// Sizes of the worlds, as width and height
+    Size(Vec<(usize, usize)>),
+}
+
+impl SweepParameter {
+    /// Parse densities as a range `start:end:step` or a list `a,b,c`
+    ///
+    /// @param s The densities
+    pub fn parse_densities(s: &str) -> Result<Self, ParseSweepError> {
+        let invalid = || ParseSweepError(s.to_string());
+        let parse = |value: &str| value.trim().parse::<f64>().map_err(|_| invalid());
+
+        let densities = if let [start, end, step] = s.split(':').collect::<Vec<_>>()[..] {
+            let (start, end, step) = (parse(start)?, parse(end)?, parse(step)?);
+            if step <= 0.0 || end < start {
+                return Err(invalid());
+            }
+
+            // Count the values rather than accumulating steps, which drifts
+            let count = ((end - start) / step + 1e-9).floor() as usize + 1;
+            (0..count)
+                .map(|index| (start + index as f64 * step) as f32)
+                .collect()
+        } else {
+            s.split(',')
+                .map(|value| parse(value).map(|density| density as f32))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        Ok(SweepParameter::Density(densities))
+    }
+
+    /// Parse rules as a list `B3/S23,B36/S23`
+    ///
+    /// @param s The rules
+    pub fn parse_rules(s: &str) -> Result<Self, ParseSweepError> {
+        s.split(',')
+            .map(|rule| rule.trim().parse::<Rule>())
+            .collect::<Result<Vec<_>, _>>()
+            .map(SweepParameter::Rule)
+            .map_err(|_| ParseSweepError(s.to_string()))
+    }
+
+    /// Parse sizes as a list `32x32,64x48`
+    ///
+    /// @param s The sizes
+    pub fn parse_sizes(s: &str) -> Result<Self, ParseSweepError> {
+        s.split(',')
+            .map(|size| {
+                let (width, height) = size.trim().split_once('x')?;
+                Some((width.parse().ok()?, height.parse().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(SweepParameter::Size)
+            .ok_or_else(|| ParseSweepError(s.to_string()))
+    }
+
+    /// Get the name of the parameter
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SweepParameter::Density(_) => "density",
+            SweepParameter::Rule(_) => "rule",
+            SweepParameter::Size(_) => "size",
+        }
+    }
+
+    /// Get the settings of the batch of each value, with the value
+    ///
+    /// @param config The settings shared by all the batches
+    fn configs(&self, config: &BatchConfig) -> Vec<(String, BatchConfig)> {
+        match self {
+            SweepParameter::Density(densities) => densities
+                .iter()
+                .map(|density| {
+                    let mut config = config.clone();
+                    config.density = *density;
+                    (density.to_string(), config)
+                })
+                .collect(),
+            SweepParameter::Rule(rules) => rules
+                .iter()
+                .map(|rule| {
+                    let mut config = config.clone();
+                    config.rule = *rule;
+                    (rule.to_string(), config)
+                })
+                .collect(),
+            SweepParameter::Size(sizes) => sizes
+                .iter()
+                .map(|(width, height)| {
+                    let mut config = config.clone();
+                    config.width = *width;
+                    config.height = *height;
+                    (format!("{}x{}", width, height), config)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Run a batch for each value of a parameter, writing the outcomes as CSV
+///
+/// Every batch uses the same seeds, so the values are compared on the same
+/// random initial states when possible.
+///
+/// @param writer Where to write the CSV
+/// @param config The settings shared by all the batches, `runs` being the
+///               number of runs per value
+/// @param parameter The swept parameter
+pub fn sweep<W: Write>(
+    writer: W,
+    config: &BatchConfig,
+    parameter: &SweepParameter,
+) -> std::io::Result<()> {
+    let mut writer = writer;
+    writeln!(
+        writer,
+        "{},runs,extinct,settled,mean_population,median_population,min_population,max_population,mean_lifespan,max_lifespan",
+        parameter.get_name()
+    )?;
+
+    for (value, config) in parameter.configs(config) {
+        let report = BatchReport::new(&run_batch(&config));
+        writeln!(
+            writer,
+            "{},{},{},{},{:.3},{},{},{},{},{}",
+            value,
+            report.runs,
+            report.extinct,
+            report.settled,
+            report.mean_population,
+            report.median_population,
+            report.min_population,
+            report.max_population,
+            report
+                .mean_lifespan
+                .map_or(String::new(), |lifespan| format!("{:.3}", lifespan)),
+            report
+                .max_lifespan
+                .map_or(String::new(), |lifespan| lifespan.to_string()),
+        )?;
+
+        // Let the lines be plotted as they come
+        writer.flush()?;
+    }
+
+    Ok(())
+}
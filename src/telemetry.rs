@@ -0,0 +1,115 @@
+//! Timings of every generation written to a file
+//!
+//! Unlike the timings shown while running, which only keep the percentiles
+//! of the last generations, the telemetry records every generation, so the
+//! performance of different configurations can be compared offline. A line
+//! holds the generation, the durations of the update, the render and the
+//! I/O in microseconds, the number of commands applied, the number of
+//! commands waiting for a later generation and the deepest queue of frames
+//! waiting to be sent to a viewer.
+//!
+//! The lines are written as CSV, or as JSON Lines, one object per line, if
+//! the path ends with `.jsonl`.
+
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// The columns of the CSV
+const CSV_HEADER: &str =
+    "generation,update_us,render_us,io_us,commands,pending_commands,viewer_queue";
+
+/// The measures of a generation
+#[derive(Clone, Copy, Default, Debug)]
+pub struct TelemetrySample {
+    /// The generation reached
+    pub generation: usize,
+    /// Time spent computing the generation
+    pub update: Duration,
+    /// Time spent drawing the generation, None if it was not drawn
+    pub render: Option<Duration>,
+    /// Time spent receiving the commands and streaming the world
+    pub io: Duration,
+    /// Number of commands applied before the generation
+    pub commands: usize,
+    /// Number of commands waiting for a later generation
+    pub pending_commands: usize,
+    /// Number of frames waiting to be sent to the most lagging viewer
+    pub viewer_queue: usize,
+}
+
+/// Writes the measures of the generations
+pub struct TelemetryWriter<W: Write> {
+    /// Where to write the lines
+    writer: W,
+    /// Whether to write JSON Lines rather than CSV
+    json: bool,
+}
+
+impl TelemetryWriter<BufWriter<File>> {
+    /// Create a file to write the measures to, as JSON Lines if its path
+    /// ends with `.jsonl`, as CSV otherwise
+    ///
+    /// @param path Path of the file
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        let json = path
+            .extension()
+            .is_some_and(|extension| extension == "jsonl");
+
+        Self::new(BufWriter::new(File::create(path)?), json)
+    }
+}
+
+impl<W: Write> TelemetryWriter<W> {
+    /// Start writing the measures, with the header of the CSV
+    ///
+    /// @param writer Where to write the lines
+    /// @param json Whether to write JSON Lines rather than CSV
+    pub fn new(mut writer: W, json: bool) -> std::io::Result<Self> {
+        if !json {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+
+        Ok(Self { writer, json })
+    }
+
+    /// Write the measures of a generation
+    ///
+    /// @param sample The measures
+    pub fn write(&mut self, sample: &TelemetrySample) -> std::io::Result<()> {
+        let microseconds = |duration: Duration| duration.as_micros() as u64;
+        let render = sample.render.map(microseconds);
+
+        if self.json {
+            let line = json!({
+                "generation": sample.generation,
+                "update_us": microseconds(sample.update),
+                "render_us": render,
+                "io_us": microseconds(sample.io),
+                "commands": sample.commands,
+                "pending_commands": sample.pending_commands,
+                "viewer_queue": sample.viewer_queue,
+            });
+            writeln!(self.writer, "{}", line)
+        } else {
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{}",
+                sample.generation,
+                microseconds(sample.update),
+                render.map_or_else(String::new, |render| render.to_string()),
+                microseconds(sample.io),
+                sample.commands,
+                sample.pending_commands,
+                sample.viewer_queue
+            )
+        }
+    }
+
+    /// Write the lines still buffered
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
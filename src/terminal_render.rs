@@ -0,0 +1,478 @@
+use crate::geometry::{Point, Rect};
+use crate::population_graph::PopulationGraph;
+use crate::raster::Rasterizer;
+use crate::render::{to_rgb8, ColorOptions, Render, RenderError, RenderOptions};
+use crate::rule::Rule;
+use crate::simulation::Command;
+use crate::speed::Speed;
+use crate::world::World;
+use std::fmt::Write as _;
+use std::io::{Read, Stdin, Stdout, Write};
+
+/// The character drawing the top half of a text cell in the foreground color
+const UPPER_HALF_BLOCK: char = '▀';
+
+/// Size of the terminal when it cannot be asked, as columns and rows
+const DEFAULT_TERMINAL_SIZE: (usize, usize) = (80, 24);
+
+/// Number of columns of the sidebar, its margin included
+const SIDEBAR_WIDTH: usize = 26;
+
+/// Number of lines of the population graph in the sidebar
+const GRAPH_ROWS: usize = 6;
+
+/// Number of columns of the terminal from which the sidebar is shown
+const SIDEBAR_MIN_COLUMNS: usize = 3 * SIDEBAR_WIDTH;
+
+/// A render drawing the world in the terminal with 24-bit colors
+///
+/// Each text cell shows two cells of the world, one above the other, as an
+/// upper half block whose foreground is the top cell and whose background is
+/// the bottom cell. The terminal must support the truecolor escape codes.
+///
+/// When the world is larger than the terminal, a viewport of the size of the
+/// terminal is shown. It is scrolled with the arrow keys or the `h`, `j`,
+/// `k` and `l` keys, by a whole screen with the shifted vim keys. Along the
+/// edges of the world which wrap around, the viewport scrolls across them,
+/// showing the structures crossing them in one piece. The last line shows
+/// the position of the viewport. The `q` key closes the render.
+///
+/// The `e` key opens the rule editor in the last line, showing a checkbox per
+/// neighbor count making a cell be born or survive. The left and right keys
+/// select a checkbox, space or enter toggles it and applies the rule at once,
+/// `e` or escape closes the editor.
+///
+/// When the population graph is given and the terminal is wide enough, a
+/// sidebar right of the viewport shows the population and its graph.
+pub struct TerminalRenderType {
+    /// Where to write the frames
+    stdout: Stdout,
+    /// Where to read the keys, when it is a terminal
+    stdin: Option<Stdin>,
+    /// The settings of the terminal before the render changed them
+    original_termios: Option<libc::termios>,
+    /// Position of the top left corner of the viewport in the world
+    offset: Point,
+    /// The last frame written, to only write the frames which changed
+    previous_frame: String,
+    /// Whether the render has been closed
+    closed: bool,
+    /// Checkbox selected in the rule editor, the births then the survivals,
+    /// None when the editor is closed
+    selected_count: Option<usize>,
+    /// Commands issued by the user
+    commands: Vec<Command>,
+    /// Colors of the cells
+    rasterizer: Rasterizer,
+    /// The population of the last generations, None until given
+    population_graph: Option<PopulationGraph>,
+    /// The speed of the simulation, None until given
+    speed: Option<Speed>,
+}
+
+impl TerminalRenderType {
+    /// Clear the terminal and read the keys as they are pressed
+    ///
+    /// @param options Options of the render, for the colors of the cells
+    pub fn new(options: &RenderOptions) -> Result<Self, RenderError> {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(b"\x1b[2J\x1b[?25l")?;
+        stdout.flush()?;
+
+        // Read the keys without waiting for a new line, nor echoing them
+        let original_termios = if is_terminal(libc::STDIN_FILENO) {
+            let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+            if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } == 0 {
+                let original_termios = termios;
+                termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+                termios.c_cc[libc::VMIN] = 0;
+                termios.c_cc[libc::VTIME] = 0;
+                unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+                Some(original_termios)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        Ok(Self {
+            stdout,
+            stdin: original_termios.map(|_| std::io::stdin()),
+            original_termios,
+            offset: options.view_offset,
+            previous_frame: String::new(),
+            closed: false,
+            selected_count: None,
+            commands: Vec::new(),
+            rasterizer: Rasterizer::new(options),
+            population_graph: None,
+            speed: None,
+        })
+    }
+
+    /// Handle the keys pressed since the last frame
+    ///
+    /// Returns how far to scroll the viewport, in columns and rows.
+    ///
+    /// @param viewport Size of the viewport, in cells of the world
+    /// @param rule The rule of the world, None if it is not a Life-like rule
+    fn handle_keys(&mut self, viewport: (usize, usize), rule: Option<Rule>) -> (isize, isize) {
+        let stdin = match self.stdin.as_mut() {
+            Some(stdin) => stdin,
+            None => return (0, 0),
+        };
+        let viewport = (viewport.0 as isize, viewport.1 as isize);
+        let (mut dx, mut dy) = (0, 0);
+
+        let mut keys = [0; 64];
+        let count = stdin.read(&mut keys).unwrap_or(0);
+        let mut keys = &keys[..count];
+        while let Some((key, rest)) = keys.split_first() {
+            keys = rest;
+            if let Some(selected_count) = self.selected_count.as_mut() {
+                match key {
+                    0x1b if keys.len() >= 2 && keys[0] == b'[' => {
+                        match keys[1] {
+                            b'C' => *selected_count = (*selected_count + 1) % 18,
+                            b'D' => *selected_count = (*selected_count + 17) % 18,
+                            _ => {}
+                        }
+                        keys = &keys[2..];
+                    }
+                    b'l' => *selected_count = (*selected_count + 1) % 18,
+                    b'h' => *selected_count = (*selected_count + 17) % 18,
+                    b' ' | b'\r' | b'\n' => {
+                        if let Some(mut rule) = rule {
+                            let count = *selected_count % 9;
+                            if *selected_count < 9 {
+                                rule.set_birth(count, !rule.is_birth(count));
+                            } else {
+                                rule.set_survival(count, !rule.is_survival(count));
+                            }
+                            self.commands.push(Command::SetRule(rule));
+                        }
+                    }
+                    // A lone escape closes the editor
+                    0x1b | b'e' => self.selected_count = None,
+                    b'q' | 0x03 => self.closed = true,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match key {
+                // The arrows are ESC [ A to D
+                0x1b if keys.len() >= 2 && keys[0] == b'[' => {
+                    match keys[1] {
+                        b'A' => dy -= 2,
+                        b'B' => dy += 2,
+                        b'C' => dx += 1,
+                        b'D' => dx -= 1,
+                        _ => {}
+                    }
+                    keys = &keys[2..];
+                }
+                b'k' => dy -= 2,
+                b'j' => dy += 2,
+                b'l' => dx += 1,
+                b'h' => dx -= 1,
+                b'K' => dy -= viewport.1,
+                b'J' => dy += viewport.1,
+                b'L' => dx += viewport.0,
+                b'H' => dx -= viewport.0,
+                b'e' => self.selected_count = Some(0),
+                // Ctrl-C is read as a key too
+                b'q' | 0x03 => self.closed = true,
+                _ => {}
+            }
+        }
+
+        (dx, dy)
+    }
+
+    /// Scroll the viewport, across the edges of the world which wrap around
+    /// and up to the others
+    ///
+    /// @param world The world
+    /// @param viewport Size of the viewport, in cells of the world
+    /// @param dx Number of columns to scroll right, negative to scroll left
+    /// @param dy Number of rows to scroll down, negative to scroll up
+    fn scroll(&mut self, world: &World, viewport: (usize, usize), dx: isize, dy: isize) {
+        let grid = world.get_grid();
+        let scroll = |offset: usize, delta: isize, size: usize, viewport: usize, wrapping: bool| {
+            let offset = offset as isize + delta;
+            if wrapping {
+                offset.rem_euclid(size.max(1) as isize) as usize
+            } else {
+                (offset.max(0) as usize).min(size.saturating_sub(viewport))
+            }
+        };
+
+        self.offset = Point::new(
+            scroll(
+                self.offset.x,
+                dx,
+                world.get_width(),
+                viewport.0,
+                grid.is_wrapping_x(),
+            ),
+            scroll(
+                self.offset.y,
+                dy,
+                world.get_height(),
+                viewport.1,
+                grid.is_wrapping_y(),
+            ),
+        );
+        self.rasterizer.set_view_offset(self.offset);
+    }
+
+    /// Get the lines of the sidebar, None if there is no room for it
+    ///
+    /// @param columns Number of columns of the terminal
+    fn get_sidebar(&self, columns: usize) -> Option<Vec<String>> {
+        let graph = self
+            .population_graph
+            .as_ref()
+            .filter(|graph| !graph.is_empty() && columns >= SIDEBAR_MIN_COLUMNS)?;
+        let (min, max) = graph.get_range()?;
+
+        let mut lines = vec![
+            "population".to_string(),
+            format!("{}", graph.get_last()?),
+            format!("min {} max {}", min, max),
+            String::new(),
+        ];
+        lines.extend(graph.to_lines(SIDEBAR_WIDTH - 2, GRAPH_ROWS));
+
+        // The lines wrapping around would break the frame
+        Some(
+            lines
+                .into_iter()
+                .map(|line| line.chars().take(SIDEBAR_WIDTH - 2).collect())
+                .collect(),
+        )
+    }
+
+    /// Draw the visible part of the world as text
+    ///
+    /// @param world The world
+    /// @param viewport Size of the viewport, in cells of the world
+    /// @param rule The rule of the world, None if it is not a Life-like rule
+    /// @param sidebar The lines of the sidebar, drawn right of the viewport
+    fn draw(
+        &self,
+        world: &World,
+        viewport: (usize, usize),
+        rule: Option<Rule>,
+        sidebar: Option<&[String]>,
+    ) -> String {
+        let background = to_rgb8(self.rasterizer.get_background(world));
+        // Along the edges which wrap around, the rasterizer scrolls the view
+        let wrapped_offset = self.rasterizer.get_wrapped_offset(world);
+        let origin = Point::new(
+            self.offset.x - wrapped_offset.x,
+            self.offset.y - wrapped_offset.y,
+        );
+        let Point {
+            x: offset_x,
+            y: offset_y,
+        } = origin;
+        let end_x = (offset_x + viewport.0).min(world.get_width());
+        let end_y = (offset_y + viewport.1).min(world.get_height());
+        let image =
+            self.rasterizer
+                .rasterize(world, Rect::at(origin, viewport.0, viewport.1), 1, 1.0);
+        let color = |x: usize, y: usize| {
+            let [red, green, blue, _] = image.get_pixel(x - offset_x, y - offset_y);
+            [red, green, blue]
+        };
+
+        // Back to the top left corner, the frames overwrite each other
+        let mut frame = String::from("\x1b[H");
+        let rows = end_y.saturating_sub(offset_y).div_ceil(2).max(
+            sidebar
+                .map_or(0, |sidebar| sidebar.len())
+                .min(viewport.1 / 2),
+        );
+        for row in 0..rows {
+            let y = offset_y + row * 2;
+            let mut colors = None;
+            for x in (offset_x..end_x).filter(|_| y < end_y) {
+                let top = color(x, y);
+                let bottom = if y + 1 < end_y {
+                    color(x, y + 1)
+                } else {
+                    background
+                };
+
+                // Only change the colors when needed, most cells are alike
+                if colors != Some((top, bottom)) {
+                    let _ = write!(
+                        frame,
+                        "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                        top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]
+                    );
+                    colors = Some((top, bottom));
+                }
+                frame.push(UPPER_HALF_BLOCK);
+            }
+            frame.push_str("\x1b[0m\x1b[K");
+            if let Some(line) = sidebar.and_then(|sidebar| sidebar.get(row)) {
+                // Past the viewport and a margin, the columns counting from 1
+                let _ = write!(frame, "\x1b[{}G{}", viewport.0 + 3, line);
+            }
+            frame.push('\n');
+        }
+
+        // Status line, erasing what remains of the previous frames
+        if let Some(selected_count) = self.selected_count {
+            match rule {
+                Some(rule) => {
+                    frame.push('B');
+                    for index in 0..18 {
+                        let count = index % 9;
+                        if index == 9 {
+                            frame.push_str(" S");
+                        }
+                        let checked = if index < 9 {
+                            rule.is_birth(count)
+                        } else {
+                            rule.is_survival(count)
+                        };
+                        let checkbox = format!("[{}]{}", if checked { 'x' } else { ' ' }, count);
+                        if index == selected_count {
+                            let _ = write!(frame, " \x1b[7m{}\x1b[0m", checkbox);
+                        } else {
+                            let _ = write!(frame, " {}", checkbox);
+                        }
+                    }
+                    let _ = write!(
+                        frame,
+                        "  {} - space to toggle, e to close\x1b[K\x1b[J",
+                        rule
+                    );
+                }
+                None => frame.push_str("\x1b[7mnot a Life-like rule\x1b[0m e to close\x1b[K\x1b[J"),
+            }
+
+            return frame;
+        }
+        let _ = write!(
+            frame,
+            "\x1b[7mx {}..{} y {}..{} of {}x{}{}\x1b[0m arrows or hjkl to scroll, e to edit the rule, q to quit\x1b[K\x1b[J",
+            self.offset.x,
+            get_end(self.offset.x, end_x - offset_x, world.get_width()),
+            self.offset.y,
+            get_end(self.offset.y, end_y.saturating_sub(offset_y), world.get_height()),
+            world.get_width(),
+            world.get_height(),
+            self.speed
+                .map_or_else(String::new, |speed| format!(" {}", speed))
+        );
+
+        frame
+    }
+}
+
+impl Render for TerminalRenderType {
+    fn render(&mut self, world: &World) -> Result<(), RenderError> {
+        if self.closed {
+            return Ok(());
+        }
+
+        // A text row shows two rows of cells, and the status takes a line
+        let (columns, rows) = terminal_size().unwrap_or(DEFAULT_TERMINAL_SIZE);
+        let sidebar = self.get_sidebar(columns);
+        let viewport = (
+            match sidebar {
+                Some(_) => columns - SIDEBAR_WIDTH,
+                None => columns,
+            },
+            rows.saturating_sub(1).max(1) * 2,
+        );
+        let rule = world.get_automaton().name().parse::<Rule>().ok();
+        let (dx, dy) = self.handle_keys(viewport, rule);
+        self.scroll(world, viewport, dx, dy);
+
+        self.rasterizer.update(world);
+        let frame = self.draw(world, viewport, rule, sidebar.as_deref());
+        if frame == self.previous_frame {
+            return Ok(());
+        }
+
+        let mut stdout = self.stdout.lock();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        self.previous_frame = frame;
+
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn take_commands(&mut self) -> Vec<Command> {
+        std::mem::take(&mut self.commands)
+    }
+
+    fn set_colors(&mut self, colors: ColorOptions) {
+        self.rasterizer.set_colors(colors);
+    }
+
+    fn set_population_graph(&mut self, graph: &PopulationGraph) {
+        self.population_graph = Some(graph.clone());
+    }
+
+    fn set_speed(&mut self, speed: Option<Speed>) {
+        self.speed = speed;
+    }
+}
+
+impl Drop for TerminalRenderType {
+    fn drop(&mut self) {
+        // Give the terminal back as it was
+        if let Some(termios) = self.original_termios.as_ref() {
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios) };
+        }
+        let _ = self.stdout.write_all(b"\x1b[0m\x1b[?25h\n");
+        let _ = self.stdout.flush();
+    }
+}
+
+/// Get the coordinate after the last cell shown along an axis, which is
+/// before the first one when the viewport wraps around the edge
+///
+/// @param offset Coordinate of the first cell shown
+/// @param shown Number of cells shown
+/// @param size Number of cells along the axis
+fn get_end(offset: usize, shown: usize, size: usize) -> usize {
+    if shown == 0 {
+        offset
+    } else {
+        (offset + shown - 1) % size + 1
+    }
+}
+
+/// Whether a file descriptor is a terminal
+fn is_terminal(fd: libc::c_int) -> bool {
+    unsafe { libc::isatty(fd) == 1 }
+}
+
+/// Get the size of the terminal, as columns and rows
+fn terminal_size() -> Option<(usize, usize)> {
+    if !is_terminal(libc::STDOUT_FILENO) {
+        return None;
+    }
+
+    let mut size = unsafe { std::mem::zeroed::<libc::winsize>() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0
+        || size.ws_col == 0
+    {
+        return None;
+    }
+
+    Some((size.ws_col as usize, size.ws_row as usize))
+}
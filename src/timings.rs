@@ -0,0 +1,150 @@
+//! Timings of the phases of the main loop
+//!
+//! The durations of the last generations are kept in a sliding window, so
+//! the percentiles follow the changes of the simulation.
+
+use serde_json::json;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Default number of durations kept per phase
+pub const DEFAULT_WINDOW: usize = 120;
+
+/// A phase of the main loop
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    /// Computing the next generation
+    Update,
+    /// Drawing the world
+    Render,
+    /// Receiving the commands and streaming the world
+    Io,
+}
+
+impl Phase {
+    /// Get all the phases
+    pub fn all() -> [Phase; 3] {
+        [Phase::Update, Phase::Render, Phase::Io]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Phase::Update => "update",
+            Phase::Render => "render",
+            Phase::Io => "io",
+        }
+    }
+}
+
+/// The durations of the phases of the last generations
+pub struct Timings {
+    /// Number of durations kept per phase
+    window: usize,
+    /// The last durations of each phase, oldest first, indexed by phase
+    durations: [VecDeque<Duration>; 3],
+}
+
+impl Timings {
+    /// Create empty timings
+    ///
+    /// @param window Number of durations kept per phase
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            durations: Default::default(),
+        }
+    }
+
+    /// Record a duration of a phase, forgetting the oldest one if the window
+    /// is full
+    ///
+    /// @param phase The phase
+    /// @param duration How long the phase took
+    pub fn record(&mut self, phase: Phase, duration: Duration) {
+        let durations = &mut self.durations[phase as usize];
+        if durations.len() == self.window {
+            durations.pop_front();
+        }
+        durations.push_back(duration);
+    }
+
+    /// Run a phase and record how long it took
+    ///
+    /// @param phase The phase
+    /// @param f The phase
+    pub fn measure<T, F: FnOnce() -> T>(&mut self, phase: Phase, f: F) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(phase, start.elapsed());
+
+        result
+    }
+
+    /// Get the last duration of a phase
+    ///
+    /// @param phase The phase
+    pub fn get_last(&self, phase: Phase) -> Option<Duration> {
+        self.durations[phase as usize].back().copied()
+    }
+
+    /// Get a percentile of the durations of a phase, by nearest rank
+    ///
+    /// @param phase The phase
+    /// @param percentile The percentile, between 0 and 100
+    pub fn get_percentile(&self, phase: Phase, percentile: f64) -> Option<Duration> {
+        let mut durations: Vec<Duration> = self.durations[phase as usize].iter().copied().collect();
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_unstable();
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * durations.len() as f64).ceil() as usize;
+        Some(durations[rank.max(1) - 1])
+    }
+
+    /// Summarize the durations of the phases on a single line
+    pub fn get_summary(&self) -> String {
+        Phase::all()
+            .iter()
+            .filter_map(|phase| {
+                Some(format!(
+                    "{} {:.1?} (p99 {:.1?})",
+                    phase.get_name(),
+                    self.get_percentile(*phase, 50.0)?,
+                    self.get_percentile(*phase, 99.0)?
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Get the percentiles of the phases as a JSON value, in milliseconds
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let milliseconds =
+            |duration: Option<Duration>| duration.map(|duration| duration.as_secs_f64() * 1000.0);
+
+        Phase::all()
+            .iter()
+            .map(|phase| {
+                (
+                    phase.get_name().to_string(),
+                    json!({
+                        "samples": self.durations[*phase as usize].len(),
+                        "last_ms": milliseconds(self.get_last(*phase)),
+                        "p50_ms": milliseconds(self.get_percentile(*phase, 50.0)),
+                        "p90_ms": milliseconds(self.get_percentile(*phase, 90.0)),
+                        "p99_ms": milliseconds(self.get_percentile(*phase, 99.0)),
+                        "max_ms": milliseconds(self.get_percentile(*phase, 100.0)),
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
@@ -0,0 +1,274 @@
+//! Self-verification of the engines on canonical patterns
+//!
+//! Each engine runs patterns whose evolution is well known, such as the
+//! period of the blinker or the generation the R-pentomino stabilizes at,
+//! so an optimized engine computing wrong generations is caught even when it
+//! agrees with itself.
+
+use crate::engine::Engine;
+use crate::geometry::Point;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::world::World;
+use std::fmt;
+
+/// A pattern whose evolution is known
+struct Check {
+    /// Description of the check
+    name: &'static str,
+    /// Run the check with an engine, returning what went wrong if it failed
+    run: fn(Engine) -> Result<(), String>,
+}
+
+/// The checks, from the quickest to the slowest
+const CHECKS: &[Check] = &[
+    Check {
+        name: "block is a still life",
+        run: |engine| expect_period(engine, "block", 1),
+    },
+    Check {
+        name: "blinker has period 2",
+        run: |engine| expect_period(engine, "blinker", 2),
+    },
+    Check {
+        name: "beacon has period 2",
+        run: |engine| expect_period(engine, "beacon", 2),
+    },
+    Check {
+        name: "block stays in a corner of a bounded world",
+        run: check_bounded_corner,
+    },
+    Check {
+        name: "glider moves (1, 1) in 4 generations",
+        run: |engine| expect_displacement(engine, "glider", (1, 1), 4),
+    },
+    Check {
+        name: "lwss moves (-2, 0) in 4 generations",
+        run: |engine| expect_displacement(engine, "lwss", (-2, 0), 4),
+    },
+    Check {
+        name: "glider wraps around the edges",
+        run: check_glider_wrap,
+    },
+    Check {
+        name: "diehard dies at generation 130",
+        run: check_diehard,
+    },
+    Check {
+        name: "r-pentomino has 116 cells at generation 1103",
+        run: check_r_pentomino,
+    },
+];
+
+/// The outcome of a check with an engine
+pub struct VerifyResult {
+    /// The engine
+    pub engine: Engine,
+    /// Description of the check
+    pub name: &'static str,
+    /// What went wrong, None if the check passed
+    pub failure: Option<String>,
+}
+
+/// The outcomes of the checks
+pub struct VerifyReport {
+    /// The outcome of each check with each engine
+    pub results: Vec<VerifyResult>,
+}
+
+impl VerifyReport {
+    /// Whether every check passed with every engine
+    pub fn is_passed(&self) -> bool {
+        self.results.iter().all(|result| result.failure.is_none())
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in self.results.iter() {
+            match result.failure.as_ref() {
+                None => writeln!(f, "{:<10} pass  {}", result.engine.get_name(), result.name)?,
+                Some(failure) => writeln!(
+                    f,
+                    "{:<10} FAIL  {}: {}",
+                    result.engine.get_name(),
+                    result.name,
+                    failure
+                )?,
+            }
+        }
+
+        let failures = self
+            .results
+            .iter()
+            .filter(|result| result.failure.is_some())
+            .count();
+        if failures == 0 {
+            writeln!(f, "all the {} checks passed", self.results.len())
+        } else {
+            writeln!(
+                f,
+                "{} of the {} checks failed",
+                failures,
+                self.results.len()
+            )
+        }
+    }
+}
+
+/// Run the canonical patterns with engines
+///
+/// @param engines The engines to check
+pub fn verify(engines: &[Engine]) -> VerifyReport {
+    let results = engines
+        .iter()
+        .flat_map(|engine| {
+            CHECKS.iter().map(move |check| VerifyResult {
+                engine: *engine,
+                name: check.name,
+                failure: (check.run)(*engine).err(),
+            })
+        })
+        .collect();
+
+    VerifyReport { results }
+}
+
+/// Create a Game of Life world with a builtin pattern in it
+///
+/// @param engine The engine updating the world
+/// @param size The width and the height of the world
+/// @param wrap Whether the edges of the world are connected
+/// @param name The name of the builtin pattern
+/// @param position Where the top left corner of the pattern is placed
+fn world_with(
+    engine: Engine,
+    (width, height): (usize, usize),
+    wrap: bool,
+    name: &str,
+    position: Point,
+) -> World {
+    let mut world = World::new(width, height);
+    world.set_wrapping(wrap, wrap);
+    world.set_automaton(engine.create(Rule::default()));
+    world.stamp(&Pattern::builtin(name).unwrap(), position);
+
+    world
+}
+
+/// Run a world for some generations
+fn run(world: &mut World, generations: usize) {
+    for _ in 0..generations {
+        world.update();
+    }
+}
+
+/// Check a pattern changes during its period and comes back after it
+fn expect_period(engine: Engine, name: &str, period: usize) -> Result<(), String> {
+    let mut world = world_with(engine, (8, 8), true, name, Point::new(2, 2));
+    let start = world.get_tiles().to_vec();
+    for generation in 1..=period {
+        world.update();
+        let same = world.get_tiles() == &start[..];
+        if generation < period && same {
+            return Err(format!("back to the start at generation {}", generation));
+        }
+        if generation == period && !same {
+            return Err(format!(
+                "not back to the start at generation {}",
+                generation
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a spaceship moves as fast and in the direction it should
+fn expect_displacement(
+    engine: Engine,
+    name: &str,
+    (dx, dy): (isize, isize),
+    generations: usize,
+) -> Result<(), String> {
+    let start = Point::new(8, 8);
+    let mut world = world_with(engine, (24, 24), true, name, start);
+    run(&mut world, generations);
+
+    let moved = world.translate(start, dx, dy).unwrap();
+    let expected = world_with(engine, (24, 24), true, name, moved);
+    if world.get_tiles() != expected.get_tiles() {
+        return Err(format!(
+            "not at {} after {} generations",
+            moved, generations
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the edges which do not wrap are dead: a block in a corner only
+/// sees itself
+fn check_bounded_corner(engine: Engine) -> Result<(), String> {
+    let mut world = world_with(engine, (6, 6), false, "block", Point::new(4, 4));
+    let start = world.get_tiles().to_vec();
+    run(&mut world, 3);
+    if world.get_tiles() != &start[..] {
+        return Err("the block changed".to_string());
+    }
+
+    Ok(())
+}
+
+/// Check a glider crossing the edges of a torus comes back where it started
+fn check_glider_wrap(engine: Engine) -> Result<(), String> {
+    // Moving (1, 1) every 4 generations, the glider crosses the 12x12 world
+    // in 48 generations
+    let mut world = world_with(engine, (12, 12), true, "glider", Point::new(9, 9));
+    let start = world.get_tiles().to_vec();
+    run(&mut world, 48);
+    if world.get_tiles() != &start[..] {
+        return Err("not back at the start after 48 generations".to_string());
+    }
+
+    Ok(())
+}
+
+/// Check the diehard vanishes at generation 130, and not before
+fn check_diehard(engine: Engine) -> Result<(), String> {
+    let mut world = world_with(engine, (64, 64), false, "diehard", Point::new(28, 30));
+    run(&mut world, 129);
+    if world.get_population() == 0 {
+        return Err("dead before generation 130".to_string());
+    }
+    world.update();
+    if world.get_population() != 0 {
+        return Err(format!(
+            "{} cells at generation 130",
+            world.get_population()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the R-pentomino settles with 116 cells, including its 6 gliders
+fn check_r_pentomino(engine: Engine) -> Result<(), String> {
+    // Large enough for the gliders not to reach the edges
+    let mut world = world_with(
+        engine,
+        (640, 640),
+        false,
+        "r-pentomino",
+        Point::new(320, 320),
+    );
+    run(&mut world, 1103);
+    if world.get_population() != 116 {
+        return Err(format!(
+            "{} cells at generation 1103",
+            world.get_population()
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! JavaScript API of the engine, built with the `wasm` feature
+//!
+//! The cells of a world can be read without copy from JavaScript:
+//!
+//! ```js
+//! const world = new World(320, 240);
+//! world.populate(0.5);
+//! world.step();
+//! const cells = new Uint8Array(memory.buffer, world.cellsPtr(), world.width() * world.height());
+//! ```
+//!
+//! The view is invalidated by `step`, which replaces the cell buffer.
+
+use crate::geometry::Point;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::world::{CellState, World};
+use wasm_bindgen::prelude::*;
+
+/// A world driven from JavaScript
+#[wasm_bindgen(js_name = World)]
+pub struct WasmWorld {
+    world: World,
+}
+
+#[wasm_bindgen(js_class = World)]
+impl WasmWorld {
+    /// Create a world of dead cells
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            world: World::new(width, height),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.world.get_width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.world.get_height()
+    }
+
+    /// Populate the world randomly
+    pub fn populate(&mut self, density: f32) {
+        self.world.populate(&mut rand::thread_rng(), density);
+    }
+
+    /// Update the world to its next generation
+    pub fn step(&mut self) {
+        self.world.update();
+    }
+
+    /// Get the rule, in the B/S notation
+    pub fn rule(&self) -> String {
+        self.world.get_automaton().name()
+    }
+
+    /// Set the rule, in the B/S or S/B notation
+    #[wasm_bindgen(js_name = setRule)]
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        let rule = rule
+            .parse::<Rule>()
+            .map_err(|err| JsValue::from_str(&err.to_string()))?;
+        self.world.set_rule(rule);
+
+        Ok(())
+    }
+
+    /// Count the alive cells
+    pub fn population(&self) -> usize {
+        self.world.get_population()
+    }
+
+    /// Get the state of a cell, 0 for a dead cell and 1 for an alive one
+    #[wasm_bindgen(js_name = getCell)]
+    pub fn get_cell(&self, x: usize, y: usize) -> u8 {
+        self.world.get_tile(x, y).0
+    }
+
+    /// Set the state of a cell
+    #[wasm_bindgen(js_name = setCell)]
+    pub fn set_cell(&mut self, x: usize, y: usize, alive: bool) {
+        let cell_state = if alive {
+            CellState::ALIVE
+        } else {
+            CellState::DEAD
+        };
+        self.world.set_tile(x, y, cell_state);
+    }
+
+    /// Copy a pattern in the RLE format into the world
+    #[wasm_bindgen(js_name = stampRle)]
+    pub fn stamp_rle(&mut self, rle: &str, x: usize, y: usize) -> Result<(), JsValue> {
+        let pattern = Pattern::from_rle(rle).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        self.world.stamp(&pattern, Point::new(x, y));
+
+        Ok(())
+    }
+
+    /// Get the address of the cells in the memory of the module, one byte per
+    /// cell, row major
+    #[wasm_bindgen(js_name = cellsPtr)]
+    pub fn cells_ptr(&self) -> *const u8 {
+        self.world.get_tiles().as_ptr() as *const u8
+    }
+}
@@ -0,0 +1,53 @@
+//! Following the changes of files on disk
+//!
+//! The files are polled for their modification time rather than watched
+//! with the notifications of the platform, which is enough for files saved
+//! by hand from an editor and works everywhere.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Modification time of a file, None if it cannot be read
+fn get_modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Follows the modification times of some files
+pub struct FileWatcher {
+    /// The files, with their modification time when last checked
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl FileWatcher {
+    /// Start following some files
+    ///
+    /// @param paths Paths of the files
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            files: paths
+                .into_iter()
+                .map(|path| {
+                    let modified = get_modified(&path);
+                    (path, modified)
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the files modified since the last check, the ones missing while
+    /// being written excluded until they are back
+    pub fn take_modified(&mut self) -> Vec<PathBuf> {
+        let mut modified_paths = Vec::new();
+        for (path, modified) in self.files.iter_mut() {
+            let current = get_modified(path);
+            if current.is_some() && current != *modified {
+                *modified = current;
+                modified_paths.push(path.clone());
+            }
+        }
+
+        modified_paths
+    }
+}
@@ -1,118 +1,460 @@
-/// The state of cell
-#[derive(Clone, Copy, PartialEq)]
-pub enum CellState {
-    /// A dead cell
-    Dead,
-    /// An alive cell
-    Alive,
+use crate::automaton::{Automaton, UpdateStats};
+use crate::geometry::{Point, Rect};
+pub use crate::grid::CellState;
+use crate::grid::{Grid, OutOfBoundsError, RegionStats};
+use crate::life::LifeLike;
+use crate::pattern::Pattern;
+use crate::predecessor::{find_world_predecessor, PredecessorError};
+use crate::rule::Rule;
+use rand::Rng;
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
+
+/// An error occurring while parsing a world in the JSON format
+#[derive(Debug)]
+pub struct ParseJsonError(String);
+
+impl fmt::Display for ParseJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid world: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseJsonError {}
+
+/// An error occurring while parsing a world drawn as text
+#[derive(Debug)]
+pub struct ParseAsciiError(String);
+
+impl fmt::Display for ParseAsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid world: {}", self.0)
+    }
 }
 
+impl std::error::Error for ParseAsciiError {}
+
 /// A world
 pub struct World {
-    /// Width of the world
-    width: usize,
-    /// Height of the world
-    height: usize,
-    /// Tiles of the world
-    tiles: Vec<Vec<CellState>>,
+    /// Cells of the world
+    grid: Grid,
+    /// Automaton updating the cells
+    automaton: Box<dyn Automaton>,
 }
 
 impl World {
-    /// Create a new world
+    /// Create a new world following the rules of Conway's Game of Life
     ///
     /// @param width Width of the world
     /// @param height Height of the world
     pub fn new(width: usize, height: usize) -> Self {
         Self {
-            width,
-            height,
-            tiles: vec![vec![CellState::Dead; width]; height],
+            grid: Grid::new(width, height),
+            automaton: Box::new(LifeLike::default()),
         }
     }
 
     pub fn get_width(&self) -> usize {
-        self.width
+        self.grid.get_width()
     }
 
     pub fn get_height(&self) -> usize {
-        self.height
+        self.grid.get_height()
     }
 
     pub fn get_tile(&self, x: usize, y: usize) -> CellState {
-        self.tiles[y][x]
+        self.grid.get_tile(x, y)
     }
 
     pub fn set_tile(&mut self, x: usize, y: usize, cell_state: CellState) {
-        self.tiles[y][x] = cell_state;
+        self.grid.set_tile(x, y, cell_state);
     }
 
-    /// Populate the world randomly
+    /// Get the rectangle covering the whole world
+    pub fn get_bounds(&self) -> Rect {
+        self.grid.get_bounds()
+    }
+
+    /// Move a point, following the edges of the world, see
+    /// `Grid::translate`
     ///
-    /// @param density The population density
-    pub fn populate(&mut self, density: f32) {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let cell_state = if rand::random::<f32>() < density {
-                    CellState::Alive
-                } else {
-                    CellState::Dead
-                };
-                self.tiles[y][x] = cell_state;
+    /// @param point The point to move
+    /// @param dx Number of columns to move by
+    /// @param dy Number of rows to move by
+    pub fn translate(&self, point: Point, dx: isize, dy: isize) -> Option<Point> {
+        self.grid.translate(point, dx, dy)
+    }
+
+    /// Get the states of the eight neighbors of a cell, following the edges
+    /// of the world, see `Grid::get_neighbors`
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    pub fn get_neighbors(&self, x: usize, y: usize) -> [CellState; 8] {
+        self.grid.get_neighbors(x, y)
+    }
+
+    /// Count the neighbors of a cell which are not dead
+    ///
+    /// @param x Column of the cell
+    /// @param y Row of the cell
+    pub fn get_live_neighbor_count(&self, x: usize, y: usize) -> usize {
+        self.grid.get_live_neighbor_count(x, y)
+    }
+
+    /// Set many cells at once, see `Grid::set_cells`
+    ///
+    /// @param cells The column, the row and the new state of each cell
+    pub fn set_cells<I>(&mut self, cells: I) -> Result<UpdateStats, OutOfBoundsError>
+    where
+        I: IntoIterator<Item = (usize, usize, CellState)>,
+    {
+        self.grid.set_cells(cells)
+    }
+
+    /// Copy rows of cells to the top left corner of the world, see
+    /// `Grid::load_rows`
+    ///
+    /// @param rows The states of the cells, the top row first
+    pub fn load_rows(&mut self, rows: &[&[CellState]]) -> Result<UpdateStats, OutOfBoundsError> {
+        self.grid.load_rows(rows)
+    }
+
+    /// Get all the tiles, row major
+    pub fn get_tiles(&self) -> &[CellState] {
+        self.grid.get_tiles()
+    }
+
+    /// Get all the tiles, row major, to edit them in place
+    pub fn get_tiles_mut(&mut self) -> &mut [CellState] {
+        self.grid.get_tiles_mut()
+    }
+
+    pub fn get_grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Connect or disconnect the opposite edges of the world, see
+    /// `Grid::set_wrapping`
+    ///
+    /// @param wrap_x Whether the left and right edges are connected
+    /// @param wrap_y Whether the top and bottom edges are connected
+    pub fn set_wrapping(&mut self, wrap_x: bool, wrap_y: bool) {
+        self.grid.set_wrapping(wrap_x, wrap_y);
+    }
+
+    /// Replace the cells of the world
+    ///
+    /// @param grid The new cells
+    pub fn set_grid(&mut self, grid: Grid) {
+        self.grid = grid;
+    }
+
+    pub fn get_automaton(&self) -> &dyn Automaton {
+        self.automaton.as_ref()
+    }
+
+    pub fn set_automaton(&mut self, automaton: Box<dyn Automaton>) {
+        self.automaton = automaton;
+    }
+
+    /// Follow a Life-like rule
+    ///
+    /// @param rule The rule
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.automaton = Box::new(LifeLike::new(rule));
+    }
+
+    /// Get the number of bytes taken by the cells and the automaton
+    pub fn get_memory_usage(&self) -> usize {
+        self.grid.get_memory_usage() + self.automaton.get_memory_usage()
+    }
+
+    /// Count the alive cells
+    pub fn get_population(&self) -> usize {
+        self.grid.get_population()
+    }
+
+    /// Get the statistics of a region of the world, see
+    /// `Grid::get_region_stats`
+    ///
+    /// @param area The region, clipped to the world
+    /// @param previous The tiles of the previous generation, row major
+    pub fn get_region_stats(&self, area: Rect, previous: Option<&[CellState]>) -> RegionStats {
+        self.grid.get_region_stats(area, previous)
+    }
+
+    /// Count the cells in each state, indexed by state
+    pub fn get_state_populations(&self) -> Vec<usize> {
+        let mut populations = vec![0; self.automaton.states()];
+        for cell_state in self.get_tiles() {
+            if let Some(population) = populations.get_mut(cell_state.0 as usize) {
+                *population += 1;
             }
         }
+
+        populations
     }
 
-    /// Update the world
-    pub fn update(&mut self) {
-        let mut new_tiles = vec![vec![CellState::Dead; self.width]; self.height];
-
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let cell_state = self.tiles[y][x];
-
-                let left_x = if x == 0 { self.width - 1 } else { x - 1 };
-                let right_x = if x == self.width - 1 { 0 } else { x + 1 };
-                let top_y = if y == self.height - 1 { 0 } else { y + 1 };
-                let bottom_y = if y == 0 { self.height - 1 } else { y - 1 };
-
-                let neighbors_count = [
-                    // Top left
-                    (left_x, top_y),
-                    // Top
-                    (x, top_y),
-                    // Top right
-                    (right_x, top_y),
-                    // Left
-                    (left_x, y),
-                    // Right
-                    (right_x, y),
-                    // Bottom left
-                    (left_x, bottom_y),
-                    // Bottom
-                    (x, bottom_y),
-                    // Bottom right
-                    (right_x, bottom_y),
-                ]
-                .iter()
-                .map(|(x, y)| self.tiles[*y][*x])
-                .filter(|cell_state| match cell_state {
-                    CellState::Alive => true,
-                    _ => false,
-                })
-                .count();
+    /// Pack the tiles as a bitmap, row major, least significant bit first
+    pub fn to_bitmap(&self) -> Vec<u8> {
+        let mut bitmap = vec![0; self.get_tiles().len().div_ceil(8)];
+        for (index, cell_state) in self.get_tiles().iter().enumerate() {
+            if cell_state.is_alive() {
+                bitmap[index / 8] |= 1 << (index % 8);
+            }
+        }
+
+        bitmap
+    }
 
-                let new_state = if neighbors_count == 3
-                    || (neighbors_count == 2 && cell_state == CellState::Alive)
-                {
-                    CellState::Alive
+    /// Create a world from a bitmap packed by `to_bitmap`
+    ///
+    /// Returns None if the bitmap is too short for the dimensions.
+    ///
+    /// @param width Width of the world
+    /// @param height Height of the world
+    /// @param bitmap The packed tiles
+    pub fn from_bitmap(width: usize, height: usize, bitmap: &[u8]) -> Option<Self> {
+        if bitmap.len() < (width * height).div_ceil(8) {
+            return None;
+        }
+
+        let mut world = Self::new(width, height);
+        for (index, cell_state) in world.grid.get_tiles_mut().iter_mut().enumerate() {
+            if bitmap[index / 8] & (1 << (index % 8)) != 0 {
+                *cell_state = CellState::ALIVE;
+            }
+        }
+
+        Some(world)
+    }
+
+    /// Draw the world as text, a line per row, `#` for the alive cells and
+    /// `.` for the dead ones
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::with_capacity((self.get_width() + 1) * self.get_height());
+        for row in self.get_tiles().chunks(self.get_width().max(1)) {
+            ascii.extend(
+                row.iter()
+                    .map(|cell_state| if cell_state.is_alive() { '#' } else { '.' }),
+            );
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+
+    /// Encode the world as JSON
+    ///
+    /// The world is an object holding its `width`, `height`, `rule` (in the
+    /// B/S notation), `population` and the `cells` alive as `[x, y]` pairs,
+    /// row major.
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// Encode the world as a JSON value, see `to_json`
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let cells: Vec<[usize; 2]> = (0..self.get_height())
+            .flat_map(|y| (0..self.get_width()).map(move |x| [x, y]))
+            .filter(|[x, y]| self.get_tile(*x, *y).is_alive())
+            .collect();
+
+        json!({
+            "width": self.get_width(),
+            "height": self.get_height(),
+            "rule": self.automaton.name(),
+            "population": cells.len(),
+            "cells": cells,
+        })
+    }
+
+    /// Create a world from JSON encoded by `to_json`
+    ///
+    /// The `rule` is optional and defaults to Conway's Game of Life, the
+    /// `population` is ignored.
+    ///
+    /// @param json The encoded world
+    pub fn from_json(json: &str) -> Result<Self, ParseJsonError> {
+        let invalid = |msg: &str| ParseJsonError(msg.to_string());
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|err| ParseJsonError(err.to_string()))?;
+
+        let width = value["width"]
+            .as_u64()
+            .ok_or_else(|| invalid("missing width"))? as usize;
+        let height = value["height"]
+            .as_u64()
+            .ok_or_else(|| invalid("missing height"))? as usize;
+        let mut world = Self::new(width, height);
+
+        if let Some(rule) = value.get("rule") {
+            let rule = rule
+                .as_str()
+                .and_then(|rule| rule.parse::<Rule>().ok())
+                .ok_or_else(|| invalid("invalid rule"))?;
+            world.set_rule(rule);
+        }
+
+        let cells = value["cells"]
+            .as_array()
+            .ok_or_else(|| invalid("missing cells"))?;
+        for cell in cells {
+            let position = cell
+                .as_array()
+                .and_then(|position| match position.as_slice() {
+                    [x, y] => Some((x.as_u64()? as usize, y.as_u64()? as usize)),
+                    _ => None,
+                });
+            match position {
+                Some((x, y)) if x < width && y < height => world.set_tile(x, y, CellState::ALIVE),
+                _ => return Err(ParseJsonError(format!("invalid cell {}", cell))),
+            }
+        }
+
+        Ok(world)
+    }
+
+    /// Copy a pattern into the world, wrapping around the edges which wrap
+    ///
+    /// The cells of the pattern beyond the edges which do not wrap are left
+    /// out.
+    ///
+    /// @param pattern The pattern to copy
+    /// @param origin Position of the top left corner of the pattern
+    pub fn stamp(&mut self, pattern: &Pattern, origin: Point) {
+        if self.get_width() == 0 || self.get_height() == 0 {
+            return;
+        }
+        let alive = self.automaton.seed_states()[0];
+        // The positions may come from remote clients, anywhere up to
+        // usize::MAX, so they are reduced before the offsets are added
+        let place = |origin: usize, offset: usize, size: usize, wrap: bool| {
+            if wrap {
+                Some((origin % size + offset % size) % size)
+            } else {
+                origin
+                    .checked_add(offset)
+                    .filter(|position| *position < size)
+            }
+        };
+        let (width, height) = (self.get_width(), self.get_height());
+        let (wrap_x, wrap_y) = (self.grid.is_wrapping_x(), self.grid.is_wrapping_y());
+        let cells: Vec<(usize, usize, CellState)> = pattern
+            .get_bounds()
+            .get_points()
+            .filter_map(|offset| {
+                let cell_state = if pattern.get_tile(offset.x, offset.y).is_alive() {
+                    alive
                 } else {
-                    CellState::Dead
+                    CellState::DEAD
                 };
 
-                new_tiles[y][x] = new_state;
+                Some((
+                    place(origin.x, offset.x, width, wrap_x)?,
+                    place(origin.y, offset.y, height, wrap_y)?,
+                    cell_state,
+                ))
+            })
+            .collect();
+        // Placed above, the cells are always in the world
+        let _ = self.set_cells(cells);
+    }
+
+    /// Populate the world randomly
+    ///
+    /// @param rng The random number generator
+    /// @param density The population density
+    pub fn populate(&mut self, rng: &mut impl Rng, density: f32) {
+        let seed_states = self.automaton.seed_states();
+        for cell_state in self.grid.get_tiles_mut().iter_mut() {
+            *cell_state = if rng.gen::<f32>() >= density {
+                CellState::DEAD
+            } else if seed_states.len() == 1 {
+                // Draw nothing more, so the soups of a seed do not change
+                seed_states[0]
+            } else {
+                seed_states[rng.gen_range(0, seed_states.len())]
+            };
+        }
+    }
+
+    /// Update the world
+    pub fn update(&mut self) -> UpdateStats {
+        self.automaton.step(&mut self.grid)
+    }
+
+    /// Go back to a previous generation of the world (experimental)
+    ///
+    /// The previous generation is searched among all the states evolving into
+    /// the current one, so only narrow worlds with a Life-like rule are
+    /// supported. When several states qualify, any of them is picked.
+    ///
+    /// Returns false if the world has no previous generation.
+    pub fn reverse_step(&mut self) -> Result<bool, PredecessorError> {
+        match find_world_predecessor(self)? {
+            Some(predecessor) => {
+                self.grid.set_tiles(predecessor.grid.get_tiles().to_vec());
+                Ok(true)
             }
+            None => Ok(false),
         }
+    }
+}
+
+impl fmt::Display for World {
+    /// Draw the world as `to_ascii` does, without the last line break
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_ascii().trim_end_matches('\n'))
+    }
+}
+
+impl FromStr for World {
+    type Err = ParseAsciiError;
+
+    /// Parse a world drawn as text, as by `to_ascii`, following the rules of
+    /// Conway's Game of Life
+    ///
+    /// The spaces around the lines and the blank lines are ignored, so a
+    /// world can be written indented in the code. The rows must all have the
+    /// same length.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&str> = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        let mut cells = Vec::with_capacity(rows.len());
+
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(ParseAsciiError(format!(
+                    "row {} is {} cells wide, not {}",
+                    y,
+                    row.chars().count(),
+                    width
+                )));
+            }
+            let row = row
+                .chars()
+                .map(|c| match c {
+                    '.' => Ok(CellState::DEAD),
+                    '#' => Ok(CellState::ALIVE),
+                    _ => Err(ParseAsciiError(format!("unexpected character {:?}", c))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            cells.push(row);
+        }
+
+        let mut world = Self::new(width, rows.len());
+        let rows: Vec<&[CellState]> = cells.iter().map(Vec::as_slice).collect();
+        world
+            .load_rows(&rows)
+            .map_err(|err| ParseAsciiError(err.to_string()))?;
 
-        self.tiles = new_tiles;
+        Ok(world)
     }
 }
@@ -0,0 +1,193 @@
+use crate::delta::{Delta, DeltaEncoder};
+use crate::world::World;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::Write;
+use std::net::{TcpListener, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tungstenite::Message;
+
+/// Number of messages buffered for a viewer before it is considered lagging
+const CLIENT_QUEUE_SIZE: usize = 16;
+
+/// Time given to a viewer to complete the WebSocket handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Kind of a frame message
+const FRAME_KEYFRAME: u8 = 0;
+const FRAME_DIFF: u8 = 1;
+
+/// A connected viewer
+struct Client {
+    /// Queue of the messages to send
+    sender: SyncSender<Arc<Vec<Message>>>,
+    /// Whether the viewer missed a frame and needs the full world
+    needs_keyframe: bool,
+    /// Number of frames in the queue
+    queued: Arc<AtomicUsize>,
+}
+
+/// A server streaming the world to WebSocket viewers
+///
+/// Each generation is sent as a binary message made of a header (kind as
+/// u8, generation as u64, width and height as u32, all little endian)
+/// followed by a zlib compressed payload. Keyframes carry the whole world as
+/// a bitmap (row major, least significant bit first) and diffs carry the
+/// cells toggled since the previous frame, encoded as described in `delta`.
+/// The frame is followed by a text message holding the stats as JSON.
+pub struct WsServer {
+    /// The connected viewers
+    clients: Arc<Mutex<Vec<Client>>>,
+    /// The deltas between the generations sent
+    delta_encoder: DeltaEncoder,
+}
+
+impl WsServer {
+    /// Start listening for viewers
+    ///
+    /// @param address The address to listen on
+    pub fn bind<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        let clients_ = clients.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let clients = clients_.clone();
+                // The handshake runs on the viewer thread, a silent peer only
+                // holds its own thread until the timeout
+                std::thread::spawn(move || {
+                    let peer = stream.peer_addr().ok();
+                    if let Err(err) = stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)) {
+                        eprintln!("websocket handshake with {:?} failed: {}", peer, err);
+                        return;
+                    }
+                    let mut socket = match tungstenite::accept(stream) {
+                        Ok(socket) => socket,
+                        Err(err) => {
+                            eprintln!("websocket handshake with {:?} failed: {}", peer, err);
+                            return;
+                        }
+                    };
+                    eprintln!("viewer {:?} connected", peer);
+
+                    let (sender, receiver): (_, Receiver<Arc<Vec<Message>>>) =
+                        sync_channel(CLIENT_QUEUE_SIZE);
+                    let queued = Arc::new(AtomicUsize::new(0));
+                    clients.lock().unwrap().push(Client {
+                        sender,
+                        needs_keyframe: true,
+                        queued: queued.clone(),
+                    });
+
+                    for messages in receiver {
+                        queued.fetch_sub(1, Ordering::Relaxed);
+                        for message in messages.iter() {
+                            if socket.send(message.clone()).is_err() {
+                                eprintln!("viewer {:?} disconnected", peer);
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            clients,
+            delta_encoder: DeltaEncoder::new(),
+        })
+    }
+
+    /// Get the number of frames waiting to be sent to the most lagging
+    /// viewer
+    pub fn get_queue_depth(&self) -> usize {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|client| client.queued.load(Ordering::Relaxed))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Send the world to the viewers
+    ///
+    /// @param world The world to send
+    /// @param generation The generation of the world
+    pub fn broadcast(&mut self, world: &World, generation: usize) {
+        let width = world.get_width();
+        let height = world.get_height();
+        let Delta {
+            toggled,
+            births,
+            deaths,
+            population,
+        } = self.delta_encoder.encode(world);
+
+        let stats = Message::text(format!(
+            "{{\"generation\":{},\"population\":{},\"births\":{},\"deaths\":{}}}",
+            generation, population, births, deaths
+        ));
+        let diff_messages = Arc::new(vec![
+            frame_message(FRAME_DIFF, generation, width, height, &toggled),
+            stats.clone(),
+        ]);
+        let mut keyframe_messages = None;
+
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain_mut(|client| {
+            let messages = if client.needs_keyframe {
+                keyframe_messages
+                    .get_or_insert_with(|| {
+                        let bitmap = world.to_bitmap();
+                        Arc::new(vec![
+                            frame_message(FRAME_KEYFRAME, generation, width, height, &bitmap),
+                            stats.clone(),
+                        ])
+                    })
+                    .clone()
+            } else {
+                diff_messages.clone()
+            };
+
+            // Counted before being sent, the viewer may take it right away
+            client.queued.fetch_add(1, Ordering::Relaxed);
+            match client.sender.try_send(messages) {
+                Ok(()) => {
+                    client.needs_keyframe = false;
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    client.queued.fetch_sub(1, Ordering::Relaxed);
+                    // The viewer is lagging, it will need a keyframe to catch up
+                    client.needs_keyframe = true;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+/// Build a binary frame message
+fn frame_message(
+    kind: u8,
+    generation: usize,
+    width: usize,
+    height: usize,
+    payload: &[u8],
+) -> Message {
+    let mut data = vec![kind];
+    data.extend_from_slice(&(generation as u64).to_le_bytes());
+    data.extend_from_slice(&(width as u32).to_le_bytes());
+    data.extend_from_slice(&(height as u32).to_le_bytes());
+
+    let mut encoder = ZlibEncoder::new(data, Compression::fast());
+    encoder.write_all(payload).unwrap();
+
+    Message::binary(encoder.finish().unwrap())
+}
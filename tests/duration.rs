@@ -0,0 +1,21 @@
+//! Durations written on the command line
+
+use gol::duration::parse_duration;
+use std::time::Duration;
+
+#[test]
+fn parse_units() {
+    assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+    assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+    assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+    assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+    assert_eq!(parse_duration("0"), Some(Duration::ZERO));
+}
+
+#[test]
+fn parse_rejects_invalid() {
+    for duration in ["", "s", "-1s", "1d", "1 s", "NaN", "inf", "1e300h"] {
+        assert_eq!(parse_duration(duration), None, "{}", duration);
+    }
+}
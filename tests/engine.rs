@@ -0,0 +1,65 @@
+//! Engines computing the same generations as the naive one
+
+use gol::engine::Engine;
+use gol::rule::Rule;
+use gol::world::World;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Number of generations compared
+const GENERATIONS: usize = 40;
+
+/// Run a world with each engine and compare the generations to the naive
+/// engine
+///
+/// @param width Width of the world
+/// @param height Height of the world
+/// @param wrap Whether the edges of the world wrap around
+fn assert_engines_match(width: usize, height: usize, wrap: bool) {
+    for rule in ["B3/S23", "B36/S23", "B2/S", "B3678/S34678"] {
+        let rule = rule.parse::<Rule>().unwrap();
+        let mut seed = World::new(width, height);
+        seed.set_wrapping(wrap, wrap);
+        seed.populate(&mut ChaCha8Rng::seed_from_u64(width as u64), 0.35);
+
+        let worlds = Engine::all().into_iter().map(|engine| {
+            let mut world = World::new(width, height);
+            world.set_automaton(engine.create(rule));
+            // The grid carries the wrapping of the edges
+            world.set_grid(seed.get_grid().clone());
+            (engine, world)
+        });
+        let (mut naive, mut others): (Vec<_>, Vec<_>) =
+            worlds.partition(|(engine, _)| *engine == Engine::Naive);
+        let naive = &mut naive[0].1;
+
+        for generation in 1..=GENERATIONS {
+            naive.update();
+            for (engine, world) in others.iter_mut() {
+                world.update();
+                assert!(
+                    world.get_grid().get_tiles() == naive.get_grid().get_tiles(),
+                    "{} with {} on {}x{} (wrap {}), generation {}",
+                    engine,
+                    rule,
+                    width,
+                    height,
+                    wrap,
+                    generation
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn engines_match_wrapping() {
+    assert_engines_match(70, 45, true);
+    assert_engines_match(3, 3, true);
+}
+
+#[test]
+fn engines_match_without_wrapping() {
+    assert_engines_match(70, 45, false);
+    assert_engines_match(1, 5, false);
+}
@@ -0,0 +1,33 @@
+//! Errors returned by the C API instead of panicking
+
+use gol::ffi::*;
+use std::ffi::CString;
+
+#[test]
+fn new_returns_null_when_too_large() {
+    assert!(gol_world_new(usize::MAX, 2).is_null());
+}
+
+#[test]
+fn populate_rejects_invalid_density() {
+    let world = gol_world_new(8, 8);
+    unsafe {
+        assert_eq!(gol_world_populate(world, f32::NAN), -1);
+        assert_eq!(gol_world_populate(world, 1.5), -1);
+        assert_eq!(gol_world_populate(world, 1.0), 0);
+        assert_eq!(gol_world_population(world), 64);
+        gol_world_free(world);
+    }
+}
+
+#[test]
+fn empty_world_steps() {
+    let world = gol_world_new(0, 0);
+    let rle = CString::new("o!").unwrap();
+    unsafe {
+        assert_eq!(gol_world_step(world), 0);
+        assert_eq!(gol_world_stamp_rle(world, rle.as_ptr(), 3, 3), 0);
+        assert_eq!(gol_world_population(world), 0);
+        gol_world_free(world);
+    }
+}
@@ -0,0 +1,91 @@
+//! Setups of the world written on the command line
+
+use gol::geometry::{Point, Rect};
+use gol::init::{parse_init, InitOperation, Soup};
+use gol::pattern::{PatternError, PatternSource, Transform};
+use gol::world::World;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn parse_setup() {
+    let operations =
+        parse_init("glider@10,10; blinker@0,0 rot90 flipy; soup 64x32@100,50 d=0.4;").unwrap();
+    assert_eq!(operations.len(), 3);
+
+    let InitOperation::Place(placement) = &operations[0] else {
+        panic!("not a placement");
+    };
+    assert_eq!(
+        placement.source,
+        PatternSource::Builtin("glider".to_string())
+    );
+    assert_eq!(placement.position, Point::new(10, 10));
+
+    let InitOperation::Place(placement) = &operations[1] else {
+        panic!("not a placement");
+    };
+    assert_eq!(
+        placement.transforms,
+        vec![Transform::Rot90, Transform::FlipY]
+    );
+
+    assert_eq!(
+        operations[2],
+        InitOperation::Soup(Soup {
+            area: Rect::new(100, 50, 64, 32),
+            density: 0.4,
+        })
+    );
+}
+
+#[test]
+fn parse_soup_default_density() {
+    let operations = parse_init("soup 8x8@0,0").unwrap();
+    assert_eq!(
+        operations,
+        vec![InitOperation::Soup(Soup {
+            area: Rect::new(0, 0, 8, 8),
+            density: 0.5,
+        })]
+    );
+}
+
+#[test]
+fn parse_setup_rejects_invalid() {
+    for setup in [
+        "soup",
+        "soup 8x8",
+        "soup 8@0,0",
+        "soup 8x8@0,0 d=1.5",
+        "soup 8x8@0,0 d=NaN",
+        "soup 8x8@0,0 density=0.4",
+        "soup 8x8@0,0 d=0.4 extra",
+        "glider@1,1 rot45",
+        "glider",
+    ] {
+        assert!(
+            matches!(parse_init(setup), Err(PatternError::InvalidPlacement(_))),
+            "{}",
+            setup
+        );
+    }
+}
+
+#[test]
+fn apply_setup() {
+    let mut world = World::new(16, 16);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for operation in parse_init("block@15,15; soup 4x4@4,4 d=1").unwrap() {
+        operation.apply(&mut world, &mut rng).unwrap();
+    }
+
+    // The block wraps around the corners, the soup is full
+    for (x, y) in [(15, 15), (0, 15), (15, 0), (0, 0)] {
+        assert!(world.get_tile(x, y).is_alive());
+    }
+    assert!(Rect::new(4, 4, 4, 4)
+        .get_points()
+        .all(|point| world.get_tile(point.x, point.y).is_alive()));
+    assert_eq!(world.get_population(), 4 + 16);
+}
@@ -0,0 +1,63 @@
+//! Kernels computing the same states whatever the instruction set
+
+use gol::kernel::{Kernel, RowKernel};
+use gol::rule::Rule;
+
+/// Fill rows of a width with pseudo-random cells
+fn get_rows(width: usize, count: usize) -> Vec<Vec<u64>> {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    (0..count)
+        .map(|_| {
+            let mut row: Vec<u64> = (0..width.div_ceil(64))
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state
+                })
+                .collect();
+            if !width.is_multiple_of(64) {
+                row[width / 64] &= (1 << (width % 64)) - 1;
+            }
+            row
+        })
+        .collect()
+}
+
+#[test]
+fn kernels_match_scalar() {
+    for rule in ["B3/S23", "B36/S23", "B2/S"] {
+        let rule = rule.parse::<Rule>().unwrap();
+        for width in [1, 37, 100, 130] {
+            let rows = get_rows(width, 3);
+            let mut scalar = RowKernel::new(&rule, width, Kernel::Scalar);
+            let mut expected = vec![0; rows[0].len()];
+            scalar.next_row(&rows[0], &rows[1], &rows[2], &mut expected);
+
+            for kernel in Kernel::all()
+                .into_iter()
+                .filter(|kernel| kernel.is_supported())
+            {
+                let mut row_kernel = RowKernel::new(&rule, width, kernel);
+                assert_eq!(row_kernel.get_kernel(), kernel);
+                let mut row = vec![0; rows[0].len()];
+                row_kernel.next_row(&rows[0], &rows[1], &rows[2], &mut row);
+                assert_eq!(row, expected, "{} with {} on {} cells", kernel, rule, width);
+            }
+        }
+    }
+}
+
+#[test]
+fn scalar_kernel_wraps_rows() {
+    // A blinker standing across the left and right edges
+    let rule = "B3/S23".parse::<Rule>().unwrap();
+    let width = 70;
+    let row = vec![0b11, 1 << (69 - 64)];
+    let mut kernel = RowKernel::new(&rule, width, Kernel::Scalar);
+    let mut next = vec![0; 2];
+    kernel.next_row(&[0, 0], &row, &[0, 0], &mut next);
+    assert_eq!(next, vec![0b1, 0]);
+    kernel.next_row(&row, &[0, 0], &[0, 0], &mut next);
+    assert_eq!(next, vec![0b1, 0]);
+}
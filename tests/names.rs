@@ -0,0 +1,48 @@
+//! Rules, kernels and engines written and parsed back
+
+use gol::engine::Engine;
+use gol::kernel::Kernel;
+use gol::rule::Rule;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn rule_round_trip() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let rules = (0..100).map(|_| Rule::random(&mut rng));
+    for rule in rules.chain([
+        Rule::default(),
+        Rule::new(&[], &[]),
+        Rule::new(&[0, 8], &[0, 8]),
+    ]) {
+        assert_eq!(rule.to_string().parse::<Rule>().unwrap(), rule);
+    }
+}
+
+#[test]
+fn rule_notations() {
+    let life = Rule::default();
+    assert_eq!(life.to_string(), "B3/S23");
+    for notation in ["B3/S23", "b3/s23", "S23/B3", "23/3"] {
+        assert_eq!(notation.parse::<Rule>().unwrap(), life, "{}", notation);
+    }
+    for notation in ["", "B3", "B9/S23", "Bx/S23"] {
+        assert!(notation.parse::<Rule>().is_err(), "{}", notation);
+    }
+}
+
+#[test]
+fn kernel_round_trip() {
+    for kernel in Kernel::all() {
+        assert_eq!(kernel.to_string().parse::<Kernel>().unwrap(), kernel);
+    }
+    assert!("sse2".parse::<Kernel>().is_err());
+}
+
+#[test]
+fn engine_round_trip() {
+    for engine in Engine::all() {
+        assert_eq!(engine.to_string().parse::<Engine>().unwrap(), engine);
+    }
+    assert!("fast".parse::<Engine>().is_err());
+}
@@ -0,0 +1,152 @@
+//! Parsing and writing of the patterns
+
+use gol::geometry::Point;
+use gol::pattern::{
+    Pattern, PatternError, PatternMetadata, PatternSource, Placement, Transform, MAX_PATTERN_SIZE,
+};
+use gol::world::World;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn rle_round_trip_builtins() {
+    for name in Pattern::builtin_names() {
+        let pattern = Pattern::builtin(name).unwrap();
+        let parsed = Pattern::from_rle(&pattern.to_rle()).unwrap();
+        assert_eq!(parsed.get_width(), pattern.get_width(), "{}", name);
+        assert_eq!(parsed.get_height(), pattern.get_height(), "{}", name);
+        assert_eq!(
+            parsed.to_plaintext_rows(),
+            pattern.to_plaintext_rows(),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn rle_round_trip_soup() {
+    // Long runs and blank rows, wrapped over several lines
+    let mut world = World::new(150, 40);
+    world.populate(&mut ChaCha8Rng::seed_from_u64(7), 0.3);
+    for x in 0..150 {
+        world.set_tile(x, 10, world.get_tile(0, 0));
+    }
+    let pattern = Pattern::from_world(&world);
+    let rle = pattern.to_rle();
+    assert!(rle.lines().all(|line| line.len() <= 70));
+
+    let parsed = Pattern::from_rle(&rle).unwrap();
+    assert_eq!(parsed.to_plaintext_rows(), pattern.to_plaintext_rows());
+}
+
+#[test]
+fn rle_round_trip_metadata() {
+    let metadata = PatternMetadata {
+        name: Some("Glider".to_string()),
+        author: Some("Richard K. Guy".to_string()),
+        comments: vec!["First line".to_string(), "Second line".to_string()],
+    };
+    let pattern = Pattern::from_plaintext(".O.\n..O\nOOO\n")
+        .unwrap()
+        .with_metadata(metadata.clone());
+    let parsed = Pattern::from_rle(&pattern.to_rle()).unwrap();
+    assert_eq!(*parsed.get_metadata(), metadata);
+}
+
+#[test]
+fn placement_from_str() {
+    let placement = "glider@10,20".parse::<Placement>().unwrap();
+    assert_eq!(
+        placement.source,
+        PatternSource::Builtin("glider".to_string())
+    );
+    assert_eq!(placement.position, Point::new(10, 20));
+    assert!(placement.transforms.is_empty());
+
+    let placement = "glider@0,3:rot90:flipx".parse::<Placement>().unwrap();
+    assert_eq!(
+        placement.transforms,
+        vec![Transform::Rot90, Transform::FlipX]
+    );
+
+    // The last @ separates the position, so paths may hold one
+    let placement = "file:dir@home/gun.rle@1,2".parse::<Placement>().unwrap();
+    assert_eq!(
+        placement.source,
+        PatternSource::File("dir@home/gun.rle".into())
+    );
+    assert_eq!(placement.position, Point::new(1, 2));
+}
+
+#[test]
+fn placement_from_str_rejects_invalid() {
+    for placement in [
+        "glider",
+        "glider@",
+        "glider@1",
+        "glider@1,2,3",
+        "glider@-1,2",
+        "glider@a,2",
+        "glider@1,2:rot45",
+    ] {
+        assert!(
+            matches!(
+                placement.parse::<Placement>(),
+                Err(PatternError::InvalidPlacement(_))
+            ),
+            "{}",
+            placement
+        );
+    }
+}
+
+#[test]
+fn rle_rejects_overflowing_run() {
+    let result = Pattern::from_rle("x = 1, y = 1\n99999999999999999999o!\n");
+    assert!(matches!(result, Err(PatternError::Parse(_))));
+}
+
+#[test]
+fn rle_rejects_runs_too_long() {
+    let content = format!("{}o!\n", MAX_PATTERN_SIZE + 1);
+    assert!(matches!(
+        Pattern::from_rle(&content),
+        Err(PatternError::Parse(_))
+    ));
+
+    // Runs within the limit adding up beyond it
+    let content = format!("{0}o{0}o!\n", MAX_PATTERN_SIZE / 2 + 1);
+    assert!(matches!(
+        Pattern::from_rle(&content),
+        Err(PatternError::Parse(_))
+    ));
+    let content = format!("o{0}$o{0}$o!\n", MAX_PATTERN_SIZE / 2 + 1);
+    assert!(matches!(
+        Pattern::from_rle(&content),
+        Err(PatternError::Parse(_))
+    ));
+}
+
+#[test]
+fn rle_rejects_header_too_large() {
+    let content = format!("x = {}, y = 1\no!\n", MAX_PATTERN_SIZE + 1);
+    assert!(matches!(
+        Pattern::from_rle(&content),
+        Err(PatternError::Parse(_))
+    ));
+    assert!(matches!(
+        Pattern::from_rle("x = 18446744073709551616, y = 1\no!\n"),
+        Err(PatternError::Parse(_))
+    ));
+}
+
+#[test]
+fn rle_rejects_area_too_large() {
+    // A long first row padding many rows
+    let content = format!("{}o{}$o!\n", MAX_PATTERN_SIZE, MAX_PATTERN_SIZE - 1);
+    assert!(matches!(
+        Pattern::from_rle(&content),
+        Err(PatternError::Parse(_))
+    ));
+}
@@ -0,0 +1,28 @@
+//! Commands applied to a running simulation
+
+use gol::observer::{StepControl, StepStats};
+use gol::simulation::{Command, Simulation, MAX_COMMAND_STEPS};
+use gol::world::World;
+
+#[test]
+fn step_command_is_capped() {
+    let mut simulation = Simulation::new(World::new(8, 8));
+    simulation.apply(Command::Step(usize::MAX));
+    assert_eq!(simulation.get_generation(), MAX_COMMAND_STEPS);
+    assert!(simulation.is_paused());
+}
+
+#[test]
+fn step_command_ends_when_stopped() {
+    let mut simulation = Simulation::new(World::new(8, 8));
+    simulation.add_observer(Box::new(|stats: &StepStats, _: &World| {
+        if stats.generation == 3 {
+            StepControl::Stop
+        } else {
+            StepControl::Continue
+        }
+    }));
+    simulation.apply(Command::Step(100));
+    assert_eq!(simulation.get_generation(), 3);
+    assert!(simulation.is_stopped());
+}
@@ -0,0 +1,29 @@
+//! Stamps of patterns anywhere up to the largest positions
+
+use gol::geometry::Point;
+use gol::pattern::Pattern;
+use gol::world::{CellState, World};
+
+#[test]
+fn stamp_wraps_huge_origin() {
+    let mut world = World::new(8, 6);
+    let pattern = Pattern::from_plaintext("O\n").unwrap();
+    world.stamp(&pattern, Point::new(usize::MAX, usize::MAX));
+
+    // usize::MAX is 7 modulo 8 and 3 modulo 6
+    assert_eq!(world.get_population(), 1);
+    assert_eq!(world.get_tile(7, 3), CellState::ALIVE);
+}
+
+#[test]
+fn stamp_leaves_out_huge_origin_without_wrapping() {
+    let mut world = World::new(8, 6);
+    world.set_wrapping(false, false);
+    let pattern = Pattern::from_plaintext("OO\nOO\n").unwrap();
+    world.stamp(&pattern, Point::new(usize::MAX, 0));
+    assert_eq!(world.get_population(), 0);
+
+    // The cells beyond the right edge are left out
+    world.stamp(&pattern, Point::new(7, 0));
+    assert_eq!(world.get_population(), 2);
+}
@@ -0,0 +1,61 @@
+//! Stamps of patterns with metadata recorded, replayed and snapshotted
+
+use gol::geometry::Point;
+use gol::pattern::{Pattern, PatternMetadata};
+use gol::replay::{Recorder, Replay};
+use gol::rng::RngKind;
+use gol::simulation::Command;
+use gol::snapshot;
+use gol::world::World;
+
+/// A glider with a name, an author and a comment
+fn get_glider() -> Pattern {
+    Pattern::from_plaintext(".O.\n..O\nOOO\n")
+        .unwrap()
+        .with_metadata(PatternMetadata {
+            name: Some("Glider".to_string()),
+            author: Some("Richard K. Guy".to_string()),
+            comments: vec!["The smallest spaceship, née en 1969".to_string()],
+        })
+}
+
+/// Check a command is a stamp of the glider at (4, 5)
+fn assert_glider_stamp(command: &Command) {
+    let Command::Stamp(pattern, position) = command else {
+        panic!("not a stamp");
+    };
+    assert_eq!(*position, Point::new(4, 5));
+    assert_eq!(pattern.to_plaintext_rows(), ".O.\n..O\nOOO\n");
+}
+
+#[test]
+fn record_and_replay_stamp() {
+    let path = std::env::temp_dir().join(format!("gol-stamp-{}.golrec", std::process::id()));
+    let mut recorder = Recorder::create(&path, 42, RngKind::Xoshiro256PlusPlus).unwrap();
+    recorder
+        .record(3, &Command::Stamp(get_glider(), Point::new(4, 5)))
+        .unwrap();
+    drop(recorder);
+
+    let mut replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.get_seed(), 42);
+    assert_eq!(replay.get_rng_kind(), RngKind::Xoshiro256PlusPlus);
+    let commands = replay.take_commands(3);
+    assert_eq!(commands.len(), 1);
+    assert_glider_stamp(&commands[0]);
+}
+
+#[test]
+fn snapshot_pending_stamp() {
+    let world = World::new(16, 16);
+    let command = Command::Stamp(get_glider(), Point::new(4, 5));
+    let mut bytes = Vec::new();
+    snapshot::write_session(&mut bytes, &world, 2, None, &[(3, &command)]).unwrap();
+
+    let snapshot = snapshot::read(bytes.as_slice()).unwrap();
+    assert_eq!(snapshot.generation, 2);
+    assert_eq!(snapshot.pending_commands.len(), 1);
+    assert_eq!(snapshot.pending_commands[0].0, 3);
+    assert_glider_stamp(&snapshot.pending_commands[0].1);
+}